@@ -1,15 +1,18 @@
 //! Build command - Compile Vue SFC files
 
+mod watch;
+
 use clap::{Args, ValueEnum};
 use ignore::Walk;
 use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Instant;
 use vize_atelier_sfc::{
-    compile_sfc, parse_sfc, ScriptCompileOptions, SfcCompileOptions, SfcParseOptions,
-    StyleCompileOptions, TemplateCompileOptions,
+    compile_sfc, generate_scope_id, parse_sfc, ScriptCompileOptions, SfcCompileOptions, SfcError,
+    SfcParseOptions, StyleCompileOptions, TemplateCompileOptions,
 };
 
 #[derive(Debug, Clone, Copy, ValueEnum, Default)]
@@ -32,6 +35,15 @@ pub enum ScriptExtension {
     Downcompile,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum, Default)]
+pub enum CssOutput {
+    /// Write the CSS of each component next to its output (Button.vue -> Button.css)
+    #[default]
+    Split,
+    /// Concatenate the CSS of all components into a single style.css
+    Bundle,
+}
+
 #[derive(Args, Default)]
 pub struct BuildArgs {
     /// Glob pattern(s) to match .vue files (default: ./**/*.vue)
@@ -42,6 +54,10 @@ pub struct BuildArgs {
     #[arg(short, long, default_value = "./dist")]
     pub output: PathBuf,
 
+    /// Root directory whose structure is mirrored in the output directory (default: .)
+    #[arg(long, default_value = ".")]
+    pub root: PathBuf,
+
     /// Output format
     #[arg(short, long, value_enum, default_value = "js")]
     pub format: OutputFormat,
@@ -54,6 +70,14 @@ pub struct BuildArgs {
     #[arg(long, value_enum, default_value = "downcompile")]
     pub script_ext: ScriptExtension,
 
+    /// CSS output: 'split' writes a .css file per component, 'bundle' writes a single style.css
+    #[arg(long, value_enum, default_value = "split")]
+    pub css: CssOutput,
+
+    /// Write source maps next to the JavaScript and CSS outputs
+    #[arg(long)]
+    pub source_map: bool,
+
     /// Number of threads (default: number of CPUs)
     #[arg(short = 'j', long)]
     pub threads: Option<usize>,
@@ -65,6 +89,14 @@ pub struct BuildArgs {
     /// Continue on errors
     #[arg(long)]
     pub continue_on_error: bool,
+
+    /// Watch for changes and recompile changed files
+    #[arg(short, long)]
+    pub watch: bool,
+
+    /// Treat TypeScript errors in script blocks as fatal
+    #[arg(long)]
+    pub strict: bool,
 }
 
 #[derive(Debug)]
@@ -96,9 +128,14 @@ struct CompileOutput {
     filename: String,
     code: String,
     css: Option<String>,
+    map: Option<serde_json::Value>,
+    css_map: Option<serde_json::Value>,
     errors: Vec<String>,
     warnings: Vec<String>,
     script_lang: String,
+    /// Files loaded through `src` attributes
+    #[serde(skip)]
+    dependencies: Vec<PathBuf>,
 }
 
 pub fn run(args: BuildArgs) {
@@ -113,7 +150,7 @@ pub fn run(args: BuildArgs) {
 
     let files = collect_files(&args.patterns);
 
-    if files.is_empty() {
+    if files.is_empty() && !args.watch {
         eprintln!("No .vue files found matching the patterns");
         std::process::exit(1);
     }
@@ -137,71 +174,42 @@ pub fn run(args: BuildArgs) {
             let source_size = fs::metadata(path).map(|m| m.len() as usize).unwrap_or(0);
             stats.total_bytes.fetch_add(source_size, Ordering::Relaxed);
 
-            match compile_file(path, args.ssr, args.script_ext) {
-                Ok(output) => {
+            let errors = match compile_file(path, &args) {
+                Ok(output) if output.errors.is_empty() => {
                     stats.success.fetch_add(1, Ordering::Relaxed);
                     stats
                         .output_bytes
                         .fetch_add(output.code.len(), Ordering::Relaxed);
 
-                    if args.profile && !output.errors.is_empty() {
-                        for err in &output.errors {
-                            eprintln!("  {} warning: {}", path.display(), err);
-                        }
-                    }
-
-                    Some((path.clone(), output))
+                    return Some((path.clone(), output));
                 }
-                Err(e) => {
-                    stats.failed.fetch_add(1, Ordering::Relaxed);
-                    eprintln!("Error compiling {}: {}", path.display(), e);
+                // The output of a file with errors is broken, so it isn't written
+                Ok(output) => output.errors,
+                Err(e) => vec![e],
+            };
 
-                    if !args.continue_on_error {
-                        std::process::exit(1);
-                    }
+            stats.failed.fetch_add(1, Ordering::Relaxed);
+            for error in errors {
+                eprintln!("Error compiling {}: {}", path.display(), error);
+            }
 
-                    None
-                }
+            if !args.continue_on_error && !args.watch {
+                std::process::exit(1);
             }
+
+            None
         })
         .collect();
     let compile_elapsed = compile_start.elapsed();
 
     let io_start = Instant::now();
-    match args.format {
-        OutputFormat::Stats => {}
-        OutputFormat::Js | OutputFormat::Json => {
-            fs::create_dir_all(&args.output).expect("Failed to create output directory");
-
-            for (path, output) in results.into_iter().flatten() {
-                let ext = match args.format {
-                    OutputFormat::Js => get_output_extension(&output.script_lang, args.script_ext),
-                    OutputFormat::Json => "json",
-                    OutputFormat::Stats => unreachable!(),
-                };
-
-                let filename = path
-                    .file_name()
-                    .map(|f| PathBuf::from(f).with_extension(ext))
-                    .unwrap_or_else(|| PathBuf::from("output").with_extension(ext));
-                let out_path = args.output.join(filename);
-
-                if let Some(parent) = out_path.parent() {
-                    fs::create_dir_all(parent).expect("Failed to create output subdirectory");
-                }
-
-                let content = match args.format {
-                    OutputFormat::Js => output.code,
-                    OutputFormat::Json => serde_json::to_string_pretty(&output).unwrap_or_default(),
-                    OutputFormat::Stats => unreachable!(),
-                };
-
-                fs::write(&out_path, content).unwrap_or_else(|e| {
-                    eprintln!("Failed to write {}: {}", out_path.display(), e);
-                });
-            }
-        }
-    }
+    let results: Vec<_> = results.into_iter().flatten().collect();
+    let dependencies: HashMap<_, _> = results
+        .iter()
+        .map(|(path, output)| (canonical(path), output.dependencies.clone()))
+        .collect();
+    let mut css_bundle = BTreeMap::new();
+    write_outputs(results, &args, &mut css_bundle);
     let io_elapsed = io_start.elapsed();
 
     let total_elapsed = start.elapsed();
@@ -235,6 +243,10 @@ pub fn run(args: BuildArgs) {
         );
     }
 
+    if args.watch {
+        watch::run(&args, files, dependencies, css_bundle);
+    }
+
     if failed > 0 {
         std::process::exit(1);
     }
@@ -317,6 +329,189 @@ fn pattern_matches(path: &std::path::Path, pattern: &str) -> bool {
     path_str.ends_with(".vue")
 }
 
+/// Write the outputs of compiled files to the output directory. The CSS of
+/// each file is kept in `css_bundle`, so that the bundle stays complete when
+/// only some of the files are recompiled.
+fn write_outputs(
+    results: Vec<(PathBuf, CompileOutput)>,
+    args: &BuildArgs,
+    css_bundle: &mut BTreeMap<PathBuf, String>,
+) {
+    if matches!(args.format, OutputFormat::Stats) {
+        return;
+    }
+
+    fs::create_dir_all(&args.output).expect("Failed to create output directory");
+
+    let root = args
+        .root
+        .canonicalize()
+        .unwrap_or_else(|_| args.root.clone());
+    for (path, output) in results {
+        let ext = match args.format {
+            OutputFormat::Js => get_output_extension(&output.script_lang, args.script_ext),
+            OutputFormat::Json => "json",
+            OutputFormat::Stats => unreachable!(),
+        };
+        let out_path = args
+            .output
+            .join(output_path(&path, &root))
+            .with_extension(ext);
+
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent).expect("Failed to create output subdirectory");
+        }
+
+        match args.format {
+            OutputFormat::Js => {
+                let mut code = output.code;
+                let map_path = out_path.with_extension(format!("{}.map", ext));
+                if let Some(map) = output.map {
+                    write_source_map(&map_path, map, &path);
+                    code.push_str(&source_mapping_url(&map_path, "//", ""));
+                } else {
+                    remove_stale_output(&map_path);
+                }
+                write_output(&out_path, code);
+
+                let css_path = out_path.with_extension("css");
+                let css_map_path = out_path.with_extension("css.map");
+                if output.css.is_none() {
+                    css_bundle.remove(&output_path(&path, &root));
+                    remove_stale_output(&css_path);
+                    remove_stale_output(&css_map_path);
+                }
+                if let Some(mut css) = output.css {
+                    match args.css {
+                        CssOutput::Split => {
+                            if let Some(map) = output.css_map {
+                                write_source_map(&css_map_path, map, &path);
+                                css.push_str(&source_mapping_url(&css_map_path, "/*", " */"));
+                            } else {
+                                remove_stale_output(&css_map_path);
+                            }
+                            write_output(&css_path, css);
+                        }
+                        CssOutput::Bundle => {
+                            css_bundle.insert(output_path(&path, &root), css);
+                        }
+                    }
+                }
+            }
+            OutputFormat::Json => {
+                write_output(
+                    &out_path,
+                    serde_json::to_string_pretty(&output).unwrap_or_default(),
+                );
+            }
+            OutputFormat::Stats => unreachable!(),
+        }
+    }
+
+    write_css_bundle(args, css_bundle);
+}
+
+/// Write the CSS of all files into a single style.css
+fn write_css_bundle(args: &BuildArgs, css_bundle: &BTreeMap<PathBuf, String>) {
+    if !matches!(args.css, CssOutput::Bundle) || css_bundle.is_empty() {
+        return;
+    }
+    let bundle: String = css_bundle
+        .iter()
+        .map(|(path, css)| format!("/* {} */\n{}\n", path.display(), css.trim_end()))
+        .collect();
+    write_output(&args.output.join("style.css"), bundle);
+}
+
+/// Path of the output for `path`, relative to the output directory.
+/// Files outside of `root` are written at the top of the output directory.
+fn output_path(path: &Path, root: &Path) -> PathBuf {
+    match canonical(path).strip_prefix(root) {
+        Ok(relative) => relative.to_path_buf(),
+        Err(_) => path
+            .file_name()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("output")),
+    }
+}
+
+/// Absolute path of `path`, or `path` itself if it doesn't exist
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Relative path from the directory `from` to `to`, both absolute
+fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from: Vec<_> = from.components().collect();
+    let to: Vec<_> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut relative = PathBuf::new();
+    for _ in common..from.len() {
+        relative.push(Component::ParentDir);
+    }
+    for component in &to[common..] {
+        relative.push(component);
+    }
+    relative
+}
+
+/// Write a source map whose source points to the SFC relative to the map
+fn write_source_map(map_path: &Path, mut map: serde_json::Value, source: &Path) {
+    let dir = map_path
+        .parent()
+        .and_then(|dir| dir.canonicalize().ok())
+        .unwrap_or_default();
+    if let Ok(source) = source.canonicalize() {
+        let relative = relative_path(&dir, &source);
+        map["sources"] = serde_json::json!([relative.to_string_lossy().replace('\\', "/")]);
+    }
+    write_output(map_path, serde_json::to_string(&map).unwrap_or_default());
+}
+
+/// Comment linking an output to the source map next to it
+fn source_mapping_url(map_path: &Path, open: &str, close: &str) -> String {
+    let name = map_path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    format!("\n{}# sourceMappingURL={}{}\n", open, name, close)
+}
+
+fn write_output(path: &Path, content: String) {
+    fs::write(path, content).unwrap_or_else(|e| {
+        eprintln!("Failed to write {}: {}", path.display(), e);
+    });
+}
+
+/// Remove an output written by a previous build that this build no longer produces
+fn remove_stale_output(path: &Path) {
+    if let Err(e) = fs::remove_file(path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            eprintln!("Failed to remove {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Remove every output a previous build may have written for `path`
+fn remove_outputs(path: &Path, args: &BuildArgs, root: &Path) {
+    let out_path = args.output.join(output_path(path, root));
+    let extensions: &[&str] = match (args.format, args.script_ext) {
+        (OutputFormat::Js, ScriptExtension::Downcompile) => &["js"],
+        (OutputFormat::Js, ScriptExtension::Preserve) => &["js", "ts", "tsx", "jsx"],
+        (OutputFormat::Json, _) => &["json"],
+        (OutputFormat::Stats, _) => return,
+    };
+    for ext in extensions {
+        remove_stale_output(&out_path.with_extension(ext));
+        remove_stale_output(&out_path.with_extension(format!("{}.map", ext)));
+    }
+    if matches!(args.format, OutputFormat::Js) {
+        remove_stale_output(&out_path.with_extension("css"));
+        remove_stale_output(&out_path.with_extension("css.map"));
+    }
+}
+
 fn detect_script_lang(source: &str) -> String {
     let script_pattern = regex_lite::Regex::new(r#"<script[^>]*\blang\s*=\s*["']([^"']+)["']"#)
         .expect("Invalid regex");
@@ -330,11 +525,7 @@ fn detect_script_lang(source: &str) -> String {
     "js".to_string()
 }
 
-fn compile_file(
-    path: &PathBuf,
-    ssr: bool,
-    script_ext: ScriptExtension,
-) -> Result<CompileOutput, String> {
+fn compile_file(path: &PathBuf, args: &BuildArgs) -> Result<CompileOutput, String> {
     let source = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
 
     let filename = path
@@ -345,51 +536,81 @@ fn compile_file(
 
     let script_lang = detect_script_lang(&source);
 
+    // The full path lets `src` attributes and type imports resolve next to the file
+    let path_str = path.to_string_lossy().into_owned();
     let parse_opts = SfcParseOptions {
-        filename: filename.clone(),
+        filename: path_str.clone(),
         ..Default::default()
     };
 
     let descriptor = parse_sfc(&source, parse_opts).map_err(|e| e.message)?;
 
     let has_scoped = descriptor.styles.iter().any(|s| s.scoped);
-    let is_ts = matches!(script_ext, ScriptExtension::Preserve);
+    let is_ts = matches!(args.script_ext, ScriptExtension::Preserve);
     let compile_opts = SfcCompileOptions {
         parse: SfcParseOptions {
-            filename: filename.clone(),
+            filename: path_str.clone(),
+            source_map: args.source_map,
             ..Default::default()
         },
         script: ScriptCompileOptions {
             id: Some(filename.clone()),
             is_ts,
+            strict: args.strict,
             ..Default::default()
         },
         template: TemplateCompileOptions {
             id: Some(filename.clone()),
             scoped: has_scoped,
-            ssr,
+            ssr: args.ssr,
             is_ts,
             ..Default::default()
         },
         style: StyleCompileOptions {
             id: filename.clone(),
             scoped: has_scoped,
+            filename: path_str,
+            source_map: args.source_map,
             ..Default::default()
         },
+        // Hash the path relative to the project root so same-named files in
+        // different directories get different scope IDs, wherever vize runs from
+        scope_id: Some(generate_scope_id(
+            &canonical(path).to_string_lossy(),
+            canonical(&args.root).to_str(),
+            &source,
+            false,
+        )),
+        ..Default::default()
     };
 
-    let result = compile_sfc(&descriptor, compile_opts).map_err(|e| e.message)?;
+    let result = compile_sfc(&descriptor, compile_opts).map_err(format_error)?;
 
     Ok(CompileOutput {
         filename,
         code: result.code,
         css: result.css,
-        errors: result.errors.into_iter().map(|e| e.message).collect(),
+        map: result.map,
+        css_map: result.css_map,
+        errors: result.errors.into_iter().map(format_error).collect(),
         warnings: result.warnings.into_iter().map(|e| e.message).collect(),
         script_lang,
+        dependencies: result
+            .dependencies
+            .iter()
+            .map(|dependency| canonical(Path::new(dependency)))
+            .collect(),
     })
 }
 
+/// Error message prefixed with its `line:column` in the SFC, if known
+fn format_error(error: SfcError) -> String {
+    match error.loc {
+        Some(loc) => format!("{}:{}: {}", loc.start_line, loc.start_column, error.message),
+        None => error.message,
+    }
+}
+
 fn get_output_extension(script_lang: &str, script_ext: ScriptExtension) -> &'static str {
     match script_ext {
         ScriptExtension::Downcompile => "js",
@@ -401,3 +622,41 @@ fn get_output_extension(script_lang: &str, script_ext: ScriptExtension) -> &'sta
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_path() {
+        let root = Path::new("/project");
+        assert_eq!(
+            output_path(Path::new("/project/src/components/A.vue"), root),
+            PathBuf::from("src/components/A.vue")
+        );
+        // Files outside of the root go to the top of the output directory
+        assert_eq!(
+            output_path(Path::new("/elsewhere/B.vue"), root),
+            PathBuf::from("B.vue")
+        );
+    }
+
+    #[test]
+    fn test_relative_path() {
+        assert_eq!(
+            relative_path(
+                Path::new("/project/dist/src"),
+                Path::new("/project/src/A.vue")
+            ),
+            PathBuf::from("../../src/A.vue")
+        );
+        assert_eq!(
+            relative_path(Path::new("/project/src"), Path::new("/project/src/A.vue")),
+            PathBuf::from("A.vue")
+        );
+        assert_eq!(
+            relative_path(Path::new("/project"), Path::new("/project/src/A.vue")),
+            PathBuf::from("src/A.vue")
+        );
+    }
+}
@@ -0,0 +1,335 @@
+//! Watch mode - Recompile Vue SFC files as they change
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use super::{
+    canonical, collect_files, compile_file, output_path, parse_pattern, remove_outputs,
+    write_css_bundle, write_outputs, BuildArgs,
+};
+
+/// How long to wait for more changes before recompiling, so that bursts of
+/// events from a single save or a checkout are handled together
+const DEBOUNCE: Duration = Duration::from_millis(50);
+
+struct WatchState {
+    /// Matched files by absolute path, mapped to the path they were found at
+    files: HashMap<PathBuf, PathBuf>,
+    /// Files loaded through `src` attributes by each SFC, by absolute path
+    dependencies: HashMap<PathBuf, Vec<PathBuf>>,
+    /// Directories being watched, by absolute path
+    watched: HashMap<PathBuf, RecursiveMode>,
+    css_bundle: BTreeMap<PathBuf, String>,
+}
+
+/// Watch the matched files and their `src` dependencies, recompiling the
+/// affected files on every change until interrupted
+pub(super) fn run(
+    args: &BuildArgs,
+    files: Vec<PathBuf>,
+    dependencies: HashMap<PathBuf, Vec<PathBuf>>,
+    css_bundle: BTreeMap<PathBuf, String>,
+) {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = match notify::recommended_watcher(tx) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            eprintln!("Failed to start watching: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut state = WatchState {
+        files: files
+            .into_iter()
+            .map(|path| (canonical(&path), path))
+            .collect(),
+        dependencies,
+        watched: HashMap::new(),
+        css_bundle,
+    };
+    for pattern in &args.patterns {
+        let (root, _) = parse_pattern(pattern);
+        let root = canonical(Path::new(&root));
+        watch_dir(&mut watcher, &mut state, root, RecursiveMode::Recursive);
+    }
+    watch_dependencies(&mut watcher, &mut state);
+
+    eprintln!("Watching for changes...");
+
+    while let Ok(event) = rx.recv() {
+        let mut events = vec![event];
+        while let Ok(event) = rx.recv_timeout(DEBOUNCE) {
+            events.push(event);
+        }
+
+        let changed: HashSet<PathBuf> = events
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|event| !matches!(event.kind, EventKind::Access(_)))
+            .flat_map(|event| event.paths)
+            .map(|path| canonical(&path))
+            .collect();
+
+        let to_compile = affected_files(args, &mut state, &changed);
+        if to_compile.is_empty() {
+            continue;
+        }
+        rebuild(args, &mut state, to_compile);
+        watch_dependencies(&mut watcher, &mut state);
+    }
+}
+
+/// Files to recompile after `changed` paths changed. New and removed SFCs
+/// are picked up by matching the patterns again.
+fn affected_files(
+    args: &BuildArgs,
+    state: &mut WatchState,
+    changed: &HashSet<PathBuf>,
+) -> Vec<PathBuf> {
+    let is_vue = |path: &PathBuf| path.extension().is_some_and(|ext| ext == "vue");
+    let mut to_compile: HashSet<PathBuf> = HashSet::new();
+
+    let files_changed = changed
+        .iter()
+        .any(|path| is_vue(path) && state.files.contains_key(path) != path.exists());
+    if files_changed {
+        let files: HashMap<_, _> = collect_files(&args.patterns)
+            .into_iter()
+            .map(|path| (canonical(&path), path))
+            .collect();
+        let root = canonical(&args.root);
+        let mut removed_any = false;
+        for removed in state.files.keys().filter(|path| !files.contains_key(*path)) {
+            state.dependencies.remove(removed);
+            state.css_bundle.remove(&output_path(removed, &root));
+            remove_outputs(removed, args, &root);
+            eprintln!("- {}", display_path(removed));
+            removed_any = true;
+        }
+        if removed_any {
+            write_css_bundle(args, &state.css_bundle);
+        }
+        to_compile.extend(
+            files
+                .keys()
+                .filter(|path| !state.files.contains_key(*path))
+                .cloned(),
+        );
+        state.files = files;
+    }
+
+    to_compile.extend(
+        changed
+            .iter()
+            .filter(|path| state.files.contains_key(*path))
+            .cloned(),
+    );
+    to_compile.extend(
+        state
+            .dependencies
+            .iter()
+            .filter(|(_, dependencies)| dependencies.iter().any(|dep| changed.contains(dep)))
+            .map(|(path, _)| path.clone()),
+    );
+
+    let mut to_compile: Vec<_> = to_compile.into_iter().collect();
+    to_compile.sort();
+    to_compile
+}
+
+/// Recompile `files` on the warm rayon pool and write their outputs
+fn rebuild(args: &BuildArgs, state: &mut WatchState, files: Vec<PathBuf>) {
+    let start = Instant::now();
+    let results: Vec<_> = files
+        .par_iter()
+        .map(|path| {
+            let source = state.files.get(path).unwrap_or(path);
+            let file_start = Instant::now();
+            let result = compile_file(source, args);
+            (path, source, result, file_start.elapsed())
+        })
+        .collect();
+
+    let mut outputs = Vec::new();
+    let mut failed = 0;
+    for (path, source, result, elapsed) in results {
+        let millis = elapsed.as_secs_f64() * 1000.0;
+        match result {
+            Ok(output) => {
+                state
+                    .dependencies
+                    .insert(path.clone(), output.dependencies.clone());
+                if output.errors.is_empty() {
+                    eprintln!("✓ {} ({:.1}ms)", display_path(path), millis);
+                    outputs.push((source.clone(), output));
+                } else {
+                    failed += 1;
+                    eprintln!("✗ {} ({:.1}ms)", display_path(path), millis);
+                    for error in &output.errors {
+                        eprintln!("  error: {}", error);
+                    }
+                }
+            }
+            Err(e) => {
+                failed += 1;
+                eprintln!("✗ {} ({:.1}ms): {}", display_path(path), millis, e);
+            }
+        }
+    }
+    write_outputs(outputs, args, &mut state.css_bundle);
+
+    let file_word = if files.len() == 1 { "file" } else { "files" };
+    if failed > 0 {
+        eprintln!(
+            "✗ {} of {} {} failed in {:.1}ms",
+            failed,
+            files.len(),
+            file_word,
+            start.elapsed().as_secs_f64() * 1000.0
+        );
+    } else {
+        eprintln!(
+            "✓ {} {} rebuilt in {:.1}ms",
+            files.len(),
+            file_word,
+            start.elapsed().as_secs_f64() * 1000.0
+        );
+    }
+}
+
+/// Watch the directories of `src` dependencies outside of the watched trees
+fn watch_dependencies(watcher: &mut RecommendedWatcher, state: &mut WatchState) {
+    let dirs: HashSet<PathBuf> = state
+        .dependencies
+        .values()
+        .flatten()
+        .filter_map(|dependency| dependency.parent())
+        .map(Path::to_path_buf)
+        .collect();
+    for dir in dirs {
+        let covered = state.watched.iter().any(|(watched, mode)| {
+            dir == *watched || (*mode == RecursiveMode::Recursive && dir.starts_with(watched))
+        });
+        if !covered {
+            watch_dir(watcher, state, dir, RecursiveMode::NonRecursive);
+        }
+    }
+}
+
+fn watch_dir(
+    watcher: &mut RecommendedWatcher,
+    state: &mut WatchState,
+    dir: PathBuf,
+    mode: RecursiveMode,
+) {
+    match watcher.watch(&dir, mode) {
+        Ok(()) => {
+            state.watched.insert(dir, mode);
+        }
+        Err(e) => eprintln!("Failed to watch {}: {}", dir.display(), e),
+    }
+}
+
+/// `path` relative to the working directory when it is inside of it
+fn display_path(path: &Path) -> String {
+    std::env::current_dir()
+        .ok()
+        .and_then(|dir| {
+            Some(
+                path.strip_prefix(canonical(&dir))
+                    .ok()?
+                    .display()
+                    .to_string(),
+            )
+        })
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// A project with two SFCs, `A.vue` loading `a.css` through `src`
+    fn fixture(name: &str) -> (BuildArgs, WatchState) {
+        let dir = std::env::temp_dir().join(format!("vize-watch-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src")).unwrap();
+        let dir = canonical(&dir);
+        fs::write(dir.join("src/A.vue"), "<style src=\"./a.css\"></style>").unwrap();
+        fs::write(dir.join("src/B.vue"), "<template><b /></template>").unwrap();
+        fs::write(dir.join("src/a.css"), ".a {}").unwrap();
+
+        let args = BuildArgs {
+            patterns: vec![format!("{}/**/*.vue", dir.display())],
+            output: dir.join("out"),
+            root: dir.clone(),
+            ..Default::default()
+        };
+        let state = WatchState {
+            files: collect_files(&args.patterns)
+                .into_iter()
+                .map(|path| (canonical(&path), path))
+                .collect(),
+            dependencies: HashMap::from([(dir.join("src/A.vue"), vec![dir.join("src/a.css")])]),
+            watched: HashMap::new(),
+            css_bundle: BTreeMap::new(),
+        };
+        (args, state)
+    }
+
+    #[test]
+    fn test_affected_files_changed_and_dependencies() {
+        let (args, mut state) = fixture("changed");
+        let src = args.root.join("src");
+
+        let changed = HashSet::from([src.join("B.vue")]);
+        assert_eq!(
+            affected_files(&args, &mut state, &changed),
+            vec![src.join("B.vue")]
+        );
+
+        let changed = HashSet::from([src.join("a.css")]);
+        assert_eq!(
+            affected_files(&args, &mut state, &changed),
+            vec![src.join("A.vue")]
+        );
+
+        let changed = HashSet::from([src.join("unrelated.css")]);
+        assert!(affected_files(&args, &mut state, &changed).is_empty());
+
+        let _ = fs::remove_dir_all(&args.root);
+    }
+
+    #[test]
+    fn test_affected_files_rename() {
+        let (args, mut state) = fixture("rename");
+        let src = args.root.join("src");
+        let out = args.output.join("src");
+        fs::create_dir_all(&out).unwrap();
+        for output in ["A.js", "A.js.map", "A.css", "A.css.map", "B.js"] {
+            fs::write(out.join(output), "").unwrap();
+        }
+
+        fs::rename(src.join("A.vue"), src.join("C.vue")).unwrap();
+        let changed = HashSet::from([src.join("A.vue"), src.join("C.vue")]);
+        assert_eq!(
+            affected_files(&args, &mut state, &changed),
+            vec![src.join("C.vue")]
+        );
+
+        assert!(!state.files.contains_key(&src.join("A.vue")));
+        assert!(!state.dependencies.contains_key(&src.join("A.vue")));
+        for output in ["A.js", "A.js.map", "A.css", "A.css.map"] {
+            assert!(!out.join(output).exists(), "{} was not removed", output);
+        }
+        assert!(out.join("B.js").exists());
+
+        let _ = fs::remove_dir_all(&args.root);
+    }
+}
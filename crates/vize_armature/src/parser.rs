@@ -61,6 +61,7 @@ struct CurrentAttribute<'a> {
     name: String,
     name_start: usize,
     name_end: usize,
+    value: String,
     value_start: Option<usize>,
     value_end: Option<usize>,
     _marker: std::marker::PhantomData<&'a ()>,
@@ -75,6 +76,7 @@ struct CurrentDirective<'a> {
     name_end: usize,
     arg: Option<(String, usize, usize, bool)>, // (content, start, end, is_dynamic)
     modifiers: Vec<'a, (String, usize, usize)>,
+    value: String,
     value_start: Option<usize>,
     value_end: Option<usize>,
     _marker: std::marker::PhantomData<&'a ()>,
@@ -88,6 +90,14 @@ impl<'a> Parser<'a> {
 
     /// Create a new parser with options
     pub fn with_options(allocator: &'a Bump, source: &'a str, options: ParserOptions) -> Self {
+        let mut newlines = Vec::new_in(allocator);
+        newlines.extend(
+            source
+                .bytes()
+                .enumerate()
+                .filter(|&(_, b)| b == b'\n')
+                .map(|(i, _)| i),
+        );
         Self {
             allocator,
             source,
@@ -98,7 +108,7 @@ impl<'a> Parser<'a> {
             current_attr: None,
             current_dir: None,
             errors: Vec::new_in(allocator),
-            newlines: Vec::new_in(allocator),
+            newlines,
             in_pre: false,
             in_v_pre: false,
         }
@@ -194,15 +204,43 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Get the last child of the current context
+    fn last_child_mut(&mut self) -> Option<&mut TemplateChildNode<'a>> {
+        match self.stack.last_mut() {
+            Some(entry) => entry.element.children.last_mut(),
+            None => self.root.as_mut()?.children.last_mut(),
+        }
+    }
+
     /// Process text content
     fn on_text_impl(&mut self, start: usize, end: usize) {
         if start >= end {
             return;
         }
 
-        let content = self.get_source(start, end);
-        let loc = self.create_loc(start, end);
+        let source = self.source;
+        self.append_text(&source[start..end], start, end);
+    }
 
+    /// Process a decoded character reference in text
+    fn on_text_entity_impl(&mut self, char: char, start: usize, end: usize) {
+        let mut buf = [0; 4];
+        self.append_text(char.encode_utf8(&mut buf), start, end);
+    }
+
+    /// Add text, merging it into a preceding text node
+    fn append_text(&mut self, content: &str, start: usize, end: usize) {
+        if let Some(TemplateChildNode::Text(text)) = self.last_child_mut() {
+            let text_start = text.loc.start.offset as usize;
+            let loc = self.create_loc(text_start, end);
+            if let Some(TemplateChildNode::Text(text)) = self.last_child_mut() {
+                text.content.push_str(content);
+                text.loc = loc;
+            }
+            return;
+        }
+
+        let loc = self.create_loc(start, end);
         let text_node = TextNode::new(content, loc);
         let boxed = Box::new_in(text_node, self.allocator);
         self.add_child(TemplateChildNode::Text(boxed));
@@ -408,6 +446,7 @@ impl<'a> Parser<'a> {
             name: name.into(),
             name_start: start,
             name_end: end,
+            value: String::default(),
             value_start: None,
             value_end: None,
             _marker: std::marker::PhantomData,
@@ -426,6 +465,7 @@ impl<'a> Parser<'a> {
             name_end: end,
             arg: None,
             modifiers: Vec::new_in(self.allocator),
+            value: String::default(),
             value_start: None,
             value_end: None,
             _marker: std::marker::PhantomData,
@@ -452,13 +492,27 @@ impl<'a> Parser<'a> {
 
     /// Process attribute data (value content)
     fn on_attrib_data_impl(&mut self, start: usize, end: usize) {
+        let source = self.source;
+        self.append_attr_value(&source[start..end], start, end);
+    }
+
+    /// Process a decoded character reference in an attribute value
+    fn on_attrib_entity_impl(&mut self, char: char, start: usize, end: usize) {
+        let mut buf = [0; 4];
+        self.append_attr_value(char.encode_utf8(&mut buf), start, end);
+    }
+
+    /// Append to the value of the current attribute or directive
+    fn append_attr_value(&mut self, content: &str, start: usize, end: usize) {
         if let Some(ref mut attr) = self.current_attr {
+            attr.value.push_str(content);
             if attr.value_start.is_none() {
                 attr.value_start = Some(start);
             }
             attr.value_end = Some(end);
         }
         if let Some(ref mut dir) = self.current_dir {
+            dir.value.push_str(content);
             if dir.value_start.is_none() {
                 dir.value_start = Some(start);
             }
@@ -489,9 +543,8 @@ impl<'a> Parser<'a> {
 
         // Add value if present
         if let (Some(v_start), Some(v_end)) = (attr.value_start, attr.value_end) {
-            let value_content = self.get_source(v_start, v_end);
             let value_loc = self.create_loc(v_start, v_end);
-            attr_node.value = Some(TextNode::new(value_content, value_loc));
+            attr_node.value = Some(TextNode::new(attr.value, value_loc));
         }
 
         if let Some(ref mut current) = self.current_element {
@@ -527,9 +580,8 @@ impl<'a> Parser<'a> {
 
         // Add expression if present
         if let (Some(v_start), Some(v_end)) = (dir.value_start, dir.value_end) {
-            let exp_content = self.get_source(v_start, v_end);
             let exp_loc = self.create_loc(v_start, v_end);
-            let exp_node = SimpleExpressionNode::new(exp_content, false, exp_loc);
+            let exp_node = SimpleExpressionNode::new(dir.value, false, exp_loc);
             let exp_boxed = Box::new_in(exp_node, self.allocator);
             dir_node.exp = Some(ExpressionNode::Simple(exp_boxed));
         }
@@ -593,8 +645,7 @@ impl<'a, 'p> Callbacks for ParserCallbacks<'a, 'p> {
     }
 
     fn on_text_entity(&mut self, char: char, start: usize, end: usize) {
-        // For now, treat entities as regular text
-        let _ = (char, start, end);
+        self.parser.on_text_entity_impl(char, start, end);
     }
 
     fn on_interpolation(&mut self, start: usize, end: usize) {
@@ -622,8 +673,8 @@ impl<'a, 'p> Callbacks for ParserCallbacks<'a, 'p> {
         self.parser.on_attrib_data_impl(start, end);
     }
 
-    fn on_attrib_entity(&mut self, _char: char, _start: usize, _end: usize) {
-        // For now, ignore entity in attributes
+    fn on_attrib_entity(&mut self, char: char, start: usize, end: usize) {
+        self.parser.on_attrib_entity_impl(char, start, end);
     }
 
     fn on_attrib_end(&mut self, quote: QuoteType, end: usize) {
@@ -678,8 +729,8 @@ fn condense_whitespace<'a>(children: &mut Vec<'a, TemplateChildNode<'a>>) {
         if let TemplateChildNode::Text(ref text) = children[i] {
             let content = text.content.as_str();
 
-            // Check if text is all whitespace
-            if content.chars().all(char::is_whitespace) {
+            // Check if text is all whitespace (`&nbsp;` and friends are content)
+            if content.bytes().all(|b| b.is_ascii_whitespace()) {
                 // Check neighbors
                 let prev_is_text = i > 0
                     && matches!(
@@ -869,4 +920,87 @@ mod tests {
             assert!(el.is_self_closing);
         }
     }
+
+    #[test]
+    fn test_parse_text_entities() {
+        let allocator = Bump::new();
+        let (root, errors) = parse(
+            &allocator,
+            "<p>a &amp; b &lt;c&gt; &#x27;d&#39; &copy 2 & e</p>",
+        );
+
+        assert!(errors.is_empty());
+        if let TemplateChildNode::Element(el) = &root.children[0] {
+            assert_eq!(el.children.len(), 1);
+            if let TemplateChildNode::Text(text) = &el.children[0] {
+                assert_eq!(text.content.as_str(), "a & b <c> 'd' \u{A9} 2 & e");
+                assert_eq!(text.loc.start.offset, 3);
+                assert_eq!(
+                    text.loc.source.as_str(),
+                    "a &amp; b &lt;c&gt; &#x27;d&#39; &copy 2 & e"
+                );
+            } else {
+                panic!("Expected text node");
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_entity_around_interpolation() {
+        let allocator = Bump::new();
+        let (root, _) = parse(&allocator, "<p>&nbsp;{{ a &amp;&amp; b }}&nbsp;</p>");
+
+        if let TemplateChildNode::Element(el) = &root.children[0] {
+            assert_eq!(el.children.len(), 3);
+            if let TemplateChildNode::Text(text) = &el.children[0] {
+                assert_eq!(text.content.as_str(), "\u{A0}");
+            }
+            // Interpolations are JavaScript, not HTML
+            if let TemplateChildNode::Interpolation(interp) = &el.children[1] {
+                if let ExpressionNode::Simple(expr) = &interp.content {
+                    assert_eq!(expr.content.as_str(), "a &amp;&amp; b");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_nbsp_is_not_condensed() {
+        let allocator = Bump::new();
+        let (root, _) = parse(&allocator, "<div><span>a</span>&nbsp;<span>b</span></div>");
+
+        if let TemplateChildNode::Element(el) = &root.children[0] {
+            assert_eq!(el.children.len(), 3);
+        }
+    }
+
+    #[test]
+    fn test_parse_attribute_entities() {
+        let allocator = Bump::new();
+        let (root, errors) = parse(
+            &allocator,
+            r#"<a title="Tom &amp; Jerry&#33;" href="?a=1&copy=2&amp;b" :class="a &amp;&amp; b" data-x='&quot;'></a>"#,
+        );
+
+        assert!(errors.is_empty());
+        if let TemplateChildNode::Element(el) = &root.children[0] {
+            let values: std::vec::Vec<&str> = el
+                .props
+                .iter()
+                .map(|prop| match prop {
+                    PropNode::Attribute(attr) => attr.value.as_ref().unwrap().content.as_str(),
+                    PropNode::Directive(dir) => match &dir.exp {
+                        Some(ExpressionNode::Simple(exp)) => exp.content.as_str(),
+                        _ => "",
+                    },
+                })
+                .collect();
+            assert_eq!(
+                values,
+                vec!["Tom & Jerry!", "?a=1&copy=2&b", "a && b", "\""]
+            );
+        } else {
+            panic!("Expected element node");
+        }
+    }
 }
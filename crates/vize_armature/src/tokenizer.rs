@@ -3,6 +3,10 @@
 //! This tokenizer is adapted from htmlparser2 and Vue's compiler-core.
 //! It uses a state machine to tokenize HTML/Vue templates.
 
+mod entities;
+
+pub use entities::{decode_entity, Entity};
+
 use vize_relief::{ErrorCode, Position};
 
 /// Character codes for fast comparison
@@ -185,6 +189,10 @@ pub struct Tokenizer<'a, C: Callbacks> {
     in_pre: bool,
     /// In v-pre
     in_v_pre: bool,
+    /// State to return to after a character reference
+    base_state: State,
+    /// Start index of the current character reference (`&`)
+    entity_start: usize,
 }
 
 impl<'a, C: Callbacks> Tokenizer<'a, C> {
@@ -212,6 +220,8 @@ impl<'a, C: Callbacks> Tokenizer<'a, C> {
             delimiter_index: 0,
             in_pre: false,
             in_v_pre: false,
+            base_state: State::Text,
+            entity_start: 0,
         }
     }
 
@@ -292,6 +302,11 @@ impl<'a, C: Callbacks> Tokenizer<'a, C> {
     }
 
     fn cleanup(&mut self) {
+        // A trailing `&` is plain text (or attribute data)
+        if self.state == State::InEntity {
+            self.state = self.base_state;
+        }
+
         if self.section_start < self.index {
             match self.state {
                 State::Text | State::Interpolation => {
@@ -332,6 +347,8 @@ impl<'a, C: Callbacks> Tokenizer<'a, C> {
             }
             self.state = State::BeforeTagName;
             self.section_start = self.index;
+        } else if c == AMP {
+            self.start_entity();
         } else if !self.in_v_pre && c == self.delimiter_open[0] {
             self.state = State::InterpolationOpen;
             self.delimiter_index = 0;
@@ -596,12 +613,16 @@ impl<'a, C: Callbacks> Tokenizer<'a, C> {
     fn state_in_attr_value_dq(&mut self, c: u8) {
         if c == DOUBLE_QUOTE {
             self.emit_attr_value(QuoteType::Double);
+        } else if c == AMP {
+            self.start_entity();
         }
     }
 
     fn state_in_attr_value_sq(&mut self, c: u8) {
         if c == SINGLE_QUOTE {
             self.emit_attr_value(QuoteType::Single);
+        } else if c == AMP {
+            self.start_entity();
         }
     }
 
@@ -611,6 +632,8 @@ impl<'a, C: Callbacks> Tokenizer<'a, C> {
             self.state_before_attr_name(c);
         } else if c == SLASH {
             self.emit_attr_value(QuoteType::Unquoted);
+        } else if c == AMP {
+            self.start_entity();
         }
     }
 
@@ -710,9 +733,56 @@ impl<'a, C: Callbacks> Tokenizer<'a, C> {
         }
     }
 
-    fn state_in_entity(&mut self, _c: u8) {
-        // TODO: Implement entity decoding
-        self.state = State::Text;
+    fn start_entity(&mut self) {
+        self.base_state = self.state;
+        self.entity_start = self.index;
+        self.state = State::InEntity;
+    }
+
+    fn state_in_entity(&mut self, c: u8) {
+        self.state = self.base_state;
+        let in_attribute = matches!(
+            self.base_state,
+            State::InAttrValueDq | State::InAttrValueSq | State::InAttrValueNq
+        );
+
+        let Some(entity) = decode_entity(&self.input[self.entity_start..], in_attribute) else {
+            // Not a character reference: the `&` stays part of the text
+            self.state_base(c);
+            return;
+        };
+
+        let start = self.entity_start;
+        let end = start + entity.len;
+        if in_attribute {
+            if self.section_start < start {
+                self.callbacks.on_attrib_data(self.section_start, start);
+            }
+            for &decoded in entity.chars() {
+                self.callbacks.on_attrib_entity(decoded, start, end);
+            }
+        } else {
+            if self.section_start < start {
+                self.callbacks.on_text(self.section_start, start);
+            }
+            for &decoded in entity.chars() {
+                self.callbacks.on_text_entity(decoded, start, end);
+            }
+        }
+
+        // Character references never contain newlines, so skipping is safe
+        self.section_start = end;
+        self.index = end - 1;
+    }
+
+    /// Re-dispatch a character in the state an entity started from
+    fn state_base(&mut self, c: u8) {
+        match self.state {
+            State::InAttrValueDq => self.state_in_attr_value_dq(c),
+            State::InAttrValueSq => self.state_in_attr_value_sq(c),
+            State::InAttrValueNq => self.state_in_attr_value_nq(c),
+            _ => self.state_text(c),
+        }
     }
 
     fn state_in_sfc_root_tag_name(&mut self, c: u8) {
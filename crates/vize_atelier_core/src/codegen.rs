@@ -11,11 +11,14 @@ mod node;
 mod patch_flag;
 mod props;
 mod slots;
+mod source_map;
+mod ssr;
 mod v_for;
 mod v_if;
 
 use crate::ast::*;
 use crate::options::CodegenOptions;
+use crate::runtime_helpers::{SsrHelper, SSR_HELPER_MODULE};
 
 pub use context::{CodegenContext, CodegenResult};
 use element::generate_root_node;
@@ -25,6 +28,8 @@ use node::generate_node;
 /// Generate code from root AST
 pub fn generate(root: &RootNode<'_>, options: CodegenOptions) -> CodegenResult {
     let mut ctx = CodegenContext::new(options);
+    // Slots allocated during transform (cached handlers) come first
+    ctx.cache_index = root.cached.len();
 
     // Generate function signature
     generate_function_signature(&mut ctx);
@@ -36,38 +41,11 @@ pub fn generate(root: &RootNode<'_>, options: CodegenOptions) -> CodegenResult {
     // Generate component/directive resolution
     generate_assets(&mut ctx, root);
 
-    // Generate return statement
-    ctx.push("return ");
-
-    // Generate root node
-    if root.children.is_empty() {
-        ctx.push("null");
-    } else if root.children.len() == 1 {
-        // Single root child - wrap in block
-        generate_root_node(&mut ctx, &root.children[0]);
+    if ctx.ssr {
+        // SSR renders by pushing markup instead of returning VNodes
+        ssr::generate_ssr_body(&mut ctx, root);
     } else {
-        // Multiple root children - wrap in fragment block
-        ctx.use_helper(RuntimeHelper::OpenBlock);
-        ctx.use_helper(RuntimeHelper::CreateElementBlock);
-        ctx.use_helper(RuntimeHelper::Fragment);
-        ctx.push("(");
-        ctx.push(ctx.helper(RuntimeHelper::OpenBlock));
-        ctx.push("(), ");
-        ctx.push(ctx.helper(RuntimeHelper::CreateElementBlock));
-        ctx.push("(");
-        ctx.push(ctx.helper(RuntimeHelper::Fragment));
-        ctx.push(", null, [");
-        ctx.indent();
-        for (i, child) in root.children.iter().enumerate() {
-            if i > 0 {
-                ctx.push(",");
-            }
-            ctx.newline();
-            generate_node(&mut ctx, child);
-        }
-        ctx.deindent();
-        ctx.newline();
-        ctx.push("], 64 /* STABLE_FRAGMENT */))");
+        generate_render_return(&mut ctx, root);
     }
 
     ctx.deindent();
@@ -79,6 +57,18 @@ pub fn generate(root: &RootNode<'_>, options: CodegenOptions) -> CodegenResult {
     // added during transform but not tracked during codegen (like Unref)
     // We don't merge ALL root.helpers because transform may add helpers that
     // get optimized away during codegen (e.g., createElementVNode -> createElementBlock)
+    // Hoists are generated after the preamble, so register their helpers up front
+    for hoist in root.hoists.iter().flatten() {
+        match hoist {
+            JsChildNode::VNodeCall(_) => ctx.use_helper(RuntimeHelper::CreateElementVNode),
+            JsChildNode::Call(call) => {
+                if let Callee::Symbol(helper) = call.callee {
+                    ctx.use_helper(helper);
+                }
+            }
+            _ => {}
+        }
+    }
     let mut all_helpers: Vec<RuntimeHelper> = ctx.used_helpers.iter().copied().collect();
     if root.helpers.contains(&RuntimeHelper::Unref) && !all_helpers.contains(&RuntimeHelper::Unref)
     {
@@ -88,6 +78,7 @@ pub fn generate(root: &RootNode<'_>, options: CodegenOptions) -> CodegenResult {
     all_helpers.sort();
 
     let mut preamble = generate_preamble_from_helpers(&ctx, &all_helpers);
+    preamble.push_str(&generate_ssr_preamble(&ctx));
 
     // Generate hoisted variable declarations (appended to preamble)
     let hoists_code = generate_hoists(&ctx, root);
@@ -96,10 +87,55 @@ pub fn generate(root: &RootNode<'_>, options: CodegenOptions) -> CodegenResult {
         preamble.push_str(&hoists_code);
     }
 
+    let map = ctx.options.source_map.then(|| {
+        source_map::generate_source_map(
+            &ctx.code,
+            &ctx.mappings,
+            &ctx.options.filename,
+            &root.source,
+        )
+    });
+
     CodegenResult {
         code: ctx.into_code(),
         preamble,
-        map: None,
+        map,
+    }
+}
+
+/// Generate the `return` statement of a client render function
+fn generate_render_return(ctx: &mut CodegenContext, root: &RootNode<'_>) {
+    ctx.push("return ");
+
+    // Generate root node
+    if root.children.is_empty() {
+        ctx.push("null");
+    } else if root.children.len() == 1 {
+        // Single root child - wrap in block
+        generate_root_node(ctx, &root.children[0]);
+    } else {
+        // Multiple root children - wrap in fragment block
+        ctx.use_helper(RuntimeHelper::OpenBlock);
+        ctx.use_helper(RuntimeHelper::CreateElementBlock);
+        ctx.use_helper(RuntimeHelper::Fragment);
+        ctx.push("(");
+        ctx.push(ctx.helper(RuntimeHelper::OpenBlock));
+        ctx.push("(), ");
+        ctx.push(ctx.helper(RuntimeHelper::CreateElementBlock));
+        ctx.push("(");
+        ctx.push(ctx.helper(RuntimeHelper::Fragment));
+        ctx.push(", null, [");
+        ctx.indent();
+        for (i, child) in root.children.iter().enumerate() {
+            if i > 0 {
+                ctx.push(",");
+            }
+            ctx.newline();
+            generate_node(ctx, child);
+        }
+        ctx.deindent();
+        ctx.newline();
+        ctx.push("], 64 /* STABLE_FRAGMENT */))");
     }
 }
 
@@ -150,10 +186,60 @@ fn generate_preamble_from_helpers(ctx: &CodegenContext, helpers: &[RuntimeHelper
     unsafe { String::from_utf8_unchecked(preamble) }
 }
 
+/// Generate the server renderer import for helpers used during SSR codegen
+fn generate_ssr_preamble(ctx: &CodegenContext) -> String {
+    if ctx.used_ssr_helpers.is_empty() {
+        return String::new();
+    }
+
+    let mut helpers: Vec<SsrHelper> = ctx.used_ssr_helpers.iter().copied().collect();
+    helpers.sort();
+
+    let mut preamble = String::with_capacity(48 + helpers.len() * 40);
+    match ctx.options.mode {
+        crate::options::CodegenMode::Module => {
+            preamble.push_str("import { ");
+            for (i, h) in helpers.iter().enumerate() {
+                if i > 0 {
+                    preamble.push_str(", ");
+                }
+                preamble.push_str(h.name());
+                preamble.push_str(" as ");
+                preamble.push_str(h.alias());
+            }
+            preamble.push_str(" } from \"");
+            preamble.push_str(SSR_HELPER_MODULE);
+            preamble.push_str("\"\n");
+        }
+        crate::options::CodegenMode::Function => {
+            preamble.push_str("const { ");
+            for (i, h) in helpers.iter().enumerate() {
+                if i > 0 {
+                    preamble.push_str(", ");
+                }
+                preamble.push_str(h.name());
+                preamble.push_str(": ");
+                preamble.push_str(h.alias());
+            }
+            preamble.push_str(" } = require(\"");
+            preamble.push_str(SSR_HELPER_MODULE);
+            preamble.push_str("\")\n");
+        }
+    }
+    preamble
+}
+
 /// Generate function signature
 fn generate_function_signature(ctx: &mut CodegenContext) {
     if ctx.options.ssr {
-        ctx.push("function ssrRender(_ctx, _push, _parent, _attrs) {");
+        match ctx.options.mode {
+            crate::options::CodegenMode::Module => {
+                ctx.push("export function ssrRender(_ctx, _push, _parent, _attrs) {");
+            }
+            crate::options::CodegenMode::Function => {
+                ctx.push("function ssrRender(_ctx, _push, _parent, _attrs) {");
+            }
+        }
     } else {
         match ctx.options.mode {
             crate::options::CodegenMode::Module => {
@@ -177,8 +263,8 @@ fn generate_hoists(ctx: &CodegenContext, root: &RootNode<'_>) -> String {
             hoists_code.extend_from_slice(b"const _hoisted_");
             hoists_code.extend_from_slice((i + 1).to_string().as_bytes());
             hoists_code.extend_from_slice(b" = ");
-            // Only add /*#__PURE__*/ for VNodeCall (createElementVNode calls)
-            if matches!(node, JsChildNode::VNodeCall(_)) {
+            // Only add /*#__PURE__*/ for VNode creation calls
+            if matches!(node, JsChildNode::VNodeCall(_) | JsChildNode::Call(_)) {
                 hoists_code.extend_from_slice(b"/*#__PURE__*/ ");
             }
             generate_js_child_node_to_bytes(ctx, node, &mut hoists_code);
@@ -201,13 +287,34 @@ fn generate_js_child_node_to_bytes(
         JsChildNode::SimpleExpression(exp) => {
             if exp.is_static {
                 out.push(b'"');
-                out.extend_from_slice(exp.content.as_bytes());
+                out.extend_from_slice(escape_js_string(&exp.content).as_bytes());
                 out.push(b'"');
             } else {
                 // Expression should already be processed by transform
                 out.extend_from_slice(exp.content.as_bytes());
             }
         }
+        JsChildNode::Call(call) => {
+            match &call.callee {
+                Callee::String(name) => out.extend_from_slice(name.as_bytes()),
+                Callee::Symbol(helper) => out.extend_from_slice(ctx.helper(*helper).as_bytes()),
+            }
+            out.push(b'(');
+            for (i, arg) in call.arguments.iter().enumerate() {
+                if i > 0 {
+                    out.extend_from_slice(b", ");
+                }
+                match arg {
+                    CallArgument::String(s) => out.extend_from_slice(s.as_bytes()),
+                    CallArgument::Symbol(helper) => {
+                        out.extend_from_slice(ctx.helper(*helper).as_bytes())
+                    }
+                    CallArgument::JsChild(node) => generate_js_child_node_to_bytes(ctx, node, out),
+                    _ => out.extend_from_slice(b"null"),
+                }
+            }
+            out.push(b')');
+        }
         JsChildNode::Object(obj) => {
             out.extend_from_slice(b"{ ");
             for (i, prop) in obj.properties.iter().enumerate() {
@@ -1,7 +1,8 @@
 //! Code generation context and result types.
 
-use crate::ast::RuntimeHelper;
+use crate::ast::{Position, RuntimeHelper, SourceLocation};
 use crate::options::CodegenOptions;
+use crate::runtime_helpers::SsrHelper;
 
 use super::helpers::default_helper_alias;
 
@@ -12,7 +13,6 @@ pub struct CodegenContext {
     /// Current indentation level
     pub(super) indent_level: u32,
     /// Whether we're in SSR mode
-    #[allow(dead_code)]
     pub(super) ssr: bool,
     /// Helper function alias map
     pub(super) helper_alias: fn(RuntimeHelper) -> &'static str,
@@ -26,10 +26,14 @@ pub struct CodegenContext {
     pub(super) pure: bool,
     /// Helpers used during codegen
     pub(super) used_helpers: std::collections::HashSet<RuntimeHelper>,
-    /// Cache index for v-once
+    /// Server renderer helpers used during SSR codegen
+    pub(super) used_ssr_helpers: std::collections::HashSet<SsrHelper>,
+    /// Next free `_cache` slot
     pub(super) cache_index: usize,
     /// Slot parameters (identifiers that should not be prefixed with _ctx.)
     pub(super) slot_params: std::collections::HashSet<String>,
+    /// Source map mappings: generated byte offset -> template position
+    pub(super) mappings: Vec<(usize, Position)>,
 }
 
 /// Code generation result
@@ -55,11 +59,23 @@ impl CodegenContext {
             options,
             pure: false,
             used_helpers: std::collections::HashSet::new(),
+            used_ssr_helpers: std::collections::HashSet::new(),
             cache_index: 0,
             slot_params: std::collections::HashSet::new(),
+            mappings: Vec::new(),
         }
     }
 
+    /// Map the current output position to the start of a template location
+    #[inline]
+    pub fn add_mapping(&mut self, loc: &SourceLocation) {
+        // Synthesized nodes carry a stub location and have nothing to map to
+        if !self.options.source_map || loc.end.offset == 0 {
+            return;
+        }
+        self.mappings.push((self.code.len(), loc.start));
+    }
+
     /// Add slot parameters (identifiers that should not be prefixed)
     pub fn add_slot_params(&mut self, params: &[String]) {
         for param in params {
@@ -148,6 +164,13 @@ impl CodegenContext {
         self.used_helpers.insert(helper);
     }
 
+    /// Track a server renderer helper and get its alias
+    #[inline]
+    pub fn ssr_helper(&mut self, helper: SsrHelper) -> &'static str {
+        self.used_ssr_helpers.insert(helper);
+        helper.alias()
+    }
+
     /// Check if a component is in binding metadata (from script setup)
     pub fn is_component_in_bindings(&self, component: &str) -> bool {
         if let Some(ref metadata) = self.options.binding_metadata {
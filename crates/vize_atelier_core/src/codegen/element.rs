@@ -1019,6 +1019,7 @@ pub fn generate_element(ctx: &mut CodegenContext, el: &ElementNode<'_>) {
 
 /// Generate root node (wrapped in block)
 pub fn generate_root_node(ctx: &mut CodegenContext, node: &TemplateChildNode<'_>) {
+    ctx.add_mapping(node.loc());
     match node {
         TemplateChildNode::Element(el) => generate_element_block(ctx, el),
         TemplateChildNode::If(if_node) => generate_if(ctx, if_node),
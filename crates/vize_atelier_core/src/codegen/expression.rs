@@ -36,6 +36,7 @@ pub fn generate_simple_expression(ctx: &mut CodegenContext, exp: &SimpleExpressi
         ctx.push(&exp.content);
         ctx.push("\"");
     } else {
+        ctx.add_mapping(&exp.loc);
         // Strip TypeScript if needed
         if ctx.options.is_ts && exp.content.contains(" as ") {
             let stripped = crate::transforms::strip_typescript_from_expression(&exp.content);
@@ -96,7 +97,7 @@ pub fn generate_event_handler(ctx: &mut CodegenContext, exp: &ExpressionNode<'_>
             };
 
             // Check if it's already an arrow function or function expression
-            if processed.contains("=>") || processed.trim().starts_with("function") {
+            if crate::transforms::v_on::is_fn_expression(&processed) {
                 ctx.push(&processed);
                 return;
             }
@@ -112,9 +113,16 @@ pub fn generate_event_handler(ctx: &mut CodegenContext, exp: &ExpressionNode<'_>
             }
 
             // Compound expression (function call, etc.): wrap as $event => (expression)
-            ctx.push("$event => (");
-            ctx.push(&processed);
-            ctx.push(")");
+            // Multiple statements need a block body: $event => {a(); b()}
+            if processed.contains(';') {
+                ctx.push("$event => {");
+                ctx.push(&processed);
+                ctx.push("}");
+            } else {
+                ctx.push("$event => (");
+                ctx.push(&processed);
+                ctx.push(")");
+            }
         }
         ExpressionNode::Compound(comp) => {
             // For compound expressions, generate normally
@@ -10,6 +10,7 @@ use super::v_if::generate_if;
 
 /// Generate node code
 pub fn generate_node(ctx: &mut CodegenContext, node: &TemplateChildNode<'_>) {
+    ctx.add_mapping(node.loc());
     match node {
         TemplateChildNode::Element(el) => generate_element(ctx, el),
         TemplateChildNode::Text(text) => generate_text(ctx, text),
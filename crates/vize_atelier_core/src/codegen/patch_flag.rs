@@ -208,7 +208,8 @@ pub fn calculate_element_patch_info(
                                     false
                                 };
 
-                                if !handler_is_const {
+                                // Cached handlers are the same function on every render
+                                if !handler_is_const && dir.cache_index.is_none() {
                                     flag |= 8; // PROPS
                                     dynamic_props.push(event_name.clone());
                                 }
@@ -244,6 +244,9 @@ fn generate_props_object(
     let has_inline_handler = props.iter().any(|p| {
         if let PropNode::Directive(dir) = p {
             if dir.name == "on" {
+                if dir.cache_index.is_some() {
+                    return true;
+                }
                 // Check for modifiers that will use withModifiers or withKeys (not event option modifiers)
                 let has_runtime_modifier = dir.modifiers.iter().any(|m| {
                     let n = m.content.as_str();
@@ -588,6 +591,16 @@ pub fn generate_directive_prop_with_static(
             let has_system_mods = !system_modifiers.is_empty();
             let has_key_mods = !key_modifiers.is_empty();
 
+            // Cached handler: _cache[0] || (_cache[0] = handler)
+            if let Some(index) = dir.cache_index {
+                let index = index.to_string();
+                ctx.push("_cache[");
+                ctx.push(&index);
+                ctx.push("] || (_cache[");
+                ctx.push(&index);
+                ctx.push("] = ");
+            }
+
             if has_key_mods {
                 ctx.use_helper(RuntimeHelper::WithKeys);
                 ctx.push("_withKeys(");
@@ -632,6 +645,10 @@ pub fn generate_directive_prop_with_static(
                 }
                 ctx.push("])");
             }
+
+            if dir.cache_index.is_some() {
+                ctx.push(")");
+            }
         }
         "model" => {
             // Handle dynamic v-model on component
@@ -262,7 +262,7 @@ pub fn generate_slots(ctx: &mut CodegenContext, el: &ElementNode<'_>) {
 }
 
 /// Generate children for a slot
-fn generate_slot_children(ctx: &mut CodegenContext, children: &[TemplateChildNode<'_>]) {
+pub(super) fn generate_slot_children(ctx: &mut CodegenContext, children: &[TemplateChildNode<'_>]) {
     // Check if all children are text/interpolation - if so, concatenate into single _createTextVNode
     let all_text_or_interp = children.iter().all(|child| {
         matches!(
@@ -354,6 +354,7 @@ fn generate_slot_expression(ctx: &mut CodegenContext, expr: &ExpressionNode<'_>)
                 ctx.push(&exp.content);
                 ctx.push("\"");
             } else {
+                ctx.add_mapping(&exp.loc);
                 // Strip _ctx. prefix for slot parameters
                 let content = strip_ctx_prefix_for_slot_params(ctx, &exp.content);
                 ctx.push(&content);
@@ -386,7 +387,10 @@ fn generate_slot_expression(ctx: &mut CodegenContext, expr: &ExpressionNode<'_>)
 }
 
 /// Strip _ctx. prefix from identifiers that are slot parameters
-fn strip_ctx_prefix_for_slot_params(ctx: &CodegenContext, content: &str) -> std::string::String {
+pub(super) fn strip_ctx_prefix_for_slot_params(
+    ctx: &CodegenContext,
+    content: &str,
+) -> std::string::String {
     let mut result = content.to_string();
     for param in &ctx.slot_params {
         // Replace _ctx.paramName with paramName
@@ -0,0 +1,139 @@
+//! Source map generation.
+//!
+//! Turns the mappings recorded by [`CodegenContext`](super::CodegenContext)
+//! into a standard v3 source map pointing back into the template source.
+
+use oxc_sourcemap::SourceMapBuilder;
+
+use crate::ast::Position;
+
+/// Build a v3 source map (JSON) for generated code
+pub(super) fn generate_source_map(
+    code: &[u8],
+    mappings: &[(usize, Position)],
+    filename: &str,
+    source: &str,
+) -> String {
+    let mut builder = SourceMapBuilder::default();
+    let source_id = builder.set_source_and_content(filename, source);
+
+    // Start offset of every source line, to resolve byte offsets into line/column
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+
+    let mut sorted = mappings.to_vec();
+    sorted.sort_by_key(|(offset, _)| *offset);
+
+    // Walk the generated code once, converting byte offsets to line/column
+    let mut line = 0u32;
+    let mut column = 0u32;
+    let mut pos = 0usize;
+    let mut last: Option<(u32, u32)> = None;
+    for (offset, src) in sorted {
+        let offset = offset.min(code.len());
+        while pos < offset {
+            let byte = code[pos];
+            if byte == b'\n' {
+                line += 1;
+                column = 0;
+            } else if byte >= 0xF0 {
+                // Astral characters take two UTF-16 code units
+                column += 2;
+            } else if byte & 0xC0 != 0x80 {
+                column += 1;
+            }
+            pos += 1;
+        }
+
+        // Keep only the outermost mapping for a generated position
+        if last == Some((line, column)) {
+            continue;
+        }
+        last = Some((line, column));
+
+        let (src_line, src_column) = resolve_position(source, &line_starts, src.offset as usize);
+        builder.add_token(line, column, src_line, src_column, Some(source_id), None);
+    }
+
+    builder.into_sourcemap().to_json_string()
+}
+
+/// Resolve a byte offset into a 0-based line and UTF-16 column
+fn resolve_position(source: &str, line_starts: &[usize], offset: usize) -> (u32, u32) {
+    // Expression locations may include the whitespace inside `{{ }}`
+    let offset = offset.min(source.len());
+    let offset = offset
+        + source
+            .get(offset..)
+            .unwrap_or("")
+            .bytes()
+            .take_while(|b| *b == b' ' || *b == b'\t')
+            .count();
+    let line = line_starts
+        .partition_point(|&start| start <= offset)
+        .saturating_sub(1);
+    let line_start = line_starts[line];
+    let column = source
+        .get(line_start..offset)
+        .map_or(0, |s| s.encode_utf16().count());
+    (line as u32, column as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compile;
+    use crate::options::{CodegenMode, CodegenOptions};
+
+    fn compile_with_map(template: &str) -> crate::CodegenResult {
+        let options = CodegenOptions {
+            mode: CodegenMode::Module,
+            source_map: true,
+            filename: "App.vue".into(),
+            ..Default::default()
+        };
+        compile!(template, options)
+    }
+
+    #[test]
+    fn test_source_map_disabled_by_default() {
+        let result = crate::compile!("<div>{{ msg }}</div>");
+        assert!(result.map.is_none());
+    }
+
+    #[test]
+    fn test_source_map_v3_shape() {
+        let result = compile_with_map("<div>\n  <span>{{ msg }}</span>\n</div>");
+        let map: serde_json::Value =
+            serde_json::from_str(result.map.as_deref().expect("map")).unwrap();
+        assert_eq!(map["version"], 3);
+        assert_eq!(map["sources"][0], "App.vue");
+        assert_eq!(
+            map["sourcesContent"][0],
+            "<div>\n  <span>{{ msg }}</span>\n</div>"
+        );
+        assert!(!map["mappings"].as_str().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_source_map_points_to_interpolation() {
+        let template = "<div>\n  <p>\n    {{ msg }}\n  </p>\n</div>";
+        let result = compile_with_map(template);
+        let map = oxc_sourcemap::SourceMap::from_json_string(result.map.as_deref().unwrap())
+            .expect("valid source map");
+
+        // Find the generated position of `msg` and look up where it came from
+        let (line, column) = result
+            .code
+            .lines()
+            .enumerate()
+            .find_map(|(i, l)| l.find("msg").map(|c| (i as u32, c as u32)))
+            .expect("msg in output");
+        let table = map.generate_lookup_table();
+        let token = map
+            .lookup_source_view_token(&table, line, column)
+            .expect("mapped token");
+        assert_eq!(token.get_src_line(), 2);
+        assert_eq!(token.get_src_col(), 7);
+    }
+}
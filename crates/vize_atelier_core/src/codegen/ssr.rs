@@ -0,0 +1,1389 @@
+//! SSR code generation.
+//!
+//! Generates the body of `ssrRender(_ctx, _push, _parent, _attrs)`.
+//! Static markup and interpolations are collected into template literals
+//! that are flushed through `_push`, while components, slots, `v-if` and
+//! `v-for` become statements calling the `vue/server-renderer` helpers.
+//! This mirrors the output of `@vue/compiler-ssr`.
+
+use vize_carton::{escape_html, is_boolean_attr, is_void_tag};
+
+use crate::ast::*;
+use crate::runtime_helpers::SsrHelper;
+use crate::transforms::v_slot::{get_slot_name, has_v_slot};
+
+use super::context::CodegenContext;
+use super::element::{has_renderable_props, is_builtin_directive};
+use super::expression::generate_expression;
+use super::helpers::{escape_js_string, is_builtin_component, is_valid_js_identifier};
+use super::props::generate_props;
+use super::slots::{generate_slot_children, has_slot_children, strip_ctx_prefix_for_slot_params};
+
+/// Generate the SSR render function body for the root node
+pub fn generate_ssr_body(ctx: &mut CodegenContext, root: &RootNode<'_>) {
+    let mut ssr = SsrContext::new(ctx);
+
+    if let Some(css_vars) = &root.ssr_css_vars {
+        ssr.begin_statement();
+        ssr.ctx.push("const _cssVars = { style: ");
+        ssr.ctx.push(css_vars);
+        ssr.ctx.push(" }");
+    }
+
+    let root_count = root
+        .children
+        .iter()
+        .filter(|child| !matches!(child, TemplateChildNode::Comment(_)))
+        .count();
+
+    if root_count > 1 {
+        // Multiple roots render as a fragment and don't receive fallthrough attrs
+        ssr.push_static("<!--[-->");
+        ssr.gen_children(&root.children);
+        ssr.push_static("<!--]-->");
+    } else {
+        for child in root.children.iter() {
+            ssr.gen_node(child, true);
+        }
+    }
+
+    ssr.flush();
+}
+
+/// SSR generation state layered on top of the shared codegen context
+struct SsrContext<'c> {
+    ctx: &'c mut CodegenContext,
+    /// Pending template literal content (already escaped)
+    buffer: String,
+    /// Whether the current block already contains a statement
+    has_statement: bool,
+    /// v-model expression of the enclosing `<select>`, used for `<option selected>`
+    select_model: Option<String>,
+    /// Template position of the next statement, for source maps
+    pending_loc: Option<Position>,
+}
+
+/// Props that never render as HTML attributes
+fn is_skipped_attr(name: &str) -> bool {
+    matches!(name, "key" | "ref" | "ref_for" | "ref_key")
+}
+
+/// Get the static argument name of a directive
+fn static_arg<'b>(dir: &'b DirectiveNode<'_>) -> Option<&'b str> {
+    match &dir.arg {
+        Some(ExpressionNode::Simple(arg)) if arg.is_static => Some(arg.content.as_str()),
+        _ => None,
+    }
+}
+
+/// Find a static attribute value by name
+fn find_attr<'b>(el: &'b ElementNode<'_>, name: &str) -> Option<&'b AttributeNode> {
+    el.props.iter().find_map(|p| match p {
+        PropNode::Attribute(attr) if attr.name == name => Some(attr.as_ref()),
+        _ => None,
+    })
+}
+
+/// Find a `v-bind` with the given static argument
+fn find_bind<'a, 'b>(el: &'b ElementNode<'a>, name: &str) -> Option<&'b ExpressionNode<'a>> {
+    el.props.iter().find_map(|p| match p {
+        PropNode::Directive(dir) if dir.name == "bind" && static_arg(dir) == Some(name) => {
+            dir.exp.as_ref()
+        }
+        _ => None,
+    })
+}
+
+/// Find a directive expression by directive name
+fn find_directive_exp<'a, 'b>(
+    el: &'b ElementNode<'a>,
+    name: &str,
+) -> Option<&'b ExpressionNode<'a>> {
+    el.props.iter().find_map(|p| match p {
+        PropNode::Directive(dir) if dir.name == name => dir.exp.as_ref(),
+        _ => None,
+    })
+}
+
+/// Quote an object key if it is not a valid identifier
+fn js_key(key: &str) -> String {
+    if is_valid_js_identifier(key) {
+        key.to_string()
+    } else {
+        format!("\"{}\"", escape_js_string(key))
+    }
+}
+
+/// Quote a static string as a JS string literal
+fn js_string(value: &str) -> String {
+    format!("\"{}\"", escape_js_string(value))
+}
+
+/// Whether a node list must be wrapped in fragment markers
+fn needs_fragment(children: &[TemplateChildNode<'_>]) -> bool {
+    children.len() != 1 || !matches!(children[0], TemplateChildNode::Element(_))
+}
+
+impl<'c> SsrContext<'c> {
+    fn new(ctx: &'c mut CodegenContext) -> Self {
+        Self {
+            ctx,
+            buffer: String::new(),
+            has_statement: false,
+            select_model: None,
+            pending_loc: None,
+        }
+    }
+
+    /// Append static markup to the pending template literal
+    fn push_static(&mut self, s: &str) {
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => self.buffer.push_str("\\\\"),
+                '`' => self.buffer.push_str("\\`"),
+                '$' if chars.peek() == Some(&'{') => self.buffer.push_str("\\$"),
+                c => self.buffer.push(c),
+            }
+        }
+    }
+
+    /// Append an interpolated expression to the pending template literal
+    fn push_dynamic(&mut self, code: &str) {
+        self.buffer.push_str("${");
+        self.buffer.push_str(code);
+        self.buffer.push('}');
+    }
+
+    /// Emit the pending template literal as a `_push` call
+    fn flush(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let buffer = std::mem::take(&mut self.buffer);
+        self.begin_statement();
+        self.mark();
+        self.ctx.push("_push(`");
+        self.ctx.push(&buffer);
+        self.ctx.push("`)");
+    }
+
+    /// Start a new statement in the current block
+    fn begin_statement(&mut self) {
+        if self.has_statement {
+            self.ctx.newline();
+        }
+        self.has_statement = true;
+    }
+
+    /// Flush pending markup and start a new statement
+    fn statement(&mut self) {
+        self.flush();
+        self.begin_statement();
+        self.mark();
+    }
+
+    /// Record a source mapping for the statement about to be emitted
+    fn mark(&mut self) {
+        if let Some(pos) = self.pending_loc.take() {
+            if self.ctx.options.source_map {
+                self.ctx.mappings.push((self.ctx.code.len(), pos));
+            }
+        }
+    }
+
+    fn open_block(&mut self) {
+        self.ctx.push("{");
+        self.ctx.indent();
+        self.ctx.newline();
+        self.has_statement = false;
+    }
+
+    fn close_block(&mut self) {
+        self.flush();
+        self.ctx.deindent();
+        self.ctx.newline();
+        self.ctx.push("}");
+        self.has_statement = true;
+    }
+
+    /// Run a codegen function and return what it wrote instead of emitting it
+    fn capture(&mut self, f: impl FnOnce(&mut CodegenContext)) -> String {
+        let start = self.ctx.code.len();
+        f(self.ctx);
+        let bytes = self.ctx.code.split_off(start);
+        // Captured code is re-emitted elsewhere, so its mappings no longer apply
+        self.ctx.mappings.retain(|(offset, _)| *offset < start);
+        String::from_utf8(bytes).unwrap_or_default()
+    }
+
+    /// Generate an expression as a string
+    fn expression(&mut self, exp: &ExpressionNode<'_>) -> String {
+        let code = self.capture(|ctx| generate_expression(ctx, exp));
+        strip_ctx_prefix_for_slot_params(self.ctx, &code)
+    }
+
+    /// Value of a prop as JS code, from either a static attribute or a binding
+    fn prop_value(&mut self, el: &ElementNode<'_>, name: &str) -> Option<String> {
+        if let Some(exp) = find_bind(el, name) {
+            return Some(self.expression(exp));
+        }
+        find_attr(el, name).map(|attr| match &attr.value {
+            Some(value) => js_string(&value.content),
+            None => "true".to_string(),
+        })
+    }
+
+    fn merge_props_helper(&mut self) -> &'static str {
+        self.ctx.use_helper(RuntimeHelper::MergeProps);
+        self.ctx.helper(RuntimeHelper::MergeProps)
+    }
+
+    fn gen_children(&mut self, children: &[TemplateChildNode<'_>]) {
+        for child in children.iter() {
+            self.gen_node(child, false);
+        }
+    }
+
+    /// Generate children, wrapping them in fragment markers when needed
+    fn gen_fragment_children(&mut self, children: &[TemplateChildNode<'_>], inject_attrs: bool) {
+        if needs_fragment(children) {
+            self.push_static("<!--[-->");
+            self.gen_children(children);
+            self.push_static("<!--]-->");
+        } else {
+            self.gen_node(&children[0], inject_attrs);
+        }
+    }
+
+    fn gen_node(&mut self, node: &TemplateChildNode<'_>, inject_attrs: bool) {
+        // Map the next emitted statement back to the first node it renders
+        let loc = node.loc();
+        if self.pending_loc.is_none() && loc.end.offset != 0 {
+            self.pending_loc = Some(loc.start);
+        }
+        match node {
+            TemplateChildNode::Element(el) => self.gen_element(el, inject_attrs),
+            TemplateChildNode::Text(text) => {
+                let escaped = escape_html(&text.content);
+                self.push_static(&escaped);
+            }
+            TemplateChildNode::Comment(comment) => {
+                self.push_static("<!--");
+                self.push_static(&comment.content);
+                self.push_static("-->");
+            }
+            TemplateChildNode::Interpolation(interp) => {
+                let exp = self.expression(&interp.content);
+                let helper = self.ctx.ssr_helper(SsrHelper::Interpolate);
+                self.push_dynamic(&format!("{}({})", helper, exp));
+            }
+            TemplateChildNode::If(if_node) => self.gen_if(if_node, inject_attrs),
+            TemplateChildNode::For(for_node) => self.gen_for(for_node),
+            _ => {}
+        }
+    }
+
+    fn gen_element(&mut self, el: &ElementNode<'_>, inject_attrs: bool) {
+        match el.tag_type {
+            ElementType::Element => self.gen_plain_element(el, inject_attrs),
+            ElementType::Component => self.gen_component(el, inject_attrs),
+            ElementType::Slot => self.gen_slot_outlet(el),
+            ElementType::Template => self.gen_children(&el.children),
+        }
+    }
+
+    fn gen_plain_element(&mut self, el: &ElementNode<'_>, inject_attrs: bool) {
+        let tag = el.tag.as_str();
+
+        self.push_static("<");
+        self.push_static(tag);
+        self.gen_element_attrs(el, inject_attrs);
+        if let Some(scope_id) = self.ctx.options.scope_id.clone() {
+            self.push_static(" ");
+            self.push_static(&scope_id);
+        }
+        self.push_static(">");
+
+        if is_void_tag(tag) {
+            return;
+        }
+
+        let model = if matches!(tag, "textarea" | "select") {
+            find_directive_exp(el, "model").map(|exp| self.expression(exp))
+        } else {
+            None
+        };
+
+        if let Some(exp) = find_directive_exp(el, "html") {
+            let html = self.expression(exp);
+            self.push_dynamic(&html);
+        } else if let Some(exp) = find_directive_exp(el, "text") {
+            let text = self.expression(exp);
+            let helper = self.ctx.ssr_helper(SsrHelper::Interpolate);
+            self.push_dynamic(&format!("{}({})", helper, text));
+        } else if tag == "textarea" && model.is_some() {
+            let helper = self.ctx.ssr_helper(SsrHelper::Interpolate);
+            self.push_dynamic(&format!("{}({})", helper, model.unwrap_or_default()));
+        } else if tag == "select" && model.is_some() {
+            let saved = std::mem::replace(&mut self.select_model, model);
+            self.gen_children(&el.children);
+            self.select_model = saved;
+        } else {
+            self.gen_children(&el.children);
+        }
+
+        self.push_static("</");
+        self.push_static(tag);
+        self.push_static(">");
+    }
+
+    /// Generate the attribute section of an opening tag
+    fn gen_element_attrs(&mut self, el: &ElementNode<'_>, inject_attrs: bool) {
+        // Object spreads, dynamic keys, custom directives and fallthrough attrs all need
+        // runtime merging, so render everything through ssrRenderAttrs in those cases
+        let needs_merge = inject_attrs
+            || el.props.iter().any(|p| match p {
+                PropNode::Directive(dir) if dir.name == "bind" => static_arg(dir).is_none(),
+                PropNode::Directive(dir) => !is_builtin_directive(&dir.name),
+                PropNode::Attribute(_) => false,
+            });
+
+        if needs_merge {
+            let props = self.merged_props(el, inject_attrs);
+            let helper = self.ctx.ssr_helper(SsrHelper::RenderAttrs);
+            self.push_dynamic(&format!("{}({})", helper, props));
+        } else {
+            self.gen_static_attrs(el);
+        }
+
+        self.gen_model_attrs(el);
+        if el.tag == "option" {
+            self.gen_option_selected(el);
+        }
+    }
+
+    /// Build a `mergeProps(...)` argument list covering every renderable prop
+    fn merged_props(&mut self, el: &ElementNode<'_>, inject_attrs: bool) -> String {
+        let mut args: Vec<String> = Vec::new();
+        let mut entries: Vec<String> = Vec::new();
+        let mut extra: Vec<String> = Vec::new();
+
+        for prop in el.props.iter() {
+            match prop {
+                PropNode::Attribute(attr) => {
+                    if is_skipped_attr(&attr.name) {
+                        continue;
+                    }
+                    let value = attr.value.as_ref().map_or("", |v| v.content.as_str());
+                    entries.push(format!("{}: {}", js_key(&attr.name), js_string(value)));
+                }
+                PropNode::Directive(dir) => match dir.name.as_str() {
+                    "bind" => {
+                        let value = match &dir.exp {
+                            Some(exp) => self.expression(exp),
+                            None => "undefined".to_string(),
+                        };
+                        match (&dir.arg, static_arg(dir)) {
+                            (None, _) => {
+                                if !entries.is_empty() {
+                                    args.push(format!("{{ {} }}", entries.join(", ")));
+                                    entries.clear();
+                                }
+                                args.push(value);
+                            }
+                            (Some(_), Some(name)) => {
+                                if !is_skipped_attr(name) {
+                                    entries.push(format!("{}: {}", js_key(name), value));
+                                }
+                            }
+                            (Some(arg), None) => {
+                                let key = self.expression(arg);
+                                entries.push(format!("[{}]: {}", key, value));
+                            }
+                        }
+                    }
+                    "show" => {
+                        if let Some(exp) = &dir.exp {
+                            let cond = self.expression(exp);
+                            extra.push(format!(
+                                "{{ style: ({}) ? null : {{ display: \"none\" }} }}",
+                                cond
+                            ));
+                        }
+                    }
+                    name if !is_builtin_directive(name) => {
+                        let directive = self.directive_props(dir);
+                        extra.push(directive);
+                    }
+                    _ => {}
+                },
+            }
+        }
+
+        if !entries.is_empty() {
+            args.push(format!("{{ {} }}", entries.join(", ")));
+        }
+        args.extend(extra);
+        if inject_attrs {
+            args.push("_attrs".to_string());
+        }
+
+        match args.len() {
+            0 => "{}".to_string(),
+            1 => args.pop().unwrap_or_default(),
+            _ => format!("{}({})", self.merge_props_helper(), args.join(", ")),
+        }
+    }
+
+    /// `_ssrGetDirectiveProps(_ctx, _directive_x, value, arg, modifiers)`
+    fn directive_props(&mut self, dir: &DirectiveNode<'_>) -> String {
+        let helper = self.ctx.ssr_helper(SsrHelper::GetDirectiveProps);
+        let mut args = vec![
+            "_ctx".to_string(),
+            format!("_directive_{}", dir.name.replace('-', "_")),
+        ];
+        let value = dir.exp.as_ref().map(|exp| self.expression(exp));
+        let arg = dir.arg.as_ref().map(|arg| self.expression(arg));
+        let modifiers = (!dir.modifiers.is_empty()).then(|| {
+            let mods: Vec<String> = dir
+                .modifiers
+                .iter()
+                .map(|m| format!("{}: true", js_key(&m.content)))
+                .collect();
+            format!("{{ {} }}", mods.join(", "))
+        });
+
+        if value.is_some() || arg.is_some() || modifiers.is_some() {
+            args.push(value.unwrap_or_else(|| "void 0".to_string()));
+        }
+        if arg.is_some() || modifiers.is_some() {
+            args.push(arg.unwrap_or_else(|| "void 0".to_string()));
+        }
+        if let Some(modifiers) = modifiers {
+            args.push(modifiers);
+        }
+
+        format!("{}({})", helper, args.join(", "))
+    }
+
+    /// Render attributes one by one when no runtime merging is needed
+    fn gen_static_attrs(&mut self, el: &ElementNode<'_>) {
+        let static_class = find_attr(el, "class").and_then(|a| a.value.as_ref());
+        let static_style = find_attr(el, "style").and_then(|a| a.value.as_ref());
+        let has_dynamic_class = find_bind(el, "class").is_some();
+        let has_dynamic_style = find_bind(el, "style").is_some();
+        let show = find_directive_exp(el, "show").map(|exp| self.expression(exp));
+
+        for prop in el.props.iter() {
+            match prop {
+                PropNode::Attribute(attr) => {
+                    let name = attr.name.as_str();
+                    if is_skipped_attr(name)
+                        || (name == "class" && has_dynamic_class)
+                        || (name == "style" && (has_dynamic_style || show.is_some()))
+                    {
+                        continue;
+                    }
+                    self.push_static(" ");
+                    self.push_static(name);
+                    if let Some(value) = &attr.value {
+                        self.push_static("=\"");
+                        self.push_static(&escape_html(&value.content));
+                        self.push_static("\"");
+                    }
+                }
+                PropNode::Directive(dir) if dir.name == "bind" => {
+                    let Some(name) = static_arg(dir) else {
+                        continue;
+                    };
+                    if is_skipped_attr(name) {
+                        continue;
+                    }
+                    let value = match &dir.exp {
+                        Some(exp) => self.expression(exp),
+                        None => "undefined".to_string(),
+                    };
+                    match name {
+                        "class" => {
+                            let class = match static_class {
+                                Some(s) => format!("[{}, {}]", js_string(&s.content), value),
+                                None => value,
+                            };
+                            let helper = self.ctx.ssr_helper(SsrHelper::RenderClass);
+                            self.push_static(" class=\"");
+                            self.push_dynamic(&format!("{}({})", helper, class));
+                            self.push_static("\"");
+                        }
+                        "style" => {
+                            let mut items = Vec::new();
+                            if let Some(s) = static_style {
+                                items.push(js_string(&s.content));
+                            }
+                            items.push(value);
+                            if let Some(cond) = &show {
+                                items.push(format!("({}) ? null : {{ display: \"none\" }}", cond));
+                            }
+                            self.gen_style_attr(items);
+                        }
+                        _ if is_boolean_attr(name) => {
+                            let helper = self.ctx.ssr_helper(SsrHelper::IncludeBooleanAttr);
+                            self.push_dynamic(&format!(
+                                "({}({})) ? \" {}\" : \"\"",
+                                helper, value, name
+                            ));
+                        }
+                        _ => {
+                            let helper = self.ctx.ssr_helper(SsrHelper::RenderAttr);
+                            self.push_dynamic(&format!(
+                                "{}({}, {})",
+                                helper,
+                                js_string(name),
+                                value
+                            ));
+                        }
+                    }
+                }
+                PropNode::Directive(dir) if dir.name == "show" && !has_dynamic_style => {
+                    let Some(cond) = &show else {
+                        continue;
+                    };
+                    let mut items = Vec::new();
+                    if let Some(s) = static_style {
+                        items.push(js_string(&s.content));
+                    }
+                    items.push(format!("({}) ? null : {{ display: \"none\" }}", cond));
+                    self.gen_style_attr(items);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn gen_style_attr(&mut self, mut items: Vec<String>) {
+        let style = if items.len() == 1 {
+            items.pop().unwrap_or_default()
+        } else {
+            format!("[{}]", items.join(", "))
+        };
+        let helper = self.ctx.ssr_helper(SsrHelper::RenderStyle);
+        self.push_static(" style=\"");
+        self.push_dynamic(&format!("{}({})", helper, style));
+        self.push_static("\"");
+    }
+
+    /// Render the attribute reflecting `v-model` state on `<input>`
+    fn gen_model_attrs(&mut self, el: &ElementNode<'_>) {
+        if el.tag != "input" {
+            return;
+        }
+        let Some(exp) = find_directive_exp(el, "model") else {
+            return;
+        };
+        let model = self.expression(exp);
+        let value = self
+            .prop_value(el, "value")
+            .unwrap_or_else(|| "null".to_string());
+
+        if let Some(type_exp) = find_bind(el, "type") {
+            let input_type = self.expression(type_exp);
+            let helper = self.ctx.ssr_helper(SsrHelper::RenderDynamicModel);
+            self.push_dynamic(&format!("{}({}, {}, {})", helper, input_type, model, value));
+            return;
+        }
+
+        let input_type = find_attr(el, "type")
+            .and_then(|a| a.value.as_ref())
+            .map(|v| v.content.as_str());
+        match input_type {
+            Some("checkbox") => {
+                let checked = match self.prop_value(el, "true-value") {
+                    Some(true_value) => {
+                        let loose_equal = self.ctx.ssr_helper(SsrHelper::LooseEqual);
+                        format!("{}({}, {})", loose_equal, model, true_value)
+                    }
+                    None => {
+                        let loose_contain = self.ctx.ssr_helper(SsrHelper::LooseContain);
+                        format!(
+                            "Array.isArray({}) ? {}({}, {}) : {}",
+                            model, loose_contain, model, value, model
+                        )
+                    }
+                };
+                self.push_boolean_attr("checked", &checked);
+            }
+            Some("radio") => {
+                let loose_equal = self.ctx.ssr_helper(SsrHelper::LooseEqual);
+                let checked = format!("{}({}, {})", loose_equal, model, value);
+                self.push_boolean_attr("checked", &checked);
+            }
+            _ => {
+                let helper = self.ctx.ssr_helper(SsrHelper::RenderAttr);
+                self.push_dynamic(&format!("{}(\"value\", {})", helper, model));
+            }
+        }
+    }
+
+    /// Render `selected` on `<option>` inside a `<select v-model>`
+    fn gen_option_selected(&mut self, el: &ElementNode<'_>) {
+        let Some(model) = self.select_model.clone() else {
+            return;
+        };
+        let value = self.prop_value(el, "value").unwrap_or_else(|| {
+            let text: String = el
+                .children
+                .iter()
+                .filter_map(|c| match c {
+                    TemplateChildNode::Text(t) => Some(t.content.as_str()),
+                    _ => None,
+                })
+                .collect();
+            js_string(text.trim())
+        });
+        let loose_contain = self.ctx.ssr_helper(SsrHelper::LooseContain);
+        let loose_equal = self.ctx.ssr_helper(SsrHelper::LooseEqual);
+        let selected = format!(
+            "Array.isArray({}) ? {}({}, {}) : {}({}, {})",
+            model, loose_contain, model, value, loose_equal, model, value
+        );
+        self.push_boolean_attr("selected", &selected);
+    }
+
+    fn push_boolean_attr(&mut self, name: &str, condition: &str) {
+        let helper = self.ctx.ssr_helper(SsrHelper::IncludeBooleanAttr);
+        self.push_dynamic(&format!(
+            "({}({})) ? \" {}\" : \"\"",
+            helper, condition, name
+        ));
+    }
+
+    fn gen_if(&mut self, if_node: &IfNode<'_>, inject_attrs: bool) {
+        self.statement();
+
+        for (i, branch) in if_node.branches.iter().enumerate() {
+            match (&branch.condition, i) {
+                (Some(cond), 0) => {
+                    let cond = self.expression(cond);
+                    self.ctx.push("if (");
+                    self.ctx.push(&cond);
+                    self.ctx.push(") ");
+                }
+                (Some(cond), _) => {
+                    let cond = self.expression(cond);
+                    self.ctx.push(" else if (");
+                    self.ctx.push(&cond);
+                    self.ctx.push(") ");
+                }
+                (None, _) => self.ctx.push(" else "),
+            }
+            self.open_block();
+            let children = match (branch.is_template_if, branch.children.first()) {
+                (true, Some(TemplateChildNode::Element(template))) => &template.children,
+                _ => &branch.children,
+            };
+            self.gen_fragment_children(children, inject_attrs);
+            self.close_block();
+        }
+
+        // A missing v-else still renders a comment anchor for hydration
+        if if_node
+            .branches
+            .last()
+            .is_some_and(|branch| branch.condition.is_some())
+        {
+            self.ctx.push(" else ");
+            self.open_block();
+            self.push_static("<!---->");
+            self.close_block();
+        }
+    }
+
+    fn gen_for(&mut self, for_node: &ForNode<'_>) {
+        self.push_static("<!--[-->");
+        self.statement();
+
+        let source = self.expression(&for_node.source);
+        let mut params: Vec<String> = [
+            &for_node.value_alias,
+            &for_node.key_alias,
+            &for_node.object_index_alias,
+        ]
+        .iter()
+        .map(|alias| match alias {
+            Some(exp) => self.expression(exp),
+            None => "_".to_string(),
+        })
+        .collect();
+        while params.len() > 1 && params.last().is_some_and(|p| p == "_") {
+            params.pop();
+        }
+
+        let helper = self.ctx.ssr_helper(SsrHelper::RenderList);
+        self.ctx.push(helper);
+        self.ctx.push("(");
+        self.ctx.push(&source);
+        self.ctx.push(", (");
+        self.ctx.push(&params.join(", "));
+        self.ctx.push(") => ");
+        self.open_block();
+        let children = match for_node.children.first() {
+            Some(TemplateChildNode::Element(el)) if el.tag_type == ElementType::Template => {
+                &el.children
+            }
+            _ => &for_node.children,
+        };
+        self.gen_fragment_children(children, false);
+        self.close_block();
+        self.ctx.push(")");
+
+        self.push_static("<!--]-->");
+    }
+
+    /// Resolve the JS reference of a component tag
+    fn component_ref(&mut self, el: &ElementNode<'_>) -> String {
+        if let Some(builtin) = is_builtin_component(&el.tag) {
+            self.ctx.use_helper(builtin);
+            return self.ctx.helper(builtin).to_string();
+        }
+        if self.ctx.is_component_in_bindings(&el.tag) {
+            // In inline mode, components are directly in scope
+            if self.ctx.options.inline {
+                return el.tag.to_string();
+            }
+            return format!("$setup.{}", el.tag);
+        }
+        format!("_component_{}", el.tag.replace('-', "_"))
+    }
+
+    /// Generate component props, merging fallthrough attrs for root components
+    fn component_props(&mut self, el: &ElementNode<'_>, inject_attrs: bool) -> String {
+        if has_renderable_props(el) {
+            let props = self.capture(|ctx| generate_props(ctx, &el.props));
+            if inject_attrs {
+                format!("{}({}, _attrs)", self.merge_props_helper(), props)
+            } else {
+                props
+            }
+        } else if inject_attrs {
+            "_attrs".to_string()
+        } else {
+            "null".to_string()
+        }
+    }
+
+    fn gen_component(&mut self, el: &ElementNode<'_>, inject_attrs: bool) {
+        match el.tag.as_str() {
+            "Teleport" => return self.gen_teleport(el),
+            "Suspense" => return self.gen_suspense(el),
+            // Transitions and KeepAlive render their content directly on the server
+            "Transition" | "BaseTransition" | "KeepAlive" => {
+                return self.gen_children(&el.children)
+            }
+            "TransitionGroup" => return self.gen_transition_group(el),
+            "component" => return self.gen_dynamic_component(el, inject_attrs),
+            _ => {}
+        }
+
+        let component = self.component_ref(el);
+        let props = self.component_props(el, inject_attrs);
+
+        self.statement();
+        let helper = self.ctx.ssr_helper(SsrHelper::RenderComponent);
+        self.ctx.push("_push(");
+        self.ctx.push(helper);
+        self.ctx.push("(");
+        self.ctx.push(&component);
+        self.ctx.push(", ");
+        self.ctx.push(&props);
+        self.ctx.push(", ");
+        if has_slot_children(el) {
+            self.gen_slots(el);
+        } else {
+            self.ctx.push("null");
+        }
+        self.ctx.push(", _parent))");
+    }
+
+    /// `<component :is>` renders through a VNode so the target is resolved at runtime
+    fn gen_dynamic_component(&mut self, el: &ElementNode<'_>, inject_attrs: bool) {
+        let is = match find_bind(el, "is") {
+            Some(exp) => self.expression(exp),
+            None => find_attr(el, "is")
+                .and_then(|a| a.value.as_ref())
+                .map(|v| js_string(&v.content))
+                .unwrap_or_else(|| "null".to_string()),
+        };
+        let props = self.component_props(el, inject_attrs);
+
+        self.ctx.use_helper(RuntimeHelper::CreateVNode);
+        self.ctx.use_helper(RuntimeHelper::ResolveDynamicComponent);
+        let create_vnode = self.ctx.helper(RuntimeHelper::CreateVNode);
+        let resolve = self.ctx.helper(RuntimeHelper::ResolveDynamicComponent);
+
+        self.statement();
+        let helper = self.ctx.ssr_helper(SsrHelper::RenderVNode);
+        self.ctx.push(helper);
+        self.ctx.push("(_push, ");
+        self.ctx.push(create_vnode);
+        self.ctx.push("(");
+        self.ctx.push(resolve);
+        self.ctx.push("(");
+        self.ctx.push(&is);
+        self.ctx.push("), ");
+        self.ctx.push(&props);
+        self.ctx.push(", ");
+        if has_slot_children(el) {
+            self.gen_slots(el);
+        } else {
+            self.ctx.push("null");
+        }
+        self.ctx.push("), _parent)");
+    }
+
+    fn gen_teleport(&mut self, el: &ElementNode<'_>) {
+        let to = self
+            .prop_value(el, "to")
+            .unwrap_or_else(|| "null".to_string());
+        let disabled = match (find_bind(el, "disabled"), find_attr(el, "disabled")) {
+            (Some(exp), _) => self.expression(exp),
+            (None, Some(_)) => "true".to_string(),
+            (None, None) => "false".to_string(),
+        };
+
+        self.statement();
+        let helper = self.ctx.ssr_helper(SsrHelper::RenderTeleport);
+        self.ctx.push(helper);
+        self.ctx.push("(_push, (_push) => ");
+        self.open_block();
+        self.gen_children(&el.children);
+        self.close_block();
+        self.ctx.push(", ");
+        self.ctx.push(&to);
+        self.ctx.push(", ");
+        self.ctx.push(&disabled);
+        self.ctx.push(", _parent)");
+    }
+
+    fn gen_suspense(&mut self, el: &ElementNode<'_>) {
+        self.statement();
+        let helper = self.ctx.ssr_helper(SsrHelper::RenderSuspense);
+        self.ctx.push(helper);
+        self.ctx.push("(_push, {");
+        self.ctx.indent();
+
+        let mut default_children: Vec<&TemplateChildNode<'_>> = Vec::new();
+        for child in el.children.iter() {
+            match child {
+                TemplateChildNode::Element(template)
+                    if template.tag == "template" && has_v_slot(template) =>
+                {
+                    let name = template.props.iter().find_map(|p| match p {
+                        PropNode::Directive(dir) if dir.name == "slot" => Some(get_slot_name(dir)),
+                        _ => None,
+                    });
+                    let name = name.unwrap_or_else(|| "default".into());
+                    self.ctx.newline();
+                    self.ctx.push(&js_key(&name));
+                    self.ctx.push(": () => ");
+                    self.open_block();
+                    self.gen_children(&template.children);
+                    self.close_block();
+                    self.ctx.push(",");
+                }
+                _ => default_children.push(child),
+            }
+        }
+
+        if !default_children.is_empty() {
+            self.ctx.newline();
+            self.ctx.push("default: () => ");
+            self.open_block();
+            for child in default_children {
+                self.gen_node(child, false);
+            }
+            self.close_block();
+            self.ctx.push(",");
+        }
+
+        self.ctx.newline();
+        self.ctx.push("_: 1 /* STABLE */");
+        self.ctx.deindent();
+        self.ctx.newline();
+        self.ctx.push("})");
+    }
+
+    fn gen_transition_group(&mut self, el: &ElementNode<'_>) {
+        let tag = find_attr(el, "tag")
+            .and_then(|a| a.value.as_ref())
+            .map(|v| v.content.to_string());
+        match tag {
+            Some(tag) => {
+                self.push_static("<");
+                self.push_static(&tag);
+                if let Some(scope_id) = self.ctx.options.scope_id.clone() {
+                    self.push_static(" ");
+                    self.push_static(&scope_id);
+                }
+                self.push_static(">");
+                self.gen_children(&el.children);
+                self.push_static("</");
+                self.push_static(&tag);
+                self.push_static(">");
+            }
+            None => {
+                self.push_static("<!--[-->");
+                self.gen_children(&el.children);
+                self.push_static("<!--]-->");
+            }
+        }
+    }
+
+    /// `<slot>` outlet: `_ssrRenderSlot(_ctx.$slots, name, props, fallback, _push, _parent)`
+    fn gen_slot_outlet(&mut self, el: &ElementNode<'_>) {
+        let name = self
+            .prop_value(el, "name")
+            .unwrap_or_else(|| "\"default\"".to_string());
+
+        let mut args: Vec<String> = Vec::new();
+        let mut entries: Vec<String> = Vec::new();
+        for prop in el.props.iter() {
+            match prop {
+                PropNode::Attribute(attr) if attr.name != "name" => {
+                    let value = attr.value.as_ref().map_or("", |v| v.content.as_str());
+                    entries.push(format!("{}: {}", js_key(&attr.name), js_string(value)));
+                }
+                PropNode::Directive(dir) if dir.name == "bind" => {
+                    let value = match &dir.exp {
+                        Some(exp) => self.expression(exp),
+                        None => "undefined".to_string(),
+                    };
+                    match (&dir.arg, static_arg(dir)) {
+                        (None, _) => {
+                            if !entries.is_empty() {
+                                args.push(format!("{{ {} }}", entries.join(", ")));
+                                entries.clear();
+                            }
+                            args.push(value);
+                        }
+                        (Some(_), Some("name")) => {}
+                        (Some(_), Some(key)) => {
+                            entries.push(format!("{}: {}", js_key(key), value));
+                        }
+                        (Some(arg), None) => {
+                            let key = self.expression(arg);
+                            entries.push(format!("[{}]: {}", key, value));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        if !entries.is_empty() {
+            args.push(format!("{{ {} }}", entries.join(", ")));
+        }
+        let props = match args.len() {
+            0 => "{}".to_string(),
+            1 => args.pop().unwrap_or_default(),
+            _ => format!("{}({})", self.merge_props_helper(), args.join(", ")),
+        };
+
+        self.statement();
+        let helper = self.ctx.ssr_helper(SsrHelper::RenderSlot);
+        self.ctx.push(helper);
+        self.ctx.push("(_ctx.$slots, ");
+        self.ctx.push(&name);
+        self.ctx.push(", ");
+        self.ctx.push(&props);
+        self.ctx.push(", ");
+        if el.children.is_empty() {
+            self.ctx.push("null");
+        } else {
+            self.ctx.push("() => ");
+            self.open_block();
+            self.gen_children(&el.children);
+            self.close_block();
+        }
+        self.ctx.push(", _push, _parent)");
+    }
+
+    /// Generate the slots object of a component.
+    ///
+    /// Each slot function renders markup through `_push` on the server and
+    /// falls back to returning VNodes when called without it.
+    fn gen_slots(&mut self, el: &ElementNode<'_>) {
+        self.ctx.use_helper(RuntimeHelper::WithCtx);
+
+        let root_slot = el.props.iter().find_map(|p| match p {
+            PropNode::Directive(dir) if dir.name == "slot" => Some(dir.as_ref()),
+            _ => None,
+        });
+
+        self.ctx.push("{");
+        self.ctx.indent();
+
+        let mut first = true;
+        if let Some(dir) = root_slot {
+            self.gen_slot_fn(&mut first, "default", dir.exp.as_ref(), &el.children);
+        } else {
+            let mut has_default = false;
+            for child in el.children.iter() {
+                if let TemplateChildNode::Element(template) = child {
+                    if template.tag != "template" || !has_v_slot(template) {
+                        continue;
+                    }
+                    let Some(dir) = template.props.iter().find_map(|p| match p {
+                        PropNode::Directive(dir) if dir.name == "slot" => Some(dir.as_ref()),
+                        _ => None,
+                    }) else {
+                        continue;
+                    };
+                    let name = get_slot_name(dir);
+                    let dynamic_arg = dir.arg.as_ref().filter(|arg| match arg {
+                        ExpressionNode::Simple(exp) => !exp.is_static,
+                        ExpressionNode::Compound(_) => true,
+                    });
+                    let key = match dynamic_arg {
+                        Some(arg) => format!("[{}]", self.expression(arg)),
+                        None => js_key(&name),
+                    };
+                    has_default |= name == "default";
+                    self.gen_slot_fn(&mut first, &key, dir.exp.as_ref(), &template.children);
+                }
+            }
+
+            let default_children: Vec<&TemplateChildNode<'_>> = el
+                .children
+                .iter()
+                .filter(|child| match child {
+                    TemplateChildNode::Element(template) => {
+                        !(template.tag == "template" && has_v_slot(template))
+                    }
+                    _ => true,
+                })
+                .collect();
+            if !default_children.is_empty() && !has_default {
+                self.gen_slot_fn_refs(&mut first, "default", None, &default_children);
+            }
+        }
+
+        if !first {
+            self.ctx.push(",");
+        }
+        self.ctx.newline();
+        self.ctx.push("_: 1 /* STABLE */");
+        self.ctx.deindent();
+        self.ctx.newline();
+        self.ctx.push("}");
+    }
+
+    fn gen_slot_fn(
+        &mut self,
+        first: &mut bool,
+        key: &str,
+        params: Option<&ExpressionNode<'_>>,
+        children: &[TemplateChildNode<'_>],
+    ) {
+        let refs: Vec<&TemplateChildNode<'_>> = children.iter().collect();
+        self.gen_slot_fn_refs(first, key, params, &refs);
+    }
+
+    fn gen_slot_fn_refs(
+        &mut self,
+        first: &mut bool,
+        key: &str,
+        params: Option<&ExpressionNode<'_>>,
+        children: &[&TemplateChildNode<'_>],
+    ) {
+        if !*first {
+            self.ctx.push(",");
+        }
+        *first = false;
+
+        // Slot params use the raw source, as the transform may have prefixed them
+        let params_src = params.map(|exp| exp.loc().source.to_string());
+        let slot_params = params_src
+            .as_deref()
+            .map(extract_param_names)
+            .unwrap_or_default();
+        self.ctx.add_slot_params(&slot_params);
+
+        self.ctx.newline();
+        self.ctx.push(key);
+        self.ctx.push(": ");
+        self.ctx.push(self.ctx.helper(RuntimeHelper::WithCtx));
+        self.ctx.push("((");
+        self.ctx.push(params_src.as_deref().unwrap_or("_"));
+        self.ctx.push(", _push, _parent, _scopeId) => ");
+        self.open_block();
+
+        self.begin_statement();
+        self.ctx.push("if (_push) ");
+        self.open_block();
+        for child in children.iter() {
+            self.gen_node(child, false);
+        }
+        self.close_block();
+
+        self.ctx.push(" else ");
+        self.open_block();
+        self.begin_statement();
+        self.ctx.push("return [");
+        self.ctx.indent();
+        let owned: Vec<&TemplateChildNode<'_>> = children.to_vec();
+        generate_slot_children_refs(self.ctx, &owned);
+        self.ctx.deindent();
+        self.ctx.newline();
+        self.ctx.push("]");
+        self.close_block();
+
+        self.close_block();
+        self.ctx.push(")");
+
+        self.ctx.remove_slot_params(&slot_params);
+    }
+}
+
+/// Generate VNode children for the client-side branch of a slot function
+fn generate_slot_children_refs(ctx: &mut CodegenContext, children: &[&TemplateChildNode<'_>]) {
+    for (i, child) in children.iter().enumerate() {
+        if i > 0 {
+            ctx.push(",");
+        }
+        generate_slot_children(ctx, std::slice::from_ref(*child));
+    }
+}
+
+/// Extract identifiers bound by slot props, e.g. `{ item, index }` -> `["item", "index"]`
+fn extract_param_names(source: &str) -> Vec<String> {
+    let trimmed = source.trim();
+    let inner = trimmed
+        .strip_prefix('{')
+        .and_then(|s| s.strip_suffix('}'))
+        .unwrap_or(trimmed);
+    inner
+        .split(',')
+        .filter_map(|part| {
+            let part = part.trim();
+            let name = part
+                .split(['=', ':'])
+                .next_back()
+                .unwrap_or(part)
+                .trim()
+                .trim_start_matches("...");
+            (!name.is_empty() && vize_carton::is_simple_identifier(name)).then(|| name.to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compile;
+    use crate::options::{CodegenMode, CodegenOptions};
+
+    fn ssr(template: &str) -> crate::CodegenResult {
+        let options = CodegenOptions {
+            ssr: true,
+            mode: CodegenMode::Module,
+            ..Default::default()
+        };
+        compile!(template, options)
+    }
+
+    #[test]
+    fn test_ssr_function_signature() {
+        let result = ssr("<div>hello</div>");
+        assert!(
+            result
+                .code
+                .starts_with("export function ssrRender(_ctx, _push, _parent, _attrs) {"),
+            "{}",
+            result.code
+        );
+        assert!(
+            !result.code.contains("createElementBlock"),
+            "{}",
+            result.code
+        );
+    }
+
+    #[test]
+    fn test_ssr_root_element_receives_attrs() {
+        let result = ssr(r#"<div id="app">{{ msg }}</div>"#);
+        assert!(
+            result.code.contains(
+                "_push(`<div${_ssrRenderAttrs(_mergeProps({ id: \"app\" }, _attrs))}>${_ssrInterpolate(msg)}</div>`)"
+            ),
+            "{}",
+            result.code
+        );
+        assert!(result
+            .preamble
+            .contains("import { mergeProps as _mergeProps } from \"vue\""));
+        assert!(result.preamble.contains("from \"vue/server-renderer\""));
+        assert!(result
+            .preamble
+            .contains("ssrRenderAttrs as _ssrRenderAttrs"));
+    }
+
+    #[test]
+    fn test_ssr_static_attrs_and_escaping() {
+        let result = ssr(r#"<div><p class="a" title="x">1 < 2 `tick`</p><br></div>"#);
+        assert!(
+            result
+                .code
+                .contains("<p class=\"a\" title=\"x\">1 &lt; 2 \\`tick\\`</p><br></div>"),
+            "{}",
+            result.code
+        );
+    }
+
+    #[test]
+    fn test_ssr_dynamic_class_style_and_show() {
+        let result =
+            ssr(r#"<div><span class="a" :class="cls" :id="id" v-show="ok">x</span></div>"#);
+        assert!(
+            result
+                .code
+                .contains("class=\"${_ssrRenderClass([\"a\", cls])}\""),
+            "{}",
+            result.code
+        );
+        assert!(result.code.contains("${_ssrRenderAttr(\"id\", id)}"));
+        assert!(result
+            .code
+            .contains("style=\"${_ssrRenderStyle((ok) ? null : { display: \"none\" })}\""));
+    }
+
+    #[test]
+    fn test_ssr_v_if() {
+        let result = ssr(r#"<div v-if="ok">a</div><span v-else>b</span>"#);
+        assert!(result.code.contains("if (ok) {"), "{}", result.code);
+        assert!(result.code.contains("} else {"), "{}", result.code);
+        assert!(result
+            .code
+            .contains("<span${_ssrRenderAttrs(_attrs)}>b</span>"));
+    }
+
+    #[test]
+    fn test_ssr_v_if_without_else_renders_anchor() {
+        let result = ssr(r#"<div><p v-if="ok">a</p></div>"#);
+        assert!(result.code.contains("_push(`<!---->`)"), "{}", result.code);
+    }
+
+    #[test]
+    fn test_ssr_v_for() {
+        let result =
+            ssr(r#"<ul><li v-for="(item, i) in items" :key="item.id">{{ item.name }}</li></ul>"#);
+        assert!(result.code.contains("<!--[-->"), "{}", result.code);
+        assert!(
+            result.code.contains("_ssrRenderList(items, (item, i) => {"),
+            "{}",
+            result.code
+        );
+        assert!(result
+            .code
+            .contains("_push(`<li>${_ssrInterpolate(item.name)}</li>`)"));
+        assert!(result.code.contains("<!--]-->"));
+    }
+
+    #[test]
+    fn test_ssr_component_with_slots() {
+        let result = ssr(r#"<div><MyComp :foo="bar">hi {{ name }}</MyComp></div>"#);
+        assert!(
+            result
+                .code
+                .contains("_push(_ssrRenderComponent(_component_MyComp, { foo: bar }, {"),
+            "{}",
+            result.code
+        );
+        assert!(result
+            .code
+            .contains("default: _withCtx((_, _push, _parent, _scopeId) => {"));
+        assert!(result.code.contains("if (_push) {"));
+        assert!(result.code.contains("_push(`hi ${_ssrInterpolate(name)}`)"));
+        assert!(result.code.contains("return ["));
+        assert!(result.code.contains("_: 1 /* STABLE */"));
+        assert!(result.code.contains(", _parent))"));
+    }
+
+    #[test]
+    fn test_ssr_slot_outlet() {
+        let result = ssr(r#"<div><slot name="header" :item="item">fallback</slot></div>"#);
+        assert!(
+            result
+                .code
+                .contains("_ssrRenderSlot(_ctx.$slots, \"header\", { item: item }, () => {"),
+            "{}",
+            result.code
+        );
+        assert!(result.code.contains("_push(`fallback`)"));
+        assert!(result.code.contains("}, _push, _parent)"));
+    }
+
+    #[test]
+    fn test_ssr_slot_outlet_object_bind() {
+        let result = ssr(r#"<div><slot v-bind="obj" foo="bar" :item="item"></slot></div>"#);
+        assert!(
+            result.code.contains(
+                "_ssrRenderSlot(_ctx.$slots, \"default\", _mergeProps(obj, { foo: \"bar\", item: item }), null, _push, _parent)"
+            ),
+            "{}",
+            result.code
+        );
+        assert!(result
+            .preamble
+            .contains("import { mergeProps as _mergeProps } from \"vue\""));
+    }
+
+    #[test]
+    fn test_ssr_v_model() {
+        let result = ssr(
+            r#"<div><input v-model="text"><input type="checkbox" v-model="checked"><textarea v-model="body"></textarea></div>"#,
+        );
+        assert!(
+            result
+                .code
+                .contains("<input${_ssrRenderAttr(\"value\", text)}>"),
+            "{}",
+            result.code
+        );
+        assert!(result.code.contains(
+            "${(_ssrIncludeBooleanAttr(Array.isArray(checked) ? _ssrLooseContain(checked, null) : checked)) ? \" checked\" : \"\"}"
+        ));
+        assert!(result
+            .code
+            .contains("<textarea>${_ssrInterpolate(body)}</textarea>"));
+    }
+
+    #[test]
+    fn test_ssr_v_html_and_v_text() {
+        let result = ssr(r#"<div><p v-html="raw"></p><p v-text="msg"></p></div>"#);
+        assert!(result.code.contains("<p>${raw}</p>"), "{}", result.code);
+        assert!(result.code.contains("<p>${_ssrInterpolate(msg)}</p>"));
+    }
+
+    #[test]
+    fn test_ssr_multiple_roots_fragment() {
+        let result = ssr("<div>a</div><div>b</div>");
+        assert!(
+            result
+                .code
+                .contains("_push(`<!--[--><div>a</div><div>b</div><!--]-->`)"),
+            "{}",
+            result.code
+        );
+    }
+
+    #[test]
+    fn test_ssr_teleport() {
+        let result = ssr(r##"<div><Teleport to="#modal"><p>hi</p></Teleport></div>"##);
+        assert!(
+            result
+                .code
+                .contains("_ssrRenderTeleport(_push, (_push) => {"),
+            "{}",
+            result.code
+        );
+        assert!(result.code.contains("}, \"#modal\", false, _parent)"));
+    }
+}
@@ -12,6 +12,7 @@
 //! that all other Vize compilers build upon.
 
 pub mod codegen;
+pub mod options;
 pub mod runtime_helpers;
 #[macro_use]
 pub mod test_macros;
@@ -27,6 +28,7 @@ pub use vize_armature::tokenizer;
 pub use vize_armature::{parse, parse_with_options, Parser};
 
 pub use codegen::*;
+pub use options::{CompilerOptions, TransformOptions};
 pub use runtime_helpers::*;
 pub use transform::*;
 pub use transforms::*;
@@ -0,0 +1,54 @@
+//! Compiler options.
+//!
+//! Parser and codegen options come from `vize_relief`. Transform options wrap
+//! the `vize_relief` ones to add user transforms that operate on
+//! [`TransformContext`](crate::transform::TransformContext).
+
+use rustc_hash::FxHashMap;
+use vize_carton::String;
+
+pub use vize_relief::options::{
+    BindingMetadata, BindingType, CodegenMode, CodegenOptions, ParseMode, ParserOptions, TextMode,
+    TransformOptions as BaseTransformOptions, WhitespaceStrategy,
+};
+
+use crate::transform::{DirectiveTransform, NodeTransform};
+
+/// Transform options
+#[derive(Debug, Clone, Default)]
+pub struct TransformOptions {
+    /// Options that don't depend on the transform context
+    pub base: BaseTransformOptions,
+    /// Extra node transforms, run in order after the built-in ones
+    pub node_transforms: Vec<NodeTransform>,
+    /// Extra directive transforms keyed by directive name (without `v-`),
+    /// run before the built-in handling of that directive
+    pub directive_transforms: FxHashMap<String, DirectiveTransform>,
+}
+
+impl From<BaseTransformOptions> for TransformOptions {
+    fn from(base: BaseTransformOptions) -> Self {
+        Self {
+            base,
+            ..Default::default()
+        }
+    }
+}
+
+/// Combined compiler options
+#[derive(Debug, Clone, Default)]
+pub struct CompilerOptions {
+    pub parser: ParserOptions,
+    pub transform: TransformOptions,
+    pub codegen: CodegenOptions,
+}
+
+impl From<vize_relief::options::CompilerOptions> for CompilerOptions {
+    fn from(options: vize_relief::options::CompilerOptions) -> Self {
+        Self {
+            parser: options.parser,
+            transform: options.transform.into(),
+            codegen: options.codegen,
+        }
+    }
+}
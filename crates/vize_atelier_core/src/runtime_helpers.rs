@@ -68,6 +68,79 @@ pub fn get_vnode_block_helper(ssr: bool, is_component: bool) -> RuntimeHelper {
     }
 }
 
+/// Module that server renderer helpers are imported from
+pub const SSR_HELPER_MODULE: &str = "vue/server-renderer";
+
+/// Server renderer helpers used by SSR codegen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SsrHelper {
+    Interpolate,
+    RenderVNode,
+    RenderComponent,
+    RenderSlot,
+    RenderClass,
+    RenderStyle,
+    RenderAttrs,
+    RenderAttr,
+    RenderDynamicAttr,
+    IncludeBooleanAttr,
+    LooseEqual,
+    LooseContain,
+    RenderDynamicModel,
+    GetDirectiveProps,
+    RenderList,
+    RenderSuspense,
+    RenderTeleport,
+}
+
+impl SsrHelper {
+    /// Exported name in `vue/server-renderer`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Interpolate => "ssrInterpolate",
+            Self::RenderVNode => "ssrRenderVNode",
+            Self::RenderComponent => "ssrRenderComponent",
+            Self::RenderSlot => "ssrRenderSlot",
+            Self::RenderClass => "ssrRenderClass",
+            Self::RenderStyle => "ssrRenderStyle",
+            Self::RenderAttrs => "ssrRenderAttrs",
+            Self::RenderAttr => "ssrRenderAttr",
+            Self::RenderDynamicAttr => "ssrRenderDynamicAttr",
+            Self::IncludeBooleanAttr => "ssrIncludeBooleanAttr",
+            Self::LooseEqual => "ssrLooseEqual",
+            Self::LooseContain => "ssrLooseContain",
+            Self::RenderDynamicModel => "ssrRenderDynamicModel",
+            Self::GetDirectiveProps => "ssrGetDirectiveProps",
+            Self::RenderList => "ssrRenderList",
+            Self::RenderSuspense => "ssrRenderSuspense",
+            Self::RenderTeleport => "ssrRenderTeleport",
+        }
+    }
+
+    /// Local alias used in generated code
+    pub fn alias(&self) -> &'static str {
+        match self {
+            Self::Interpolate => "_ssrInterpolate",
+            Self::RenderVNode => "_ssrRenderVNode",
+            Self::RenderComponent => "_ssrRenderComponent",
+            Self::RenderSlot => "_ssrRenderSlot",
+            Self::RenderClass => "_ssrRenderClass",
+            Self::RenderStyle => "_ssrRenderStyle",
+            Self::RenderAttrs => "_ssrRenderAttrs",
+            Self::RenderAttr => "_ssrRenderAttr",
+            Self::RenderDynamicAttr => "_ssrRenderDynamicAttr",
+            Self::IncludeBooleanAttr => "_ssrIncludeBooleanAttr",
+            Self::LooseEqual => "_ssrLooseEqual",
+            Self::LooseContain => "_ssrLooseContain",
+            Self::RenderDynamicModel => "_ssrRenderDynamicModel",
+            Self::GetDirectiveProps => "_ssrGetDirectiveProps",
+            Self::RenderList => "_ssrRenderList",
+            Self::RenderSuspense => "_ssrRenderSuspense",
+            Self::RenderTeleport => "_ssrRenderTeleport",
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
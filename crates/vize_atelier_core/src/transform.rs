@@ -8,6 +8,10 @@ use vize_carton::{Box, Bump, String, Vec};
 use crate::ast::*;
 use crate::errors::{CompilerError, ErrorCode};
 use crate::options::TransformOptions;
+use crate::transforms::transform_expression::collect_param_names;
+use crate::transforms::v_on::cache_v_on_handlers;
+use crate::transforms::v_once::{has_v_once, transform_v_once};
+use crate::transforms::v_slot::get_slot_props_string;
 
 /// Check if a directive is a built-in directive (not custom)
 fn is_builtin_directive_name(name: &str) -> bool {
@@ -32,14 +36,16 @@ fn is_builtin_directive_name(name: &str) -> bool {
 }
 
 /// Transform function for nodes - returns optional exit function(s)
-pub type NodeTransform<'a> =
-    fn(&mut TransformContext<'a>, &mut TemplateChildNode<'a>) -> Option<std::vec::Vec<ExitFn<'a>>>;
+pub type NodeTransform = for<'a> fn(
+    &mut TransformContext<'a>,
+    &mut TemplateChildNode<'a>,
+) -> Option<std::vec::Vec<ExitFn<'a>>>;
 
 /// Exit function called after children are processed
 pub type ExitFn<'a> = std::boxed::Box<dyn FnOnce(&mut TransformContext<'a>) + 'a>;
 
 /// Transform function for directives
-pub type DirectiveTransform<'a> = fn(
+pub type DirectiveTransform = for<'a> fn(
     &mut TransformContext<'a>,
     &mut ElementNode<'a>,
     &DirectiveNode<'a>,
@@ -137,7 +143,7 @@ impl<'a> ParentNode<'a> {
 impl<'a> TransformContext<'a> {
     /// Create a new transform context
     pub fn new(allocator: &'a Bump, source: String, options: TransformOptions) -> Self {
-        let ssr = options.ssr;
+        let ssr = options.base.ssr;
         Self {
             allocator,
             source,
@@ -327,8 +333,12 @@ fn clone_expression<'a>(allocator: &'a Bump, exp: &ExpressionNode<'a>) -> Expres
     }
 }
 
-/// Transform the root AST node
-pub fn transform<'a>(allocator: &'a Bump, root: &mut RootNode<'a>, options: TransformOptions) {
+/// Transform the root AST node, returning the errors reported along the way
+pub fn transform<'a>(
+    allocator: &'a Bump,
+    root: &mut RootNode<'a>,
+    options: TransformOptions,
+) -> std::vec::Vec<CompilerError> {
     let source = root.source.clone();
     let mut ctx = TransformContext::new(allocator, source, options);
     ctx.root = Some(root as *mut _);
@@ -340,6 +350,9 @@ pub fn transform<'a>(allocator: &'a Bump, root: &mut RootNode<'a>, options: Tran
     use crate::transforms::hoist_static::hoist_static;
     hoist_static(&mut ctx, &mut root.children);
 
+    // Bind `v-bind()` values from `<style>` on the SSR root elements
+    crate::transforms::ssr_css_vars::inject_ssr_css_vars(&mut ctx, root);
+
     // Create root codegen node
     create_root_codegen(&mut ctx, root);
 
@@ -357,8 +370,14 @@ pub fn transform<'a>(allocator: &'a Bump, root: &mut RootNode<'a>, options: Tran
     for hoist in ctx.hoists.into_iter() {
         root.hoists.push(hoist);
     }
+    // Transfer cache slots to root so codegen allocates after them
+    for cached in ctx.cached.into_iter() {
+        root.cached.push(cached);
+    }
     root.temps = ctx.temps;
     root.transformed = true;
+
+    ctx.errors
 }
 
 /// Traverse children of a parent node
@@ -389,30 +408,43 @@ fn traverse_node<'a>(ctx: &mut TransformContext<'a>, node: &mut TemplateChildNod
     // Collect exit functions from transforms
     let mut exit_fns: std::vec::Vec<ExitFn<'a>> = std::vec::Vec::new();
 
+    // Track v-once subtrees (restored on exit)
+    let was_in_v_once = ctx.in_v_once;
+    if let TemplateChildNode::Element(el) = node {
+        if !ctx.in_v_once && has_v_once(el) {
+            transform_v_once(ctx, el);
+            exit_fns.push(std::boxed::Box::new(|ctx: &mut TransformContext<'a>| {
+                ctx.in_v_once = false;
+            }));
+        }
+    }
+
     // Apply node transforms based on node type
     match node {
         TemplateChildNode::Element(el) => {
             // Check for structural directives first
             let structural_result = check_structural_directive(el);
 
-            if let Some((dir_name, exp, exp_loc)) = structural_result {
+            if let Some((dir_name, exp, dir_loc)) = structural_result {
                 // Remove the directive from props
                 remove_structural_directive(el, &dir_name);
 
                 // Handle the structural directive
                 match dir_name.as_str() {
-                    "if" => {
-                        if let Some(exits) = transform_v_if(ctx, exp.as_ref(), exp_loc, true) {
-                            exit_fns.extend(exits);
+                    "if" | "else-if" | "else" => {
+                        // Only v-else goes without a condition
+                        if dir_name != "else"
+                            && exp.as_ref().is_none_or(|e| e.content.trim().is_empty())
+                        {
+                            ctx.on_error(ErrorCode::VIfNoExpression, Some(dir_loc.clone()));
                         }
-                    }
-                    "else-if" | "else" => {
-                        if let Some(exits) = transform_v_if(ctx, exp.as_ref(), exp_loc, false) {
+                        let is_root = dir_name == "if";
+                        if let Some(exits) = transform_v_if(ctx, exp.as_ref(), dir_loc, is_root) {
                             exit_fns.extend(exits);
                         }
                     }
                     "for" => {
-                        if let Some(exits) = transform_v_for(ctx, exp.as_ref(), exp_loc) {
+                        if let Some(exits) = transform_v_for(ctx, exp.as_ref(), dir_loc) {
                             exit_fns.extend(exits);
                         }
                     }
@@ -423,7 +455,15 @@ fn traverse_node<'a>(ctx: &mut TransformContext<'a>, node: &mut TemplateChildNod
                 if let Some(current_ptr) = ctx.current_node {
                     let current = unsafe { &mut *current_ptr };
                     match current {
-                        TemplateChildNode::If(if_node) => {
+                        TemplateChildNode::If(_) => {
+                            // Node transforms see the new if node before its branches
+                            if !apply_node_transforms(ctx, current, &mut exit_fns) {
+                                ctx.in_v_once = was_in_v_once;
+                                return;
+                            }
+                            let TemplateChildNode::If(if_node) = current else {
+                                unreachable!()
+                            };
                             // Traverse if branches that were just created
                             for i in 0..if_node.branches.len() {
                                 let branch_ptr = &mut if_node.branches[i] as *mut IfBranchNode<'a>;
@@ -435,7 +475,14 @@ fn traverse_node<'a>(ctx: &mut TransformContext<'a>, node: &mut TemplateChildNod
                             }
                             return;
                         }
-                        TemplateChildNode::For(for_node) => {
+                        TemplateChildNode::For(_) => {
+                            if !apply_node_transforms(ctx, current, &mut exit_fns) {
+                                ctx.in_v_once = was_in_v_once;
+                                return;
+                            }
+                            let TemplateChildNode::For(for_node) = current else {
+                                unreachable!()
+                            };
                             // Add loop identifiers to scope
                             if let Some(ExpressionNode::Simple(exp)) = &for_node.value_alias {
                                 ctx.add_identifier(exp.content.clone());
@@ -484,6 +531,7 @@ fn traverse_node<'a>(ctx: &mut TransformContext<'a>, node: &mut TemplateChildNod
                     }
                 } else {
                     // Node was removed, return early
+                    ctx.in_v_once = was_in_v_once;
                     return;
                 }
             } else {
@@ -543,10 +591,32 @@ fn traverse_node<'a>(ctx: &mut TransformContext<'a>, node: &mut TemplateChildNod
         _ => {}
     }
 
-    // Traverse children for element nodes
+    if !apply_node_transforms(ctx, node, &mut exit_fns) {
+        ctx.in_v_once = was_in_v_once;
+        return;
+    }
+
+    // Traverse children for element nodes, with slot props in scope
     if let TemplateChildNode::Element(el) = node {
+        let slot_params = el
+            .props
+            .iter()
+            .find_map(|prop| match prop {
+                PropNode::Directive(dir) if dir.name == "slot" => get_slot_props_string(dir),
+                _ => None,
+            })
+            .map(|params| collect_param_names(&params))
+            .unwrap_or_default();
+        for param in slot_params.iter() {
+            ctx.add_identifier(param.as_str());
+        }
+
         let el_ptr = el.as_mut() as *mut ElementNode<'a>;
         traverse_children(ctx, ParentNode::Element(el_ptr));
+
+        for param in slot_params.iter() {
+            ctx.remove_identifier(param);
+        }
     }
 
     // Call exit functions in reverse order
@@ -556,14 +626,70 @@ fn traverse_node<'a>(ctx: &mut TransformContext<'a>, node: &mut TemplateChildNod
     }
 }
 
-/// Check if element has a structural directive
+/// Run the node transforms registered in the options.
+/// Returns `false` if a transform removed the node.
+fn apply_node_transforms<'a>(
+    ctx: &mut TransformContext<'a>,
+    node: &mut TemplateChildNode<'a>,
+    exit_fns: &mut std::vec::Vec<ExitFn<'a>>,
+) -> bool {
+    if ctx.options.node_transforms.is_empty() {
+        return true;
+    }
+    let transforms = ctx.options.node_transforms.clone();
+    for node_transform in transforms {
+        if let Some(exits) = node_transform(ctx, node) {
+            exit_fns.extend(exits);
+        }
+        if ctx.was_node_removed() {
+            return false;
+        }
+    }
+    true
+}
+
+/// Run the directive transforms registered in the options.
+/// Produced props take the directive's place; removed directives are dropped.
+fn apply_directive_transforms<'a>(ctx: &mut TransformContext<'a>, el: &mut ElementNode<'a>) {
+    if ctx.options.directive_transforms.is_empty() {
+        return;
+    }
+
+    let mut i = 0;
+    while i < el.props.len() {
+        let transform = match &el.props[i] {
+            PropNode::Directive(dir) => ctx.options.directive_transforms.get(&dir.name).copied(),
+            PropNode::Attribute(_) => None,
+        };
+        let Some(directive_transform) = transform else {
+            i += 1;
+            continue;
+        };
+
+        let PropNode::Directive(dir) = el.props.remove(i) else {
+            unreachable!()
+        };
+        let result = directive_transform(ctx, el, &dir);
+        let (props, remove_directive) = match result {
+            Some(result) => (Some(result.props), result.remove_directive),
+            None => (None, false),
+        };
+        if !remove_directive {
+            el.props.insert(i, PropNode::Directive(dir));
+            i += 1;
+        }
+        for prop in props.into_iter().flatten() {
+            el.props.insert(i, prop);
+            i += 1;
+        }
+    }
+}
+
+/// Check if element has a structural directive, returning its name,
+/// expression and location
 fn check_structural_directive<'a>(
     el: &ElementNode<'a>,
-) -> Option<(
-    String,
-    Option<SimpleExpressionContent>,
-    Option<SourceLocation>,
-)> {
+) -> Option<(String, Option<SimpleExpressionContent>, SourceLocation)> {
     for prop in el.props.iter() {
         if let PropNode::Directive(dir) = prop {
             match dir.name.as_str() {
@@ -580,8 +706,7 @@ fn check_structural_directive<'a>(
                             loc: c.loc.clone(),
                         },
                     });
-                    let exp_loc = dir.exp.as_ref().map(|e| e.loc().clone());
-                    return Some((dir.name.clone(), exp_content, exp_loc));
+                    return Some((dir.name.clone(), exp_content, dir.loc.clone()));
                 }
                 _ => {}
             }
@@ -638,7 +763,7 @@ fn remove_structural_directive<'a>(el: &mut Box<'a, ElementNode<'a>>, dir_name:
 fn transform_v_if<'a>(
     ctx: &mut TransformContext<'a>,
     exp: Option<&SimpleExpressionContent>,
-    _exp_loc: Option<SourceLocation>,
+    dir_loc: SourceLocation,
     is_root: bool,
 ) -> Option<std::vec::Vec<ExitFn<'a>>> {
     let allocator = ctx.allocator;
@@ -676,7 +801,7 @@ fn transform_v_if<'a>(
                 allocator,
             ));
             // Process expression to add $setup. prefix
-            if ctx.options.prefix_identifiers || ctx.options.is_ts {
+            if ctx.options.base.prefix_identifiers || ctx.options.base.is_ts {
                 crate::transforms::transform_expression::process_expression(ctx, &raw_exp, false)
             } else {
                 raw_exp
@@ -782,7 +907,7 @@ fn transform_v_if<'a>(
                     allocator,
                 ));
                 // Process expression to add $setup. prefix
-                if ctx.options.prefix_identifiers || ctx.options.is_ts {
+                if ctx.options.base.prefix_identifiers || ctx.options.base.is_ts {
                     crate::transforms::transform_expression::process_expression(
                         ctx, &raw_exp, false,
                     )
@@ -838,7 +963,7 @@ fn transform_v_if<'a>(
             // Remove the placeholder we left
             ctx.remove_node();
         } else {
-            ctx.on_error(ErrorCode::VElseNoAdjacentIf, None);
+            ctx.on_error(ErrorCode::VElseNoAdjacentIf, Some(dir_loc));
         }
 
         None
@@ -849,14 +974,17 @@ fn transform_v_if<'a>(
 fn transform_v_for<'a>(
     ctx: &mut TransformContext<'a>,
     exp: Option<&SimpleExpressionContent>,
-    _exp_loc: Option<SourceLocation>,
+    dir_loc: SourceLocation,
 ) -> Option<std::vec::Vec<ExitFn<'a>>> {
     let allocator = ctx.allocator;
 
     let Some(exp) = exp else {
-        ctx.on_error(ErrorCode::VForNoExpression, None);
+        ctx.on_error(ErrorCode::VForNoExpression, Some(dir_loc));
         return None;
     };
+    if !exp.content.contains(" in ") && !exp.content.contains(" of ") {
+        ctx.on_error(ErrorCode::VForMalformedExpression, Some(exp.loc.clone()));
+    }
 
     // Take the current element from parent
     let taken = ctx.take_current_node();
@@ -872,7 +1000,7 @@ fn transform_v_for<'a>(
         parse_v_for_expression(allocator, &exp.content, &exp.loc);
 
     // Process source expression to add _ctx. prefix if needed
-    if ctx.options.prefix_identifiers || ctx.options.is_ts {
+    if ctx.options.base.prefix_identifiers || ctx.options.base.is_ts {
         use crate::transforms::transform_expression::prefix_identifiers_in_expression;
         if let ExpressionNode::Simple(ref mut source_exp) = source {
             let processed = prefix_identifiers_in_expression(&source_exp.content);
@@ -917,6 +1045,27 @@ fn transform_v_for<'a>(
     None
 }
 
+/// Split `(value, key, index)` aliases on top-level commas, keeping
+/// destructuring patterns intact
+fn split_v_for_aliases(inner: &str) -> std::vec::Vec<&str> {
+    let mut aliases = std::vec::Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '{' | '[' | '(' => depth += 1,
+            '}' | ']' | ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                aliases.push(inner[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    aliases.push(inner[start..].trim());
+    aliases
+}
+
 /// Parse v-for expression
 fn parse_v_for_expression<'a>(
     allocator: &'a Bump,
@@ -972,7 +1121,7 @@ fn parse_v_for_expression<'a>(
     // Parse aliases
     let (value, key, index) = if alias_str.starts_with('(') && alias_str.ends_with(')') {
         let inner = &alias_str[1..alias_str.len() - 1];
-        let aliases: std::vec::Vec<&str> = inner.split(',').map(|s| s.trim()).collect();
+        let aliases = split_v_for_aliases(inner);
 
         let value = if !aliases.is_empty() && !aliases[0].is_empty() {
             Some(ExpressionNode::Simple(Box::new_in(
@@ -1068,6 +1217,7 @@ fn transform_element<'a>(
             // Only add ResolveComponent if component is not in binding metadata
             let is_in_bindings = ctx
                 .options
+                .base
                 .binding_metadata
                 .as_ref()
                 .map(|m| m.bindings.contains_key(&el.tag))
@@ -1146,10 +1296,12 @@ fn process_element_props<'a>(ctx: &mut TransformContext<'a>, el: &mut Box<'a, El
     let is_component = el.tag_type == ElementType::Component;
 
     // Process directive expressions with _ctx prefix if needed
-    if ctx.options.prefix_identifiers || ctx.options.is_ts {
+    if ctx.options.base.prefix_identifiers || ctx.options.base.is_ts {
         process_directive_expressions(ctx, el);
     }
 
+    apply_directive_transforms(ctx, el);
+
     // Collect indices of v-model directives to process
     let mut model_indices: std::vec::Vec<usize> = std::vec::Vec::new();
     for (i, prop) in el.props.iter().enumerate() {
@@ -1324,6 +1476,7 @@ fn process_element_props<'a>(ctx: &mut TransformContext<'a>, el: &mut Box<'a, El
                     ))),
                     modifiers: Vec::new_in(allocator),
                     for_parse_result: None,
+                    cache_index: None,
                     loc: data.dir_loc.clone(),
                 },
                 allocator,
@@ -1349,6 +1502,7 @@ fn process_element_props<'a>(ctx: &mut TransformContext<'a>, el: &mut Box<'a, El
                     ))),
                     modifiers: Vec::new_in(allocator),
                     for_parse_result: None,
+                    cache_index: None,
                     loc: data.dir_loc.clone(),
                 },
                 allocator,
@@ -1377,6 +1531,7 @@ fn process_element_props<'a>(ctx: &mut TransformContext<'a>, el: &mut Box<'a, El
                         ))),
                         modifiers: Vec::new_in(allocator),
                         for_parse_result: None,
+                        cache_index: None,
                         loc: SourceLocation::STUB,
                     },
                     allocator,
@@ -1409,6 +1564,7 @@ fn process_element_props<'a>(ctx: &mut TransformContext<'a>, el: &mut Box<'a, El
                     ))),
                     modifiers: Vec::new_in(allocator),
                     for_parse_result: None,
+                    cache_index: None,
                     loc: data.dir_loc.clone(),
                 },
                 allocator,
@@ -1417,6 +1573,8 @@ fn process_element_props<'a>(ctx: &mut TransformContext<'a>, el: &mut Box<'a, El
             el.props.insert(data.idx + 1, event_prop);
         }
     }
+
+    cache_v_on_handlers(ctx, el);
 }
 
 /// Transform interpolation node
@@ -1427,7 +1585,7 @@ fn transform_interpolation<'a>(
     ctx.helper(RuntimeHelper::ToDisplayString);
 
     // Process the expression to add _ctx. prefix and/or strip TypeScript if needed
-    if ctx.options.prefix_identifiers || ctx.options.is_ts {
+    if ctx.options.base.prefix_identifiers || ctx.options.base.is_ts {
         use crate::transforms::transform_expression::process_expression;
         let processed = process_expression(ctx, &interp.content, false);
         interp.content = processed;
@@ -1455,6 +1613,7 @@ fn create_root_codegen<'a>(ctx: &mut TransformContext<'a>, root: &mut RootNode<'
 mod tests {
     use super::transform;
     use crate::codegen::generate;
+    use crate::errors::ErrorCode;
     use crate::options::{CodegenOptions, TransformOptions};
     use crate::parser::parse;
     use bumpalo::Bump;
@@ -1511,6 +1670,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_transform_reports_structural_errors() {
+        let allocator = Bump::new();
+        let (mut root, errors) = parse(
+            &allocator,
+            "<p>a</p>\n<div v-else>no</div>\n<div v-for=\"items\"></div>",
+        );
+        assert!(errors.is_empty(), "Parse errors: {:?}", errors);
+
+        let errors = transform(&allocator, &mut root, TransformOptions::default());
+
+        let codes: std::vec::Vec<_> = errors.iter().map(|e| e.code).collect();
+        assert_eq!(
+            codes,
+            vec![
+                ErrorCode::VElseNoAdjacentIf,
+                ErrorCode::VForMalformedExpression
+            ]
+        );
+        let v_else = errors[0].loc.as_ref().unwrap();
+        assert_eq!((v_else.start.line, v_else.start.column), (2, 6));
+        assert_eq!(v_else.source.as_str(), "v-else");
+        assert_eq!(errors[1].loc.as_ref().unwrap().source.as_str(), "items");
+    }
+
     #[test]
     fn test_v_if_else_creates_branches() {
         let allocator = Bump::new();
@@ -1604,4 +1788,105 @@ mod tests {
             "Should contain openBlock"
         );
     }
+
+    fn compile_with(template: &str, options: TransformOptions) -> std::string::String {
+        let allocator = Bump::new();
+        let (mut root, errors) = parse(&allocator, template);
+        assert!(errors.is_empty(), "Parse errors: {:?}", errors);
+        transform(&allocator, &mut root, options);
+        generate(&root, CodegenOptions::default()).code.to_string()
+    }
+
+    /// `v-test-id` is stripped from the output
+    fn strip_test_id<'a>(
+        ctx: &mut super::TransformContext<'a>,
+        _el: &mut crate::ast::ElementNode<'a>,
+        _dir: &crate::ast::DirectiveNode<'a>,
+    ) -> Option<super::DirectiveTransformResult<'a>> {
+        Some(super::DirectiveTransformResult {
+            props: vize_carton::Vec::new_in(ctx.allocator),
+            remove_directive: true,
+            ssr_tag_type: None,
+        })
+    }
+
+    /// `v-t="key"` becomes `:textContent="$t(key)"`
+    fn translate<'a>(
+        ctx: &mut super::TransformContext<'a>,
+        _el: &mut crate::ast::ElementNode<'a>,
+        dir: &crate::ast::DirectiveNode<'a>,
+    ) -> Option<super::DirectiveTransformResult<'a>> {
+        use crate::ast::*;
+        let Some(ExpressionNode::Simple(exp)) = &dir.exp else {
+            return None;
+        };
+        let mut bind = DirectiveNode::new(ctx.allocator, "bind", dir.loc.clone());
+        bind.arg = Some(ExpressionNode::Simple(vize_carton::Box::new_in(
+            SimpleExpressionNode::new("textContent", true, dir.loc.clone()),
+            ctx.allocator,
+        )));
+        bind.exp = Some(ExpressionNode::Simple(vize_carton::Box::new_in(
+            SimpleExpressionNode::new(format!("$t({})", exp.content), false, exp.loc.clone()),
+            ctx.allocator,
+        )));
+        let mut props = vize_carton::Vec::new_in(ctx.allocator);
+        props.push(PropNode::Directive(vize_carton::Box::new_in(
+            bind,
+            ctx.allocator,
+        )));
+        Some(super::DirectiveTransformResult {
+            props,
+            remove_directive: true,
+            ssr_tag_type: None,
+        })
+    }
+
+    #[test]
+    fn test_custom_directive_transforms() {
+        let mut options = TransformOptions::default();
+        options
+            .directive_transforms
+            .insert("test-id".into(), strip_test_id);
+        options.directive_transforms.insert("t".into(), translate);
+
+        let code = compile_with(r#"<p v-test-id="'p'" v-t="'hello'"></p>"#, options);
+        assert!(code.contains("textContent: $t('hello')"), "{}", code);
+        assert!(!code.contains("resolveDirective"), "{}", code);
+        assert!(!code.contains("test-id"), "{}", code);
+    }
+
+    #[test]
+    fn test_unregistered_directive_stays_runtime() {
+        let code = compile_with(r#"<p v-t="'hello'"></p>"#, TransformOptions::default());
+        assert!(code.contains("_resolveDirective(\"t\")"), "{}", code);
+    }
+
+    /// Drops comments and upper-cases text
+    fn shout<'a>(
+        ctx: &mut super::TransformContext<'a>,
+        node: &mut crate::ast::TemplateChildNode<'a>,
+    ) -> Option<std::vec::Vec<super::ExitFn<'a>>> {
+        match node {
+            crate::ast::TemplateChildNode::Comment(_) => ctx.remove_node(),
+            crate::ast::TemplateChildNode::Text(text) => {
+                text.content = text.content.to_uppercase();
+            }
+            _ => {}
+        }
+        None
+    }
+
+    #[test]
+    fn test_custom_node_transforms() {
+        let options = TransformOptions {
+            node_transforms: vec![shout],
+            ..Default::default()
+        };
+        let code = compile_with(
+            "<div><!-- note --><span v-if=\"ok\">hi</span></div>",
+            options,
+        );
+        assert!(code.contains("\"HI\""), "{}", code);
+        assert!(!code.contains("note"), "{}", code);
+    }
 }
@@ -4,6 +4,8 @@
 //! directives and node types during the transform phase.
 
 pub mod hoist_static;
+pub mod ssr_css_vars;
+pub mod stringify_static;
 pub mod transform_element;
 pub mod transform_expression;
 pub mod transform_text;
@@ -17,10 +19,12 @@ pub mod v_once;
 pub mod v_slot;
 
 pub use hoist_static::*;
+pub use ssr_css_vars::*;
+pub use stringify_static::*;
 pub use transform_element::*;
 pub use transform_expression::{
     is_simple_identifier, prefix_identifiers_in_expression, process_expression,
-    process_inline_handler, strip_typescript_from_expression,
+    process_inline_handler, rewrite_standalone_expression, strip_typescript_from_expression,
 };
 pub use transform_text::*;
 pub use v_bind::*;
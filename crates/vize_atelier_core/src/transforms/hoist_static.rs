@@ -6,6 +6,7 @@ use vize_carton::{Box, Bump, Vec};
 
 use crate::ast::*;
 use crate::transform::TransformContext;
+use crate::transforms::stringify_static::stringify_static;
 
 /// Check if a node is fully static (can be hoisted)
 pub fn is_static_node(node: &TemplateChildNode<'_>) -> bool {
@@ -118,6 +119,9 @@ pub fn hoist_static<'a>(
     ctx: &mut TransformContext<'a>,
     children: &mut Vec<'a, TemplateChildNode<'a>>,
 ) {
+    if ctx.options.base.hoist_static && !ctx.options.base.ssr {
+        stringify_static(ctx, children, true);
+    }
     hoist_static_inner(ctx, children, true)
 }
 
@@ -127,7 +131,8 @@ fn hoist_static_inner<'a>(
     children: &mut Vec<'a, TemplateChildNode<'a>>,
     is_root: bool,
 ) {
-    if !ctx.options.hoist_static {
+    // SSR output is string concatenation, so there is nothing to hoist
+    if !ctx.options.base.hoist_static || ctx.options.base.ssr {
         return;
     }
 
@@ -148,14 +153,19 @@ fn hoist_static_inner<'a>(
                             hoist_element_props(ctx, el, allocator);
                         }
                     }
-                } else {
-                    // Non-root static elements can be fully hoisted
-                    if let TemplateChildNode::Element(el) = &children[i] {
+                } else if let TemplateChildNode::Element(el) = &mut children[i] {
+                    if el.children.is_empty()
+                        || create_children_expression(allocator, &el.children).is_some()
+                    {
+                        // Non-root static elements can be fully hoisted
                         let vnode_call = create_vnode_call_from_element(allocator, el);
                         let hoist_index = ctx.hoist(vnode_call);
                         // Replace with hoisted reference
                         children[i] = TemplateChildNode::Hoisted(hoist_index);
                         ctx.helper(RuntimeHelper::CreateElementVNode);
+                    } else {
+                        // Nested children can't be expressed in a hoisted VNode yet
+                        hoist_static_inner(ctx, &mut el.children, false);
                     }
                 }
             }
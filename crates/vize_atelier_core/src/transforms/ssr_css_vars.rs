@@ -0,0 +1,114 @@
+//! SSR CSS variables.
+//!
+//! With `ssr_css_vars` set, the render function declares
+//! `const _cssVars = { style: { ... } }` and the root elements are bound with
+//! `v-bind="_cssVars"`, so `v-bind()` values in `<style>` are rendered into
+//! the server markup.
+
+use vize_carton::{Box, String, Vec};
+
+use crate::ast::*;
+use crate::transform::TransformContext;
+
+use super::transform_expression::rewrite_standalone_expression;
+
+/// Process `ssr_css_vars` and bind `_cssVars` on the root elements
+pub fn inject_ssr_css_vars<'a>(ctx: &mut TransformContext<'a>, root: &mut RootNode<'a>) {
+    if !ctx.options.base.ssr {
+        return;
+    }
+    let Some(css_vars) = ctx.options.base.ssr_css_vars.clone() else {
+        return;
+    };
+
+    let (code, used_unref) = rewrite_standalone_expression(&css_vars, ctx.options.clone());
+    if used_unref {
+        ctx.helper(RuntimeHelper::Unref);
+    }
+    root.ssr_css_vars = Some(String::from(code));
+
+    bind_css_vars(ctx, &mut root.children);
+}
+
+/// Add `v-bind="_cssVars"` to the element roots in `children`
+fn bind_css_vars<'a>(ctx: &TransformContext<'a>, children: &mut Vec<'a, TemplateChildNode<'a>>) {
+    for child in children.iter_mut() {
+        match child {
+            TemplateChildNode::Element(el)
+                if matches!(el.tag_type, ElementType::Element | ElementType::Component) =>
+            {
+                if el.tag == "Suspense" || el.tag == "suspense" {
+                    bind_css_vars(ctx, &mut el.children);
+                    continue;
+                }
+                let mut dir = DirectiveNode::new(ctx.allocator, "bind", SourceLocation::STUB);
+                dir.exp = Some(ExpressionNode::Simple(Box::new_in(
+                    SimpleExpressionNode::new("_cssVars", false, SourceLocation::STUB),
+                    ctx.allocator,
+                )));
+                el.props
+                    .push(PropNode::Directive(Box::new_in(dir, ctx.allocator)));
+            }
+            TemplateChildNode::If(if_node) => {
+                for branch in if_node.branches.iter_mut() {
+                    bind_css_vars(ctx, &mut branch.children);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::options::{BaseTransformOptions, CodegenMode, CodegenOptions, TransformOptions};
+
+    fn compile_ssr_css_vars(template: &str) -> std::string::String {
+        let allocator = bumpalo::Bump::new();
+        let (mut root, errors) = crate::parser::parse(&allocator, template);
+        assert!(errors.is_empty(), "Parse errors: {:?}", errors);
+        let options = TransformOptions {
+            base: BaseTransformOptions {
+                ssr: true,
+                prefix_identifiers: true,
+                ssr_css_vars: Some("{ \"--abc-color\": (color) }".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        crate::transform::transform(&allocator, &mut root, options);
+        let codegen = CodegenOptions {
+            ssr: true,
+            mode: CodegenMode::Module,
+            prefix_identifiers: true,
+            ..Default::default()
+        };
+        crate::codegen::generate(&root, codegen).code.to_string()
+    }
+
+    #[test]
+    fn test_ssr_css_vars_single_root() {
+        let code = compile_ssr_css_vars("<div>hi</div>");
+        assert!(
+            code.contains("const _cssVars = { style: { \"--abc-color\": (_ctx.color) } }"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("_ssrRenderAttrs(_mergeProps(_cssVars, _attrs))"),
+            "{}",
+            code
+        );
+    }
+
+    #[test]
+    fn test_ssr_css_vars_fragment_and_if() {
+        let code = compile_ssr_css_vars("<div v-if=\"ok\">a</div><p v-else>b</p><span>c</span>");
+        assert_eq!(
+            code.matches("_ssrRenderAttrs(_cssVars)").count(),
+            3,
+            "{}",
+            code
+        );
+    }
+}
@@ -0,0 +1,315 @@
+//! Static stringification.
+//!
+//! Turns long runs of static siblings into a single hoisted
+//! `_createStaticVNode("<html>", count)` call, so large static sections are
+//! created from an HTML string instead of one hoisted VNode per element.
+
+use vize_carton::{escape_html, is_void_tag, Box, String, Vec};
+
+use crate::ast::*;
+use crate::transform::TransformContext;
+
+/// Number of consecutive static nodes that triggers stringification
+pub const STRINGIFY_NODE_THRESHOLD: usize = 20;
+
+/// Number of static elements with attributes that triggers stringification
+pub const STRINGIFY_ELEMENT_WITH_BINDING_THRESHOLD: usize = 5;
+
+/// Stringify static runs in the subtree below `children`.
+///
+/// The list itself is left alone when it holds the template roots, since root
+/// nodes must be created inline as blocks.
+pub fn stringify_static<'a>(
+    ctx: &mut TransformContext<'a>,
+    children: &mut Vec<'a, TemplateChildNode<'a>>,
+    is_root: bool,
+) {
+    if !is_root {
+        stringify_runs(ctx, children);
+    }
+
+    for child in children.iter_mut() {
+        match child {
+            TemplateChildNode::Element(el) if el.tag_type == ElementType::Element => {
+                stringify_static(ctx, &mut el.children, false);
+            }
+            TemplateChildNode::If(if_node) => {
+                // Branch roots carry a key and must stay inline
+                for branch in if_node.branches.iter_mut() {
+                    stringify_static(ctx, &mut branch.children, true);
+                }
+            }
+            TemplateChildNode::For(for_node) => {
+                stringify_static(ctx, &mut for_node.children, true);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Replace every run of stringifiable siblings that crosses a threshold
+fn stringify_runs<'a>(
+    ctx: &mut TransformContext<'a>,
+    children: &mut Vec<'a, TemplateChildNode<'a>>,
+) {
+    let mut i = 0;
+    while i < children.len() {
+        let start = i;
+        let mut node_count = 0;
+        let mut element_count = 0;
+        let mut has_element = false;
+        while i < children.len() {
+            let Some((nodes, elements)) = analyze_node(&children[i]) else {
+                break;
+            };
+            has_element |= matches!(children[i], TemplateChildNode::Element(_));
+            node_count += nodes;
+            element_count += elements;
+            i += 1;
+        }
+
+        if !has_element
+            || (node_count < STRINGIFY_NODE_THRESHOLD
+                && element_count < STRINGIFY_ELEMENT_WITH_BINDING_THRESHOLD)
+        {
+            // Skip the node that ended the run
+            i = i.max(start + 1);
+            continue;
+        }
+
+        let mut html = std::string::String::new();
+        let scope_id = ctx.options.base.scope_id.as_deref();
+        for node in children[start..i].iter() {
+            stringify_node(node, scope_id, &mut html);
+        }
+        let hoist_index = ctx.hoist(create_static_call(ctx, &html, i - start));
+        ctx.helper(RuntimeHelper::CreateStatic);
+
+        children[start] = TemplateChildNode::Hoisted(hoist_index);
+        // The rest of the run is covered by the static vnode
+        children.drain(start + 1..i);
+        i = start + 1;
+    }
+}
+
+/// Count `(nodes, elements with attributes)` in a stringifiable node,
+/// or `None` if the node can't be turned into plain HTML
+fn analyze_node(node: &TemplateChildNode<'_>) -> Option<(usize, usize)> {
+    match node {
+        TemplateChildNode::Text(_) | TemplateChildNode::Comment(_) => Some((1, 0)),
+        TemplateChildNode::Element(el) => {
+            if el.tag_type != ElementType::Element || !is_stringifiable_tag(&el.tag) {
+                return None;
+            }
+            let mut elements = 0;
+            for prop in el.props.iter() {
+                match prop {
+                    PropNode::Attribute(attr) if is_stringifiable_attr(&attr.name) => {
+                        elements = 1;
+                    }
+                    _ => return None,
+                }
+            }
+            let mut nodes = 1;
+            for child in el.children.iter() {
+                let (child_nodes, child_elements) = analyze_node(child)?;
+                nodes += child_nodes;
+                elements += child_elements;
+            }
+            Some((nodes, elements))
+        }
+        _ => None,
+    }
+}
+
+/// Elements whose runtime state differs from their markup can't be stringified,
+/// nor can table parts, since the HTML parser inserts implicit `<tbody>` etc.
+fn is_stringifiable_tag(tag: &str) -> bool {
+    !matches!(
+        tag,
+        "select"
+            | "option"
+            | "textarea"
+            | "input"
+            | "caption"
+            | "thead"
+            | "tr"
+            | "th"
+            | "tbody"
+            | "td"
+            | "tfoot"
+            | "colgroup"
+            | "col"
+    )
+}
+
+/// Attributes that mean the same thing as markup and as a VNode prop
+fn is_stringifiable_attr(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b':'))
+}
+
+/// Serialize a static node to HTML. Elements carry the scope id, which the
+/// runtime only adds to elements it creates itself.
+fn stringify_node(
+    node: &TemplateChildNode<'_>,
+    scope_id: Option<&str>,
+    out: &mut std::string::String,
+) {
+    match node {
+        TemplateChildNode::Text(text) => out.push_str(&escape_html(&text.content)),
+        TemplateChildNode::Comment(comment) => {
+            out.push_str("<!--");
+            out.push_str(&comment.content);
+            out.push_str("-->");
+        }
+        TemplateChildNode::Element(el) => {
+            out.push('<');
+            out.push_str(&el.tag);
+            for prop in el.props.iter() {
+                if let PropNode::Attribute(attr) = prop {
+                    out.push(' ');
+                    out.push_str(&attr.name);
+                    if let Some(value) = &attr.value {
+                        out.push_str("=\"");
+                        out.push_str(&escape_html(&value.content));
+                        out.push('"');
+                    }
+                }
+            }
+            if let Some(scope_id) = scope_id {
+                out.push(' ');
+                out.push_str(scope_id);
+            }
+            out.push('>');
+            if is_void_tag(&el.tag) {
+                return;
+            }
+            for child in el.children.iter() {
+                stringify_node(child, scope_id, out);
+            }
+            out.push_str("</");
+            out.push_str(&el.tag);
+            out.push('>');
+        }
+        _ => {}
+    }
+}
+
+/// Build `_createStaticVNode("<html>", count)`
+fn create_static_call<'a>(ctx: &TransformContext<'a>, html: &str, count: usize) -> JsChildNode<'a> {
+    let allocator = ctx.allocator;
+    let mut call = CallExpression::new(
+        allocator,
+        Callee::Symbol(RuntimeHelper::CreateStatic),
+        SourceLocation::STUB,
+    );
+    let content = SimpleExpressionNode::new(html, true, SourceLocation::STUB);
+    call.arguments
+        .push(CallArgument::JsChild(JsChildNode::SimpleExpression(
+            Box::new_in(content, allocator),
+        )));
+    call.arguments
+        .push(CallArgument::String(String::from(count.to_string())));
+    JsChildNode::Call(Box::new_in(call, allocator))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::options::{BaseTransformOptions, CodegenOptions, TransformOptions};
+
+    fn compile_hoisted(template: &str) -> (std::string::String, std::string::String) {
+        let allocator = bumpalo::Bump::new();
+        let (mut root, errors) = crate::parser::parse(&allocator, template);
+        assert!(errors.is_empty(), "Parse errors: {:?}", errors);
+        let options = TransformOptions {
+            base: BaseTransformOptions {
+                hoist_static: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        crate::transform::transform(&allocator, &mut root, options);
+        let result = crate::codegen::generate(&root, CodegenOptions::default());
+        (result.preamble.to_string(), result.code.to_string())
+    }
+
+    #[test]
+    fn test_stringify_node_threshold() {
+        let items = "<span>x</span>".repeat(20);
+        let template = format!("<div>{{{{ msg }}}}<div>{}</div></div>", items);
+        let (preamble, code) = compile_hoisted(&template);
+
+        assert!(
+            preamble.contains(&format!(
+                "const _hoisted_1 = /*#__PURE__*/ _createStaticVNode(\"<div>{}</div>\", 1)",
+                items
+            )),
+            "{}",
+            preamble
+        );
+        assert!(preamble.contains("createStaticVNode: _createStaticVNode"));
+        assert!(code.contains("_hoisted_1"), "{}", code);
+        assert!(!code.contains("\"span\""), "{}", code);
+    }
+
+    #[test]
+    fn test_stringify_element_with_binding_threshold() {
+        let items = "<p class=\"a\">x</p>".repeat(5);
+        let template = format!("<div :id=\"id\">{}</div>", items);
+        let (preamble, code) = compile_hoisted(&template);
+
+        assert!(
+            preamble.contains("_createStaticVNode(\"<p class=\\\"a\\\">x</p>"),
+            "{}",
+            preamble
+        );
+        assert!(preamble.contains("</p>\", 5)"), "{}", preamble);
+        assert!(code.contains("[\n    _hoisted_1\n  ]"), "{}", code);
+    }
+
+    #[test]
+    fn test_stringify_stops_at_dynamic_nodes() {
+        let items = "<p class=\"a\">x</p>".repeat(5);
+        let template = format!("<div :id=\"id\">{0}<p>{{{{ msg }}}}</p>{0}</div>", items);
+        let (preamble, code) = compile_hoisted(&template);
+
+        assert!(preamble.contains("_hoisted_1 = "), "{}", preamble);
+        assert!(preamble.contains("_hoisted_2 = "), "{}", preamble);
+        assert!(code.contains("_toDisplayString(msg)"), "{}", code);
+    }
+
+    #[test]
+    fn test_stringify_below_threshold() {
+        let items = "<span>x</span>".repeat(4);
+        let template = format!("<div :id=\"id\">{}</div>", items);
+        let (preamble, _) = compile_hoisted(&template);
+
+        assert!(!preamble.contains("_createStaticVNode"), "{}", preamble);
+    }
+
+    #[test]
+    fn test_stringify_escapes_content() {
+        let items = "<p title=\"a &quot;b&quot;\">1 &lt; 2</p>".repeat(5);
+        let template = format!("<div :id=\"id\">{}</div>", items);
+        let (preamble, _) = compile_hoisted(&template);
+
+        assert!(
+            preamble.contains("<p title=\\\"a &quot;b&quot;\\\">1 &lt; 2</p>"),
+            "{}",
+            preamble
+        );
+    }
+
+    #[test]
+    fn test_stringify_skips_table_parts() {
+        let rows = "<tr><td class=\"a\">x</td></tr>".repeat(12);
+        let template = format!("<div>{{{{ msg }}}}<table>{}</table></div>", rows);
+        let (preamble, code) = compile_hoisted(&template);
+
+        assert!(!preamble.contains("_createStaticVNode"), "{}", preamble);
+        assert!(code.contains("\"table\"") || preamble.contains("\"table\""));
+    }
+}
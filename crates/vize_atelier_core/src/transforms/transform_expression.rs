@@ -88,7 +88,7 @@ pub fn process_expression<'a>(
     let allocator = ctx.allocator;
 
     // If not prefixing identifiers and not TypeScript, just clone
-    if !ctx.options.prefix_identifiers && !ctx.options.is_ts {
+    if !ctx.options.base.prefix_identifiers && !ctx.options.base.is_ts {
         return clone_expression(exp, allocator);
     }
 
@@ -106,14 +106,14 @@ pub fn process_expression<'a>(
             }
 
             // Strip TypeScript if needed, then optionally prefix identifiers
-            let processed = if ctx.options.prefix_identifiers {
+            let processed = if ctx.options.base.prefix_identifiers {
                 // rewrite_expression handles both TS stripping and prefixing
                 let result = rewrite_expression(content, ctx, as_params);
                 if result.used_unref {
                     ctx.helper(crate::ast::RuntimeHelper::Unref);
                 }
                 result.code
-            } else if ctx.options.is_ts {
+            } else if ctx.options.base.is_ts {
                 // Only strip TypeScript, no prefixing
                 strip_typescript_from_expression(content)
             } else {
@@ -141,6 +141,20 @@ pub fn process_expression<'a>(
     }
 }
 
+/// Rewrite an expression that lives outside the template, such as a `v-bind()`
+/// in `<style>`, the same way template expressions are rewritten.
+///
+/// Returns the rewritten code and whether it uses the `_unref` helper.
+pub fn rewrite_standalone_expression(
+    content: &str,
+    options: crate::options::TransformOptions,
+) -> (std::string::String, bool) {
+    let allocator = Bump::new();
+    let ctx = TransformContext::new(&allocator, String::new(content), options);
+    let result = rewrite_expression(content, &ctx, false);
+    (result.code, result.used_unref)
+}
+
 /// Result of expression rewriting
 struct RewriteResult {
     code: std::string::String,
@@ -154,7 +168,7 @@ fn rewrite_expression(
     _as_params: bool,
 ) -> RewriteResult {
     // First, if this is TypeScript, strip type annotations
-    let js_content = if ctx.options.is_ts {
+    let js_content = if ctx.options.base.is_ts {
         strip_typescript_from_expression(content)
     } else {
         content.to_string()
@@ -237,6 +251,122 @@ fn rewrite_expression(
     }
 }
 
+/// Rewrite `;`-separated statements one at a time, keeping the separators
+fn rewrite_statements(content: &str, ctx: &TransformContext<'_>) -> RewriteResult {
+    let mut code = std::string::String::with_capacity(content.len());
+    let mut used_unref = false;
+
+    for (i, statement) in split_statements(content).into_iter().enumerate() {
+        if i > 0 {
+            code.push(';');
+        }
+        let trimmed = statement.trim();
+        if trimmed.is_empty() {
+            code.push_str(statement);
+            continue;
+        }
+        let leading = &statement[..statement.len() - statement.trim_start().len()];
+        let trailing = &statement[statement.trim_end().len()..];
+        let result = rewrite_expression(trimmed, ctx, false);
+        used_unref |= result.used_unref;
+        code.push_str(leading);
+        code.push_str(&result.code);
+        code.push_str(trailing);
+    }
+
+    RewriteResult { code, used_unref }
+}
+
+/// Split source on top-level `;`, ignoring those inside brackets and strings
+fn split_statements(content: &str) -> std::vec::Vec<&str> {
+    let mut statements = std::vec::Vec::new();
+    let mut depth = 0i32;
+    let mut quote: Option<u8> = None;
+    let mut escaped = false;
+    let mut start = 0;
+
+    for (i, &b) in content.as_bytes().iter().enumerate() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if b == b'\\' {
+                escaped = true;
+            } else if b == q {
+                quote = None;
+            }
+            continue;
+        }
+        match b {
+            b'\'' | b'"' | b'`' => quote = Some(b),
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth -= 1,
+            b';' if depth == 0 => {
+                statements.push(&content[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    statements.push(&content[start..]);
+    statements
+}
+
+/// Check if an expression is a member expression or identifier, i.e. a
+/// reference to a handler function rather than an inline statement
+pub fn is_member_expression(content: &str) -> bool {
+    let allocator = OxcAllocator::default();
+    let source_type = SourceType::default()
+        .with_module(true)
+        .with_typescript(true);
+    let Ok(expr) = Parser::new(&allocator, content, source_type).parse_expression() else {
+        return false;
+    };
+
+    let mut expr = &expr;
+    loop {
+        match expr {
+            oxc_ast_types::Expression::TSAsExpression(e) => expr = &e.expression,
+            oxc_ast_types::Expression::TSSatisfiesExpression(e) => expr = &e.expression,
+            oxc_ast_types::Expression::TSNonNullExpression(e) => expr = &e.expression,
+            oxc_ast_types::Expression::ParenthesizedExpression(e) => expr = &e.expression,
+            _ => break,
+        }
+    }
+    matches!(
+        expr,
+        oxc_ast_types::Expression::Identifier(_)
+            | oxc_ast_types::Expression::StaticMemberExpression(_)
+            | oxc_ast_types::Expression::ComputedMemberExpression(_)
+            | oxc_ast_types::Expression::PrivateFieldExpression(_)
+    )
+}
+
+/// Collect the names bound by a parameter list such as v-slot props
+/// (e.g. `{ item, index: i }` binds `item` and `i`)
+pub fn collect_param_names(params: &str) -> std::vec::Vec<std::string::String> {
+    let allocator = OxcAllocator::default();
+    let source_type = SourceType::default()
+        .with_module(true)
+        .with_typescript(true);
+    let source = format!("({}) => {{}}", params);
+    let Ok(expr) = Parser::new(&allocator, &source, source_type).parse_expression() else {
+        return std::vec::Vec::new();
+    };
+
+    let mut names = FxHashSet::default();
+    if let oxc_ast_types::Expression::ArrowFunctionExpression(arrow) = &expr {
+        for param in &arrow.params.items {
+            collect_binding_names(&param.pattern, &mut names);
+        }
+        if let Some(rest) = &arrow.params.rest {
+            collect_binding_names(&rest.argument, &mut names);
+        }
+    }
+    let mut names: std::vec::Vec<_> = names.into_iter().collect();
+    names.sort();
+    names
+}
+
 /// Check if expression contains TypeScript syntax that needs stripping
 fn needs_typescript_stripping(content: &str) -> bool {
     // Quick check for common TypeScript patterns
@@ -377,6 +507,9 @@ fn collect_identifiers_for_prefix(
             for param in &arrow.params.items {
                 collect_binding_names(&param.pattern, local_vars);
             }
+            if let Some(rest) = &arrow.params.rest {
+                collect_binding_names(&rest.argument, local_vars);
+            }
             // Process body statements
             for stmt in arrow.body.statements.iter() {
                 if let oxc_ast::ast::Statement::ExpressionStatement(expr_stmt) = stmt {
@@ -434,6 +567,31 @@ fn collect_identifiers_for_prefix(
         Expression::UnaryExpression(unary) => {
             collect_identifiers_for_prefix(&unary.argument, rewrites, local_vars, _original);
         }
+        Expression::AssignmentExpression(assign) => {
+            use oxc_ast::ast::AssignmentTarget;
+
+            match &assign.left {
+                AssignmentTarget::AssignmentTargetIdentifier(id) => {
+                    let name = id.name.as_str();
+                    if !JS_GLOBALS.contains(name) && !local_vars.contains(name) {
+                        let start = id.span.start as usize - 1;
+                        let end = id.span.end as usize - 1;
+                        rewrites.push((start, end, ["_ctx.", name].concat()));
+                    }
+                }
+                target => {
+                    if let Some(member) = target.as_member_expression() {
+                        collect_identifiers_for_prefix(
+                            member.object(),
+                            rewrites,
+                            local_vars,
+                            _original,
+                        );
+                    }
+                }
+            }
+            collect_identifiers_for_prefix(&assign.right, rewrites, local_vars, _original);
+        }
         Expression::ObjectExpression(obj) => {
             for prop in &obj.properties {
                 match prop {
@@ -537,8 +695,8 @@ impl<'a, 'ctx> IdentifierCollector<'a, 'ctx> {
         }
 
         // Check if this is an inline mode ref binding
-        if self.ctx.options.inline {
-            if let Some(bindings) = &self.ctx.options.binding_metadata {
+        if self.ctx.options.base.inline {
+            if let Some(bindings) = &self.ctx.options.base.binding_metadata {
                 if let Some(binding_type) = bindings.bindings.get(name) {
                     // SetupRef needs .value access
                     return matches!(binding_type, crate::options::BindingType::SetupRef);
@@ -557,8 +715,8 @@ impl<'a, 'ctx> IdentifierCollector<'a, 'ctx> {
         }
 
         // Check if this is an inline mode let/maybe-ref binding
-        if self.ctx.options.inline {
-            if let Some(bindings) = &self.ctx.options.binding_metadata {
+        if self.ctx.options.base.inline {
+            if let Some(bindings) = &self.ctx.options.base.binding_metadata {
                 if let Some(binding_type) = bindings.bindings.get(name) {
                     // SetupLet and SetupMaybeRef need _unref()
                     return matches!(
@@ -622,6 +780,9 @@ impl<'a, 'ctx> Visit<'_> for IdentifierCollector<'a, 'ctx> {
         for param in &arrow.params.items {
             self.collect_binding_pattern(&param.pattern);
         }
+        if let Some(rest) = &arrow.params.rest {
+            self.collect_binding_pattern(&rest.argument);
+        }
 
         // Visit body
         self.visit_function_body(&arrow.body);
@@ -672,8 +833,8 @@ fn get_identifier_prefix(name: &str, ctx: &TransformContext<'_>) -> Option<&'sta
     }
 
     // In inline mode, check binding metadata
-    if ctx.options.inline {
-        if let Some(bindings) = &ctx.options.binding_metadata {
+    if ctx.options.base.inline {
+        if let Some(bindings) = &ctx.options.base.binding_metadata {
             if let Some(binding_type) = bindings.bindings.get(name) {
                 // Props need __props. prefix
                 if matches!(
@@ -699,8 +860,8 @@ fn should_prefix_identifier(name: &str, ctx: &TransformContext<'_>) -> bool {
 
 /// Check if a simple identifier is a ref binding in inline mode
 fn is_ref_binding_simple(name: &str, ctx: &TransformContext<'_>) -> bool {
-    if ctx.options.inline {
-        if let Some(bindings) = &ctx.options.binding_metadata {
+    if ctx.options.base.inline {
+        if let Some(bindings) = &ctx.options.base.binding_metadata {
             if let Some(binding_type) = bindings.bindings.get(name) {
                 return matches!(binding_type, crate::options::BindingType::SetupRef);
             }
@@ -772,9 +933,9 @@ pub fn process_inline_handler<'a>(
             let content = &simple.content;
 
             // Check if it's an inline function expression
-            if content.contains("=>") || content.starts_with("function") {
+            if crate::transforms::v_on::is_fn_expression(content) {
                 // Process identifiers in the handler
-                if ctx.options.prefix_identifiers {
+                if ctx.options.base.prefix_identifiers {
                     let result = rewrite_expression(content, ctx, false);
                     if result.used_unref {
                         ctx.helper(crate::ast::RuntimeHelper::Unref);
@@ -792,7 +953,7 @@ pub fn process_inline_handler<'a>(
                         },
                         allocator,
                     ));
-                } else if ctx.options.is_ts {
+                } else if ctx.options.base.is_ts {
                     // Strip TypeScript type annotations even without prefix_identifiers
                     let stripped = strip_typescript_from_expression(content);
                     return ExpressionNode::Simple(Box::new_in(
@@ -815,12 +976,13 @@ pub fn process_inline_handler<'a>(
             // Check if it's a simple identifier (method name)
             // Vue passes method references directly, no wrapping needed
             if is_simple_identifier(content) {
-                let new_content =
-                    if ctx.options.prefix_identifiers && should_prefix_identifier(content, ctx) {
-                        ["_ctx.", content].concat()
-                    } else {
-                        content.to_string()
-                    };
+                let new_content = if ctx.options.base.prefix_identifiers
+                    && should_prefix_identifier(content, ctx)
+                {
+                    ["_ctx.", content].concat()
+                } else {
+                    content.to_string()
+                };
 
                 return ExpressionNode::Simple(Box::new_in(
                     SimpleExpressionNode {
@@ -837,20 +999,51 @@ pub fn process_inline_handler<'a>(
                 ));
             }
 
-            // Compound expression - rewrite and wrap in arrow function
-            let rewritten = if ctx.options.prefix_identifiers {
+            // Member expressions (e.g. `handlers.save`) are function references too
+            if ctx.options.base.prefix_identifiers && is_member_expression(content) {
                 let result = rewrite_expression(content, ctx, false);
                 if result.used_unref {
                     ctx.helper(crate::ast::RuntimeHelper::Unref);
                 }
+                return ExpressionNode::Simple(Box::new_in(
+                    SimpleExpressionNode {
+                        content: String::new(&result.code),
+                        is_static: false,
+                        const_type: ConstantType::NotConstant,
+                        loc: simple.loc.clone(),
+                        js_ast: None,
+                        hoisted: None,
+                        identifiers: None,
+                        is_handler_key: true,
+                    },
+                    allocator,
+                ));
+            }
+
+            // Compound expression - rewrite and wrap in arrow function
+            let has_multiple_statements = content.contains(';');
+            let rewritten = if ctx.options.base.prefix_identifiers {
+                let result = if has_multiple_statements {
+                    rewrite_statements(content, ctx)
+                } else {
+                    rewrite_expression(content, ctx, false)
+                };
+                if result.used_unref {
+                    ctx.helper(crate::ast::RuntimeHelper::Unref);
+                }
                 result.code
-            } else if ctx.options.is_ts {
+            } else if ctx.options.base.is_ts {
                 // Strip TypeScript type annotations even without prefix_identifiers
                 strip_typescript_from_expression(content)
             } else {
                 content.to_string()
             };
-            let new_content = ["$event => (", &rewritten, ")"].concat();
+            // Multiple statements need a block body
+            let new_content = if has_multiple_statements {
+                ["$event => {", &rewritten, "}"].concat()
+            } else {
+                ["$event => (", &rewritten, ")"].concat()
+            };
 
             ExpressionNode::Simple(Box::new_in(
                 SimpleExpressionNode {
@@ -886,6 +1079,42 @@ mod tests {
         assert!(!is_simple_identifier(""));
     }
 
+    #[test]
+    fn test_is_member_expression() {
+        assert!(is_member_expression("foo"));
+        assert!(is_member_expression("_ctx.foo"));
+        assert!(is_member_expression("foo.bar[baz]"));
+        assert!(is_member_expression("(foo as Handler)"));
+        assert!(!is_member_expression("foo()"));
+        assert!(!is_member_expression("count++"));
+        assert!(!is_member_expression("() => foo"));
+    }
+
+    #[test]
+    fn test_split_statements() {
+        assert_eq!(split_statements("a++; b(';')"), vec!["a++", " b(';')"]);
+        assert_eq!(split_statements("f({ a: 1; })"), vec!["f({ a: 1; })"]);
+    }
+
+    #[test]
+    fn test_collect_param_names() {
+        assert_eq!(collect_param_names("{ item, index: i }"), vec!["i", "item"]);
+        assert_eq!(collect_param_names("props"), vec!["props"]);
+        assert!(collect_param_names("").is_empty());
+    }
+
+    #[test]
+    fn test_prefix_identifiers_in_assignment() {
+        assert_eq!(
+            prefix_identifiers_in_expression("(el) => last = el"),
+            "(el) => _ctx.last = el"
+        );
+        assert_eq!(
+            prefix_identifiers_in_expression("(el) => refs.input = el"),
+            "(el) => _ctx.refs.input = el"
+        );
+    }
+
     #[test]
     fn test_js_globals() {
         assert!(JS_GLOBALS.contains("Array"));
@@ -103,6 +103,7 @@ pub fn transform_v_model<'a>(
                 ))),
                 modifiers: Vec::new_in(allocator),
                 for_parse_result: None,
+                cache_index: None,
                 loc: dir.loc.clone(),
             },
             allocator,
@@ -127,6 +128,7 @@ pub fn transform_v_model<'a>(
                 ))),
                 modifiers: Vec::new_in(allocator),
                 for_parse_result: None,
+                cache_index: None,
                 loc: dir.loc.clone(),
             },
             allocator,
@@ -151,6 +153,7 @@ pub fn transform_v_model<'a>(
                 ))),
                 modifiers: Vec::new_in(allocator),
                 for_parse_result: None,
+                cache_index: None,
                 loc: dir.loc.clone(),
             },
             allocator,
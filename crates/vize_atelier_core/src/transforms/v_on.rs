@@ -2,10 +2,12 @@
 //!
 //! Transforms v-on (@ shorthand) directives for event handling.
 
-use vize_carton::String;
+use vize_carton::{Box, String};
 
 use crate::ast::*;
+use crate::options::BindingType;
 use crate::transform::TransformContext;
+use crate::transforms::transform_expression::{is_member_expression, is_simple_identifier};
 
 /// Event modifier flags
 #[derive(Debug, Clone, Default)]
@@ -68,6 +70,179 @@ pub fn process_v_on(_ctx: &mut TransformContext<'_>, dir: &DirectiveNode<'_>) {
     }
 }
 
+/// Cache v-on handlers (`_cache[n] || (_cache[n] = handler)`) so the same
+/// function is passed down on every render and the prop never needs patching.
+///
+/// Without `prefix_identifiers` there is no scope analysis, so only handlers
+/// without an expression can be cached.
+pub fn cache_v_on_handlers<'a>(ctx: &mut TransformContext<'a>, el: &mut ElementNode<'a>) {
+    // Nothing inside v-once re-renders, and SSR doesn't emit handlers
+    if !ctx.options.base.cache_handlers || ctx.in_v_once || ctx.in_ssr {
+        return;
+    }
+    let is_component = el.tag_type == ElementType::Component;
+
+    for prop in el.props.iter_mut() {
+        let PropNode::Directive(dir) = prop else {
+            continue;
+        };
+        // v-on="obj" is spread with toHandlers and has nothing to cache
+        if dir.name != "on" || dir.arg.is_none() {
+            continue;
+        }
+
+        let Some(exp) = &mut dir.exp else {
+            dir.cache_index = Some(cache_handler(ctx, "() => {}", &dir.loc));
+            continue;
+        };
+        if !ctx.options.base.prefix_identifiers {
+            continue;
+        }
+        let ExpressionNode::Simple(simple) = exp else {
+            continue;
+        };
+        if simple.is_static {
+            continue;
+        }
+
+        let content = simple.content.as_str();
+        let is_fn = is_fn_expression(content);
+        let is_member = !is_fn && is_member_expression(content);
+        if (!is_fn && !is_member)
+            // Components may check a handler's arity (e.g. <Transition> hooks),
+            // so member expressions are passed through unwrapped
+            || (is_member && is_component)
+            // Runtime constants never change and don't need caching
+            || is_const_handler(simple.loc.source.trim(), ctx)
+            // Handlers closing over v-for / v-slot variables must be fresh
+            || references_scope(content, ctx)
+        {
+            continue;
+        }
+
+        if is_member {
+            // Call through so the latest function is always used
+            simple.content = format!("(...args) => ({0} && {0}(...args))", content).into();
+        }
+        let content = simple.content.clone();
+        dir.cache_index = Some(cache_handler(ctx, &content, &simple.loc));
+    }
+}
+
+/// Allocate a cache slot for a handler
+fn cache_handler(ctx: &mut TransformContext<'_>, handler: &str, loc: &SourceLocation) -> u32 {
+    let index = ctx.cached.len() as u32;
+    let value = SimpleExpressionNode::new(handler, false, loc.clone());
+    ctx.cache(CacheExpression {
+        index,
+        value: JsChildNode::SimpleExpression(Box::new_in(value, ctx.allocator)),
+        need_pause_tracking: false,
+        in_v_once: false,
+        need_array_spread: false,
+        loc: loc.clone(),
+    }) as u32
+}
+
+/// Check if a handler is already a function expression, anchored at its start
+/// like Vue's `fnExpRE`, so calls taking a callback (`foo(() => 1)`) don't count
+pub(crate) fn is_fn_expression(content: &str) -> bool {
+    let content = content.trim_start();
+    if is_arrow_after_params(content) {
+        return true;
+    }
+    if let Some(rest) = content.strip_prefix("async") {
+        if is_arrow_after_params(rest.trim_start()) {
+            return true;
+        }
+    }
+
+    let rest = match content.strip_prefix("async") {
+        Some(rest) if rest.starts_with(char::is_whitespace) => rest.trim_start(),
+        _ => content,
+    };
+    let Some(rest) = rest.strip_prefix("function") else {
+        return false;
+    };
+    if rest.starts_with(is_ident_char) {
+        return false;
+    }
+    rest.trim_start()
+        .trim_start_matches(is_ident_char)
+        .trim_start()
+        .starts_with('(')
+}
+
+/// `(params) =>` or `param =>`, optionally with a return type annotation
+fn is_arrow_after_params(content: &str) -> bool {
+    let rest = if let Some(params) = content.strip_prefix('(') {
+        match params.find(')') {
+            Some(end) => &params[end + 1..],
+            None => return false,
+        }
+    } else {
+        let rest = content.trim_start_matches(is_ident_char);
+        if rest.len() == content.len() {
+            return false;
+        }
+        rest
+    };
+    let rest = rest.trim_start();
+    let rest = match rest.strip_prefix(':') {
+        Some(annotation) => match annotation.find('=') {
+            Some(end) if end > 0 => &annotation[end..],
+            _ => return false,
+        },
+        None => rest,
+    };
+    rest.starts_with("=>")
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Check if a handler is a constant setup binding
+fn is_const_handler(raw: &str, ctx: &TransformContext<'_>) -> bool {
+    is_simple_identifier(raw)
+        && ctx
+            .options
+            .base
+            .binding_metadata
+            .as_ref()
+            .is_some_and(|meta| {
+                matches!(
+                    meta.bindings.get(raw),
+                    Some(BindingType::SetupConst | BindingType::LiteralConst)
+                )
+            })
+}
+
+/// Check if an expression references an identifier in the current scope
+fn references_scope(content: &str, ctx: &TransformContext<'_>) -> bool {
+    if ctx.identifiers.is_empty() {
+        return false;
+    }
+
+    let bytes = content.as_bytes();
+    let is_ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_' || b == b'$';
+    let mut i = 0;
+    while i < bytes.len() {
+        if !is_ident(bytes[i]) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < bytes.len() && is_ident(bytes[i]) {
+            i += 1;
+        }
+        let is_property = content[..start].trim_end().ends_with('.');
+        if !is_property && !bytes[start].is_ascii_digit() && ctx.is_in_scope(&content[start..i]) {
+            return true;
+        }
+    }
+    false
+}
+
 /// Get event name from v-on directive
 pub fn get_event_name(dir: &DirectiveNode<'_>) -> Option<String> {
     dir.arg.as_ref().map(|arg| match arg {
@@ -134,6 +309,95 @@ pub fn create_on_name(event: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::options::{BaseTransformOptions, CodegenOptions, TransformOptions};
+
+    fn compile_cached(template: &str) -> std::string::String {
+        let allocator = bumpalo::Bump::new();
+        let (mut root, errors) = crate::parser::parse(&allocator, template);
+        assert!(errors.is_empty(), "Parse errors: {:?}", errors);
+        let options = TransformOptions {
+            base: BaseTransformOptions {
+                prefix_identifiers: true,
+                cache_handlers: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        crate::transform::transform(&allocator, &mut root, options);
+        let options = CodegenOptions {
+            prefix_identifiers: true,
+            ..Default::default()
+        };
+        crate::codegen::generate(&root, options).code.to_string()
+    }
+
+    #[test]
+    fn test_cache_member_handler() {
+        let code = compile_cached(r#"<div @click="foo">hi</div>"#);
+        assert!(
+            code.contains(
+                "onClick: _cache[0] || (_cache[0] = (...args) => (_ctx.foo && _ctx.foo(...args)))"
+            ),
+            "{}",
+            code
+        );
+        // Cached handlers never need patching
+        assert!(!code.contains("PROPS"), "{}", code);
+    }
+
+    #[test]
+    fn test_cache_inline_statement() {
+        let code = compile_cached(r#"<div @click="count++">hi</div>"#);
+        assert!(
+            code.contains("_cache[0] || (_cache[0] = $event => (_ctx.count++))"),
+            "{}",
+            code
+        );
+
+        let code = compile_cached(r#"<div @click="foo();bar()">hi</div>"#);
+        assert!(
+            code.contains("_cache[0] || (_cache[0] = $event => {"),
+            "{}",
+            code
+        );
+    }
+
+    #[test]
+    fn test_cache_call_with_callback_argument() {
+        let code = compile_cached(r#"<div @click="foo(() => 1)">hi</div>"#);
+        assert!(
+            code.contains("_cache[0] || (_cache[0] = $event => (_ctx.foo(() => 1)))"),
+            "{}",
+            code
+        );
+    }
+
+    #[test]
+    fn test_is_fn_expression() {
+        assert!(is_fn_expression("() => foo()"));
+        assert!(is_fn_expression("  e => foo(e)"));
+        assert!(is_fn_expression("(a, b): void => foo(a, b)"));
+        assert!(is_fn_expression("async (e) => await foo(e)"));
+        assert!(is_fn_expression("async e => foo(e)"));
+        assert!(is_fn_expression("function (e) { foo(e) }"));
+        assert!(is_fn_expression("async function named(e) { foo(e) }"));
+        assert!(!is_fn_expression("foo(() => 1)"));
+        assert!(!is_fn_expression("list.map(x => x)"));
+        assert!(!is_fn_expression("functional(e)"));
+        assert!(!is_fn_expression("a >= b"));
+    }
+
+    #[test]
+    fn test_no_cache_for_scope_variables() {
+        let code = compile_cached(r#"<div v-for="i in list" @click="foo(i)">hi</div>"#);
+        assert!(!code.contains("_cache["), "{}", code);
+    }
+
+    #[test]
+    fn test_cache_handlers_disabled_by_default() {
+        let result = crate::compile!(r#"<div @click="foo">hi</div>"#);
+        assert!(!result.code.contains("_cache["), "{}", result.code);
+    }
 
     #[test]
     fn test_parse_modifiers() {
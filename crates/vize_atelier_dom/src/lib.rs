@@ -24,7 +24,7 @@ pub use vize_atelier_core::{
 use vize_atelier_core::codegen::CodegenResult;
 use vize_atelier_core::{
     codegen::generate,
-    options::{CodegenOptions, ParserOptions, TransformOptions},
+    options::{BaseTransformOptions, CodegenOptions, ParserOptions, TransformOptions},
     parser::parse_with_options,
     transform::transform as do_transform,
 };
@@ -44,18 +44,8 @@ pub fn compile_template_with_options<'a>(
     source: &'a str,
     options: DomCompilerOptions,
 ) -> (RootNode<'a>, Vec<CompilerError>, CodegenResult) {
-    // Create parser options with DOM-specific settings
-    let parser_opts = ParserOptions {
-        is_void_tag: vize_carton::is_void_tag,
-        is_native_tag: Some(vize_carton::is_native_tag),
-        is_pre_tag: |tag| tag == "pre",
-        get_namespace,
-        comments: options.comments,
-        ..ParserOptions::default()
-    };
-
     // Parse
-    let (mut root, errors) = parse_with_options(allocator, source, parser_opts);
+    let (mut root, errors) = parse_with_options(allocator, source, parser_options(&options));
 
     if !errors.is_empty() {
         let codegen_result = CodegenResult {
@@ -91,15 +81,20 @@ pub fn compile_template_with_options<'a>(
     });
 
     let transform_opts = TransformOptions {
-        prefix_identifiers: options.prefix_identifiers,
-        hoist_static: options.hoist_static,
-        cache_handlers: options.cache_handlers,
-        scope_id: options.scope_id.clone(),
-        ssr: options.ssr,
-        is_ts: options.is_ts,
-        inline: options.inline,
-        binding_metadata,
-        ..Default::default()
+        base: BaseTransformOptions {
+            prefix_identifiers: options.prefix_identifiers,
+            hoist_static: options.hoist_static,
+            cache_handlers: options.cache_handlers,
+            scope_id: options.scope_id.clone(),
+            ssr: options.ssr,
+            ssr_css_vars: options.ssr_css_vars.clone(),
+            is_ts: options.is_ts,
+            inline: options.inline,
+            binding_metadata,
+            ..Default::default()
+        },
+        node_transforms: options.node_transforms.clone(),
+        directive_transforms: options.directive_transforms.clone(),
     };
     do_transform(allocator, &mut root, transform_opts);
 
@@ -129,6 +124,11 @@ pub fn compile_template_with_options<'a>(
     let codegen_opts = CodegenOptions {
         mode: options.mode,
         source_map: options.source_map,
+        filename: options
+            .filename
+            .clone()
+            .unwrap_or_else(|| CodegenOptions::default().filename),
+        scope_id: options.scope_id.clone(),
         ssr: options.ssr,
         is_ts: options.is_ts,
         inline: options.inline,
@@ -140,6 +140,18 @@ pub fn compile_template_with_options<'a>(
     (root, errors.to_vec(), codegen_result)
 }
 
+/// Parser options with DOM-specific settings
+pub fn parser_options(options: &DomCompilerOptions) -> ParserOptions {
+    ParserOptions {
+        is_void_tag: vize_carton::is_void_tag,
+        is_native_tag: Some(vize_carton::is_native_tag),
+        is_pre_tag: |tag| tag == "pre",
+        get_namespace,
+        comments: options.comments,
+        ..ParserOptions::default()
+    }
+}
+
 /// Get the namespace for an element based on its parent
 fn get_namespace(tag: &str, parent: Option<&str>) -> Namespace {
     if vize_carton::is_svg_tag(tag) {
@@ -1,7 +1,9 @@
 //! DOM compiler options.
 
+use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use vize_atelier_core::options::CodegenMode;
+use vize_atelier_core::{DirectiveTransform, NodeTransform};
 use vize_carton::String;
 
 /// DOM compiler options
@@ -32,10 +34,18 @@ pub struct DomCompilerOptions {
     #[serde(default)]
     pub ssr: bool,
 
+    /// CSS variables object from `v-bind()` in `<style>`, rendered on SSR roots
+    #[serde(default)]
+    pub ssr_css_vars: Option<String>,
+
     /// Whether to generate source map
     #[serde(default)]
     pub source_map: bool,
 
+    /// Filename recorded as the source map source
+    #[serde(default)]
+    pub filename: Option<String>,
+
     /// Whether to preserve comments
     #[serde(default)]
     pub comments: bool,
@@ -51,6 +61,14 @@ pub struct DomCompilerOptions {
     /// Whether is TypeScript
     #[serde(default)]
     pub is_ts: bool,
+
+    /// Extra node transforms, run in order after the built-in ones
+    #[serde(skip)]
+    pub node_transforms: Vec<NodeTransform>,
+
+    /// Extra directive transforms keyed by directive name (without `v-`)
+    #[serde(skip)]
+    pub directive_transforms: FxHashMap<String, DirectiveTransform>,
 }
 
 impl Default for DomCompilerOptions {
@@ -62,11 +80,15 @@ impl Default for DomCompilerOptions {
             cache_handlers: false,
             scope_id: None,
             ssr: false,
+            ssr_css_vars: None,
             source_map: false,
+            filename: None,
             comments: false,
             inline: false,
             binding_metadata: None,
             is_ts: false,
+            node_transforms: Vec::new(),
+            directive_transforms: FxHashMap::default(),
         }
     }
 }
@@ -4,14 +4,20 @@
 //! Following the Vue.js core structure, template/script/style compilation
 //! is delegated to specialized modules.
 
+use crate::compile_script::typescript::{check_typescript_block, transform_typescript_or_report};
 use crate::compile_script::{compile_script_setup_inline, TemplateParts};
 use crate::compile_template::{
     compile_template_block, compile_template_block_vapor, extract_template_parts,
-    extract_template_parts_full,
+    extract_template_parts_full, extract_vapor_template_parts, render_body_map,
+    TemplateModuleParts,
 };
+use crate::css_vars::{gen_css_vars_code, gen_normal_script_css_vars_code, gen_ssr_css_vars};
 use crate::rewrite_default::rewrite_default;
-use crate::script::ScriptCompileContext;
+use crate::script::{normalize_path, ScriptCompileContext, TypeResolveOptions, TypeResolver};
+use crate::source_map::{find_line, SfcSourceMapGenerator};
 use crate::types::*;
+use std::borrow::Cow;
+use std::rc::Rc;
 
 // Re-export ScriptCompileResult for public API
 pub use crate::compile_script::ScriptCompileResult;
@@ -26,11 +32,41 @@ pub fn compile_sfc(
     let mut code = String::new();
     let mut css = None;
 
+    let mut dependencies = Vec::new();
+    let loaded = load_src_blocks(descriptor, &options, &mut dependencies, &mut errors);
+    let descriptor = loaded.as_ref().unwrap_or(descriptor);
+
+    // Broken TypeScript can't be transformed and would end up in the output as is
+    let ts_errors: Vec<_> = [&descriptor.script, &descriptor.script_setup]
+        .into_iter()
+        .flatten()
+        .flat_map(|block| check_typescript_block(block))
+        .collect();
+    let has_ts_errors = !ts_errors.is_empty();
+    report_ts_errors(ts_errors, &options, &mut errors)?;
+
     let filename = options.script.id.as_deref().unwrap_or("anonymous.vue");
 
-    // Generate scope ID from filename
-    let scope_id = generate_scope_id(filename);
+    let scope_id = options.scope_id.clone().unwrap_or_else(|| {
+        generate_scope_id(
+            filename,
+            options.root.as_deref(),
+            &descriptor.source,
+            options.is_prod,
+        )
+    });
     let has_scoped = descriptor.styles.iter().any(|s| s.scoped);
+    let ssr = options.template.ssr;
+
+    // During SSR, `v-bind()` values in `<style>` are rendered on the root elements
+    let mut template_options = options.template.clone();
+    if ssr && !descriptor.css_vars.is_empty() {
+        template_options.ssr_css_vars = Some(gen_ssr_css_vars(
+            &descriptor.css_vars,
+            &scope_id,
+            options.is_prod,
+        ));
+    }
 
     // Detect vapor mode from script attrs
     let is_vapor = descriptor
@@ -62,33 +98,68 @@ pub fn compile_sfc(
     if !has_script && !has_script_setup && has_template {
         let template = descriptor.template.as_ref().unwrap();
         // Enable hoisting for template-only SFCs (hoisted consts go at module level)
-        let mut template_opts = options.template.clone();
+        let mut template_opts = template_options.clone();
         let mut dom_opts = template_opts.compiler_options.take().unwrap_or_default();
         dom_opts.hoist_static = true;
+        dom_opts.source_map |= options.parse.source_map;
         template_opts.compiler_options = Some(dom_opts);
         let template_result =
             compile_template_block(template, &template_opts, &scope_id, has_scoped, is_ts, None);
 
+        let mut template_map = None;
         match template_result {
-            Ok(template_code) => {
-                code = template_code;
+            Ok(compiled) => {
+                code = compiled.code;
+                template_map = compiled.map;
             }
             Err(e) => errors.push(e),
         }
+        if !ssr {
+            inject_css_vars(&mut code, descriptor, &scope_id, options.is_prod);
+        }
 
         // Compile styles
-        let all_css = compile_styles(&descriptor.styles, &scope_id, &options.style, &mut warnings);
-        if !all_css.is_empty() {
-            css = Some(all_css);
+        let styles = compile_styles(
+            &descriptor.styles,
+            &scope_id,
+            &component_name,
+            source_map_filename(descriptor, &options),
+            &options.style,
+            options.is_prod,
+            &mut errors,
+        );
+        inject_css_modules(&mut code, &styles.modules, ssr);
+        if options.hmr && !options.is_prod {
+            inject_hmr(&mut code, &scope_id, filename, ssr);
+        }
+        let css_map = css_source_map(descriptor, &options, &styles);
+        let css_modules = styles.modules;
+        if !styles.css.is_empty() {
+            css = Some(styles.css);
         }
 
+        let template_map = template_map.and_then(|map| {
+            Some(VerbatimTemplate {
+                block: template,
+                map,
+                line: render_fn_line(&code)?,
+            })
+        });
+        let map = options
+            .parse
+            .source_map
+            .then(|| module_source_map(descriptor, &options, &code, template_map.as_ref()));
+
         return Ok(SfcCompileResult {
             code,
             css,
-            map: None,
+            map,
+            css_map,
             errors,
             warnings,
             bindings: None,
+            css_modules: (!css_modules.is_empty()).then_some(css_modules),
+            dependencies,
         });
     }
 
@@ -100,11 +171,13 @@ pub fn compile_sfc(
         let (rewritten_script, _has_default) = rewrite_default(&script.content, "_sfc_main", is_ts);
 
         // Compile template if present
+        let mut template_map = None;
         if has_template {
             let template = descriptor.template.as_ref().unwrap();
-            let mut template_opts = options.template.clone();
+            let mut template_opts = template_options.clone();
             let mut dom_opts = template_opts.compiler_options.take().unwrap_or_default();
             dom_opts.hoist_static = true;
+            dom_opts.source_map |= options.parse.source_map;
             template_opts.compiler_options = Some(dom_opts);
 
             let template_result = compile_template_block(
@@ -117,10 +190,12 @@ pub fn compile_sfc(
             );
 
             match template_result {
-                Ok(template_code) => {
+                Ok(compiled) => {
+                    template_map = compiled.map.map(|map| (template, map));
+
                     // Extract template parts (imports, hoisted, render function)
                     let (template_imports, template_hoisted, render_fn) =
-                        extract_template_parts_full(&template_code);
+                        extract_template_parts_full(&compiled.code);
 
                     // Build output: imports + script + hoisted + render + export
                     code.push_str(&template_imports);
@@ -141,7 +216,11 @@ pub fn compile_sfc(
                     code.push('\n');
 
                     // Export the component with render attached
-                    code.push_str("_sfc_main.render = render\n");
+                    if template_opts.ssr {
+                        code.push_str("_sfc_main.ssrRender = ssrRender\n");
+                    } else {
+                        code.push_str("_sfc_main.render = render\n");
+                    }
                     code.push_str("export default _sfc_main\n");
                 }
                 Err(e) => {
@@ -156,20 +235,52 @@ pub fn compile_sfc(
             code.push_str(&rewritten_script);
             code.push_str("\nexport default _sfc_main\n");
         }
+        if !ssr {
+            inject_css_vars(&mut code, descriptor, &scope_id, options.is_prod);
+        }
 
         // Compile styles
-        let all_css = compile_styles(&descriptor.styles, &scope_id, &options.style, &mut warnings);
-        if !all_css.is_empty() {
-            css = Some(all_css);
+        let styles = compile_styles(
+            &descriptor.styles,
+            &scope_id,
+            &component_name,
+            source_map_filename(descriptor, &options),
+            &options.style,
+            options.is_prod,
+            &mut errors,
+        );
+        inject_css_modules(&mut code, &styles.modules, ssr);
+        if options.hmr && !options.is_prod {
+            inject_hmr(&mut code, &scope_id, filename, ssr);
         }
+        let css_map = css_source_map(descriptor, &options, &styles);
+        let css_modules = styles.modules;
+        if !styles.css.is_empty() {
+            css = Some(styles.css);
+        }
+
+        let template_map = template_map.and_then(|(block, map)| {
+            Some(VerbatimTemplate {
+                block,
+                map,
+                line: render_fn_line(&code)?,
+            })
+        });
+        let map = options
+            .parse
+            .source_map
+            .then(|| module_source_map(descriptor, &options, &code, template_map.as_ref()));
 
         return Ok(SfcCompileResult {
             code,
             css,
-            map: None,
+            map,
+            css_map,
             errors,
             warnings,
             bindings: None,
+            css_modules: (!css_modules.is_empty()).then_some(css_modules),
+            dependencies,
         });
     }
 
@@ -203,111 +314,585 @@ pub fn compile_sfc(
             &script.content,
             source_is_ts,
             is_ts,
+            &mut errors,
         ))
     } else {
         None
     };
 
     // Analyze script first to get bindings
+    let type_resolver = Rc::new(TypeResolver::new(TypeResolveOptions {
+        filename: source_map_filename(descriptor, &options).to_string(),
+        fs: options.file_loader.clone(),
+        generic: script_setup.attrs.get("generic").map(|g| g.to_string()),
+        script: descriptor
+            .script
+            .as_ref()
+            .map(|script| script.content.to_string()),
+    }));
     let mut ctx = ScriptCompileContext::new(&script_setup.content);
+    ctx.type_resolver = type_resolver.clone();
     ctx.analyze();
     let script_bindings = ctx.bindings.clone();
+    for dependency in ctx.type_dependencies {
+        if !dependencies.contains(&dependency) {
+            dependencies.push(dependency);
+        }
+    }
 
-    // Compile template with bindings (if present) to get the render function
+    // Compile template with bindings (if present) to get the render function.
+    // Vapor components render on the server like any other component, so
+    // only the client build uses the Vapor compiler.
+    let vapor = is_vapor && !ssr;
+    let mut template_map = None;
     let template_result = if let Some(template) = &descriptor.template {
-        if is_vapor {
-            Some(compile_template_block_vapor(
-                template, &scope_id, has_scoped,
-            ))
+        if vapor {
+            Some(
+                compile_template_block_vapor(
+                    template,
+                    &scope_id,
+                    has_scoped,
+                    Some(&script_bindings),
+                )
+                .map(|compiled| {
+                    warnings.extend(compiled.warnings);
+                    compiled.code
+                }),
+            )
         } else {
-            Some(compile_template_block(
-                template,
-                &options.template,
-                &scope_id,
-                has_scoped,
-                is_ts,
-                Some(&script_bindings), // Pass bindings for proper ref handling
-            ))
+            let mut template_opts = template_options.clone();
+            let mut dom_opts = template_opts.compiler_options.take().unwrap_or_default();
+            dom_opts.source_map |= options.parse.source_map;
+            template_opts.compiler_options = Some(dom_opts);
+            Some(
+                compile_template_block(
+                    template,
+                    &template_opts,
+                    &scope_id,
+                    has_scoped,
+                    is_ts,
+                    Some(&script_bindings), // Pass bindings for proper ref handling
+                )
+                .map(|compiled| {
+                    template_map = compiled.map;
+                    compiled.code
+                })
+                .map_err(|e| vec![e]),
+            )
         }
     } else {
         None
     };
 
     // Extract render function code from template result
-    let (template_imports, template_hoisted, template_preamble, render_body) =
-        match &template_result {
-            Some(Ok(template_code)) => extract_template_parts(template_code),
-            Some(Err(e)) => {
-                errors.push(e.clone());
-                (String::new(), String::new(), String::new(), String::new())
+    let template_parts = match &template_result {
+        Some(Ok(template_code)) if vapor => {
+            let (imports, hoisted) = extract_vapor_template_parts(template_code);
+            TemplateModuleParts {
+                imports,
+                hoisted,
+                ..Default::default()
             }
-            None => (String::new(), String::new(), String::new(), String::new()),
-        };
+        }
+        // A mapped render body is reprinted instead of compacted
+        Some(Ok(template_code)) => extract_template_parts(template_code, template_map.is_none()),
+        Some(Err(template_errors)) => {
+            errors.extend(template_errors.iter().cloned());
+            TemplateModuleParts::default()
+        }
+        None => TemplateModuleParts::default(),
+    };
+    let render_map = match (&template_result, &template_map) {
+        (Some(Ok(template_code)), Some(map)) => {
+            render_body_map(template_code, map, &template_parts)
+        }
+        _ => None,
+    };
 
     // Compile script setup with inline template
+    // `v-bind()` in `<style>` reads setup bindings directly
+    let css_vars = (!ssr && !descriptor.css_vars.is_empty()).then(|| {
+        gen_css_vars_code(
+            &descriptor.css_vars,
+            &scope_id,
+            options.is_prod,
+            Some(&script_bindings),
+        )
+    });
+
     let script_result = compile_script_setup_inline(
         &script_setup.content,
         &component_name,
         is_ts,
         TemplateParts {
-            imports: &template_imports,
-            hoisted: &template_hoisted,
-            preamble: &template_preamble,
-            render_body: &render_body,
+            imports: &template_parts.imports,
+            hoisted: &template_parts.hoisted,
+            preamble: &template_parts.preamble,
+            render_body: &template_parts.render_body,
+            render_map: render_map.as_ref(),
+            ssr,
+            vapor: vapor && matches!(template_result, Some(Ok(_))),
         },
         normal_script_content.as_deref(),
+        css_vars.as_ref(),
+        &type_resolver,
     )?;
     code = script_result.code;
+    if !has_ts_errors {
+        report_ts_errors(script_result.errors, &options, &mut errors)?;
+    }
 
     // Compile styles
-    let all_css = compile_styles(&descriptor.styles, &scope_id, &options.style, &mut warnings);
-    if !all_css.is_empty() {
-        css = Some(all_css);
+    let styles = compile_styles(
+        &descriptor.styles,
+        &scope_id,
+        &component_name,
+        source_map_filename(descriptor, &options),
+        &options.style,
+        options.is_prod,
+        &mut errors,
+    );
+    inject_css_modules(&mut code, &styles.modules, ssr);
+    if options.hmr && !options.is_prod {
+        inject_hmr(&mut code, &scope_id, filename, ssr);
+    }
+    let css_map = css_source_map(descriptor, &options, &styles);
+    let css_modules = styles.modules;
+    if !styles.css.is_empty() {
+        css = Some(styles.css);
     }
 
+    // The statements injected above only add lines after the render function
+    let template_map = descriptor
+        .template
+        .as_ref()
+        .zip(script_result.template_map)
+        .map(|(block, map)| VerbatimTemplate {
+            block,
+            map,
+            line: 0,
+        });
+    let map = options
+        .parse
+        .source_map
+        .then(|| module_source_map(descriptor, &options, &code, template_map.as_ref()));
+
     Ok(SfcCompileResult {
         code,
         css,
-        map: None,
+        map,
+        css_map,
         errors,
         warnings,
         bindings: script_result.bindings,
+        css_modules: (!css_modules.is_empty()).then_some(css_modules),
+        dependencies,
     })
 }
 
+/// Add TypeScript errors to `errors`, or fail with the first one in strict mode
+fn report_ts_errors(
+    ts_errors: Vec<SfcError>,
+    options: &SfcCompileOptions,
+    errors: &mut Vec<SfcError>,
+) -> Result<(), SfcError> {
+    if options.script.strict {
+        if let Some(error) = ts_errors.into_iter().next() {
+            return Err(error);
+        }
+    } else {
+        errors.extend(ts_errors);
+    }
+    Ok(())
+}
+
+/// Output of all style blocks
+struct CompiledStyles<'a> {
+    css: String,
+    modules: CssModules,
+    /// Source maps of blocks that carry their own map
+    maps: Vec<StyleBlockMap<'a>>,
+}
+
+/// Source map of a single compiled style block
+struct StyleBlockMap<'a> {
+    block: &'a SfcStyleBlock<'a>,
+    map: String,
+    /// Line of the block's first rule in the combined CSS
+    line: u32,
+}
+
 /// Helper to compile all style blocks
-fn compile_styles(
-    styles: &[SfcStyleBlock],
+fn compile_styles<'a>(
+    styles: &'a [SfcStyleBlock<'a>],
     scope_id: &str,
+    component_name: &str,
+    filename: &str,
     base_opts: &StyleCompileOptions,
-    warnings: &mut Vec<SfcError>,
-) -> String {
+    is_prod: bool,
+    errors: &mut Vec<SfcError>,
+) -> CompiledStyles<'a> {
+    let filename = if base_opts.filename.is_empty() {
+        filename
+    } else {
+        &base_opts.filename
+    };
     let mut all_css = String::new();
+    let mut css_modules = CssModules::new();
+    let mut maps = Vec::new();
     for style in styles {
         let style_opts = StyleCompileOptions {
             id: format!("data-v-{}", scope_id),
             scoped: style.scoped,
+            is_prod,
+            filename: match &style.src {
+                Some(src) => resolve_src(filename, src).to_string_lossy().into_owned(),
+                None => filename.to_string(),
+            },
+            // External styles are still named after the component
+            modules_pattern: Some(
+                base_opts
+                    .modules_pattern
+                    .as_deref()
+                    .unwrap_or(crate::style::DEFAULT_MODULES_PATTERN)
+                    .replace("[name]", component_name),
+            ),
             ..base_opts.clone()
         };
         match crate::style::compile_style(style, &style_opts) {
-            Ok(style_css) => {
+            Ok(compiled) => {
+                if let Some(module_name) = &style.module {
+                    css_modules
+                        .entry(module_name.to_string())
+                        .or_default()
+                        .extend(compiled.modules);
+                }
                 if !all_css.is_empty() {
                     all_css.push('\n');
                 }
-                all_css.push_str(&style_css);
+                // Maps of external styles don't point into the SFC
+                if let Some(map) = compiled.map.filter(|_| style.src.is_none()) {
+                    maps.push(StyleBlockMap {
+                        block: style,
+                        map,
+                        line: all_css.matches('\n').count() as u32,
+                    });
+                }
+                all_css.push_str(&compiled.code);
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+    CompiledStyles {
+        css: all_css,
+        modules: css_modules,
+        maps,
+    }
+}
+
+/// Attach CSS modules to the component as `_sfc_main.__cssModules`, which is
+/// where `$style` and `useCssModule()` look them up at runtime.
+fn inject_css_modules(code: &mut String, css_modules: &CssModules, ssr: bool) {
+    if css_modules.is_empty() || code.is_empty() {
+        return;
+    }
+    let modules = serde_json::to_string_pretty(css_modules).unwrap_or_default();
+    inject_component_statements(
+        code,
+        &format!("_sfc_main.__cssModules = {}\n", modules),
+        ssr,
+    );
+}
+
+/// Register the component with the Vue HMR runtime, keyed by its scope ID,
+/// and record its file for devtools
+fn inject_hmr(code: &mut String, scope_id: &str, filename: &str, ssr: bool) {
+    if code.is_empty() {
+        return;
+    }
+    let mut statements = format!(
+        "_sfc_main.__hmrId = {}\n_sfc_main.__file = {}\n",
+        serde_json::to_string(scope_id).unwrap_or_default(),
+        serde_json::to_string(filename).unwrap_or_default(),
+    );
+    if !ssr {
+        statements.push_str(
+            "typeof __VUE_HMR_RUNTIME__ !== 'undefined' && \
+             __VUE_HMR_RUNTIME__.createRecord(_sfc_main.__hmrId, _sfc_main)\n",
+        );
+    }
+    inject_component_statements(code, &statements, ssr);
+}
+
+/// Set the `v-bind()` custom properties from `setup()` for components without
+/// script setup (script setup calls `useCssVars` inline)
+fn inject_css_vars(code: &mut String, descriptor: &SfcDescriptor, scope_id: &str, is_prod: bool) {
+    if descriptor.css_vars.is_empty() || code.is_empty() {
+        return;
+    }
+    let css_vars = gen_css_vars_code(&descriptor.css_vars, scope_id, is_prod, None);
+    code.insert_str(0, "import { useCssVars as _useCssVars } from 'vue'\n");
+    inject_component_statements(
+        code,
+        &gen_normal_script_css_vars_code(&css_vars.code),
+        false,
+    );
+}
+
+/// Insert statements extending the component right before its default export,
+/// binding the component to `_sfc_main` first if needed
+fn inject_component_statements(code: &mut String, statements: &str, ssr: bool) {
+    // Normal script: the component is already bound to `_sfc_main`
+    if let Some(pos) = code.find("\nexport default _sfc_main") {
+        code.insert_str(pos + 1, statements);
+        return;
+    }
+
+    // Script setup: bind the default export so it can be extended
+    let default_export = if code.starts_with("export default ") {
+        Some(0)
+    } else {
+        code.find("\nexport default ").map(|pos| pos + 1)
+    };
+    if let Some(pos) = default_export {
+        code.replace_range(pos..pos + "export default ".len(), "const _sfc_main = ");
+    } else {
+        // Template only: there is no component object yet
+        if !code.ends_with('\n') {
+            code.push('\n');
+        }
+        let render = if ssr { "ssrRender" } else { "render" };
+        code.push_str("const _sfc_main = {}\n");
+        code.push_str(&format!("_sfc_main.{0} = {0}\n", render));
+    }
+    if !code.ends_with('\n') {
+        code.push('\n');
+    }
+    code.push_str(statements);
+    code.push_str("export default _sfc_main\n");
+}
+
+/// Template render function in the compiled module
+struct VerbatimTemplate<'a> {
+    block: &'a SfcTemplateBlock<'a>,
+    /// Source map of the render function back to the template content
+    map: String,
+    /// Module line that line 0 of the map refers to
+    line: u32,
+}
+
+/// Find the line of the render function signature in compiled code
+fn render_fn_line(code: &str) -> Option<u32> {
+    [
+        "export function render(",
+        "export function ssrRender(",
+        "function render(",
+        "function ssrRender(",
+    ]
+    .iter()
+    .find_map(|signature| find_line(code, signature))
+}
+
+/// Filename recorded as the source of generated maps
+fn source_map_filename<'a>(
+    descriptor: &'a SfcDescriptor,
+    options: &'a SfcCompileOptions,
+) -> &'a str {
+    if !descriptor.filename.is_empty() {
+        &descriptor.filename
+    } else if !options.parse.filename.is_empty() {
+        &options.parse.filename
+    } else {
+        "anonymous.vue"
+    }
+}
+
+/// Load the content of blocks with a `src` attribute into a copy of the
+/// descriptor, or return `None` if there are none
+fn load_src_blocks<'a>(
+    descriptor: &SfcDescriptor<'a>,
+    options: &SfcCompileOptions,
+    dependencies: &mut Vec<String>,
+    errors: &mut Vec<SfcError>,
+) -> Option<SfcDescriptor<'a>> {
+    let has_src = descriptor.template.iter().any(|block| block.src.is_some())
+        || descriptor.script.iter().any(|block| block.src.is_some())
+        || descriptor
+            .script_setup
+            .iter()
+            .any(|block| block.src.is_some())
+        || descriptor.styles.iter().any(|block| block.src.is_some());
+    if !has_src {
+        return None;
+    }
+
+    if let Some(script_setup) = &descriptor.script_setup {
+        if script_setup.src.is_some() {
+            errors.push(SfcError {
+                message: "<script setup> cannot use the \"src\" attribute because its syntax \
+                          will be ambiguous outside of the component."
+                    .to_string(),
+                code: Some("SCRIPT_SETUP_SRC".to_string()),
+                loc: Some(script_setup.loc.clone()),
+            });
+        }
+    }
+
+    let filename = source_map_filename(descriptor, options);
+    let loader: &dyn FileLoader = options.file_loader.as_deref().unwrap_or(&FsFileLoader);
+    let mut load = |src: &str, loc: &BlockLocation| -> Option<String> {
+        let path = resolve_src(filename, src);
+        dependencies.push(path.to_string_lossy().into_owned());
+        loader
+            .read_file(&path)
+            .map_err(|e| {
+                errors.push(SfcError {
+                    message: format!("Failed to load \"{}\": {}", path.display(), e),
+                    code: Some("SRC_LOAD_ERROR".to_string()),
+                    loc: Some(loc.clone()),
+                })
+            })
+            .ok()
+    };
+
+    let mut loaded = descriptor.clone();
+    if let Some(template) = &mut loaded.template {
+        if let Some(content) = template
+            .src
+            .as_deref()
+            .and_then(|src| load(src, &template.loc))
+        {
+            template.content = Cow::Owned(content);
+        }
+    }
+    if let Some(script) = &mut loaded.script {
+        if let Some(content) = script.src.as_deref().and_then(|src| load(src, &script.loc)) {
+            script.content = Cow::Owned(content);
+            if script.lang.is_none() {
+                script.lang = src_lang(script.src.as_deref(), &["ts", "tsx", "jsx"]);
+            }
+        }
+    }
+    let mut styles_loaded = false;
+    for style in &mut loaded.styles {
+        if let Some(content) = style.src.as_deref().and_then(|src| load(src, &style.loc)) {
+            style.content = Cow::Owned(content);
+            if style.lang.is_none() {
+                style.lang = src_lang(style.src.as_deref(), &["scss", "sass", "less", "styl"]);
+            }
+            styles_loaded = true;
+        }
+    }
+
+    // `v-bind()` in external styles
+    if styles_loaded {
+        loaded.css_vars.clear();
+        for var in loaded
+            .styles
+            .iter()
+            .flat_map(|style| crate::style::extract_css_vars(&style.content))
+        {
+            if !loaded.css_vars.iter().any(|v| *v == var) {
+                loaded.css_vars.push(Cow::Owned(var));
             }
-            Err(e) => warnings.push(e),
         }
     }
-    all_css
+
+    Some(loaded)
+}
+
+/// Resolve a `src` attribute against the directory of the SFC, dropping `.`
+/// and `..` components so each dependency is reported under one path
+fn resolve_src(filename: &str, src: &str) -> std::path::PathBuf {
+    let dir = std::path::Path::new(filename)
+        .parent()
+        .unwrap_or(std::path::Path::new(""));
+    normalize_path(&dir.join(src))
+}
+
+/// The language implied by the extension of a `src` file, if it's one of `langs`
+fn src_lang<'a>(src: Option<&str>, langs: &[&str]) -> Option<Cow<'a, str>> {
+    let ext = std::path::Path::new(src?).extension()?.to_str()?;
+    langs.contains(&ext).then(|| Cow::Owned(ext.to_string()))
+}
+
+/// Build the source map of the compiled module
+fn module_source_map(
+    descriptor: &SfcDescriptor,
+    options: &SfcCompileOptions,
+    code: &str,
+    template: Option<&VerbatimTemplate<'_>>,
+) -> serde_json::Value {
+    let filename = source_map_filename(descriptor, options);
+    let mut generator = SfcSourceMapGenerator::new(filename, &descriptor.source);
+
+    if let Some(template) = template.filter(|template| template.block.src.is_none()) {
+        generator.add_block_map(&template.map, template.line, template.block.loc.start);
+    }
+
+    let script_ranges: Vec<(usize, usize)> = [&descriptor.script, &descriptor.script_setup]
+        .into_iter()
+        .flatten()
+        .map(|block| (block.loc.start, block.loc.end))
+        .collect();
+    generator.add_line_anchors(code, &script_ranges);
+
+    generator.into_json()
+}
+
+/// Build the source map of the compiled CSS when requested
+fn css_source_map(
+    descriptor: &SfcDescriptor,
+    options: &SfcCompileOptions,
+    styles: &CompiledStyles<'_>,
+) -> Option<serde_json::Value> {
+    if !options.style.source_map || styles.css.is_empty() {
+        return None;
+    }
+
+    let filename = source_map_filename(descriptor, options);
+    let mut generator = SfcSourceMapGenerator::new(filename, &descriptor.source);
+    for style in styles.maps.iter() {
+        generator.add_block_map(&style.map, style.line, style.block.loc.start);
+    }
+    let style_ranges: Vec<(usize, usize)> = descriptor
+        .styles
+        .iter()
+        .map(|style| (style.loc.start, style.loc.end))
+        .collect();
+    generator.add_line_anchors(&styles.css, &style_ranges);
+
+    Some(generator.into_json())
 }
 
-/// Generate scope ID from filename
-fn generate_scope_id(filename: &str) -> String {
-    use std::hash::{Hash, Hasher};
-    let mut hasher = std::collections::hash_map::DefaultHasher::new();
-    filename.hash(&mut hasher);
-    format!("{:08x}", hasher.finish() & 0xFFFFFFFF)
+/// Generate a scope ID the same way `@vitejs/plugin-vue` does: the first 8 hex
+/// digits of a SHA-256 over the project-relative path, plus the source in production.
+pub fn generate_scope_id(
+    filename: &str,
+    root: Option<&str>,
+    source: &str,
+    is_prod: bool,
+) -> String {
+    use sha2::{Digest, Sha256};
+
+    let path = std::path::Path::new(filename);
+    let relative = root
+        .and_then(|root| path.strip_prefix(root).ok())
+        .unwrap_or(path);
+    let normalized = relative
+        .components()
+        .filter(|c| !matches!(c, std::path::Component::CurDir))
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let mut hasher = Sha256::new();
+    hasher.update(normalized.as_bytes());
+    if is_prod {
+        hasher.update(source.as_bytes());
+    }
+    let digest = hasher.finalize();
+    digest[..4].iter().map(|b| format!("{:02x}", b)).collect()
 }
 
 /// Extract component name from filename
@@ -327,14 +912,17 @@ fn extract_component_name(filename: &str) -> String {
 /// - `content`: The script content
 /// - `source_is_ts`: Whether the source script is TypeScript (has lang="ts")
 /// - `output_is_ts`: Whether to preserve TypeScript in output (false = transpile to JS)
-fn extract_normal_script_content(content: &str, source_is_ts: bool, output_is_ts: bool) -> String {
+/// - `errors`: Where a failure to transpile the script is reported
+fn extract_normal_script_content(
+    content: &str,
+    source_is_ts: bool,
+    output_is_ts: bool,
+    errors: &mut Vec<SfcError>,
+) -> String {
     use oxc_allocator::Allocator;
     use oxc_ast::ast::Statement;
-    use oxc_codegen::Codegen;
     use oxc_parser::Parser;
-    use oxc_semantic::SemanticBuilder;
     use oxc_span::{GetSpan, SourceType};
-    use oxc_transformer::{TransformOptions, Transformer, TypeScriptOptions};
 
     // Always parse as TypeScript if source is TypeScript
     let source_type = if source_is_ts {
@@ -395,36 +983,7 @@ fn extract_normal_script_content(content: &str, source_is_ts: bool, output_is_ts
 
     // If source is TypeScript and we need JavaScript output, transpile
     if source_is_ts && !output_is_ts {
-        // Re-parse the extracted content
-        let allocator2 = Allocator::default();
-        let ret2 = Parser::new(&allocator2, &extracted, SourceType::ts()).parse();
-        if ret2.errors.is_empty() {
-            let mut program2 = ret2.program;
-
-            // Run semantic analysis
-            let semantic_ret = SemanticBuilder::new().build(&program2);
-            if semantic_ret.errors.is_empty() {
-                let (symbols, scopes) = semantic_ret.semantic.into_symbol_table_and_scope_tree();
-
-                // Transform TypeScript to JavaScript
-                // Use only_remove_type_imports to preserve imports that might be used in template
-                let transform_options = TransformOptions {
-                    typescript: TypeScriptOptions {
-                        only_remove_type_imports: true,
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                };
-                let transform_ret =
-                    Transformer::new(&allocator2, std::path::Path::new(""), &transform_options)
-                        .build_with_symbols_and_scopes(symbols, scopes, &mut program2);
-
-                if transform_ret.errors.is_empty() {
-                    // Generate JavaScript code
-                    return Codegen::new().build(&program2).code;
-                }
-            }
-        }
+        return transform_typescript_or_report(&extracted, errors);
     }
 
     extracted
@@ -437,8 +996,247 @@ mod tests {
 
     #[test]
     fn test_generate_scope_id() {
-        let id = generate_scope_id("src/App.vue");
+        let id = generate_scope_id("src/App.vue", None, "", false);
         assert_eq!(id.len(), 8);
+        // sha256("App.vue")
+        assert_eq!(generate_scope_id("App.vue", None, "", false), "f13b4d11");
+        // Same relative path under different roots, or with `./`
+        assert_eq!(
+            generate_scope_id("/home/a/proj/src/test.vue", Some("/home/a/proj"), "", false),
+            "810ff8b7"
+        );
+        assert_eq!(
+            generate_scope_id("/ci/build/src/test.vue", Some("/ci/build/"), "", false),
+            "810ff8b7"
+        );
+        assert_eq!(
+            generate_scope_id("./src/test.vue", None, "", false),
+            "810ff8b7"
+        );
+        // Same-named files in different directories don't collide
+        assert_ne!(
+            generate_scope_id("a/App.vue", None, "", false),
+            generate_scope_id("b/App.vue", None, "", false)
+        );
+    }
+
+    #[test]
+    fn test_generate_scope_id_prod_includes_source() {
+        let dev = generate_scope_id("App.vue", None, "<template/>", false);
+        let prod = generate_scope_id("App.vue", None, "<template/>", true);
+        assert_eq!(dev, "f13b4d11");
+        // sha256("App.vue<template/>")
+        assert_eq!(prod, "c115d3f8");
+        assert_ne!(
+            prod,
+            generate_scope_id("App.vue", None, "<template></template>", true)
+        );
+    }
+
+    #[test]
+    fn test_compile_scope_id_option() {
+        let source = "<template><div>hi</div></template>\n<style scoped>.a { color: red; }</style>";
+        let descriptor = parse_sfc(source, SfcParseOptions::default()).unwrap();
+        let opts = SfcCompileOptions {
+            scope_id: Some("deadbeef".into()),
+            ..Default::default()
+        };
+        let result = compile_sfc(&descriptor, opts).unwrap();
+        assert!(result.css.unwrap().contains("[data-v-deadbeef]"));
+    }
+
+    fn compile_with_scope_id(source: &str) -> SfcCompileResult {
+        let descriptor = parse_sfc(source, SfcParseOptions::default()).unwrap();
+        let opts = SfcCompileOptions {
+            scope_id: Some("abcd1234".into()),
+            ..Default::default()
+        };
+        compile_sfc(&descriptor, opts).unwrap()
+    }
+
+    #[test]
+    fn test_css_modules_template_only() {
+        let result = compile_with_scope_id(
+            "<template><div :class=\"$style.red\">hi</div></template>\n\
+             <style module>.red { color: red; }</style>",
+        );
+        assert!(result.css.unwrap().contains("._red_abcd1234"));
+        let modules = result.css_modules.unwrap();
+        assert_eq!(modules["$style"]["red"], "_red_abcd1234");
+        assert!(result.code.contains("const _sfc_main = {}"));
+        assert!(result.code.contains("_sfc_main.render = render"));
+        assert!(result.code.contains(
+            "_sfc_main.__cssModules = {\n  \"$style\": {\n    \"red\": \"_red_abcd1234\"\n  }\n}"
+        ));
+        assert!(result.code.ends_with("export default _sfc_main\n"));
+    }
+
+    #[test]
+    fn test_css_modules_normal_script() {
+        let result = compile_with_scope_id(
+            "<script>\nexport default { name: 'A' }\n</script>\n\
+             <template><div>hi</div></template>\n\
+             <style module>.red { color: red; }</style>",
+        );
+        let css_modules_pos = result.code.find("_sfc_main.__cssModules").unwrap();
+        let export_pos = result.code.find("export default _sfc_main").unwrap();
+        assert!(css_modules_pos < export_pos, "{}", result.code);
+        assert_eq!(result.code.matches("export default").count(), 1);
+    }
+
+    #[test]
+    fn test_css_modules_script_setup_named() {
+        let result = compile_with_scope_id(
+            "<script setup>\nimport { useCssModule } from 'vue'\n\
+             const classes = useCssModule('classes')\n</script>\n\
+             <template><div :class=\"classes.title\">hi</div></template>\n\
+             <style module=\"classes\">.title { font-weight: bold; }</style>\n\
+             <style module>.red { color: red; }</style>",
+        );
+        let modules = result.css_modules.unwrap();
+        assert_eq!(modules["classes"]["title"], "_title_abcd1234");
+        assert_eq!(modules["$style"]["red"], "_red_abcd1234");
+        assert!(!result.code.contains("export default {"), "{}", result.code);
+        assert!(
+            result.code.contains("const _sfc_main = "),
+            "{}",
+            result.code
+        );
+        assert!(result.code.contains("\"classes\": {"), "{}", result.code);
+        assert!(result.code.ends_with("export default _sfc_main\n"));
+    }
+
+    #[test]
+    fn test_css_modules_pattern_option() {
+        let source = "<template><div/></template>\n<style module>.red { color: red; }</style>";
+        let descriptor = parse_sfc(source, SfcParseOptions::default()).unwrap();
+        let opts = SfcCompileOptions {
+            script: ScriptCompileOptions {
+                id: Some("src/Card.vue".into()),
+                ..Default::default()
+            },
+            style: StyleCompileOptions {
+                modules_pattern: Some("[name]_[local]".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let result = compile_sfc(&descriptor, opts).unwrap();
+        assert!(result.css.unwrap().contains(".Card_red"));
+    }
+
+    #[test]
+    fn test_no_css_modules_without_module_attr() {
+        let result = compile_with_scope_id(
+            "<template><div/></template>\n<style>.red { color: red; }</style>",
+        );
+        assert!(result.css_modules.is_none());
+        assert!(!result.code.contains("__cssModules"));
+    }
+
+    #[test]
+    fn test_css_vars_script_setup() {
+        let result = compile_with_scope_id(
+            "<script setup>\nimport { ref } from 'vue'\nconst color = ref('red')\n\
+             const props = defineProps({ size: Number })\n</script>\n\
+             <template><div>hi</div></template>\n\
+             <style>.a { color: v-bind(color); width: v-bind('props.size + \"px\"'); }</style>",
+        );
+        let css = result.css.unwrap();
+        assert!(css.contains("color: var(--abcd1234-color)"), "{}", css);
+        assert!(
+            css.contains("width: var(--abcd1234-props\\.size\\ \\+\\ \\\"px\\\")"),
+            "{}",
+            css
+        );
+        // The script setup output is reprinted, which normalizes quotes and parentheses
+        assert!(result
+            .code
+            .contains("import { useCssVars as _useCssVars } from \"vue\""));
+        assert!(
+            result.code.contains("\"abcd1234-color\": color.value"),
+            "{}",
+            result.code
+        );
+        assert!(
+            result
+                .code
+                .contains("\"abcd1234-props.size + \\\"px\\\"\": props.size + \"px\""),
+            "{}",
+            result.code
+        );
+        // Set up before user code runs
+        assert!(
+            result.code.find("_useCssVars(").unwrap() < result.code.find("const color").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_css_vars_options_api() {
+        let result = compile_with_scope_id(
+            "<script>\nexport default { data: () => ({ color: 'red' }) }\n</script>\n\
+             <template><div>hi</div></template>\n\
+             <style>.a { color: v-bind(color); }</style>",
+        );
+        assert!(result
+            .code
+            .starts_with("import { useCssVars as _useCssVars } from 'vue'\n"));
+        assert!(
+            result.code.contains("\"abcd1234-color\": (_ctx.color)"),
+            "{}",
+            result.code
+        );
+        assert!(result.code.contains("const __setup__ = _sfc_main.setup"));
+        let inject_pos = result.code.find("_sfc_main.setup = __setup__").unwrap();
+        assert!(inject_pos < result.code.find("export default _sfc_main").unwrap());
+    }
+
+    #[test]
+    fn test_css_vars_template_only() {
+        let result = compile_with_scope_id(
+            "<template><div>hi</div></template>\n<style>.a { color: v-bind(color); }</style>",
+        );
+        assert!(
+            result.code.contains("const _sfc_main = {}"),
+            "{}",
+            result.code
+        );
+        assert!(result.code.contains("_sfc_main.render = render"));
+        assert!(result.code.contains("\"abcd1234-color\": (_ctx.color)"));
+        assert!(result.code.ends_with("export default _sfc_main\n"));
+    }
+
+    #[test]
+    fn test_css_vars_ssr() {
+        let source =
+            "<script setup>\nimport { ref } from 'vue'\nconst color = ref('red')\n</script>\n\
+                      <template><div>hi</div></template>\n\
+                      <style>.a { color: v-bind(color); }</style>";
+        let descriptor = parse_sfc(source, SfcParseOptions::default()).unwrap();
+        let opts = SfcCompileOptions {
+            scope_id: Some("abcd1234".into()),
+            template: TemplateCompileOptions {
+                ssr: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let result = compile_sfc(&descriptor, opts).unwrap();
+        assert!(!result.code.contains("_useCssVars"), "{}", result.code);
+        assert!(
+            result
+                .code
+                .contains("const _cssVars = { style: { \"--abcd1234-color\": color.value } }"),
+            "{}",
+            result.code
+        );
+        assert!(
+            result
+                .code
+                .contains("_ssrRenderAttrs(_mergeProps(_cssVars, _attrs))"),
+            "{}",
+            result.code
+        );
     }
 
     #[test]
@@ -812,6 +1610,24 @@ var c = 3
         );
     }
 
+    #[test]
+    fn test_compile_normal_script_transform_error() {
+        let source =
+            "<script lang=\"ts\">\nnamespace Config {\n  export let debug = false\n}\n</script>\n\
+                      <script setup lang=\"ts\">\nconst a = 1\n</script>\n\
+                      <template><div>{{ a }}</div></template>";
+        let descriptor = parse_sfc(source, SfcParseOptions::default()).unwrap();
+        let result = compile_sfc(&descriptor, SfcCompileOptions::default()).unwrap();
+
+        // Reported once, instead of leaving TypeScript in the output unnoticed
+        assert_eq!(result.errors.len(), 1, "{:?}", result.errors);
+        assert_eq!(result.errors[0].code.as_deref(), Some("TS_TRANSFORM_ERROR"));
+        assert!(result.errors[0]
+            .message
+            .contains("Namespaces exporting non-const"));
+        assert!(!result.code.contains("namespace"), "{}", result.code);
+    }
+
     #[test]
     fn test_extract_normal_script_content() {
         let input = r#"import type { NuxtRoute } from "@typed-router";
@@ -828,7 +1644,7 @@ export default {
 }
 "#;
         // Test preserving TypeScript output
-        let result = extract_normal_script_content(input, true, true);
+        let result = extract_normal_script_content(input, true, true, &mut Vec::new());
         eprintln!("Extracted normal script content (preserve TS):\n{}", result);
 
         // Should contain imports
@@ -995,4 +1811,879 @@ const title = defineModel('title')
             result.code
         );
     }
+
+    #[test]
+    fn test_compile_ssr_dynamic_slot_name() {
+        let source = r#"<script setup>
+import { ref } from 'vue'
+import Child from './Child.vue'
+const name = ref('header')
+</script>
+
+<template>
+  <Child><template #[name]>hi</template></Child>
+</template>"#;
+
+        let descriptor =
+            parse_sfc(source, SfcParseOptions::default()).expect("Failed to parse SFC");
+        let mut opts = SfcCompileOptions::default();
+        opts.template.ssr = true;
+        let result = compile_sfc(&descriptor, opts).expect("Failed to compile SFC");
+
+        assert!(
+            result.code.contains("[name.value]: _withCtx("),
+            "Should resolve the slot name through the setup bindings. Got:\n{}",
+            result.code
+        );
+        assert!(!result.code.contains("_ctx.name"), "{}", result.code);
+    }
+
+    #[test]
+    fn test_compile_ssr_script_setup() {
+        let source = r#"<script setup>
+import { ref } from 'vue'
+import Child from './Child.vue'
+const msg = ref('hello')
+const items = ref([1, 2])
+</script>
+
+<template>
+  <div class="wrapper">
+    <span>{{ msg }}</span>
+    <Child v-for="item in items" :key="item" :item="item" />
+  </div>
+</template>"#;
+
+        let descriptor =
+            parse_sfc(source, SfcParseOptions::default()).expect("Failed to parse SFC");
+        let mut opts = SfcCompileOptions::default();
+        opts.template.ssr = true;
+        let result = compile_sfc(&descriptor, opts).expect("Failed to compile SFC");
+
+        assert!(
+            result.code.contains("from \"vue/server-renderer\""),
+            "Should import server renderer helpers. Got:\n{}",
+            result.code
+        );
+        assert!(
+            result.code.contains("__ssrInlineRender: true"),
+            "Should mark inline SSR render. Got:\n{}",
+            result.code
+        );
+        assert!(
+            result
+                .code
+                .contains("return (_ctx, _push, _parent, _attrs) => {"),
+            "Should return an SSR render function. Got:\n{}",
+            result.code
+        );
+        assert!(
+            result.code.contains("_push(_ssrRenderComponent(Child, {"),
+            "Should render child component. Got:\n{}",
+            result.code
+        );
+        assert!(
+            !result.code.contains("_createElementBlock"),
+            "Should not contain client VNode helpers. Got:\n{}",
+            result.code
+        );
+    }
+
+    #[test]
+    fn test_compile_ssr_normal_script() {
+        let source = r#"<script>
+export default { data: () => ({ msg: 'hi' }) }
+</script>
+
+<template>
+  <p>{{ msg }}</p>
+</template>"#;
+
+        let descriptor =
+            parse_sfc(source, SfcParseOptions::default()).expect("Failed to parse SFC");
+        let mut opts = SfcCompileOptions::default();
+        opts.template.ssr = true;
+        let result = compile_sfc(&descriptor, opts).expect("Failed to compile SFC");
+
+        assert!(
+            result
+                .code
+                .contains("export function ssrRender(_ctx, _push, _parent, _attrs) {"),
+            "Should emit ssrRender. Got:\n{}",
+            result.code
+        );
+        assert!(
+            result.code.contains("_sfc_main.ssrRender = ssrRender"),
+            "Should attach ssrRender. Got:\n{}",
+            result.code
+        );
+    }
+
+    /// `v-t="key"` becomes `:textContent="$t(key)"`
+    fn translate<'a>(
+        ctx: &mut vize_atelier_core::TransformContext<'a>,
+        _el: &mut vize_atelier_core::ElementNode<'a>,
+        dir: &vize_atelier_core::DirectiveNode<'a>,
+    ) -> Option<vize_atelier_core::DirectiveTransformResult<'a>> {
+        use vize_atelier_core::{DirectiveNode, ExpressionNode, PropNode, SimpleExpressionNode};
+        use vize_carton::Box;
+
+        let Some(ExpressionNode::Simple(exp)) = &dir.exp else {
+            return None;
+        };
+        let mut bind = DirectiveNode::new(ctx.allocator, "bind", dir.loc.clone());
+        bind.arg = Some(ExpressionNode::Simple(Box::new_in(
+            SimpleExpressionNode::new("textContent", true, dir.loc.clone()),
+            ctx.allocator,
+        )));
+        bind.exp = Some(ExpressionNode::Simple(Box::new_in(
+            SimpleExpressionNode::new(format!("$t({})", exp.content), false, exp.loc.clone()),
+            ctx.allocator,
+        )));
+        let mut props = vize_carton::Vec::new_in(ctx.allocator);
+        props.push(PropNode::Directive(Box::new_in(bind, ctx.allocator)));
+        Some(vize_atelier_core::DirectiveTransformResult {
+            props,
+            remove_directive: true,
+            ssr_tag_type: None,
+        })
+    }
+
+    #[test]
+    fn test_compile_custom_directive_transform() {
+        let source = r#"<script setup>
+const key = 'hello'
+</script>
+
+<template>
+  <p v-t="key"></p>
+</template>"#;
+
+        let descriptor =
+            parse_sfc(source, SfcParseOptions::default()).expect("Failed to parse SFC");
+        let mut compiler_options = vize_atelier_dom::DomCompilerOptions::default();
+        compiler_options
+            .directive_transforms
+            .insert("t".into(), translate);
+        let mut opts = SfcCompileOptions::default();
+        opts.template.compiler_options = Some(compiler_options);
+        let result = compile_sfc(&descriptor, opts).expect("Failed to compile SFC");
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        assert!(
+            result.code.contains("textContent: $t(key)"),
+            "Should apply the directive transform. Got:\n{}",
+            result.code
+        );
+        assert!(
+            !result.code.contains("resolveDirective"),
+            "Should not resolve the directive at runtime. Got:\n{}",
+            result.code
+        );
+    }
+
+    #[test]
+    fn test_compile_scoped_static_content() {
+        let items = r#"<p class="a">x</p>"#.repeat(5);
+        let source = format!(
+            r#"<script setup>
+const id = 'list'
+</script>
+
+<template>
+  <div :id="id">{}</div>
+</template>
+
+<style scoped>
+.a {{ color: red; }}
+</style>"#,
+            items
+        );
+
+        let descriptor =
+            parse_sfc(&source, SfcParseOptions::default()).expect("Failed to parse SFC");
+        let opts = SfcCompileOptions {
+            scope_id: Some("100fdcb8".to_string()),
+            ..Default::default()
+        };
+        let result = compile_sfc(&descriptor, opts).expect("Failed to compile SFC");
+
+        assert!(
+            result
+                .code
+                .contains(r#"_createStaticVNode("<p class=\"a\" data-v-100fdcb8>x</p>"#),
+            "Static content should carry the scope id. Got:\n{}",
+            result.code
+        );
+        assert!(
+            result
+                .css
+                .as_deref()
+                .unwrap()
+                .contains(".a[data-v-100fdcb8]"),
+            "{:?}",
+            result.css
+        );
+    }
+
+    #[test]
+    fn test_compile_vapor_script_setup() {
+        let source = r#"<script setup vapor>
+import { ref } from 'vue'
+const msg = ref('hello')
+</script>
+
+<template>
+  <div><span>{{ msg }}</span><p v-if="msg">yes</p></div>
+</template>"#;
+
+        let descriptor =
+            parse_sfc(source, SfcParseOptions::default()).expect("Failed to parse SFC");
+        let result =
+            compile_sfc(&descriptor, SfcCompileOptions::default()).expect("Failed to compile SFC");
+
+        assert!(
+            result
+                .code
+                .contains("const t1 = _template(\"<div><span> </span></div>\", true)"),
+            "Should declare the root template. Got:\n{}",
+            result.code
+        );
+        assert!(
+            result
+                .code
+                .contains("function _sfc_render(_ctx, $props, $emit, $attrs, $slots) {"),
+            "Should emit the Vapor render function. Got:\n{}",
+            result.code
+        );
+        assert!(
+            result.code.contains("const n0 = _child(n4)"),
+            "Should locate children in the root template. Got:\n{}",
+            result.code
+        );
+        assert!(
+            result.code.contains("__vapor: true") && result.code.contains("render: _sfc_render"),
+            "Should mark the component as Vapor. Got:\n{}",
+            result.code
+        );
+        assert!(
+            result.code.contains("msg,") && result.code.contains("ref\n"),
+            "Should return setup bindings. Got:\n{}",
+            result.code
+        );
+    }
+
+    #[test]
+    fn test_compile_vapor_template_errors() {
+        let source = r#"<script setup vapor>
+const items = []
+</script>
+
+<template>
+  <ul>
+    <li v-for="1 in items"></li>
+  </ul>
+</template>"#;
+
+        let descriptor =
+            parse_sfc(source, SfcParseOptions::default()).expect("Failed to parse SFC");
+        let result =
+            compile_sfc(&descriptor, SfcCompileOptions::default()).expect("Failed to compile SFC");
+
+        assert_eq!(result.errors.len(), 1, "{:?}", result.errors);
+        let error = &result.errors[0];
+        assert_eq!(error.code.as_deref(), Some("VForMalformedExpression"));
+        assert_eq!(error.message, "v-for has invalid alias \"1\".");
+        let loc = error.loc.as_ref().expect("Error should be located");
+        assert_eq!((loc.start_line, loc.start_column), (7, 5));
+        assert_eq!(&source[loc.start..loc.start + 4], "<li ");
+    }
+
+    #[test]
+    fn test_compile_vapor_v_model_and_object_bindings() {
+        let source = r#"<script setup vapor>
+import { ref } from 'vue'
+const msg = ref('')
+const attrs = { id: 'a' }
+const handlers = {}
+</script>
+
+<template>
+  <div v-bind="attrs" v-on="handlers"><input v-model="msg"></div>
+</template>"#;
+
+        let descriptor =
+            parse_sfc(source, SfcParseOptions::default()).expect("Failed to parse SFC");
+        let result =
+            compile_sfc(&descriptor, SfcCompileOptions::default()).expect("Failed to compile SFC");
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        assert!(
+            result
+                .code
+                .contains("function _sfc_render(_ctx, $props, $emit, $attrs, $slots) {"),
+            "Should emit the Vapor render function. Got:\n{}",
+            result.code
+        );
+        assert!(
+            result.code.contains("__vapor: true"),
+            "Should mark the component as Vapor. Got:\n{}",
+            result.code
+        );
+        assert!(
+            result
+                .code
+                .contains("_applyTextModel(n0, () => _ctx.msg, (_value) => _ctx.msg = _value)"),
+            "Should apply v-model. Got:\n{}",
+            result.code
+        );
+        assert!(
+            result.code.contains("_setDynamicProps(n1, [_ctx.attrs])"),
+            "Should bind the attrs object. Got:\n{}",
+            result.code
+        );
+
+        // Object v-on isn't supported yet, which leaves it out with a warning
+        assert_eq!(result.warnings.len(), 1, "{:?}", result.warnings);
+        assert_eq!(
+            result.warnings[0].code.as_deref(),
+            Some("VaporUnsupportedDirective")
+        );
+    }
+
+    #[test]
+    fn test_compile_vapor_ssr() {
+        let source = r#"<script setup vapor>
+import { ref } from 'vue'
+const msg = ref('hello')
+</script>
+
+<template>
+  <div><span>{{ msg }}</span></div>
+</template>"#;
+
+        let descriptor =
+            parse_sfc(source, SfcParseOptions::default()).expect("Failed to parse SFC");
+        let mut opts = SfcCompileOptions::default();
+        opts.template.ssr = true;
+        let result = compile_sfc(&descriptor, opts).expect("Failed to compile SFC");
+
+        assert!(
+            result.code.contains("__ssrInlineRender: true"),
+            "Should render through the SSR renderer. Got:\n{}",
+            result.code
+        );
+        assert!(
+            result.code.contains("_ssrInterpolate(msg.value)"),
+            "Should render the template to a string. Got:\n{}",
+            result.code
+        );
+        assert!(
+            !result.code.contains("_template(") && !result.code.contains("__vapor"),
+            "Should not contain Vapor client code. Got:\n{}",
+            result.code
+        );
+    }
+
+    #[test]
+    fn test_compile_source_map_normal_script() {
+        let source = r#"<script>
+export default {
+  data() {
+    return { msg: 'hi' }
+  }
+}
+</script>
+
+<template>
+  <p>{{ msg }}</p>
+</template>
+
+<style>
+.a { color: red; }
+</style>"#;
+
+        let descriptor =
+            parse_sfc(source, SfcParseOptions::default()).expect("Failed to parse SFC");
+        let mut opts = SfcCompileOptions::default();
+        opts.parse.source_map = true;
+        opts.style.source_map = true;
+        let result = compile_sfc(&descriptor, opts).expect("Failed to compile SFC");
+
+        let map = result.map.expect("module map");
+        assert_eq!(map["version"], 3);
+        let map = oxc_sourcemap::SourceMap::from_json_string(&map.to_string()).unwrap();
+        let table = map.generate_lookup_table();
+
+        // `data() {` stays on its own line and maps back to line 3 of the SFC
+        let data_line = result
+            .code
+            .lines()
+            .position(|l| l.trim_start().starts_with("data()"))
+            .unwrap() as u32;
+        let token = map
+            .lookup_source_view_token(&table, data_line, 4)
+            .expect("script line mapped");
+        assert_eq!(token.get_src_line(), 2);
+
+        // Template interpolation maps into the template block
+        let (line, col) = result
+            .code
+            .lines()
+            .enumerate()
+            .find_map(|(i, l)| l.find("_ctx.msg").map(|c| (i as u32, c as u32)))
+            .expect("interpolation in output");
+        let token = map
+            .lookup_source_view_token(&table, line, col)
+            .expect("template mapped");
+        assert_eq!(token.get_src_line(), 9);
+
+        assert!(result.css_map.is_some());
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn test_compile_css_source_map_minified() {
+        let source = "<template><div class=\"a\"></div></template>\n\
+                      <style scoped>\n.a { color: red; }\n</style>\n\
+                      <style>\n@media print {\n  .b { color: blue; }\n}\n</style>";
+        let descriptor = parse_sfc(source, SfcParseOptions::default()).unwrap();
+        let mut opts = SfcCompileOptions {
+            scope_id: Some("abcd1234".into()),
+            ..Default::default()
+        };
+        opts.style.minify = true;
+        opts.style.source_map = true;
+        let result = compile_sfc(&descriptor, opts).unwrap();
+
+        let css = result.css.unwrap();
+        assert_eq!(
+            css,
+            ".a[data-v-abcd1234]{color:red}\n@media print{.b{color:#00f}}"
+        );
+
+        let map = result.css_map.expect("css map");
+        let map = oxc_sourcemap::SourceMap::from_json_string(&map.to_string()).unwrap();
+        let table = map.generate_lookup_table();
+        let column = css.lines().nth(1).unwrap().find(".b").unwrap() as u32;
+        let token = map
+            .lookup_source_view_token(&table, 1, column)
+            .expect("second block mapped");
+        assert_eq!(token.get_src_line(), 6);
+        assert_eq!(token.get_src_col(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn test_compile_css_syntax_error() {
+        let source =
+            "<template><div></div></template>\n<style>\n.a { color: red; }\n.b!! {}\n</style>";
+        let descriptor = parse_sfc(source, SfcParseOptions::default()).unwrap();
+        let result = compile_sfc(&descriptor, SfcCompileOptions::default()).unwrap();
+
+        assert_eq!(result.errors.len(), 1);
+        let error = &result.errors[0];
+        assert_eq!(error.code.as_deref(), Some("CSS_SYNTAX_ERROR"));
+        let loc = error.loc.as_ref().unwrap();
+        assert_eq!((loc.start_line, loc.start_column), (4, 3));
+        assert_eq!(&source[loc.start..loc.start + 2], "!!");
+    }
+
+    #[test]
+    fn test_compile_typescript_error() {
+        let source = "<template><div>{{ a }}</div></template>\n<script setup lang=\"ts\">\nconst a: number = 1\nconst b = (a as ) + 1\n</script>";
+        let descriptor = parse_sfc(source, SfcParseOptions::default()).unwrap();
+        let result = compile_sfc(&descriptor, SfcCompileOptions::default()).unwrap();
+
+        assert_eq!(result.errors.len(), 1, "{:?}", result.errors);
+        let error = &result.errors[0];
+        assert_eq!(error.code.as_deref(), Some("TS_ERROR"));
+        let loc = error.loc.as_ref().unwrap();
+        assert_eq!((loc.start_line, loc.start_column), (4, 17));
+        assert_eq!(&source[loc.start..loc.start + 1], ")");
+
+        let strict = compile_sfc(
+            &descriptor,
+            SfcCompileOptions {
+                script: ScriptCompileOptions {
+                    strict: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        assert_eq!(strict.unwrap_err().code.as_deref(), Some("TS_ERROR"));
+    }
+
+    #[test]
+    fn test_compile_source_map_disabled_by_default() {
+        let source =
+            "<script setup>\nconst a = 1\n</script>\n<template><div>{{ a }}</div></template>";
+        let descriptor =
+            parse_sfc(source, SfcParseOptions::default()).expect("Failed to parse SFC");
+        let result =
+            compile_sfc(&descriptor, SfcCompileOptions::default()).expect("Failed to compile SFC");
+        assert!(result.map.is_none());
+        assert!(result.css_map.is_none());
+    }
+
+    #[test]
+    fn test_compile_source_map_script_setup() {
+        let source = r#"<script setup lang="ts">
+import { ref } from 'vue'
+const count = ref<number>(0)
+function inc(): void {
+  count.value++
+}
+</script>
+
+<template>
+  <button @click="inc">{{ count }}</button>
+</template>"#;
+
+        let descriptor =
+            parse_sfc(source, SfcParseOptions::default()).expect("Failed to parse SFC");
+        // Transpiled to JavaScript (reprinted by OXC) and kept as TypeScript
+        for is_ts in [false, true] {
+            let mut opts = SfcCompileOptions::default();
+            opts.parse.source_map = true;
+            opts.script.is_ts = is_ts;
+            let result = compile_sfc(&descriptor, opts).expect("Failed to compile SFC");
+
+            let map = result.map.expect("module map");
+            let map = oxc_sourcemap::SourceMap::from_json_string(&map.to_string()).unwrap();
+            let table = map.generate_lookup_table();
+            let find = |needle: &str| {
+                result
+                    .code
+                    .lines()
+                    .enumerate()
+                    .find_map(|(i, l)| l.find(needle).map(|c| (i as u32, c as u32)))
+                    .unwrap_or_else(|| panic!("{} in output:\n{}", needle, result.code))
+            };
+
+            let (line, col) = find("count.value++");
+            let token = map
+                .lookup_source_view_token(&table, line, col)
+                .expect("setup line mapped");
+            assert_eq!(token.get_src_line(), 4);
+
+            // The inlined render function maps into the template block
+            let (line, col) = find("_toDisplayString(count.value)");
+            let token = map
+                .lookup_source_view_token(&table, line, col + "_toDisplayString(".len() as u32)
+                .expect("interpolation mapped");
+            assert_eq!(
+                (token.get_src_line(), token.get_src_col()),
+                (9, 26),
+                "{}",
+                result.code
+            );
+            let (line, col) = find("_createElementBlock(");
+            let token = map
+                .lookup_source_view_token(&table, line, col)
+                .expect("element mapped");
+            assert_eq!(token.get_src_line(), 9);
+        }
+    }
+
+    #[test]
+    fn test_compile_source_map_script_setup_nested() {
+        let source = r#"<script setup>
+const msg = 'hi'
+function inc() {}
+</script>
+
+<template>
+  <div>
+    <span>{{ msg }}</span>
+    <b @click="inc">{{ msg }}</b>
+  </div>
+</template>"#;
+
+        let descriptor =
+            parse_sfc(source, SfcParseOptions::default()).expect("Failed to parse SFC");
+        for is_ts in [false, true] {
+            let mut opts = SfcCompileOptions::default();
+            opts.parse.source_map = true;
+            opts.script.is_ts = is_ts;
+            let result = compile_sfc(&descriptor, opts).expect("Failed to compile SFC");
+
+            let map = result.map.expect("module map");
+            let map = oxc_sourcemap::SourceMap::from_json_string(&map.to_string()).unwrap();
+            let table = map.generate_lookup_table();
+            let source_of = |needle: &str, nth: usize| {
+                let (line, col) = result
+                    .code
+                    .lines()
+                    .enumerate()
+                    .flat_map(|(i, l)| l.match_indices(needle).map(move |(c, _)| (i, c)))
+                    .nth(nth)
+                    .unwrap_or_else(|| panic!("{} in output:\n{}", needle, result.code));
+                let token = map
+                    .lookup_source_view_token(&table, line as u32, col as u32)
+                    .unwrap_or_else(|| panic!("{} not mapped:\n{}", needle, result.code));
+                (token.get_src_line(), token.get_src_col())
+            };
+
+            // Both children and the handler map to their own template lines
+            assert_eq!(source_of("msg)", 0), (7, 13), "{}", result.code);
+            assert_eq!(source_of("msg)", 1), (8, 23), "{}", result.code);
+            assert_eq!(source_of("onClick", 0).0, 8, "{}", result.code);
+            assert_eq!(source_of("_createElementVNode(\"span\"", 0).0, 7);
+            assert_eq!(source_of("_createElementVNode(\"b\"", 0).0, 8);
+        }
+    }
+
+    /// Files by path, recording the paths read
+    #[derive(Debug, Default)]
+    struct MemoryLoader(
+        Vec<(&'static str, &'static str)>,
+        std::sync::Mutex<Vec<std::path::PathBuf>>,
+    );
+
+    impl FileLoader for MemoryLoader {
+        fn read_file(&self, path: &std::path::Path) -> std::io::Result<String> {
+            self.1.lock().unwrap().push(path.to_path_buf());
+            self.0
+                .iter()
+                .find(|(name, _)| std::path::Path::new(name) == path)
+                .map(|(_, content)| content.to_string())
+                .ok_or_else(|| std::io::ErrorKind::NotFound.into())
+        }
+    }
+
+    fn compile_with_loader(
+        source: &str,
+        files: Vec<(&'static str, &'static str)>,
+    ) -> SfcCompileResult {
+        let descriptor = parse_sfc(
+            source,
+            SfcParseOptions {
+                filename: "src/App.vue".into(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let options = SfcCompileOptions {
+            scope_id: Some("abc123".into()),
+            file_loader: Some(std::sync::Arc::new(MemoryLoader(files, Default::default()))),
+            ..Default::default()
+        };
+        compile_sfc(&descriptor, options).unwrap()
+    }
+
+    #[test]
+    fn test_compile_src_blocks() {
+        let result = compile_with_loader(
+            "<template src=\"./App.html\"></template>\n\
+             <script src=\"./app.js\"></script>\n\
+             <style scoped src=\"../styles/app.css\"></style>",
+            vec![
+                ("src/App.html", "<div class=\"app\">{{ msg }}</div>"),
+                (
+                    "src/app.js",
+                    "export default { data: () => ({ msg: 'hi' }) }",
+                ),
+                ("styles/app.css", ".app { color: v-bind(color) }"),
+            ],
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        assert!(result.code.contains("class: \"app\""), "{}", result.code);
+        assert!(result.code.contains("msg: 'hi'"), "{}", result.code);
+        let css = result.css.unwrap();
+        assert!(css.contains(".app[data-v-abc123]"), "{}", css);
+        assert!(css.contains("var(--abc123-color)"), "{}", css);
+        assert_eq!(
+            result.dependencies,
+            ["src/App.html", "src/app.js", "styles/app.css"]
+        );
+    }
+
+    #[test]
+    fn test_compile_src_load_error() {
+        let result = compile_with_loader(
+            "<template><div></div></template>\n<style src=\"./missing.css\"></style>",
+            vec![],
+        );
+
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].code.as_deref(), Some("SRC_LOAD_ERROR"));
+        assert!(result.errors[0].message.contains("src/missing.css"));
+        assert_eq!(result.dependencies, ["src/missing.css"]);
+    }
+
+    #[test]
+    fn test_compile_script_setup_src_error() {
+        let result = compile_with_loader(
+            "<script setup src=\"./setup.js\"></script>\n<template><div></div></template>",
+            vec![("src/setup.js", "const a = 1")],
+        );
+
+        assert_eq!(result.errors[0].code.as_deref(), Some("SCRIPT_SETUP_SRC"));
+        assert!(result.dependencies.is_empty());
+    }
+
+    #[test]
+    fn test_compile_imported_macro_types() {
+        let result = compile_with_loader(
+            "<script setup lang=\"ts\">\n\
+             import type { Props, Emits } from './types'\n\
+             defineProps<Props & { extra?: boolean }>()\n\
+             defineEmits<Emits>()\n\
+             </script>\n\
+             <template><div>{{ title }}</div></template>",
+            vec![(
+                "src/types.ts",
+                "interface Base { id: string | number }\n\
+                 export interface Props extends Base { title: string }\n\
+                 export type Emits = { select: [id: string]; close: [] }",
+            )],
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let code: String = result.code.split_whitespace().collect();
+        assert!(
+            code.contains(
+                "props:{extra:{type:Boolean,required:false},\
+                 id:{type:[String,Number],required:true},\
+                 title:{type:String,required:true}}"
+            ),
+            "{}",
+            result.code
+        );
+        assert!(
+            code.contains("emits:[\"select\",\"close\"]"),
+            "{}",
+            result.code
+        );
+        assert!(code.contains("_toDisplayString(__props.title)"));
+        assert_eq!(result.dependencies, ["src/types.ts"]);
+    }
+
+    #[test]
+    fn test_compile_macro_types_read_once() {
+        let source = "<script setup lang=\"ts\">\n\
+                      import type { Props, Emits } from './types'\n\
+                      defineProps<Props>()\n\
+                      defineEmits<Emits>()\n\
+                      </script>\n\
+                      <template><div>{{ title }}</div></template>";
+        let descriptor = parse_sfc(
+            source,
+            SfcParseOptions {
+                filename: "src/App.vue".into(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let loader = std::sync::Arc::new(MemoryLoader(
+            vec![(
+                "src/types.ts",
+                "export interface Props { title: string }\n\
+                 export type Emits = { close: [] }",
+            )],
+            Default::default(),
+        ));
+        let options = SfcCompileOptions {
+            file_loader: Some(loader.clone()),
+            ..Default::default()
+        };
+        let result = compile_sfc(&descriptor, options).unwrap();
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let reads = loader.1.lock().unwrap();
+        let type_reads = reads
+            .iter()
+            .filter(|path| path.as_path() == std::path::Path::new("src/types.ts"))
+            .count();
+        assert_eq!(type_reads, 1, "{:?}", reads);
+    }
+
+    #[test]
+    fn test_compile_macro_types_from_normal_script() {
+        let source = r#"<script lang="ts">
+export interface Props {
+  title: string
+  count?: number
+}
+</script>
+
+<script setup lang="ts">
+defineProps<Props>()
+</script>
+
+<template><div>{{ title }}</div></template>"#;
+        let descriptor = parse_sfc(source, SfcParseOptions::default()).unwrap();
+        let result = compile_sfc(&descriptor, SfcCompileOptions::default()).unwrap();
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let code: String = result.code.split_whitespace().collect();
+        assert!(
+            code.contains(
+                "props:{count:{type:Number,required:false},\
+                 title:{type:String,required:true}}"
+            ),
+            "{}",
+            result.code
+        );
+    }
+
+    #[test]
+    fn test_compile_hmr() {
+        let source =
+            "<script setup>\nconst a = 1\n</script>\n<template><div>{{ a }}</div></template>";
+        let descriptor = parse_sfc(source, SfcParseOptions::default()).unwrap();
+        let options = SfcCompileOptions {
+            script: ScriptCompileOptions {
+                id: Some("src/App.vue".into()),
+                ..Default::default()
+            },
+            scope_id: Some("abc123".into()),
+            hmr: true,
+            ..Default::default()
+        };
+        let result = compile_sfc(&descriptor, options.clone()).unwrap();
+
+        assert!(result.code.contains("_sfc_main.__hmrId = \"abc123\"\n"));
+        assert!(result.code.contains("_sfc_main.__file = \"src/App.vue\"\n"));
+        assert!(result
+            .code
+            .contains("__VUE_HMR_RUNTIME__.createRecord(_sfc_main.__hmrId, _sfc_main)"));
+        assert!(result.code.ends_with("export default _sfc_main\n"));
+
+        let prod = compile_sfc(
+            &descriptor,
+            SfcCompileOptions {
+                is_prod: true,
+                ..options
+            },
+        )
+        .unwrap();
+        assert!(!prod.code.contains("__hmrId"));
+    }
+
+    #[test]
+    fn test_compile_hmr_template_only() {
+        let descriptor = parse_sfc(
+            "<template><div></div></template>",
+            SfcParseOptions::default(),
+        )
+        .unwrap();
+        let result = compile_sfc(
+            &descriptor,
+            SfcCompileOptions {
+                scope_id: Some("abc123".into()),
+                hmr: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert!(result
+            .code
+            .contains("_sfc_main.render = render\n_sfc_main.__hmrId"));
+        assert!(result.code.contains("_sfc_main.__file = \"anonymous.vue\""));
+    }
 }
@@ -15,7 +15,7 @@ pub mod typescript;
 use crate::types::{BindingMetadata, ScriptCompileOptions, SfcDescriptor, SfcError};
 
 use self::function_mode::compile_script_setup;
-use self::typescript::transform_typescript_to_js;
+use self::typescript::transform_typescript_or_report;
 
 // Re-export commonly used items
 pub use self::function_mode::compile_script_setup as compile_script_setup_function_mode;
@@ -33,6 +33,10 @@ pub use self::props::{
 pub struct ScriptCompileResult {
     pub code: String,
     pub bindings: Option<BindingMetadata>,
+    /// Errors from transforming the generated TypeScript
+    pub errors: Vec<SfcError>,
+    /// Map of the inlined render function back to the template content
+    pub template_map: Option<String>,
 }
 
 /// Template parts for inline compilation
@@ -42,6 +46,13 @@ pub struct TemplateParts<'a> {
     /// Component/directive resolution statements (inside render function, before return)
     pub preamble: &'a str,
     pub render_body: &'a str,
+    /// Map of `render_body` back to the template content
+    pub render_map: Option<&'a oxc_sourcemap::SourceMap>,
+    /// Whether the render body is an SSR statement list (`ssrRender`)
+    pub ssr: bool,
+    /// Whether `hoisted` declares a Vapor `_sfc_render` function, which reads
+    /// the bindings returned from setup
+    pub vapor: bool,
 }
 
 /// Compile script block(s)
@@ -73,14 +84,17 @@ pub fn compile_script(
             code.push_str("\nconst __sfc__ = __default__\n");
         }
         // Transform TypeScript to JavaScript using OXC if source is TypeScript
+        let mut errors = Vec::new();
         let final_code = if is_ts {
-            transform_typescript_to_js(&code)
+            transform_typescript_or_report(&code, &mut errors)
         } else {
             code
         };
         Ok(ScriptCompileResult {
             code: final_code,
             bindings: None,
+            errors,
+            template_map: None,
         })
     } else {
         // No script - generate empty component
@@ -88,11 +102,15 @@ pub fn compile_script(
             Ok(ScriptCompileResult {
                 code: "const __sfc__ = { __vapor: true }\n".to_string(),
                 bindings: None,
+                errors: Vec::new(),
+                template_map: None,
             })
         } else {
             Ok(ScriptCompileResult {
                 code: "const __sfc__ = {}\n".to_string(),
                 bindings: None,
+                errors: Vec::new(),
+                template_map: None,
             })
         }
     }
@@ -18,7 +18,7 @@ use super::macros::{
     is_macro_call_line, is_multiline_macro_start, is_paren_macro_start, is_props_destructure_line,
 };
 use super::props::{extract_emit_names_from_type, extract_prop_types_from_type};
-use super::typescript::transform_typescript_to_js;
+use super::typescript::transform_typescript_or_report;
 use super::ScriptCompileResult;
 
 /// Compile script setup content following Vue.js core format
@@ -331,7 +331,10 @@ pub fn compile_script_setup(
     } else if let Some(ref props_macro) = ctx.macros.define_props {
         if let Some(ref type_args) = props_macro.type_args {
             // For type-based props, extract full prop definitions
-            let prop_types = extract_prop_types_from_type(type_args);
+            let prop_types = ctx
+                .prop_types
+                .clone()
+                .unwrap_or_else(|| extract_prop_types_from_type(type_args));
             if !prop_types.is_empty() {
                 output.extend_from_slice(b"  props: {\n");
                 // Sort props for deterministic output
@@ -348,6 +351,9 @@ pub fn compile_script_setup(
                     } else {
                         b"true"
                     });
+                    if prop_type.skip_check {
+                        output.extend_from_slice(b", skipCheck: true");
+                    }
                     output.extend_from_slice(b" },\n");
                 }
                 output.extend_from_slice(b"  },\n");
@@ -363,7 +369,10 @@ pub fn compile_script_setup(
     if let Some(ref emits_macro) = ctx.macros.define_emits {
         if let Some(ref type_args) = emits_macro.type_args {
             // Extract emit names from type
-            let emit_names = extract_emit_names_from_type(type_args);
+            let emit_names = ctx
+                .emit_names
+                .clone()
+                .unwrap_or_else(|| extract_emit_names_from_type(type_args));
             if !emit_names.is_empty() {
                 output.extend_from_slice(b"  emits: [");
                 for (i, name) in emit_names.iter().enumerate() {
@@ -566,8 +575,9 @@ pub fn compile_script_setup(
     let output_str = unsafe { String::from_utf8_unchecked(output.into_iter().collect()) };
 
     // Transform TypeScript to JavaScript using OXC if lang="ts"
+    let mut errors = Vec::new();
     let final_code = if is_ts {
-        transform_typescript_to_js(&output_str)
+        transform_typescript_or_report(&output_str, &mut errors)
     } else {
         output_str
     };
@@ -575,5 +585,7 @@ pub fn compile_script_setup(
     Ok(ScriptCompileResult {
         code: final_code,
         bindings: Some(ctx.bindings),
+        errors,
+        template_map: None,
     })
 }
@@ -3,8 +3,12 @@
 //! This module handles compilation of script setup with inline template mode,
 //! where the render function is inlined into the setup function.
 
-use crate::script::{transform_destructured_props, ScriptCompileContext};
-use crate::types::SfcError;
+use std::rc::Rc;
+
+use crate::css_vars::CssVarsCode;
+use crate::script::{transform_destructured_props, ScriptCompileContext, TypeResolver};
+use crate::source_map::{compose, relocate_lines};
+use crate::types::{BindingType, SfcError};
 
 use super::import_utils::process_import_for_types;
 use super::macros::{
@@ -13,7 +17,7 @@ use super::macros::{
 use super::props::{
     extract_emit_names_from_type, extract_prop_types_from_type, extract_with_defaults_defaults,
 };
-use super::typescript::transform_typescript_to_js;
+use super::typescript::{transform_typescript_or_report, transform_typescript_with_map_or_report};
 use super::{ScriptCompileResult, TemplateParts};
 
 /// Compile script setup with inline template (Vue's inline template mode)
@@ -23,8 +27,11 @@ pub fn compile_script_setup_inline(
     is_ts: bool,
     template: TemplateParts<'_>,
     normal_script_content: Option<&str>,
+    css_vars: Option<&CssVarsCode>,
+    type_resolver: &Rc<TypeResolver>,
 ) -> Result<ScriptCompileResult, SfcError> {
     let mut ctx = ScriptCompileContext::new(content);
+    ctx.type_resolver = type_resolver.clone();
     ctx.analyze();
 
     // Use arena-allocated Vec for better performance
@@ -60,6 +67,17 @@ pub fn compile_script_setup_inline(
         output.extend_from_slice(b"import { useModel as _useModel } from 'vue'\n");
     }
 
+    // useCssVars import for v-bind() in <style>
+    if let Some(css_vars) = css_vars {
+        if css_vars.used_unref && !template.imports.contains("unref as _unref") {
+            output.extend_from_slice(
+                b"import { useCssVars as _useCssVars, unref as _unref } from 'vue'\n",
+            );
+        } else {
+            output.extend_from_slice(b"import { useCssVars as _useCssVars } from 'vue'\n");
+        }
+    }
+
     // defineComponent and PropType imports for TypeScript
     if is_ts {
         // Check if we need PropType (when there are typed props)
@@ -403,6 +421,13 @@ pub fn compile_script_setup_inline(
     }
     output.extend_from_slice(component_name.as_bytes());
     output.extend_from_slice(b"',\n");
+    if template.ssr && !template.render_body.is_empty() {
+        output.extend_from_slice(b"  __ssrInlineRender: true,\n");
+    }
+    if template.vapor {
+        output.extend_from_slice(b"  __vapor: true,\n");
+        output.extend_from_slice(b"  render: _sfc_render,\n");
+    }
 
     // Props definition
     // Extract defaults from withDefaults if present
@@ -459,7 +484,10 @@ pub fn compile_script_setup_inline(
     if let Some(ref props_macro) = ctx.macros.define_props {
         if let Some(ref type_args) = props_macro.type_args {
             // Type-based props: extract prop definitions from type
-            let prop_types = extract_prop_types_from_type(type_args);
+            let prop_types = ctx
+                .prop_types
+                .clone()
+                .unwrap_or_else(|| extract_prop_types_from_type(type_args));
             if !prop_types.is_empty() || !model_infos.is_empty() {
                 output.extend_from_slice(b"  props: {\n");
                 // Sort props for deterministic output
@@ -484,6 +512,9 @@ pub fn compile_script_setup_inline(
                     } else {
                         b"true"
                     });
+                    if prop_type.skip_check {
+                        output.extend_from_slice(b", skipCheck: true");
+                    }
                     // Add default value from withDefaults or props destructure
                     let mut has_default = false;
                     if let Some(ref defaults) = with_defaults_args {
@@ -589,7 +620,10 @@ pub fn compile_script_setup_inline(
             }
         } else if let Some(ref type_args) = emits_macro.type_args {
             // Type-based syntax: defineEmits<{ (e: 'click'): void }>()
-            let emit_names = extract_emit_names_from_type(type_args);
+            let emit_names = ctx
+                .emit_names
+                .clone()
+                .unwrap_or_else(|| extract_emit_names_from_type(type_args));
             all_emits.extend(emit_names);
         }
     }
@@ -636,17 +670,37 @@ pub fn compile_script_setup_inline(
         }
     }
 
-    if setup_args.is_empty() {
-        output.extend_from_slice(b"  setup(__props) {\n");
+    // Generic components declare their type parameters on setup, which
+    // keeps `__props` from being typed through defineComponent
+    let generic = ctx
+        .type_resolver
+        .options()
+        .generic
+        .as_deref()
+        .filter(|_| is_ts);
+    output.extend_from_slice(b"  setup");
+    if let Some(generic) = generic {
+        output.push(b'<');
+        output.extend_from_slice(generic.as_bytes());
+        output.extend_from_slice(b">(__props: any");
     } else {
-        output.extend_from_slice(b"  setup(__props, { ");
+        output.extend_from_slice(b"(__props");
+    }
+    if !setup_args.is_empty() {
+        output.extend_from_slice(b", { ");
         output.extend_from_slice(setup_args.join(", ").as_bytes());
-        output.extend_from_slice(b" }) {\n");
+        output.extend_from_slice(b" }");
     }
+    output.extend_from_slice(b") {\n");
 
     // Always add a blank line after setup signature
     output.push(b'\n');
 
+    if let Some(css_vars) = css_vars {
+        output.extend_from_slice(css_vars.code.as_bytes());
+        output.push(b'\n');
+    }
+
     // Emit binding: const emit = __emit
     if let Some(ref emits_macro) = ctx.macros.define_emits {
         if let Some(ref binding_name) = emits_macro.binding_name {
@@ -698,7 +752,35 @@ pub fn compile_script_setup_inline(
 
     // Inline render function as return (blank line before)
     output.push(b'\n');
-    if !template.render_body.is_empty() {
+    // Output line and column offset of each render body line
+    let mut render_lines: Vec<(u32, u32)> = Vec::new();
+    if template.ssr && !template.render_body.is_empty() {
+        output.extend_from_slice(b"return (_ctx, _push, _parent, _attrs) => {\n");
+
+        for line in template.preamble.lines() {
+            if !line.trim().is_empty() {
+                output.extend_from_slice(b"  ");
+                output.extend_from_slice(line.as_bytes());
+                output.push(b'\n');
+            }
+        }
+        if !template.preamble.is_empty() {
+            output.push(b'\n');
+        }
+
+        // SSR render body is already a list of statements
+        let first_line = count_lines(&output);
+        for (line_number, line) in (first_line..).zip(template.render_body.lines()) {
+            let indent = if line.trim().is_empty() { 0 } else { 2 };
+            render_lines.push((line_number, indent));
+            if indent > 0 {
+                output.extend_from_slice(b"  ");
+            }
+            output.extend_from_slice(line.as_bytes());
+            output.push(b'\n');
+        }
+        output.extend_from_slice(b"}\n");
+    } else if !template.render_body.is_empty() {
         output.extend_from_slice(b"return (_ctx, _cache) => {\n");
 
         // Output component/directive resolution statements (preamble)
@@ -714,16 +796,21 @@ pub fn compile_script_setup_inline(
         }
 
         // Indent the render body properly
+        let mut line_number = count_lines(&output);
         let mut first_line = true;
         for line in template.render_body.lines() {
             if first_line {
+                render_lines.push((line_number, "  return ".len() as u32));
                 output.extend_from_slice(b"  return ");
                 output.extend_from_slice(line.as_bytes());
                 first_line = false;
             } else {
                 output.push(b'\n');
+                line_number += 1;
                 // Preserve existing indentation by adding 2 spaces (setup indent)
-                if !line.trim().is_empty() {
+                let indent = if line.trim().is_empty() { 0 } else { 2 };
+                render_lines.push((line_number, indent));
+                if indent > 0 {
                     output.extend_from_slice(b"  ");
                 }
                 output.extend_from_slice(line.as_bytes());
@@ -731,6 +818,21 @@ pub fn compile_script_setup_inline(
         }
         output.push(b'\n');
         output.extend_from_slice(b"}\n");
+    } else if template.vapor {
+        // The hoisted Vapor render function reads setup bindings from `_ctx`
+        let mut returned: Vec<&str> = ctx
+            .bindings
+            .bindings
+            .iter()
+            .filter(|(_, binding_type)| {
+                !matches!(binding_type, BindingType::Props | BindingType::PropsAliased)
+            })
+            .map(|(name, _)| name.as_str())
+            .collect();
+        returned.sort_unstable();
+        output.extend_from_slice(b"return { ");
+        output.extend_from_slice(returned.join(", ").as_bytes());
+        output.extend_from_slice(b" }\n");
     }
 
     output.extend_from_slice(b"}\n");
@@ -749,29 +851,51 @@ pub fn compile_script_setup_inline(
     // is_ts here indicates whether to preserve TypeScript output (true) or transpile to JS (false)
     // When is_ts = false, we always run the transform to strip any TypeScript syntax
     // When is_ts = true, we keep the code as-is (preserve TypeScript)
-    let transformed_code = if is_ts {
+    let mut errors = Vec::new();
+    let (transformed_code, reprint_map) = if is_ts {
         // Preserve TypeScript output - no transformation
-        output_str
+        (output_str, None)
+    } else if template.render_map.is_some() {
+        // Keep track of where the render function ends up in the reprinted code
+        transform_typescript_with_map_or_report(&output_str, &mut errors)
     } else {
         // Transpile to JavaScript - always run transform to strip TypeScript syntax
-        transform_typescript_to_js(&output_str)
+        (
+            transform_typescript_or_report(&output_str, &mut errors),
+            None,
+        )
     };
-
-    // Prepend preserved normal script content
-    // If transpiling to JS (is_ts = false), also transform the normal script content
-    let final_code = if let Some(normal_script) = preserved_normal_script {
-        let transformed_normal = if is_ts {
-            normal_script
-        } else {
-            transform_typescript_to_js(&normal_script)
-        };
-        format!("{}\n\n{}", transformed_normal, transformed_code)
+    let template_map = template.render_map.map(|render_map| {
+        let inlined = relocate_lines(render_map, |line| render_lines.get(line as usize).copied());
+        match &reprint_map {
+            Some(reprint_map) => compose(reprint_map, &inlined),
+            None => inlined,
+        }
+    });
+
+    // Prepend preserved normal script content, which is already transpiled
+    // when the output is JavaScript
+    let (final_code, template_map) = if let Some(transformed_normal) = preserved_normal_script {
+        let shift = count_lines(transformed_normal.as_bytes()) + 2;
+        let template_map =
+            template_map.map(|map| relocate_lines(&map, |line| Some((line + shift, 0))));
+        (
+            format!("{}\n\n{}", transformed_normal, transformed_code),
+            template_map,
+        )
     } else {
-        transformed_code
+        (transformed_code, template_map)
     };
 
     Ok(ScriptCompileResult {
         code: final_code,
         bindings: Some(ctx.bindings),
+        errors,
+        template_map: template_map.map(|map| map.to_json_string()),
     })
 }
+
+/// Number of line breaks in `code`
+fn count_lines(code: &[u8]) -> u32 {
+    code.iter().filter(|&&b| b == b'\n').count() as u32
+}
@@ -15,6 +15,8 @@ pub struct PropTypeInfo {
     pub ts_type: Option<String>,
     /// Whether the prop is optional
     pub optional: bool,
+    /// Whether runtime type checking is skipped, for types that can't be fully checked
+    pub skip_check: bool,
 }
 
 /// Extract prop types from TypeScript type definition
@@ -77,6 +79,7 @@ fn extract_prop_type_info(segment: &str, props: &mut HashMap<String, PropTypeInf
                     js_type,
                     ts_type: Some(ts_type_str),
                     optional,
+                    skip_check: false,
                 },
             );
         }
@@ -7,7 +7,9 @@ mod tests {
     use crate::compile_script::props::{
         extract_prop_types_from_type, extract_with_defaults_defaults, is_valid_identifier,
     };
-    use crate::compile_script::typescript::transform_typescript_to_js;
+    use crate::compile_script::typescript::{
+        transform_typescript_or_report, transform_typescript_to_js,
+    };
     use crate::types::SfcDescriptor;
 
     #[test]
@@ -449,7 +451,7 @@ const count = ref(0)
     return x.toString();
 }
 const foo: string = "bar";"#;
-        let result = transform_typescript_to_js(ts_code);
+        let result = transform_typescript_to_js(ts_code).unwrap();
         eprintln!("TypeScript transform result:\n{}", result);
 
         // Should NOT contain type annotations
@@ -465,6 +467,17 @@ const foo: string = "bar";"#;
         );
     }
 
+    #[test]
+    fn test_transform_typescript_to_js_reports_errors() {
+        let errors = transform_typescript_to_js("const a: number = ;").unwrap_err();
+        assert!(!errors.is_empty());
+
+        let mut errors = Vec::new();
+        let code = transform_typescript_or_report("const a: number = ;", &mut errors);
+        assert!(code.is_empty());
+        assert_eq!(errors[0].code.as_deref(), Some("TS_TRANSFORM_ERROR"));
+    }
+
     #[test]
     fn test_compile_script_setup_strips_typescript() {
         let content = r#"
@@ -1,24 +1,38 @@
 //! TypeScript transformation utilities.
 //!
-//! This module handles transforming TypeScript code to JavaScript using OXC.
+//! This module handles transforming TypeScript code to JavaScript using OXC,
+//! and checking `lang="ts"` script blocks so that their errors are reported
+//! with locations instead of ending up in the output.
 
 use oxc_allocator::Allocator;
-use oxc_codegen::Codegen;
+use oxc_codegen::{Codegen, CodegenOptions};
+use oxc_diagnostics::OxcDiagnostic;
 use oxc_parser::Parser;
 use oxc_semantic::SemanticBuilder;
+use oxc_sourcemap::SourceMap;
 use oxc_span::SourceType;
 use oxc_transformer::{TransformOptions, Transformer, TypeScriptOptions};
 
-/// Transform TypeScript code to JavaScript using OXC
-pub fn transform_typescript_to_js(code: &str) -> String {
+use crate::source_map::{build_map, Token};
+use crate::types::{BlockLocation, SfcError, SfcScriptBlock};
+
+/// Transform TypeScript code to JavaScript using OXC.
+/// Fails with the diagnostics of the stage that rejected the code.
+pub fn transform_typescript_to_js(code: &str) -> Result<String, Vec<OxcDiagnostic>> {
+    transform(code, false).map(|(code, _)| code)
+}
+
+fn transform(
+    code: &str,
+    source_map: bool,
+) -> Result<(String, Option<SourceMap>), Vec<OxcDiagnostic>> {
     let allocator = Allocator::default();
     let source_type = SourceType::ts();
     let parser = Parser::new(&allocator, code, source_type);
     let parse_result = parser.parse();
 
     if !parse_result.errors.is_empty() {
-        // If parsing fails, return original code
-        return code.to_string();
+        return Err(parse_result.errors);
     }
 
     let mut program = parse_result.program;
@@ -29,8 +43,7 @@ pub fn transform_typescript_to_js(code: &str) -> String {
         .build(&program);
 
     if !semantic_ret.errors.is_empty() {
-        // If semantic analysis fails, return original code
-        return code.to_string();
+        return Err(semantic_ret.errors);
     }
 
     let (symbols, scopes) = semantic_ret.semantic.into_symbol_table_and_scope_tree();
@@ -49,11 +62,156 @@ pub fn transform_typescript_to_js(code: &str) -> String {
         .build_with_symbols_and_scopes(symbols, scopes, &mut program);
 
     if !ret.errors.is_empty() {
-        // If transformation fails, return original code
-        return code.to_string();
+        return Err(ret.errors);
     }
 
     // Generate JavaScript code
+    let ret = Codegen::new()
+        .with_options(CodegenOptions {
+            source_map_path: source_map.then(|| std::path::PathBuf::from("script.ts")),
+            ..Default::default()
+        })
+        .build(&program);
+    let map = ret.map.map(|map| expand_tabs_in_map(&map, &ret.code));
+
     // Replace tabs with 2 spaces for consistent indentation
-    Codegen::new().build(&program).code.replace('\t', "  ")
+    Ok((ret.code.replace('\t', "  "), map))
+}
+
+/// Shift the generated columns of `map` for the tabs of `code` replaced by 2 spaces
+fn expand_tabs_in_map(map: &SourceMap, code: &str) -> SourceMap {
+    let lines: Vec<&str> = code.lines().collect();
+    let tokens = map
+        .get_tokens()
+        .map(|token| {
+            let line = lines.get(token.get_dst_line() as usize).unwrap_or(&"");
+            let mut units = 0;
+            let tabs = line
+                .chars()
+                .take_while(|c| {
+                    units += c.len_utf16();
+                    units <= token.get_dst_col() as usize
+                })
+                .filter(|&c| c == '\t')
+                .count() as u32;
+            Token {
+                dst_line: token.get_dst_line(),
+                dst_col: token.get_dst_col() + tabs,
+                src_line: token.get_src_line(),
+                src_col: token.get_src_col(),
+            }
+        })
+        .collect();
+    let (source, content) = map.get_source_and_content(0).unwrap_or(("", ""));
+    build_map(source, content, tokens)
+}
+
+/// Transform generated TypeScript to JavaScript. On failure the errors are
+/// pushed to `errors` and no code is returned, so TypeScript never ends up
+/// in JavaScript output.
+pub fn transform_typescript_or_report(code: &str, errors: &mut Vec<SfcError>) -> String {
+    transform_typescript_to_js(code).unwrap_or_else(|diagnostics| {
+        report_transform_errors(diagnostics, errors);
+        String::new()
+    })
+}
+
+/// Like [`transform_typescript_or_report`], also returning a map from the
+/// JavaScript back to `code`.
+pub fn transform_typescript_with_map_or_report(
+    code: &str,
+    errors: &mut Vec<SfcError>,
+) -> (String, Option<SourceMap>) {
+    match transform(code, true) {
+        Ok(transformed) => transformed,
+        Err(diagnostics) => {
+            report_transform_errors(diagnostics, errors);
+            (String::new(), None)
+        }
+    }
+}
+
+fn report_transform_errors(diagnostics: Vec<OxcDiagnostic>, errors: &mut Vec<SfcError>) {
+    errors.extend(diagnostics.into_iter().map(|diagnostic| SfcError {
+        message: format!("Failed to transform TypeScript: {}", diagnostic.message),
+        code: Some("TS_TRANSFORM_ERROR".to_string()),
+        loc: None,
+    }));
+}
+
+/// Check a `lang="ts"` or `lang="tsx"` script block for syntax and semantic
+/// errors, located in the SFC. Other blocks are not checked.
+pub fn check_typescript_block(block: &SfcScriptBlock) -> Vec<SfcError> {
+    let source_type = match block.lang.as_deref() {
+        Some("ts") => SourceType::ts(),
+        Some("tsx") => SourceType::tsx(),
+        _ => return Vec::new(),
+    };
+
+    let allocator = Allocator::default();
+    let ret = Parser::new(&allocator, &block.content, source_type).parse();
+    let diagnostics = if !ret.errors.is_empty() {
+        ret.errors
+    } else {
+        SemanticBuilder::new()
+            .with_check_syntax_error(true)
+            .build(&ret.program)
+            .errors
+    };
+
+    diagnostics
+        .into_iter()
+        .map(|diagnostic| {
+            let span = diagnostic
+                .labels
+                .as_ref()
+                .and_then(|labels| labels.first())
+                .map(|label| (label.offset(), label.offset() + label.len()));
+            SfcError {
+                message: diagnostic.message.to_string(),
+                code: Some("TS_ERROR".to_string()),
+                loc: Some(match span {
+                    Some((start, end)) => locate(block, start, end),
+                    None => block.loc.clone(),
+                }),
+            }
+        })
+        .collect()
+}
+
+/// Location in the SFC of a byte range of a block's content
+fn locate(block: &SfcScriptBlock, start: usize, end: usize) -> BlockLocation {
+    let (start_offset, start_line, start_column) = position(block, start);
+    let (end_offset, end_line, end_column) = position(block, end.max(start));
+    BlockLocation {
+        start: start_offset,
+        end: end_offset,
+        start_line,
+        start_column,
+        end_line,
+        end_column,
+    }
+}
+
+/// Offset, line and column in the SFC of an offset in a block's content
+fn position(block: &SfcScriptBlock, offset: usize) -> (usize, usize, usize) {
+    let content = &block.content;
+    let mut offset = offset.min(content.len());
+    while !content.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &content[..offset];
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = before[line_start..].chars().count() + 1;
+    let column = if line == 0 {
+        block.loc.start_column + column - 1
+    } else {
+        column
+    };
+    (
+        block.loc.start + offset,
+        block.loc.start_line + line,
+        column,
+    )
 }
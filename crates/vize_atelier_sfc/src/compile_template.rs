@@ -3,11 +3,27 @@
 //! This module handles compilation of `<template>` blocks,
 //! supporting both DOM mode and Vapor mode.
 
+use vize_atelier_core::{Position, SourceLocation};
 use vize_atelier_vapor::{compile_vapor, VaporCompilerOptions};
 use vize_carton::Bump;
 
+use crate::source_map::{build_map, Token};
 use crate::types::*;
 
+/// Compiled template module code
+pub(crate) struct CompiledTemplate {
+    pub code: String,
+    /// Source map of the render function, relative to the template content
+    pub map: Option<String>,
+}
+
+/// Compiled Vapor template module code
+pub(crate) struct CompiledVaporTemplate {
+    pub code: String,
+    /// Diagnostics for parts of the template that were left out
+    pub warnings: Vec<SfcError>,
+}
+
 /// Compile template block
 pub(crate) fn compile_template_block(
     template: &SfcTemplateBlock,
@@ -16,7 +32,7 @@ pub(crate) fn compile_template_block(
     has_scoped: bool,
     is_ts: bool,
     bindings: Option<&BindingMetadata>,
-) -> Result<String, SfcError> {
+) -> Result<CompiledTemplate, SfcError> {
     let allocator = Bump::new();
 
     // Build DOM compiler options
@@ -29,6 +45,7 @@ pub(crate) fn compile_template_block(
         None
     };
     dom_opts.ssr = options.ssr;
+    dom_opts.ssr_css_vars = options.ssr_css_vars.as_deref().map(Into::into);
     dom_opts.is_ts = is_ts;
 
     // For script setup, use inline mode (render function inside setup return)
@@ -87,125 +104,176 @@ pub(crate) fn compile_template_block(
     output.push_str(&result.code);
     output.push('\n');
 
-    Ok(output)
+    Ok(CompiledTemplate {
+        code: output,
+        map: result.map,
+    })
 }
 
-/// Compile template block using Vapor mode
+/// Compile template block using Vapor mode.
+///
+/// The output declares the templates and a `_sfc_render` function taking
+/// `(_ctx, $props, $emit, $attrs, $slots)`, for components whose setup
+/// returns its bindings. Server rendering uses [`compile_template_block`].
+///
+/// Script setup bindings let custom directives and template refs resolve
+/// against the setup scope.
+///
+/// Errors reported by the Vapor compiler are returned, located in the SFC.
+/// Directives that Vapor mode doesn't support yet are only warnings, so the
+/// rest of the template still renders.
 pub(crate) fn compile_template_block_vapor(
     template: &SfcTemplateBlock,
     scope_id: &str,
     has_scoped: bool,
-) -> Result<String, SfcError> {
+    bindings: Option<&BindingMetadata>,
+) -> Result<CompiledVaporTemplate, Vec<SfcError>> {
     let allocator = Bump::new();
 
+    // Pass binding metadata from script setup to the Vapor compiler
+    let binding_metadata = bindings.map(Into::into);
+
     // Build Vapor compiler options
     let vapor_opts = VaporCompilerOptions {
         prefix_identifiers: false,
         ssr: false,
+        binding_metadata,
         ..Default::default()
     };
 
     // Compile template with Vapor
     let result = compile_vapor(&allocator, &template.content, vapor_opts);
 
-    if !result.error_messages.is_empty() {
-        return Err(SfcError {
-            message: format!(
-                "Vapor template compilation errors: {:?}",
-                result.error_messages
-            ),
-            code: Some("VAPOR_TEMPLATE_ERROR".to_string()),
-            loc: Some(template.loc.clone()),
-        });
+    let (warnings, errors): (Vec<_>, Vec<_>) = result
+        .errors
+        .into_iter()
+        .map(|err| {
+            let is_warning = err.code.is_vapor_error();
+            let loc = err.loc.as_ref().map_or_else(
+                || template.loc.clone(),
+                |loc| locate_template_error(template, loc),
+            );
+            let err = SfcError {
+                loc: Some(loc),
+                ..SfcError::from(err)
+            };
+            (is_warning, err)
+        })
+        .partition(|(is_warning, _)| *is_warning);
+    if !errors.is_empty() {
+        return Err(errors.into_iter().map(|(_, err)| err).collect());
     }
 
-    // Process the Vapor output to extract imports and render function
+    let scope_attr = format!("data-v-{}", scope_id);
     let mut output = String::new();
-    let scope_attr = if has_scoped {
-        format!("data-v-{}", scope_id)
-    } else {
-        String::new()
-    };
-
-    // Parse the Vapor output to separate imports and function body
-    let code = &result.code;
-
-    // Extract import line
-    if let Some(import_end) = code.find('\n') {
-        let import_line = &code[..import_end];
-        // Rewrite import to use 'vue' instead of 'vue/vapor' for compatibility
-        output.push_str(import_line);
-        output.push('\n');
-
-        // Extract template declarations and function body
-        let rest = &code[import_end + 1..];
-
-        // Find template declarations (const tN = ...)
-        let mut template_decls = Vec::new();
-        let mut func_start = 0;
-        for (i, line) in rest.lines().enumerate() {
-            if line.starts_with("const t") && line.contains("_template(") {
-                // Add scope ID to template if scoped
-                if has_scoped && !scope_attr.is_empty() {
-                    let modified = add_scope_id_to_template(line, &scope_attr);
-                    template_decls.push(modified);
-                } else {
-                    template_decls.push(line.to_string());
-                }
-            } else if line.starts_with("export default") {
-                func_start = i;
-                break;
+    let mut in_render = false;
+    for line in result.code.lines() {
+        if in_render {
+            output.push_str(line);
+            output.push('\n');
+            if line == "}" {
+                in_render = false;
             }
-        }
-
-        // Output template declarations
-        for decl in template_decls {
-            output.push_str(&decl);
+        } else if line.starts_with("export function render(") {
+            output.push_str("function _sfc_render(_ctx, $props, $emit, $attrs, $slots) {\n");
+            in_render = true;
+        } else if line.starts_with("const t") && line.contains("_template(") && has_scoped {
+            output.push_str(&add_scope_id_to_template(line, &scope_attr));
+            output.push('\n');
+        } else if !line.is_empty() {
+            // Imports, templates and event delegation
+            output.push_str(line);
             output.push('\n');
         }
+    }
 
-        // Extract and convert the function body
-        let lines: Vec<&str> = rest.lines().collect();
-        if func_start < lines.len() {
-            // Convert "export default () => {" to "function render(_ctx, $props, $emit, $attrs, $slots) {"
-            output.push_str("function render(_ctx, $props, $emit, $attrs, $slots) {\n");
-
-            // Copy function body (skip "export default () => {" and final "}")
-            for line in lines.iter().skip(func_start + 1) {
-                if *line == "}" {
-                    break;
-                }
-                output.push_str(line);
-                output.push('\n');
-            }
+    Ok(CompiledVaporTemplate {
+        code: output,
+        warnings: warnings.into_iter().map(|(_, err)| err).collect(),
+    })
+}
 
-            output.push_str("}\n");
-        }
+/// Location in the SFC of a template compiler error
+fn locate_template_error(template: &SfcTemplateBlock, loc: &SourceLocation) -> BlockLocation {
+    let position = |pos: &Position| {
+        let line = pos.line.max(1) as usize;
+        let column = if line == 1 {
+            template.loc.start_column + pos.column as usize - 1
+        } else {
+            pos.column as usize
+        };
+        (
+            template.loc.start + pos.offset as usize,
+            template.loc.start_line + line - 1,
+            column,
+        )
+    };
+    let (start, start_line, start_column) = position(&loc.start);
+    let (end, end_line, end_column) = position(&loc.end);
+    BlockLocation {
+        start,
+        end,
+        start_line,
+        start_column,
+        end_line,
+        end_column,
     }
+}
 
-    Ok(output)
+/// Split compiled Vapor template code into imports and the module-level
+/// declarations (templates and the render function)
+pub(crate) fn extract_vapor_template_parts(template_code: &str) -> (String, String) {
+    let mut imports = String::new();
+    let mut hoisted = String::new();
+    for line in template_code.lines() {
+        let target = if line.starts_with("import ") {
+            &mut imports
+        } else {
+            &mut hoisted
+        };
+        target.push_str(line);
+        target.push('\n');
+    }
+    (imports, hoisted)
 }
 
-/// Add scope ID to template string
+/// Add scope ID attribute to every element of a template declaration
 fn add_scope_id_to_template(template_line: &str, scope_id: &str) -> String {
-    // Find the template string content and add scope_id to the first element
-    if let Some(start) = template_line.find("\"<") {
-        if let Some(end) = template_line.rfind(">\"") {
-            let prefix = &template_line[..start + 2]; // up to and including "<"
-            let content = &template_line[start + 2..end + 1]; // element content
-            let suffix = &template_line[end + 1..]; // closing quote and paren
-
-            // Find end of first tag name
-            if let Some(tag_end) = content.find(|c: char| c.is_whitespace() || c == '>') {
-                let tag_name = &content[..tag_end];
-                let rest = &content[tag_end..];
-
-                // Insert scope_id attribute after tag name
-                return format!("{}{} {}{}{}", prefix, tag_name, scope_id, rest, suffix);
-            }
+    let (Some(start), Some(end)) = (template_line.find("_template(\""), template_line.rfind('"'))
+    else {
+        return template_line.to_string();
+    };
+    let start = start + "_template(\"".len();
+    if end < start {
+        return template_line.to_string();
+    }
+
+    let content = &template_line[start..end];
+    let mut scoped = String::with_capacity(content.len() + scope_id.len() * 2);
+    let mut rest = content;
+    while let Some(open) = rest.find('<') {
+        let (before, tag) = rest.split_at(open + 1);
+        scoped.push_str(before);
+        if !tag.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            rest = tag;
+            continue;
         }
+        let name_end = tag
+            .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .unwrap_or(tag.len());
+        scoped.push_str(&tag[..name_end]);
+        scoped.push(' ');
+        scoped.push_str(scope_id);
+        rest = &tag[name_end..];
     }
-    template_line.to_string()
+    scoped.push_str(rest);
+
+    format!(
+        "{}{}{}",
+        &template_line[..start],
+        scoped,
+        &template_line[end..]
+    )
 }
 
 /// Compact render body by removing unnecessary line breaks inside function calls and arrays
@@ -293,6 +361,8 @@ pub(crate) fn extract_template_parts_full(template_code: &str) -> (String, Strin
             hoisted.push('\n');
         } else if trimmed.starts_with("export function render(")
             || trimmed.starts_with("function render(")
+            || trimmed.starts_with("export function ssrRender(")
+            || trimmed.starts_with("function ssrRender(")
         {
             in_render = true;
             brace_depth = 0;
@@ -315,20 +385,38 @@ pub(crate) fn extract_template_parts_full(template_code: &str) -> (String, Strin
     (imports, hoisted, render_fn)
 }
 
+/// Parts of a compiled template module, inlined into `setup()`
+#[derive(Default)]
+pub(crate) struct TemplateModuleParts {
+    pub imports: String,
+    pub hoisted: String,
+    /// Component and directive resolution statements
+    pub preamble: String,
+    pub render_body: String,
+    /// Line and column in the template module each render body line is
+    /// copied from. Empty when the body was compacted.
+    pub render_origins: Vec<(u32, u32)>,
+}
+
 /// Extract imports, hoisted consts, preamble (component/directive resolution), and render body
 /// from compiled template code.
-/// Returns (imports, hoisted, preamble, render_body)
-pub(crate) fn extract_template_parts(template_code: &str) -> (String, String, String, String) {
+///
+/// With `compact`, line breaks inside the calls and arrays of a DOM render
+/// body are removed. Bodies are left as they are when a map of them is needed.
+pub(crate) fn extract_template_parts(template_code: &str, compact: bool) -> TemplateModuleParts {
     let mut imports = String::new();
     let mut hoisted = String::new();
     let mut preamble = String::new(); // Component/directive resolution statements
     let mut render_body = String::new();
+    let mut render_origins = Vec::new();
     let mut in_render = false;
+    let mut in_ssr_render = false;
+    let mut is_ssr = false;
     let mut in_return = false;
     let mut brace_depth = 0;
     let mut return_brace_depth = 0;
 
-    for line in template_code.lines() {
+    for (line_index, line) in (0u32..).zip(template_code.lines()) {
         let trimmed = line.trim();
 
         if trimmed.starts_with("import ") {
@@ -346,6 +434,32 @@ pub(crate) fn extract_template_parts(template_code: &str) -> (String, String, St
             // Count opening braces
             brace_depth += line.matches('{').count() as i32;
             brace_depth -= line.matches('}').count() as i32;
+        } else if trimmed.starts_with("export function ssrRender(")
+            || trimmed.starts_with("function ssrRender(")
+        {
+            in_ssr_render = true;
+            is_ssr = true;
+            brace_depth = line.matches('{').count() as i32 - line.matches('}').count() as i32;
+        } else if in_ssr_render {
+            brace_depth += line.matches('{').count() as i32;
+            brace_depth -= line.matches('}').count() as i32;
+
+            if brace_depth == 0 {
+                in_ssr_render = false;
+            } else if trimmed.starts_with("const _component_")
+                || trimmed.starts_with("const _directive_")
+            {
+                preamble.push_str(trimmed);
+                preamble.push('\n');
+            } else if !trimmed.is_empty() {
+                // SSR renders through statements, so the whole body is kept (dedented once)
+                if !render_body.is_empty() {
+                    render_body.push('\n');
+                }
+                let dedented = line.strip_prefix("  ").unwrap_or(line);
+                render_origins.push((line_index, (line.len() - dedented.len()) as u32));
+                render_body.push_str(dedented);
+            }
         } else if in_render {
             brace_depth += line.matches('{').count() as i32;
             brace_depth -= line.matches('}').count() as i32;
@@ -355,6 +469,7 @@ pub(crate) fn extract_template_parts(template_code: &str) -> (String, String, St
                 // Continue collecting return body
                 render_body.push('\n');
                 render_body.push_str(line);
+                render_origins.push((line_index, 0));
                 return_brace_depth += line.matches('(').count() as i32;
                 return_brace_depth -= line.matches(')').count() as i32;
 
@@ -369,6 +484,8 @@ pub(crate) fn extract_template_parts(template_code: &str) -> (String, String, St
                 }
             } else if let Some(stripped) = trimmed.strip_prefix("return ") {
                 render_body = stripped.to_string();
+                let column = line.len() - line.trim_start().len() + "return ".len();
+                render_origins = vec![(line_index, column as u32)];
                 // Count parentheses to handle multi-line return
                 return_brace_depth =
                     stripped.matches('(').count() as i32 - stripped.matches(')').count() as i32;
@@ -394,10 +511,56 @@ pub(crate) fn extract_template_parts(template_code: &str) -> (String, String, St
         }
     }
 
-    // Compact the render body to remove unnecessary line breaks inside function calls
-    let compacted = compact_render_body(&render_body);
+    // SSR bodies are statement lists and must keep their line breaks
+    if compact && !is_ssr {
+        // Compact the render body to remove unnecessary line breaks inside function calls
+        render_body = compact_render_body(&render_body);
+        render_origins.clear();
+    }
 
-    (imports, hoisted, preamble, compacted)
+    TemplateModuleParts {
+        imports,
+        hoisted,
+        preamble,
+        render_body,
+        render_origins,
+    }
+}
+
+/// Carry the codegen map of a compiled template over to the render body
+/// returned by [`extract_template_parts`], which must not be compacted
+pub(crate) fn render_body_map(
+    template_code: &str,
+    map: &str,
+    parts: &TemplateModuleParts,
+) -> Option<oxc_sourcemap::SourceMap> {
+    let map = oxc_sourcemap::SourceMap::from_json_string(map).ok()?;
+    // The map starts at the line of the render function signature
+    let signature = template_code
+        .find("function render(")
+        .or_else(|| template_code.find("function ssrRender("))?;
+    let first_line = template_code[..signature].matches('\n').count() as u32;
+
+    // Body line and column of each template module line copied into the body
+    let body_lines: std::collections::HashMap<u32, (u32, u32)> = (0u32..)
+        .zip(&parts.render_origins)
+        .map(|(body_line, &(line, column))| (line, (body_line, column)))
+        .collect();
+
+    let tokens = map
+        .get_tokens()
+        .filter_map(|token| {
+            let &(dst_line, column) = body_lines.get(&(first_line + token.get_dst_line()))?;
+            Some(Token {
+                dst_line,
+                dst_col: token.get_dst_col().checked_sub(column)?,
+                src_line: token.get_src_line(),
+                src_col: token.get_src_col(),
+            })
+        })
+        .collect();
+    let (source, content) = map.get_source_and_content(0).unwrap_or(("", ""));
+    Some(build_map(source, content, tokens))
 }
 
 #[cfg(test)]
@@ -411,6 +574,16 @@ mod tests {
         assert!(result.contains("data-v-abc123"));
     }
 
+    #[test]
+    fn test_add_scope_id_to_nested_template() {
+        let input = r#"const t0 = _template("<div><span> </span><br/></div>", true)"#;
+        let result = add_scope_id_to_template(input, "data-v-abc123");
+        assert_eq!(
+            result,
+            r#"const t0 = _template("<div data-v-abc123><span data-v-abc123> </span><br data-v-abc123/></div>", true)"#
+        );
+    }
+
     #[test]
     fn test_extract_template_parts_basic() {
         let template_code = r#"import { createVNode as _createVNode } from 'vue'
@@ -421,10 +594,10 @@ export function render(_ctx, _cache) {
   return _createVNode("div", _hoisted_1, "Hello")
 }"#;
 
-        let (imports, hoisted, _preamble, render_body) = extract_template_parts(template_code);
+        let parts = extract_template_parts(template_code, true);
 
-        assert!(imports.contains("import"));
-        assert!(hoisted.contains("_hoisted_1"));
-        assert!(render_body.contains("_createVNode"));
+        assert!(parts.imports.contains("import"));
+        assert!(parts.hoisted.contains("_hoisted_1"));
+        assert!(parts.render_body.contains("_createVNode"));
     }
 }
@@ -35,7 +35,7 @@ pub struct CssCompileOptions {
     #[serde(default)]
     pub source_map: bool,
 
-    /// Browser targets for autoprefixing
+    /// Browser targets for autoprefixing, Vite's default build target when not set
     #[serde(default)]
     pub targets: Option<CssTargets>,
 
@@ -64,7 +64,7 @@ pub struct CssTargets {
 
 #[cfg(feature = "native")]
 impl CssTargets {
-    fn to_lightningcss_targets(&self) -> Targets {
+    pub(crate) fn to_lightningcss_targets(&self) -> Targets {
         let mut browsers = Browsers::default();
 
         if let Some(v) = self.chrome {
@@ -90,6 +90,27 @@ impl CssTargets {
     }
 }
 
+/// Browsers targeted when none are configured: Vite's default build target.
+/// Without targets LightningCSS prints newer syntax, like media query ranges,
+/// that older browsers don't understand.
+#[cfg(feature = "native")]
+const DEFAULT_TARGETS: CssTargets = CssTargets {
+    chrome: Some(87),
+    firefox: Some(78),
+    safari: Some(14),
+    edge: Some(88),
+    ios: None,
+    android: None,
+};
+
+/// LightningCSS targets for the configured browsers, or [`DEFAULT_TARGETS`]
+#[cfg(feature = "native")]
+pub(crate) fn lightningcss_targets(targets: Option<&CssTargets>) -> Targets {
+    targets
+        .unwrap_or(&DEFAULT_TARGETS)
+        .to_lightningcss_targets()
+}
+
 /// Convert major version to LightningCSS format (major << 16)
 #[cfg(feature = "native")]
 fn version_to_u32(major: u32) -> u32 {
@@ -141,11 +162,7 @@ pub fn compile_css(css: &str, options: &CssCompileOptions) -> CssCompileResult {
     };
 
     // Apply targets for autoprefixing
-    let targets = options
-        .targets
-        .as_ref()
-        .map(|t| t.to_lightningcss_targets())
-        .unwrap_or_default();
+    let targets = lightningcss_targets(options.targets.as_ref());
 
     // Parse and process CSS
     let (code, errors) = compile_css_internal(scoped_css, filename, options.minify, targets);
@@ -432,7 +449,9 @@ fn apply_scoped_css<'a>(bump: &'a Bump, css: &str, scope_id: &str) -> &'a str {
     }
 
     // SAFETY: input is valid UTF-8, we only add ASCII bytes
-    unsafe { std::str::from_utf8_unchecked(bump.alloc_slice_copy(&output)) }
+    let scoped = unsafe { std::str::from_utf8_unchecked(&output) };
+    let id = scope_id.strip_prefix("data-v-").unwrap_or(scope_id);
+    bump.alloc_str(&crate::style::scope_keyframes(scoped, id))
 }
 
 /// Add scope attribute to a selector
@@ -0,0 +1,167 @@
+//! `v-bind()` in `<style>`.
+//!
+//! Every `v-bind(expr)` becomes a `var(--name)` in the CSS (see
+//! [`rewrite_css_vars`](crate::style::rewrite_css_vars)), and the component
+//! sets the matching custom properties at runtime with `useCssVars`. During
+//! SSR the values are rendered on the root elements instead.
+
+use vize_atelier_core::options::{
+    BaseTransformOptions, BindingMetadata as CoreBindingMetadata, TransformOptions,
+};
+use vize_atelier_core::transforms::rewrite_standalone_expression;
+
+use crate::style::gen_css_var_name;
+use crate::types::BindingMetadata;
+
+/// Code that sets the custom properties for `vars`
+pub struct CssVarsCode {
+    /// `_useCssVars(_ctx => ({ ... }))`
+    pub code: String,
+    /// Whether the expressions use `_unref`
+    pub used_unref: bool,
+}
+
+/// Generate the `_useCssVars()` call.
+///
+/// With script setup bindings the call is placed inside `setup()` and reads
+/// bindings directly, otherwise it reads them from `_ctx`.
+pub(crate) fn gen_css_vars_code(
+    vars: &[impl AsRef<str>],
+    id: &str,
+    is_prod: bool,
+    bindings: Option<&BindingMetadata>,
+) -> CssVarsCode {
+    let options = TransformOptions {
+        base: BaseTransformOptions {
+            prefix_identifiers: true,
+            inline: bindings.is_some(),
+            binding_metadata: bindings.map(CoreBindingMetadata::from),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let mut used_unref = false;
+    let mut entries = Vec::with_capacity(vars.len());
+    for var in vars {
+        let var = var.as_ref();
+        let (exp, unref) = rewrite_standalone_expression(var, options.clone());
+        used_unref |= unref;
+        entries.push(format!(
+            "  {}: ({})",
+            js_string(&gen_css_var_name(id, var, is_prod)),
+            exp
+        ));
+    }
+
+    CssVarsCode {
+        code: format!("_useCssVars(_ctx => ({{\n{}\n}}))", entries.join(",\n")),
+        used_unref,
+    }
+}
+
+/// Generate the `ssrCssVars` object passed to the SSR template compiler,
+/// which processes the expressions along with the template
+pub(crate) fn gen_ssr_css_vars(vars: &[impl AsRef<str>], id: &str, is_prod: bool) -> String {
+    let entries: Vec<String> = vars
+        .iter()
+        .map(|var| {
+            let var = var.as_ref();
+            let name = format!("--{}", gen_css_var_name(id, var, is_prod));
+            format!("{}: ({})", js_string(&name), var)
+        })
+        .collect();
+    format!("{{ {} }}", entries.join(", "))
+}
+
+/// Wrap `_sfc_main.setup` so the custom properties are set for components
+/// without script setup
+pub(crate) fn gen_normal_script_css_vars_code(css_vars_code: &str) -> String {
+    format!(
+        "const __injectCSSVars__ = () => {{\n{}\n}}\n\
+         const __setup__ = _sfc_main.setup\n\
+         _sfc_main.setup = __setup__\n  \
+         ? (props, ctx) => {{ __injectCSSVars__(); return __setup__(props, ctx) }}\n  \
+         : __injectCSSVars__\n",
+        css_vars_code
+    )
+}
+
+/// JSON-style double quoted string
+fn js_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::BindingType;
+
+    #[test]
+    fn test_css_vars_code_options_api() {
+        let result = gen_css_vars_code(&["color", "theme.size + 'px'"], "abc", false, None);
+        assert_eq!(
+            result.code,
+            "_useCssVars(_ctx => ({\n  \"abc-color\": (_ctx.color),\n  \"abc-theme.size + 'px'\": (_ctx.theme.size + 'px')\n}))"
+        );
+        assert!(!result.used_unref);
+    }
+
+    #[test]
+    fn test_css_vars_code_script_setup_bindings() {
+        let mut bindings = BindingMetadata::default();
+        bindings
+            .bindings
+            .insert("count".to_string(), BindingType::SetupRef);
+        bindings
+            .bindings
+            .insert("maybe".to_string(), BindingType::SetupMaybeRef);
+        bindings
+            .bindings
+            .insert("size".to_string(), BindingType::Props);
+        bindings
+            .bindings
+            .insert("theme".to_string(), BindingType::SetupReactiveConst);
+
+        let result = gen_css_vars_code(
+            &["count", "maybe", "size", "theme.color", "other"],
+            "abc",
+            false,
+            Some(&bindings),
+        );
+        assert!(
+            result.code.contains("\"abc-count\": (count.value)"),
+            "{}",
+            result.code
+        );
+        assert!(
+            result.code.contains("\"abc-maybe\": (_unref(maybe))"),
+            "{}",
+            result.code
+        );
+        assert!(
+            result.code.contains("\"abc-size\": (__props.size)"),
+            "{}",
+            result.code
+        );
+        assert!(
+            result.code.contains("\"abc-theme.color\": (theme.color)"),
+            "{}",
+            result.code
+        );
+        assert!(
+            result.code.contains("\"abc-other\": (_ctx.other)"),
+            "{}",
+            result.code
+        );
+        assert!(result.used_unref);
+    }
+
+    #[test]
+    fn test_ssr_css_vars() {
+        assert_eq!(
+            gen_ssr_css_vars(&["color", "a.b"], "abc", false),
+            "{ \"--abc-color\": (color), \"--abc-a.b\": (a.b) }"
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! Hot module replacement support.
+//!
+//! [`diff_descriptors`] compares two parses of the same SFC and reports the
+//! cheapest update that applies the change, following `@vitejs/plugin-vue`:
+//! style-only changes swap the CSS, template-only changes rerender, and
+//! anything else reloads the component.
+
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+use crate::types::*;
+
+/// How a changed SFC has to be hot updated, from cheapest to most expensive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HmrUpdateKind {
+    /// Nothing that affects the output changed
+    None,
+    /// Only the CSS changed
+    StyleOnly,
+    /// The render function changed, component state can be kept
+    Rerender,
+    /// The component has to be reloaded
+    Reload,
+}
+
+/// Differences between two descriptors of the same SFC
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DescriptorDiff {
+    /// Update needed to apply all the changes
+    pub kind: HmrUpdateKind,
+
+    /// Whether the template changed
+    pub template_changed: bool,
+
+    /// Whether `<script>` or `<script setup>` changed
+    pub script_changed: bool,
+
+    /// Indices of the style blocks in the next descriptor that changed or were added
+    pub styles_changed: Vec<usize>,
+
+    /// Whether a style block was removed
+    pub styles_removed: bool,
+}
+
+/// Compare two descriptors of the same SFC for a hot update
+pub fn diff_descriptors(prev: &SfcDescriptor, next: &SfcDescriptor) -> DescriptorDiff {
+    let template_changed =
+        prev.template.as_ref().map(template_parts) != next.template.as_ref().map(template_parts);
+    let script_changed = prev.script.as_ref().map(script_parts)
+        != next.script.as_ref().map(script_parts)
+        || prev.script_setup.as_ref().map(script_parts)
+            != next.script_setup.as_ref().map(script_parts);
+
+    let styles_changed: Vec<usize> = next
+        .styles
+        .iter()
+        .enumerate()
+        .filter(|(i, style)| {
+            prev.styles
+                .get(*i)
+                .is_none_or(|prev| style_parts(prev) != style_parts(style))
+        })
+        .map(|(i, _)| i)
+        .collect();
+    let styles_removed = next.styles.len() < prev.styles.len();
+
+    let mut kind = HmrUpdateKind::None;
+    if !styles_changed.is_empty() || styles_removed {
+        kind = HmrUpdateKind::StyleOnly;
+    }
+    // Scoped styles change the scope attributes the render function adds
+    if prev.styles.iter().any(|s| s.scoped) != next.styles.iter().any(|s| s.scoped) {
+        kind = kind.max(HmrUpdateKind::Rerender);
+    }
+    if template_changed {
+        // Templates compiled inline into `<script setup>` have no separate render function
+        kind = kind.max(if next.should_force_reload {
+            HmrUpdateKind::Reload
+        } else {
+            HmrUpdateKind::Rerender
+        });
+    }
+    // `v-bind()` variables and CSS modules are wired up in the component itself
+    let modules_changed = prev
+        .styles
+        .iter()
+        .filter_map(|s| s.module.as_ref())
+        .ne(next.styles.iter().filter_map(|s| s.module.as_ref()));
+    if script_changed
+        || prev.css_vars != next.css_vars
+        || modules_changed
+        || !same_custom_blocks(prev, next)
+    {
+        kind = HmrUpdateKind::Reload;
+    }
+
+    DescriptorDiff {
+        kind,
+        template_changed,
+        script_changed,
+        styles_changed,
+        styles_removed,
+    }
+}
+
+/// Parts of a block that affect the output: content, `lang`, `src` and attributes
+type BlockParts<'b, 'a> = (
+    &'b str,
+    Option<&'b str>,
+    Option<&'b str>,
+    &'b FxHashMap<Cow<'a, str>, Cow<'a, str>>,
+);
+
+fn template_parts<'b, 'a>(block: &'b SfcTemplateBlock<'a>) -> BlockParts<'b, 'a> {
+    (
+        &block.content,
+        block.lang.as_deref(),
+        block.src.as_deref(),
+        &block.attrs,
+    )
+}
+
+fn script_parts<'b, 'a>(block: &'b SfcScriptBlock<'a>) -> BlockParts<'b, 'a> {
+    (
+        &block.content,
+        block.lang.as_deref(),
+        block.src.as_deref(),
+        &block.attrs,
+    )
+}
+
+fn style_parts<'b, 'a>(block: &'b SfcStyleBlock<'a>) -> BlockParts<'b, 'a> {
+    (
+        &block.content,
+        block.lang.as_deref(),
+        block.src.as_deref(),
+        &block.attrs,
+    )
+}
+
+fn same_custom_blocks(prev: &SfcDescriptor, next: &SfcDescriptor) -> bool {
+    prev.custom_blocks.len() == next.custom_blocks.len()
+        && prev
+            .custom_blocks
+            .iter()
+            .zip(&next.custom_blocks)
+            .all(|(a, b)| {
+                a.block_type == b.block_type && a.content == b.content && a.attrs == b.attrs
+            })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_sfc, SfcParseOptions};
+
+    fn diff(prev: &str, next: &str) -> DescriptorDiff {
+        let prev = parse_sfc(prev, SfcParseOptions::default()).unwrap();
+        let next = parse_sfc(next, SfcParseOptions::default()).unwrap();
+        diff_descriptors(&prev, &next)
+    }
+
+    const SCRIPT: &str = "<script>\nexport default { data: () => ({ a: 1 }) }\n</script>\n";
+
+    #[test]
+    fn test_diff_unchanged() {
+        let source = format!("{}<template><div>{{{{ a }}}}</div></template>", SCRIPT);
+        let diff = diff(&source, &source);
+        assert_eq!(diff.kind, HmrUpdateKind::None);
+        assert!(!diff.template_changed && !diff.script_changed);
+    }
+
+    #[test]
+    fn test_diff_template_rerender() {
+        let diff = diff(
+            &format!("{}<template><div>{{{{ a }}}}</div></template>", SCRIPT),
+            &format!("{}<template><span>{{{{ a }}}}</span></template>", SCRIPT),
+        );
+        assert_eq!(diff.kind, HmrUpdateKind::Rerender);
+        assert!(diff.template_changed);
+    }
+
+    #[test]
+    fn test_diff_inline_template_reloads() {
+        let diff = diff(
+            "<script setup>const a = 1</script><template><div>{{ a }}</div></template>",
+            "<script setup>const a = 1</script><template><p>{{ a }}</p></template>",
+        );
+        assert_eq!(diff.kind, HmrUpdateKind::Reload);
+        assert!(!diff.script_changed);
+    }
+
+    #[test]
+    fn test_diff_script_reload() {
+        let diff = diff(
+            "<script setup>const a = 1</script><template><div /></template>",
+            "<script setup lang=\"ts\">const a = 1</script><template><div /></template>",
+        );
+        assert_eq!(diff.kind, HmrUpdateKind::Reload);
+        assert!(diff.script_changed);
+    }
+
+    #[test]
+    fn test_diff_style_only() {
+        let diff = diff(
+            "<template><div /></template><style>.a { color: red }</style>",
+            "<template><div /></template><style>.a { color: blue }</style><style>.b {}</style>",
+        );
+        assert_eq!(diff.kind, HmrUpdateKind::StyleOnly);
+        assert_eq!(diff.styles_changed, [0, 1]);
+        assert!(!diff.styles_removed);
+    }
+
+    #[test]
+    fn test_diff_style_scoped_and_vars() {
+        let prev = "<template><div /></template><style>.a { color: red }</style>";
+        let scoped = diff(
+            prev,
+            "<template><div /></template><style scoped>.a { color: red }</style>",
+        );
+        assert_eq!(scoped.kind, HmrUpdateKind::Rerender);
+
+        let vars = diff(
+            prev,
+            "<template><div /></template><style>.a { color: v-bind(c) }</style>",
+        );
+        assert_eq!(vars.kind, HmrUpdateKind::Reload);
+    }
+}
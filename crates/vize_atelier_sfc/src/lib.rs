@@ -8,6 +8,7 @@
 //! - `compile_template` - Template block compilation (DOM and Vapor)
 //! - `compile` - Main SFC compilation orchestration
 //! - `style` - Style block compilation with scoped CSS
+//! - `hmr` - Descriptor diffing for hot updates
 //! - `css` - Low-level CSS compilation with LightningCSS
 //!
 //! # Example
@@ -42,15 +43,19 @@ pub mod compile;
 pub mod compile_script;
 pub mod compile_template;
 pub mod css;
+mod css_vars;
+pub mod hmr;
 pub mod parse;
 pub mod rewrite_default;
 pub mod script;
+mod source_map;
 pub mod style;
 pub mod types;
 
 // Re-exports for public API
 pub use compile::*;
 pub use css::{compile_css, compile_style_block, CssCompileOptions, CssCompileResult, CssTargets};
+pub use hmr::{diff_descriptors, DescriptorDiff, HmrUpdateKind};
 pub use parse::*;
 pub use types::*;
 
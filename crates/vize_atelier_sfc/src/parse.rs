@@ -182,6 +182,19 @@ pub fn parse_sfc<'a>(
         }
     }
 
+    // The template is compiled inline into `<script setup>`, so template
+    // changes can't be hot updated by swapping the render function
+    descriptor.should_force_reload = descriptor.script_setup.is_some();
+
+    // Collect `v-bind()` expressions used in styles
+    for style in &descriptor.styles {
+        for var in crate::style::extract_css_vars(&style.content) {
+            if !descriptor.css_vars.iter().any(|v| *v == var) {
+                descriptor.css_vars.push(Cow::Owned(var));
+            }
+        }
+    }
+
     Ok(descriptor)
 }
 
@@ -16,6 +16,7 @@ mod define_props;
 mod define_props_destructure;
 mod define_slots;
 mod import_usage_check;
+mod resolve_type;
 mod utils;
 
 // Re-export main types
@@ -32,6 +33,8 @@ pub use import_usage_check::{
     is_used_in_template, resolve_template_used_identifiers, resolve_template_v_model_identifiers,
     TemplateUsedIdentifiers,
 };
+pub(crate) use resolve_type::normalize_path;
+pub use resolve_type::{TypeResolveOptions, TypeResolver};
 pub use utils::{
     get_escaped_prop_name, is_compiler_macro_line, is_valid_identifier, MacroCall,
     ScriptSetupMacros,
@@ -10,10 +10,13 @@ use oxc_ast::ast::{
 use oxc_parser::Parser;
 use oxc_span::{GetSpan, SourceType};
 
+use crate::compile_script::props::PropTypeInfo;
 use crate::types::{BindingMetadata, BindingType};
 
 use super::define_props_destructure::process_props_destructure;
-use super::{MacroCall, ScriptSetupMacros};
+use std::rc::Rc;
+
+use super::{MacroCall, ScriptSetupMacros, TypeResolveOptions, TypeResolver};
 
 /// Script compile context - holds all state during compilation
 #[derive(Debug)]
@@ -62,6 +65,20 @@ pub struct ScriptCompileContext {
     /// TypeScript type alias definitions (name -> body)
     /// Used to resolve type references in defineProps<TypeName>()
     pub type_aliases: std::collections::HashMap<String, String>,
+
+    // --- Type resolution ---
+    /// Resolves the types of defineProps<T>() and defineEmits<T>(), sharing the
+    /// files it reads with the other analyses of the component
+    pub type_resolver: Rc<TypeResolver>,
+
+    /// Props resolved from the defineProps type parameter
+    pub prop_types: Option<std::collections::HashMap<String, PropTypeInfo>>,
+
+    /// Event names resolved from the defineEmits type parameter
+    pub emit_names: Option<Vec<String>>,
+
+    /// Files types were imported from
+    pub type_dependencies: Vec<String>,
 }
 
 impl ScriptCompileContext {
@@ -82,6 +99,10 @@ impl ScriptCompileContext {
             emit_decl_id: None,
             interfaces: std::collections::HashMap::new(),
             type_aliases: std::collections::HashMap::new(),
+            type_resolver: Rc::new(TypeResolver::new(TypeResolveOptions::default())),
+            prop_types: None,
+            emit_names: None,
+            type_dependencies: Vec::new(),
         }
     }
 
@@ -399,7 +420,14 @@ impl ScriptCompileContext {
                 self.extract_props_bindings(&call);
                 self.macros.define_props = Some(call);
             }
-            "defineEmits" => self.macros.define_emits = Some(call),
+            "defineEmits" => {
+                if let Some(ref type_args) = call.type_args {
+                    let resolver = self.type_resolver.clone();
+                    self.emit_names = resolver.resolve_emits(&self.source, type_args);
+                    self.add_type_dependencies(&resolver);
+                }
+                self.macros.define_emits = Some(call);
+            }
             "defineExpose" => self.macros.define_expose = Some(call),
             "defineOptions" => self.macros.define_options = Some(call),
             "defineSlots" => self.macros.define_slots = Some(call),
@@ -417,7 +445,20 @@ impl ScriptCompileContext {
     fn extract_props_bindings(&mut self, call: &MacroCall) {
         // Handle type-based defineProps: defineProps<{ msg: string }>()
         if let Some(ref type_args) = call.type_args {
-            self.extract_props_from_type_args(type_args);
+            let resolver = self.type_resolver.clone();
+            self.prop_types = resolver.resolve_props(&self.source, type_args);
+            self.add_type_dependencies(&resolver);
+            match &self.prop_types {
+                Some(props) => {
+                    for name in props.keys() {
+                        self.bindings
+                            .bindings
+                            .insert(name.clone(), BindingType::Props);
+                    }
+                }
+                // Fall back to reading the type's source text
+                None => self.extract_props_from_type_args(type_args),
+            }
             return;
         }
 
@@ -464,6 +505,14 @@ impl ScriptCompileContext {
         }
     }
 
+    fn add_type_dependencies(&mut self, resolver: &TypeResolver) {
+        for dependency in resolver.dependencies() {
+            if !self.type_dependencies.contains(&dependency) {
+                self.type_dependencies.push(dependency);
+            }
+        }
+    }
+
     /// Extract prop names from TypeScript type arguments
     fn extract_props_from_type_args(&mut self, type_args: &str) {
         let content = type_args.trim();
@@ -607,7 +656,7 @@ fn infer_binding_type(init: &Expression<'_>, kind: VariableDeclarationKind) -> B
                 // Individual prop names are registered separately as Props bindings
                 "defineProps" => return BindingType::SetupReactiveConst,
                 "ref" | "shallowRef" | "customRef" | "toRef" => return BindingType::SetupRef,
-                "computed" | "toRefs" => return BindingType::SetupRef,
+                "computed" | "toRefs" | "defineModel" => return BindingType::SetupRef,
                 "reactive" | "shallowReactive" => return BindingType::SetupReactiveConst,
                 _ => {}
             }
@@ -0,0 +1,1826 @@
+//! Type resolution for type-based `defineProps` and `defineEmits`.
+//!
+//! Follows `resolveType` in `@vue/compiler-sfc`: the macro's type argument is
+//! resolved on the OXC AST to its properties, going through interfaces and
+//! their `extends`, intersections, unions, mapped and indexed access types,
+//! utility types (`Partial`, `Required`, `Pick`, `Omit`, `Record`...), enums
+//! and generics, including the `generic` type parameters of the component,
+//! which have no runtime type. Imported types are loaded through a [`FileLoader`], from
+//! relative paths or the `paths` and `baseUrl` of the closest `tsconfig.json`.
+
+use std::cell::{OnceCell, RefCell};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use oxc_allocator::Allocator;
+use oxc_ast::ast::*;
+use oxc_ast::Visit;
+use oxc_parser::Parser;
+use oxc_span::{GetSpan, SourceType};
+
+use crate::compile_script::props::PropTypeInfo;
+use crate::types::{FileLoader, FsFileLoader};
+
+/// Runtime type of values the resolver can't infer
+const UNKNOWN: &str = "Unknown";
+
+/// Name of the alias the macro's type argument is declared as
+const MACRO_TYPE: &str = "__VizeMacroType";
+
+/// Limit on nested references, guarding against recursive types
+const MAX_DEPTH: usize = 32;
+
+/// Types that exist at runtime and can be used as a prop `type` as is
+const RUNTIME_CONSTRUCTORS: &[&str] = &[
+    "String",
+    "Number",
+    "Boolean",
+    "Symbol",
+    "BigInt",
+    "Object",
+    "Array",
+    "Function",
+    "Date",
+    "RegExp",
+    "Error",
+    "Map",
+    "Set",
+    "WeakMap",
+    "WeakSet",
+    "Promise",
+    "ArrayBuffer",
+    "DataView",
+    "Int8Array",
+    "Uint8Array",
+    "Int16Array",
+    "Uint16Array",
+    "Int32Array",
+    "Uint32Array",
+    "Float32Array",
+    "Float64Array",
+    "BigInt64Array",
+    "BigUint64Array",
+    "URL",
+    "URLSearchParams",
+    "FormData",
+    "Blob",
+    "File",
+];
+
+/// Utility types whose properties are computed from their arguments
+const UTILITY_TYPES: &[&str] = &[
+    "Partial",
+    "Required",
+    "Readonly",
+    "NonNullable",
+    "Pick",
+    "Omit",
+    "Record",
+];
+
+/// Options for resolving macro types
+#[derive(Debug, Clone, Default)]
+pub struct TypeResolveOptions {
+    /// Path of the SFC, relative imports and `tsconfig.json` are looked up from it
+    pub filename: String,
+
+    /// Loader for imported files, reading from the file system when unset
+    pub fs: Option<Arc<dyn FileLoader>>,
+
+    /// Type parameters from the `generic` attribute of `<script setup>`
+    pub generic: Option<String>,
+
+    /// Content of the normal `<script>` block, whose types are in scope of the macros
+    pub script: Option<String>,
+}
+
+/// Resolves macro type arguments, caching the files it reads.
+/// One resolver is shared by the analyses of a component.
+#[derive(Debug)]
+pub struct TypeResolver {
+    options: TypeResolveOptions,
+    /// File contents by path, `None` if the file couldn't be read
+    files: RefCell<HashMap<PathBuf, Option<String>>>,
+    /// Files types were imported from
+    dependencies: RefCell<Vec<PathBuf>>,
+    tsconfig: OnceCell<Option<TsConfig>>,
+}
+
+/// Module resolution settings of a `tsconfig.json`
+#[derive(Debug, Clone, Default)]
+struct TsConfig {
+    base_url: Option<PathBuf>,
+    /// `paths` patterns with their targets, most specific first
+    paths: Vec<(String, Vec<String>)>,
+    /// Directory `paths` targets are relative to without a `baseUrl`
+    paths_dir: PathBuf,
+}
+
+impl TypeResolver {
+    /// Create a resolver
+    pub fn new(options: TypeResolveOptions) -> Self {
+        Self {
+            options,
+            files: RefCell::new(HashMap::new()),
+            dependencies: RefCell::new(Vec::new()),
+            tsconfig: OnceCell::new(),
+        }
+    }
+
+    /// Options the resolver was created with
+    pub fn options(&self) -> &TypeResolveOptions {
+        &self.options
+    }
+
+    /// Resolve the props of `defineProps<type_args>()` in the script `source`.
+    /// Returns `None` if the type doesn't resolve to an object type.
+    pub fn resolve_props(
+        &self,
+        source: &str,
+        type_args: &str,
+    ) -> Option<HashMap<String, PropTypeInfo>> {
+        let allocator = Allocator::default();
+        let session = Session::new(self, &allocator);
+        let (ty, env) = session.macro_type(source, type_args)?;
+        let elements = session.elements(ty, &env, 0)?;
+        Some(
+            elements
+                .props
+                .iter()
+                .map(|(key, element)| (key.clone(), session.prop_info(element)))
+                .collect(),
+        )
+    }
+
+    /// Resolve the event names of `defineEmits<type_args>()` in the script `source`,
+    /// from call signatures or from property keys
+    pub fn resolve_emits(&self, source: &str, type_args: &str) -> Option<Vec<String>> {
+        let allocator = Allocator::default();
+        let session = Session::new(self, &allocator);
+        let (ty, env) = session.macro_type(source, type_args)?;
+        let mut names = Vec::new();
+        for name in session.emit_names(ty, &env, 0)? {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        Some(names)
+    }
+
+    /// Files types were imported from so far
+    pub fn dependencies(&self) -> Vec<String> {
+        self.dependencies
+            .borrow()
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    fn read(&self, path: &Path) -> Option<String> {
+        if let Some(contents) = self.files.borrow().get(path) {
+            return contents.clone();
+        }
+        let loader: &dyn FileLoader = self.options.fs.as_deref().unwrap_or(&FsFileLoader);
+        let contents = loader.read_file(path).ok();
+        self.files
+            .borrow_mut()
+            .insert(path.to_path_buf(), contents.clone());
+        contents
+    }
+
+    /// Resolve an import specifier to a file
+    fn resolve_module(&self, specifier: &str, from: Option<&Path>) -> Option<PathBuf> {
+        if specifier.starts_with('.') {
+            let from = from.unwrap_or(Path::new(&self.options.filename));
+            let dir = from.parent().unwrap_or(Path::new(""));
+            return self.resolve_file(&dir.join(specifier));
+        }
+        if specifier.starts_with('/') {
+            return self.resolve_file(Path::new(specifier));
+        }
+
+        let config = self.tsconfig()?;
+        let base = config.base_url.as_ref().unwrap_or(&config.paths_dir);
+        for (pattern, targets) in &config.paths {
+            let matched = match pattern.split_once('*') {
+                Some((prefix, suffix)) => specifier
+                    .strip_prefix(prefix)
+                    .and_then(|rest| rest.strip_suffix(suffix)),
+                None => (pattern == specifier).then_some(""),
+            };
+            if let Some(matched) = matched {
+                for target in targets {
+                    if let Some(file) = self.resolve_file(&base.join(target.replace('*', matched)))
+                    {
+                        return Some(file);
+                    }
+                }
+            }
+        }
+        self.resolve_file(&config.base_url.as_ref()?.join(specifier))
+    }
+
+    /// Find the file a module path refers to, trying TypeScript extensions and index files
+    fn resolve_file(&self, path: &Path) -> Option<PathBuf> {
+        let path = normalize_path(path);
+        let name = path.to_string_lossy();
+        let mut candidates = Vec::new();
+        if [".ts", ".tsx", ".mts", ".cts", ".vue"]
+            .iter()
+            .any(|ext| name.ends_with(ext))
+        {
+            candidates.push(path.clone());
+        }
+        // `import './foo.js'` refers to `foo.ts` in TypeScript
+        if let Some(stem) = [".js", ".mjs", ".cjs", ".jsx"]
+            .iter()
+            .find_map(|ext| name.strip_suffix(ext))
+        {
+            candidates.extend(
+                [".ts", ".tsx", ".d.ts"]
+                    .iter()
+                    .map(|ext| PathBuf::from(format!("{}{}", stem, ext))),
+            );
+        }
+        candidates.extend(
+            [".ts", ".tsx", ".d.ts"]
+                .iter()
+                .map(|ext| PathBuf::from(format!("{}{}", name, ext))),
+        );
+        candidates.extend(
+            ["index.ts", "index.tsx", "index.d.ts"]
+                .iter()
+                .map(|index| path.join(index)),
+        );
+        candidates
+            .into_iter()
+            .find(|candidate| self.read(candidate).is_some())
+    }
+
+    /// The closest `tsconfig.json` to the SFC
+    fn tsconfig(&self) -> Option<&TsConfig> {
+        self.tsconfig
+            .get_or_init(|| {
+                let mut dir = Path::new(&self.options.filename).parent();
+                while let Some(current) = dir {
+                    let path = current.join("tsconfig.json");
+                    if self.read(&path).is_some() {
+                        return self.load_tsconfig(&path, 0);
+                    }
+                    dir = current.parent();
+                }
+                None
+            })
+            .as_ref()
+    }
+
+    fn load_tsconfig(&self, path: &Path, depth: usize) -> Option<TsConfig> {
+        let json: serde_json::Value =
+            serde_json::from_str(&strip_json_comments(&self.read(path)?)).ok()?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+
+        let mut config = match json.get("extends").and_then(|extends| extends.as_str()) {
+            Some(extends) if extends.starts_with('.') && depth < 8 => {
+                let mut base = normalize_path(&dir.join(extends));
+                if !extends.ends_with(".json") {
+                    base.as_mut_os_string().push(".json");
+                }
+                self.load_tsconfig(&base, depth + 1).unwrap_or_default()
+            }
+            _ => TsConfig::default(),
+        };
+
+        if let Some(options) = json.get("compilerOptions") {
+            if let Some(base_url) = options.get("baseUrl").and_then(|base| base.as_str()) {
+                config.base_url = Some(normalize_path(&dir.join(base_url)));
+            }
+            if let Some(paths) = options.get("paths").and_then(|paths| paths.as_object()) {
+                config.paths = paths
+                    .iter()
+                    .map(|(pattern, targets)| {
+                        let targets = targets
+                            .as_array()
+                            .into_iter()
+                            .flatten()
+                            .filter_map(|target| target.as_str().map(String::from))
+                            .collect();
+                        (pattern.clone(), targets)
+                    })
+                    .collect();
+                // Exact patterns first, then the longest prefix wins
+                config.paths.sort_by_key(|(pattern, _)| {
+                    std::cmp::Reverse(pattern.find('*').unwrap_or(usize::MAX))
+                });
+                config.paths_dir = dir.to_path_buf();
+            }
+        }
+        Some(config)
+    }
+}
+
+/// Lexically normalize `.` and `..` components
+pub(crate) fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Strip comments and trailing commas, which `tsconfig.json` allows
+fn strip_json_comments(source: &str) -> String {
+    let mut output = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            output.push(c);
+            if c == '\\' {
+                output.extend(chars.next());
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match c {
+            '"' => {
+                in_string = true;
+                output.push(c);
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                if chars.by_ref().any(|c| c == '\n') {
+                    output.push('\n');
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = ' ';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            ',' if matches!(chars.clone().find(|c| !c.is_whitespace()), Some('}' | ']')) => {}
+            _ => output.push(c),
+        }
+    }
+    output
+}
+
+/// Declarations of a file, by local name
+struct TypeScope<'a> {
+    /// File the scope was loaded from, `None` for the SFC script
+    path: Option<PathBuf>,
+    source: &'a str,
+    decls: HashMap<&'a str, TypeDecl<'a>>,
+    imports: HashMap<&'a str, Import<'a>>,
+    exports: HashMap<&'a str, Export<'a>>,
+    /// Sources of `export * from`
+    star_exports: Vec<&'a str>,
+}
+
+#[derive(Clone, Copy)]
+enum TypeDecl<'a> {
+    Interface(&'a TSInterfaceDeclaration<'a>),
+    Alias(&'a TSTypeAliasDeclaration<'a>),
+    Enum(&'a TSEnumDeclaration<'a>),
+    Class,
+}
+
+#[derive(Clone, Copy)]
+struct Import<'a> {
+    source: &'a str,
+    /// Imported name, `None` for a namespace import
+    imported: Option<&'a str>,
+}
+
+#[derive(Clone, Copy)]
+enum Export<'a> {
+    Local(&'a str),
+    /// Re-export, of the whole module as a namespace when `name` is `None`
+    From {
+        source: &'a str,
+        name: Option<&'a str>,
+    },
+}
+
+impl<'a> TypeScope<'a> {
+    fn new(program: &'a Program<'a>, source: &'a str, path: Option<PathBuf>) -> Self {
+        let mut scope = Self {
+            path,
+            source,
+            decls: HashMap::new(),
+            imports: HashMap::new(),
+            exports: HashMap::new(),
+            star_exports: Vec::new(),
+        };
+
+        for stmt in &program.body {
+            match stmt {
+                Statement::ImportDeclaration(import) => {
+                    let source = import.source.value.as_str();
+                    for specifier in import.specifiers.iter().flatten() {
+                        let (local, imported) = match specifier {
+                            ImportDeclarationSpecifier::ImportSpecifier(s) => {
+                                (s.local.name.as_str(), Some(s.imported.name().as_str()))
+                            }
+                            ImportDeclarationSpecifier::ImportDefaultSpecifier(s) => {
+                                (s.local.name.as_str(), Some("default"))
+                            }
+                            ImportDeclarationSpecifier::ImportNamespaceSpecifier(s) => {
+                                (s.local.name.as_str(), None)
+                            }
+                        };
+                        scope.imports.insert(local, Import { source, imported });
+                    }
+                }
+                Statement::ExportNamedDeclaration(export) => {
+                    if let Some(name) = export
+                        .declaration
+                        .as_ref()
+                        .and_then(|decl| scope.add_declaration(decl))
+                    {
+                        scope.exports.insert(name, Export::Local(name));
+                    }
+                    for specifier in &export.specifiers {
+                        let local = specifier.local.name().as_str();
+                        let exported = match &export.source {
+                            Some(source) => Export::From {
+                                source: source.value.as_str(),
+                                name: Some(local),
+                            },
+                            None => Export::Local(local),
+                        };
+                        scope
+                            .exports
+                            .insert(specifier.exported.name().as_str(), exported);
+                    }
+                }
+                Statement::ExportAllDeclaration(export) => {
+                    let source = export.source.value.as_str();
+                    match &export.exported {
+                        Some(name) => {
+                            scope
+                                .exports
+                                .insert(name.name().as_str(), Export::From { source, name: None });
+                        }
+                        None => scope.star_exports.push(source),
+                    }
+                }
+                Statement::ExportDefaultDeclaration(export) => {
+                    if let ExportDefaultDeclarationKind::TSInterfaceDeclaration(iface) =
+                        &export.declaration
+                    {
+                        let name = iface.id.name.as_str();
+                        scope.decls.insert(name, TypeDecl::Interface(iface));
+                        scope.exports.insert("default", Export::Local(name));
+                    }
+                }
+                Statement::TSInterfaceDeclaration(iface) => {
+                    scope
+                        .decls
+                        .insert(iface.id.name.as_str(), TypeDecl::Interface(iface));
+                }
+                Statement::TSTypeAliasDeclaration(alias) => {
+                    scope
+                        .decls
+                        .insert(alias.id.name.as_str(), TypeDecl::Alias(alias));
+                }
+                Statement::TSEnumDeclaration(decl) => {
+                    scope
+                        .decls
+                        .insert(decl.id.name.as_str(), TypeDecl::Enum(decl));
+                }
+                Statement::ClassDeclaration(class) => {
+                    if let Some(id) = &class.id {
+                        scope.decls.insert(id.name.as_str(), TypeDecl::Class);
+                    }
+                }
+                _ => {}
+            }
+        }
+        scope
+    }
+
+    fn add_declaration(&mut self, decl: &'a Declaration<'a>) -> Option<&'a str> {
+        let (name, decl) = match decl {
+            Declaration::TSInterfaceDeclaration(iface) => {
+                (iface.id.name.as_str(), TypeDecl::Interface(iface))
+            }
+            Declaration::TSTypeAliasDeclaration(alias) => {
+                (alias.id.name.as_str(), TypeDecl::Alias(alias))
+            }
+            Declaration::TSEnumDeclaration(decl) => (decl.id.name.as_str(), TypeDecl::Enum(decl)),
+            Declaration::ClassDeclaration(class) => {
+                (class.id.as_ref()?.name.as_str(), TypeDecl::Class)
+            }
+            _ => return None,
+        };
+        self.decls.insert(name, decl);
+        Some(name)
+    }
+}
+
+/// Scope a type is resolved in, with the type parameters bound to arguments
+#[derive(Clone)]
+struct Env<'a> {
+    scope: Rc<TypeScope<'a>>,
+    /// `None` for parameters without an argument, like the component's `generic` ones
+    params: Rc<HashMap<&'a str, Option<(&'a TSType<'a>, Env<'a>)>>>,
+}
+
+impl<'a> Env<'a> {
+    fn new(scope: Rc<TypeScope<'a>>) -> Self {
+        Self {
+            scope,
+            params: Rc::default(),
+        }
+    }
+}
+
+/// One of the types a property can have
+#[derive(Clone)]
+enum PropType<'a> {
+    Type(&'a TSType<'a>, Env<'a>),
+    Function,
+    Unknown,
+}
+
+#[derive(Clone)]
+struct Element<'a> {
+    types: Vec<PropType<'a>>,
+    optional: bool,
+}
+
+/// Properties and call signatures of an object type
+#[derive(Default)]
+struct Elements<'a> {
+    props: Vec<(String, Element<'a>)>,
+    calls: Vec<(&'a FormalParameters<'a>, Env<'a>)>,
+}
+
+impl<'a> Elements<'a> {
+    fn get(&self, key: &str) -> Option<&Element<'a>> {
+        self.props
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, element)| element)
+    }
+
+    /// Add a property, replacing one with the same key
+    fn insert(&mut self, key: String, element: Element<'a>) {
+        match self.props.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, existing)) => *existing = element,
+            None => self.props.push((key, element)),
+        }
+    }
+
+    /// Add the members of `other`, which override these
+    fn extend(&mut self, other: Elements<'a>) {
+        for (key, element) in other.props {
+            self.insert(key, element);
+        }
+        self.calls.extend(other.calls);
+    }
+
+    /// Combine with another branch of a union or intersection
+    fn merge(&mut self, other: Elements<'a>, union: bool) {
+        for (key, element) in other.props {
+            match self.props.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, existing)) => {
+                    existing.types.extend(element.types);
+                    existing.optional = if union {
+                        existing.optional || element.optional
+                    } else {
+                        existing.optional && element.optional
+                    };
+                }
+                None => self.props.push((key, element)),
+            }
+        }
+        self.calls.extend(other.calls);
+    }
+}
+
+/// Parsed files of one resolution, living in its allocator
+struct Session<'r, 'a> {
+    resolver: &'r TypeResolver,
+    allocator: &'a Allocator,
+    scopes: RefCell<HashMap<PathBuf, Rc<TypeScope<'a>>>>,
+}
+
+fn type_args<'a>(params: Option<&'a TSTypeParameterInstantiation<'a>>) -> &'a [TSType<'a>] {
+    params.map_or(&[], |params| &params.params[..])
+}
+
+impl<'r, 'a> Session<'r, 'a> {
+    fn new(resolver: &'r TypeResolver, allocator: &'a Allocator) -> Self {
+        Self {
+            resolver,
+            allocator,
+            scopes: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Parse the script with the macro's type argument declared as an alias,
+    /// after the normal script so that its declarations are in scope
+    fn macro_type(&self, source: &str, type_args: &str) -> Option<(&'a TSType<'a>, Env<'a>)> {
+        let generic = match &self.resolver.options.generic {
+            Some(generic) => format!("<{}>", generic),
+            None => String::new(),
+        };
+        let script = self.resolver.options.script.as_deref().unwrap_or_default();
+        let source = self.allocator.alloc_str(&format!(
+            "{}\n;{}\n;type {}{} = {};\n",
+            script, source, MACRO_TYPE, generic, type_args
+        ));
+        let scope = self.parse_scope(source, None)?;
+        match *scope.decls.get(MACRO_TYPE)? {
+            TypeDecl::Alias(alias) => {
+                // Generic parameters stay unbound, even with a default
+                let params = alias
+                    .type_parameters
+                    .iter()
+                    .flat_map(|params| params.params.iter())
+                    .map(|param| (param.name.name.as_str(), None))
+                    .collect();
+                let env = Env {
+                    scope: scope.clone(),
+                    params: Rc::new(params),
+                };
+                Some((&alias.type_annotation, env))
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_scope(&self, source: &'a str, path: Option<PathBuf>) -> Option<Rc<TypeScope<'a>>> {
+        let source_type = path
+            .as_deref()
+            .and_then(|path| SourceType::from_path(path).ok())
+            .filter(|source_type| source_type.is_typescript())
+            .unwrap_or(SourceType::ts());
+        let ret = Parser::new(self.allocator, source, source_type).parse();
+        if ret.panicked {
+            return None;
+        }
+        let program = self.allocator.alloc(ret.program);
+        Some(Rc::new(TypeScope::new(program, source, path)))
+    }
+
+    fn load_module(&self, specifier: &str, from: &TypeScope<'a>) -> Option<Rc<TypeScope<'a>>> {
+        let path = self
+            .resolver
+            .resolve_module(specifier, from.path.as_deref())?;
+        if let Some(scope) = self.scopes.borrow().get(&path) {
+            return Some(scope.clone());
+        }
+
+        let contents = self.resolver.read(&path)?;
+        let contents = if path.extension().is_some_and(|ext| ext == "vue") {
+            vue_script_content(&contents)?
+        } else {
+            contents
+        };
+        let scope = self.parse_scope(self.allocator.alloc_str(&contents), Some(path.clone()))?;
+        let mut dependencies = self.resolver.dependencies.borrow_mut();
+        if !dependencies.contains(&path) {
+            dependencies.push(path.clone());
+        }
+        self.scopes.borrow_mut().insert(path, scope.clone());
+        Some(scope)
+    }
+
+    /// Find a declaration by its local name
+    fn lookup_name(&self, name: &str, env: &Env<'a>) -> Option<(TypeDecl<'a>, Env<'a>)> {
+        if let Some(decl) = env.scope.decls.get(name) {
+            return Some((*decl, Env::new(env.scope.clone())));
+        }
+        let import = env.scope.imports.get(name)?;
+        let scope = self.load_module(import.source, &env.scope)?;
+        self.export(&scope, import.imported?, 0)
+    }
+
+    /// Find `ns.member` where `ns` is a namespace import
+    fn lookup_member(
+        &self,
+        ns: &str,
+        member: &str,
+        env: &Env<'a>,
+    ) -> Option<(TypeDecl<'a>, Env<'a>)> {
+        let import = env.scope.imports.get(ns)?;
+        if import.imported.is_some() {
+            return None;
+        }
+        let scope = self.load_module(import.source, &env.scope)?;
+        self.export(&scope, member, 0)
+    }
+
+    fn lookup_ref(
+        &self,
+        name: &str,
+        member: Option<&str>,
+        env: &Env<'a>,
+    ) -> Option<(TypeDecl<'a>, Env<'a>)> {
+        match member {
+            Some(member) => self.lookup_member(name, member, env),
+            None => self.lookup_name(name, env),
+        }
+    }
+
+    fn lookup(&self, name: &'a TSTypeName<'a>, env: &Env<'a>) -> Option<(TypeDecl<'a>, Env<'a>)> {
+        match name {
+            TSTypeName::IdentifierReference(id) => self.lookup_name(id.name.as_str(), env),
+            TSTypeName::QualifiedName(name) => match &name.left {
+                TSTypeName::IdentifierReference(ns) => {
+                    self.lookup_member(ns.name.as_str(), name.right.name.as_str(), env)
+                }
+                TSTypeName::QualifiedName(_) => None,
+            },
+        }
+    }
+
+    /// Find the declaration a module exports as `name`
+    fn export(
+        &self,
+        scope: &Rc<TypeScope<'a>>,
+        name: &str,
+        depth: usize,
+    ) -> Option<(TypeDecl<'a>, Env<'a>)> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        match scope.exports.get(name) {
+            Some(Export::Local(local)) => self.lookup_name(local, &Env::new(scope.clone())),
+            Some(Export::From {
+                source,
+                name: Some(name),
+            }) => {
+                let from = self.load_module(source, scope)?;
+                self.export(&from, name, depth + 1)
+            }
+            Some(Export::From { name: None, .. }) => None,
+            None => scope.star_exports.iter().find_map(|source| {
+                let from = self.load_module(source, scope)?;
+                self.export(&from, name, depth + 1)
+            }),
+        }
+    }
+
+    fn import_type(
+        &self,
+        import: &'a TSImportType<'a>,
+        env: &Env<'a>,
+    ) -> Option<(TypeDecl<'a>, Env<'a>)> {
+        let TSType::TSLiteralType(literal) = &import.parameter else {
+            return None;
+        };
+        let TSLiteral::StringLiteral(source) = &literal.literal else {
+            return None;
+        };
+        let TSTypeName::IdentifierReference(name) = import.qualifier.as_ref()? else {
+            return None;
+        };
+        let scope = self.load_module(source.value.as_str(), &env.scope)?;
+        self.export(&scope, name.name.as_str(), 0)
+    }
+
+    /// Bind the type parameters of a declaration to the arguments of a reference
+    fn bind(
+        &self,
+        params: Option<&'a TSTypeParameterDeclaration<'a>>,
+        args: &'a [TSType<'a>],
+        args_env: &Env<'a>,
+        scope: &Rc<TypeScope<'a>>,
+    ) -> Env<'a> {
+        let mut bound = HashMap::new();
+        for (i, param) in params.iter().flat_map(|p| p.params.iter()).enumerate() {
+            let name = param.name.name.as_str();
+            let arg = match (args.get(i), &param.default) {
+                (Some(arg), _) => Some((arg, args_env.clone())),
+                (None, Some(default)) => Some((default, Env::new(scope.clone()))),
+                _ => None,
+            };
+            bound.insert(name, arg);
+        }
+        Env {
+            scope: scope.clone(),
+            params: Rc::new(bound),
+        }
+    }
+
+    fn is_local(&self, name: &str, env: &Env<'a>) -> bool {
+        env.params.contains_key(name)
+            || env.scope.decls.contains_key(name)
+            || env.scope.imports.contains_key(name)
+    }
+
+    /// Resolve a type to its properties
+    fn elements(&self, ty: &'a TSType<'a>, env: &Env<'a>, depth: usize) -> Option<Elements<'a>> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        match ty {
+            TSType::TSTypeLiteral(literal) => Some(self.signature_elements(&literal.members, env)),
+            TSType::TSParenthesizedType(ty) => self.elements(&ty.type_annotation, env, depth + 1),
+            TSType::TSIntersectionType(ty) => {
+                let mut elements = Elements::default();
+                for ty in &ty.types {
+                    elements.merge(self.elements(ty, env, depth + 1)?, false);
+                }
+                Some(elements)
+            }
+            TSType::TSUnionType(ty) => {
+                let mut elements = Elements::default();
+                for ty in &ty.types {
+                    elements.merge(self.elements(ty, env, depth + 1)?, true);
+                }
+                Some(elements)
+            }
+            TSType::TSMappedType(mapped) => {
+                let keys = self.keys(mapped.type_parameter.constraint.as_ref()?, env, depth + 1)?;
+                let optional = matches!(
+                    mapped.optional,
+                    TSMappedTypeModifierOperator::True | TSMappedTypeModifierOperator::Plus
+                );
+                let types = match &mapped.type_annotation {
+                    Some(ty) => vec![PropType::Type(ty, env.clone())],
+                    None => vec![PropType::Unknown],
+                };
+                Some(Elements {
+                    props: keys
+                        .into_iter()
+                        .map(|key| {
+                            let element = Element {
+                                types: types.clone(),
+                                optional,
+                            };
+                            (key, element)
+                        })
+                        .collect(),
+                    calls: Vec::new(),
+                })
+            }
+            TSType::TSIndexedAccessType(ty) => {
+                let object = self.elements(&ty.object_type, env, depth + 1)?;
+                let mut elements = Elements::default();
+                for key in self.keys(&ty.index_type, env, depth + 1)? {
+                    for ty in &object.get(&key)?.types {
+                        let PropType::Type(ty, env) = ty else {
+                            return None;
+                        };
+                        elements.merge(self.elements(ty, env, depth + 1)?, true);
+                    }
+                }
+                Some(elements)
+            }
+            TSType::TSTypeReference(reference) => {
+                let args = type_args(reference.type_parameters.as_deref());
+                match &reference.type_name {
+                    TSTypeName::IdentifierReference(id) => {
+                        self.reference_elements(id.name.as_str(), None, args, env, depth)
+                    }
+                    TSTypeName::QualifiedName(name) => match &name.left {
+                        TSTypeName::IdentifierReference(ns) => self.reference_elements(
+                            ns.name.as_str(),
+                            Some(name.right.name.as_str()),
+                            args,
+                            env,
+                            depth,
+                        ),
+                        TSTypeName::QualifiedName(_) => None,
+                    },
+                }
+            }
+            TSType::TSImportType(import) => {
+                let (decl, scope_env) = self.import_type(import, env)?;
+                let args = type_args(import.type_parameters.as_deref());
+                self.decl_elements(decl, &scope_env, args, env, depth + 1)
+            }
+            _ => None,
+        }
+    }
+
+    fn signature_elements(&self, members: &'a [TSSignature<'a>], env: &Env<'a>) -> Elements<'a> {
+        let mut elements = Elements::default();
+        for member in members {
+            match member {
+                TSSignature::TSPropertySignature(prop) => {
+                    let Some(key) = prop.key.static_name() else {
+                        continue;
+                    };
+                    let types = match &prop.type_annotation {
+                        Some(annotation) => {
+                            vec![PropType::Type(&annotation.type_annotation, env.clone())]
+                        }
+                        None => vec![PropType::Unknown],
+                    };
+                    let element = Element {
+                        types,
+                        optional: prop.optional,
+                    };
+                    elements.insert(key.into_owned(), element);
+                }
+                TSSignature::TSMethodSignature(method) => {
+                    let Some(key) = method.key.static_name() else {
+                        continue;
+                    };
+                    let element = Element {
+                        types: vec![PropType::Function],
+                        optional: method.optional,
+                    };
+                    elements.insert(key.into_owned(), element);
+                }
+                TSSignature::TSCallSignatureDeclaration(call) => {
+                    elements.calls.push((&call.params, env.clone()));
+                }
+                _ => {}
+            }
+        }
+        elements
+    }
+
+    fn reference_elements(
+        &self,
+        name: &str,
+        member: Option<&str>,
+        args: &'a [TSType<'a>],
+        env: &Env<'a>,
+        depth: usize,
+    ) -> Option<Elements<'a>> {
+        if member.is_none() {
+            if let Some(param) = env.params.get(name) {
+                let (ty, env) = param.as_ref()?;
+                return self.elements(ty, env, depth + 1);
+            }
+            if UTILITY_TYPES.contains(&name) && !self.is_local(name, env) {
+                return self.utility_elements(name, args, env, depth);
+            }
+        }
+        let (decl, scope_env) = self.lookup_ref(name, member, env)?;
+        self.decl_elements(decl, &scope_env, args, env, depth + 1)
+    }
+
+    fn utility_elements(
+        &self,
+        name: &str,
+        args: &'a [TSType<'a>],
+        env: &Env<'a>,
+        depth: usize,
+    ) -> Option<Elements<'a>> {
+        match (name, args) {
+            ("Partial" | "Required", [ty]) => {
+                let mut elements = self.elements(ty, env, depth + 1)?;
+                for (_, element) in &mut elements.props {
+                    element.optional = name == "Partial";
+                }
+                Some(elements)
+            }
+            ("Readonly" | "NonNullable", [ty]) => self.elements(ty, env, depth + 1),
+            ("Pick" | "Omit", [ty, keys]) => {
+                let mut elements = self.elements(ty, env, depth + 1)?;
+                let keys = self.keys(keys, env, depth + 1)?;
+                let pick = name == "Pick";
+                elements.props.retain(|(key, _)| keys.contains(key) == pick);
+                Some(elements)
+            }
+            ("Record", [keys, ty]) => {
+                let props = self
+                    .keys(keys, env, depth + 1)?
+                    .into_iter()
+                    .map(|key| {
+                        let element = Element {
+                            types: vec![PropType::Type(ty, env.clone())],
+                            optional: false,
+                        };
+                        (key, element)
+                    })
+                    .collect();
+                Some(Elements {
+                    props,
+                    calls: Vec::new(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn decl_elements(
+        &self,
+        decl: TypeDecl<'a>,
+        scope_env: &Env<'a>,
+        args: &'a [TSType<'a>],
+        env: &Env<'a>,
+        depth: usize,
+    ) -> Option<Elements<'a>> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        match decl {
+            TypeDecl::Interface(iface) => {
+                let iface_env = self.bind(
+                    iface.type_parameters.as_deref(),
+                    args,
+                    env,
+                    &scope_env.scope,
+                );
+                let mut elements = Elements::default();
+                for heritage in iface.extends.iter().flatten() {
+                    let base_args = type_args(heritage.type_parameters.as_deref());
+                    let base = match &heritage.expression {
+                        Expression::Identifier(id) => self.reference_elements(
+                            id.name.as_str(),
+                            None,
+                            base_args,
+                            &iface_env,
+                            depth,
+                        ),
+                        Expression::StaticMemberExpression(expr) => match &expr.object {
+                            Expression::Identifier(ns) => self.reference_elements(
+                                ns.name.as_str(),
+                                Some(expr.property.name.as_str()),
+                                base_args,
+                                &iface_env,
+                                depth,
+                            ),
+                            _ => None,
+                        },
+                        _ => None,
+                    };
+                    elements.extend(base?);
+                }
+                elements.extend(self.signature_elements(&iface.body.body, &iface_env));
+                Some(elements)
+            }
+            TypeDecl::Alias(alias) => {
+                let alias_env = self.bind(
+                    alias.type_parameters.as_deref(),
+                    args,
+                    env,
+                    &scope_env.scope,
+                );
+                self.elements(&alias.type_annotation, &alias_env, depth + 1)
+            }
+            TypeDecl::Enum(_) | TypeDecl::Class => None,
+        }
+    }
+
+    /// Resolve a type used as a key to the string keys it stands for
+    fn keys(&self, ty: &'a TSType<'a>, env: &Env<'a>, depth: usize) -> Option<Vec<String>> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        match ty {
+            TSType::TSLiteralType(literal) => match &literal.literal {
+                TSLiteral::StringLiteral(s) => Some(vec![s.value.to_string()]),
+                TSLiteral::NumericLiteral(n) => Some(vec![n.value.to_string()]),
+                _ => None,
+            },
+            TSType::TSUnionType(ty) => {
+                let mut keys = Vec::new();
+                for ty in &ty.types {
+                    keys.extend(self.keys(ty, env, depth + 1)?);
+                }
+                Some(keys)
+            }
+            TSType::TSParenthesizedType(ty) => self.keys(&ty.type_annotation, env, depth + 1),
+            TSType::TSTypeOperatorType(op) if op.operator == TSTypeOperatorOperator::Keyof => {
+                let elements = self.elements(&op.type_annotation, env, depth + 1)?;
+                Some(elements.props.into_iter().map(|(key, _)| key).collect())
+            }
+            TSType::TSTypeReference(reference) => {
+                if let TSTypeName::IdentifierReference(id) = &reference.type_name {
+                    if let Some(param) = env.params.get(id.name.as_str()) {
+                        let (ty, env) = param.as_ref()?;
+                        return self.keys(ty, env, depth + 1);
+                    }
+                }
+                match self.lookup(&reference.type_name, env)? {
+                    (TypeDecl::Alias(alias), scope_env) => {
+                        let args = type_args(reference.type_parameters.as_deref());
+                        let alias_env = self.bind(
+                            alias.type_parameters.as_deref(),
+                            args,
+                            env,
+                            &scope_env.scope,
+                        );
+                        self.keys(&alias.type_annotation, &alias_env, depth + 1)
+                    }
+                    (TypeDecl::Enum(decl), _) => decl
+                        .members
+                        .iter()
+                        .map(|member| match &member.initializer {
+                            Some(Expression::StringLiteral(s)) => Some(s.value.to_string()),
+                            _ => None,
+                        })
+                        .collect(),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Build the runtime declaration of a prop
+    fn prop_info(&self, element: &Element<'a>) -> PropTypeInfo {
+        let mut types = self.element_types(element, 0);
+        let mut skip_check = false;
+        if types.contains(&UNKNOWN) {
+            if types.contains(&"Boolean") || types.contains(&"Function") {
+                types.retain(|ty| *ty != UNKNOWN);
+                skip_check = true;
+            } else {
+                types = vec!["null"];
+            }
+        }
+        let js_type = match types.as_slice() {
+            [] => "null".to_string(),
+            [ty] => ty.to_string(),
+            types => format!("[{}]", types.join(", ")),
+        };
+
+        // Only types written in the SFC itself, outside of generics, can be
+        // referenced from its output
+        let ts_type = match element.types.as_slice() {
+            [PropType::Type(ty, env)]
+                if env.scope.path.is_none() && !references_params(ty, &env.params) =>
+            {
+                let span = ty.span();
+                Some(env.scope.source[span.start as usize..span.end as usize].to_string())
+            }
+            _ => None,
+        };
+
+        PropTypeInfo {
+            js_type,
+            ts_type,
+            optional: element.optional,
+            skip_check,
+        }
+    }
+
+    fn element_types(&self, element: &Element<'a>, depth: usize) -> Vec<&'static str> {
+        let mut types = Vec::new();
+        for ty in &element.types {
+            let resolved = match ty {
+                PropType::Type(ty, env) => self.runtime_types(ty, env, depth + 1),
+                PropType::Function => vec!["Function"],
+                PropType::Unknown => vec![UNKNOWN],
+            };
+            push_unique(&mut types, resolved);
+        }
+        types
+    }
+
+    /// Infer the runtime constructors of the values of a type
+    fn runtime_types(&self, ty: &'a TSType<'a>, env: &Env<'a>, depth: usize) -> Vec<&'static str> {
+        if depth > MAX_DEPTH {
+            return vec![UNKNOWN];
+        }
+        match ty {
+            TSType::TSStringKeyword(_) | TSType::TSTemplateLiteralType(_) => vec!["String"],
+            TSType::TSNumberKeyword(_) => vec!["Number"],
+            TSType::TSBooleanKeyword(_) => vec!["Boolean"],
+            TSType::TSObjectKeyword(_) => vec!["Object"],
+            TSType::TSBigIntKeyword(_) => vec!["BigInt"],
+            TSType::TSSymbolKeyword(_) => vec!["Symbol"],
+            TSType::TSNullKeyword(_) => vec!["null"],
+            TSType::TSUndefinedKeyword(_)
+            | TSType::TSVoidKeyword(_)
+            | TSType::TSNeverKeyword(_) => {
+                vec![]
+            }
+            TSType::TSLiteralType(literal) => match &literal.literal {
+                TSLiteral::StringLiteral(_) | TSLiteral::TemplateLiteral(_) => vec!["String"],
+                TSLiteral::NumericLiteral(_) | TSLiteral::UnaryExpression(_) => vec!["Number"],
+                TSLiteral::BooleanLiteral(_) => vec!["Boolean"],
+                TSLiteral::BigIntLiteral(_) => vec!["BigInt"],
+                TSLiteral::NullLiteral(_) => vec!["null"],
+                TSLiteral::RegExpLiteral(_) => vec!["RegExp"],
+            },
+            TSType::TSFunctionType(_) | TSType::TSConstructorType(_) => vec!["Function"],
+            TSType::TSArrayType(_) | TSType::TSTupleType(_) => vec!["Array"],
+            TSType::TSTypeLiteral(literal) => {
+                let callable = !literal.members.is_empty()
+                    && literal
+                        .members
+                        .iter()
+                        .all(|m| matches!(m, TSSignature::TSCallSignatureDeclaration(_)));
+                vec![if callable { "Function" } else { "Object" }]
+            }
+            TSType::TSMappedType(_) => vec!["Object"],
+            TSType::TSParenthesizedType(ty) => {
+                self.runtime_types(&ty.type_annotation, env, depth + 1)
+            }
+            TSType::TSUnionType(ty) => self.flatten_types(&ty.types, env, depth),
+            TSType::TSIntersectionType(ty) => {
+                let mut types = self.flatten_types(&ty.types, env, depth);
+                types.retain(|ty| *ty != UNKNOWN);
+                types
+            }
+            TSType::TSConditionalType(ty) => {
+                let mut types = self.runtime_types(&ty.true_type, env, depth + 1);
+                push_unique(
+                    &mut types,
+                    self.runtime_types(&ty.false_type, env, depth + 1),
+                );
+                types
+            }
+            TSType::TSTypeOperatorType(op) => match op.operator {
+                TSTypeOperatorOperator::Keyof => {
+                    match self.elements(&op.type_annotation, env, depth + 1) {
+                        Some(elements) if !elements.props.is_empty() => {
+                            let mut types = Vec::new();
+                            for (key, _) in &elements.props {
+                                let ty = if key.parse::<f64>().is_ok() {
+                                    "Number"
+                                } else {
+                                    "String"
+                                };
+                                push_unique(&mut types, [ty]);
+                            }
+                            types
+                        }
+                        _ => vec!["String", "Number", "Symbol"],
+                    }
+                }
+                _ => self.runtime_types(&op.type_annotation, env, depth + 1),
+            },
+            TSType::TSIndexedAccessType(ty) => self.indexed_types(ty, env, depth),
+            TSType::TSTypeReference(reference) => {
+                let args = type_args(reference.type_parameters.as_deref());
+                match &reference.type_name {
+                    TSTypeName::IdentifierReference(id) => {
+                        self.reference_types(id.name.as_str(), None, args, env, depth)
+                    }
+                    TSTypeName::QualifiedName(name) => match &name.left {
+                        TSTypeName::IdentifierReference(ns) => self.reference_types(
+                            ns.name.as_str(),
+                            Some(name.right.name.as_str()),
+                            args,
+                            env,
+                            depth,
+                        ),
+                        TSTypeName::QualifiedName(_) => vec![UNKNOWN],
+                    },
+                }
+            }
+            TSType::TSImportType(import) => match self.import_type(import, env) {
+                Some((decl, scope_env)) => {
+                    let args = type_args(import.type_parameters.as_deref());
+                    self.decl_types(decl, &scope_env, args, env, depth + 1)
+                }
+                None => vec![UNKNOWN],
+            },
+            _ => vec![UNKNOWN],
+        }
+    }
+
+    fn flatten_types(
+        &self,
+        types: &'a [TSType<'a>],
+        env: &Env<'a>,
+        depth: usize,
+    ) -> Vec<&'static str> {
+        let mut flattened = Vec::new();
+        for ty in types {
+            push_unique(&mut flattened, self.runtime_types(ty, env, depth + 1));
+        }
+        flattened
+    }
+
+    fn indexed_types(
+        &self,
+        ty: &'a TSIndexedAccessType<'a>,
+        env: &Env<'a>,
+        depth: usize,
+    ) -> Vec<&'static str> {
+        // `Item[]` indexed by `number`
+        if let (TSType::TSArrayType(array), TSType::TSNumberKeyword(_)) =
+            (&ty.object_type, &ty.index_type)
+        {
+            return self.runtime_types(&array.element_type, env, depth + 1);
+        }
+
+        let elements = self.elements(&ty.object_type, env, depth + 1);
+        let keys = self.keys(&ty.index_type, env, depth + 1);
+        let (Some(elements), Some(keys)) = (elements, keys) else {
+            return vec![UNKNOWN];
+        };
+        let mut types = Vec::new();
+        for key in keys {
+            match elements.get(&key) {
+                Some(element) => push_unique(&mut types, self.element_types(element, depth)),
+                None => return vec![UNKNOWN],
+            }
+        }
+        types
+    }
+
+    fn reference_types(
+        &self,
+        name: &str,
+        member: Option<&str>,
+        args: &'a [TSType<'a>],
+        env: &Env<'a>,
+        depth: usize,
+    ) -> Vec<&'static str> {
+        if member.is_none() {
+            if let Some(param) = env.params.get(name) {
+                return match param {
+                    Some((ty, env)) => self.runtime_types(ty, env, depth + 1),
+                    None => vec![UNKNOWN],
+                };
+            }
+            if !self.is_local(name, env) {
+                if let Some(types) = self.builtin_types(name, args, env, depth) {
+                    return types;
+                }
+            }
+        }
+
+        // `Enum.Member`
+        if let Some(member) = member {
+            if let Some((TypeDecl::Enum(decl), _)) = self.lookup_name(name, env) {
+                return enum_types(decl, Some(member));
+            }
+        }
+
+        match self.lookup_ref(name, member, env) {
+            Some((decl, scope_env)) => self.decl_types(decl, &scope_env, args, env, depth + 1),
+            None => vec![UNKNOWN],
+        }
+    }
+
+    fn builtin_types(
+        &self,
+        name: &str,
+        args: &'a [TSType<'a>],
+        env: &Env<'a>,
+        depth: usize,
+    ) -> Option<Vec<&'static str>> {
+        if let Some(constructor) = RUNTIME_CONSTRUCTORS.iter().find(|c| **c == name) {
+            return Some(vec![*constructor]);
+        }
+        let ty = match name {
+            "ReadonlyArray" | "Parameters" | "ConstructorParameters" => "Array",
+            "ReadonlyMap" => "Map",
+            "ReadonlySet" => "Set",
+            "Partial" | "Required" | "Readonly" | "Record" | "Pick" | "Omit" | "InstanceType" => {
+                "Object"
+            }
+            "Uppercase" | "Lowercase" | "Capitalize" | "Uncapitalize" => "String",
+            "NonNullable" => {
+                let mut types = self.runtime_types(args.first()?, env, depth + 1);
+                types.retain(|ty| *ty != "null");
+                return Some(types);
+            }
+            "Exclude" | "OmitThisParameter" => {
+                return Some(self.runtime_types(args.first()?, env, depth + 1));
+            }
+            "Extract" => return Some(self.runtime_types(args.get(1)?, env, depth + 1)),
+            _ => return None,
+        };
+        Some(vec![ty])
+    }
+
+    fn decl_types(
+        &self,
+        decl: TypeDecl<'a>,
+        scope_env: &Env<'a>,
+        args: &'a [TSType<'a>],
+        env: &Env<'a>,
+        depth: usize,
+    ) -> Vec<&'static str> {
+        match decl {
+            TypeDecl::Interface(iface) => {
+                let callable = !iface.body.body.is_empty()
+                    && iface
+                        .body
+                        .body
+                        .iter()
+                        .all(|m| matches!(m, TSSignature::TSCallSignatureDeclaration(_)));
+                vec![if callable { "Function" } else { "Object" }]
+            }
+            TypeDecl::Alias(alias) => {
+                let alias_env = self.bind(
+                    alias.type_parameters.as_deref(),
+                    args,
+                    env,
+                    &scope_env.scope,
+                );
+                self.runtime_types(&alias.type_annotation, &alias_env, depth + 1)
+            }
+            TypeDecl::Enum(decl) => enum_types(decl, None),
+            TypeDecl::Class => vec!["Object"],
+        }
+    }
+
+    /// Collect event names from the first parameter of call signatures,
+    /// or from property keys with the `{ change: [id: number] }` syntax
+    fn emit_names(&self, ty: &'a TSType<'a>, env: &Env<'a>, depth: usize) -> Option<Vec<String>> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        match ty {
+            TSType::TSFunctionType(function) => self.event_names(&function.params, env, depth),
+            TSType::TSParenthesizedType(ty) => self.emit_names(&ty.type_annotation, env, depth + 1),
+            TSType::TSUnionType(ty) => self.union_emit_names(&ty.types, env, depth),
+            TSType::TSIntersectionType(ty) => self.union_emit_names(&ty.types, env, depth),
+            TSType::TSTypeReference(reference) => {
+                if let Some((TypeDecl::Alias(alias), scope_env)) =
+                    self.lookup(&reference.type_name, env)
+                {
+                    let args = type_args(reference.type_parameters.as_deref());
+                    let alias_env = self.bind(
+                        alias.type_parameters.as_deref(),
+                        args,
+                        env,
+                        &scope_env.scope,
+                    );
+                    return self.emit_names(&alias.type_annotation, &alias_env, depth + 1);
+                }
+                self.element_emit_names(ty, env, depth)
+            }
+            _ => self.element_emit_names(ty, env, depth),
+        }
+    }
+
+    fn union_emit_names(
+        &self,
+        types: &'a [TSType<'a>],
+        env: &Env<'a>,
+        depth: usize,
+    ) -> Option<Vec<String>> {
+        let mut names = Vec::new();
+        for ty in types {
+            names.extend(self.emit_names(ty, env, depth + 1)?);
+        }
+        Some(names)
+    }
+
+    fn element_emit_names(
+        &self,
+        ty: &'a TSType<'a>,
+        env: &Env<'a>,
+        depth: usize,
+    ) -> Option<Vec<String>> {
+        let elements = self.elements(ty, env, depth + 1)?;
+        let mut names = Vec::new();
+        for (params, env) in &elements.calls {
+            names.extend(self.event_names(params, env, depth)?);
+        }
+        names.extend(elements.props.into_iter().map(|(key, _)| key));
+        Some(names)
+    }
+
+    fn event_names(
+        &self,
+        params: &'a FormalParameters<'a>,
+        env: &Env<'a>,
+        depth: usize,
+    ) -> Option<Vec<String>> {
+        let annotation = params.items.first()?.pattern.type_annotation.as_ref()?;
+        self.keys(&annotation.type_annotation, env, depth + 1)
+    }
+}
+
+/// Whether a type mentions one of the type parameters in scope
+fn references_params<'a, T>(ty: &TSType<'a>, params: &HashMap<&'a str, T>) -> bool {
+    struct Finder<'p, 'a, T> {
+        params: &'p HashMap<&'a str, T>,
+        found: bool,
+    }
+
+    impl<'a, T> Visit<'a> for Finder<'_, 'a, T> {
+        fn visit_ts_type_name(&mut self, name: &TSTypeName<'a>) {
+            if let TSTypeName::IdentifierReference(id) = name {
+                self.found |= self.params.contains_key(id.name.as_str());
+            }
+        }
+    }
+
+    if params.is_empty() {
+        return false;
+    }
+    let mut finder = Finder {
+        params,
+        found: false,
+    };
+    finder.visit_ts_type(ty);
+    finder.found
+}
+
+fn push_unique(types: &mut Vec<&'static str>, new: impl IntoIterator<Item = &'static str>) {
+    for ty in new {
+        if !types.contains(&ty) {
+            types.push(ty);
+        }
+    }
+}
+
+/// Runtime types of the values of an enum, or of one of its members
+fn enum_types(decl: &TSEnumDeclaration<'_>, member: Option<&str>) -> Vec<&'static str> {
+    let mut types = Vec::new();
+    for m in &decl.members {
+        let name = match &m.id {
+            TSEnumMemberName::Identifier(id) => id.name.as_str(),
+            TSEnumMemberName::String(s) => s.value.as_str(),
+        };
+        if member.is_some_and(|member| member != name) {
+            continue;
+        }
+        let ty = match &m.initializer {
+            Some(Expression::StringLiteral(_) | Expression::TemplateLiteral(_)) => "String",
+            _ => "Number",
+        };
+        push_unique(&mut types, [ty]);
+    }
+    if types.is_empty() {
+        types.push(UNKNOWN);
+    }
+    types
+}
+
+/// Script content of an imported SFC
+fn vue_script_content(source: &str) -> Option<String> {
+    let descriptor = crate::parse_sfc(source, crate::SfcParseOptions::default()).ok()?;
+    let scripts: Vec<&str> = [&descriptor.script, &descriptor.script_setup]
+        .into_iter()
+        .flatten()
+        .map(|script| script.content.as_ref())
+        .collect();
+    Some(scripts.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct MemoryLoader(Vec<(&'static str, &'static str)>);
+
+    impl FileLoader for MemoryLoader {
+        fn read_file(&self, path: &Path) -> std::io::Result<String> {
+            self.0
+                .iter()
+                .find(|(name, _)| Path::new(name) == path)
+                .map(|(_, content)| content.to_string())
+                .ok_or_else(|| std::io::ErrorKind::NotFound.into())
+        }
+    }
+
+    fn resolver(files: Vec<(&'static str, &'static str)>) -> TypeResolver {
+        TypeResolver::new(TypeResolveOptions {
+            filename: "/project/src/components/Comp.vue".to_string(),
+            fs: Some(Arc::new(MemoryLoader(files))),
+            ..Default::default()
+        })
+    }
+
+    /// Props as `name: type [required]` lines, sorted by name
+    fn props(resolver: &TypeResolver, source: &str, type_args: &str) -> Vec<String> {
+        let props = resolver.resolve_props(source, type_args).unwrap();
+        let mut lines: Vec<String> = props
+            .iter()
+            .map(|(name, info)| {
+                format!(
+                    "{}: {}{}{}",
+                    name,
+                    info.js_type,
+                    if info.optional { "" } else { " required" },
+                    if info.skip_check { " skipCheck" } else { "" },
+                )
+            })
+            .collect();
+        lines.sort();
+        lines
+    }
+
+    #[test]
+    fn test_resolve_interface_extends_and_intersection() {
+        let source = r#"
+interface Base { id: string | number; tags?: string[] }
+interface Props extends Base { label: string; onClick(): void }
+type Extra = { count?: number }
+"#;
+        let resolver = resolver(vec![]);
+        assert_eq!(
+            props(
+                &resolver,
+                source,
+                "Props & Extra & { flag: boolean | unknown }"
+            ),
+            [
+                "count: Number",
+                "flag: Boolean required skipCheck",
+                "id: [String, Number] required",
+                "label: String required",
+                "onClick: Function required",
+                "tags: Array",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_utility_types() {
+        let source = r#"
+enum Size { Small = 'sm', Large = 'lg' }
+enum Level { Low, High }
+interface Item { name: string; size: Size; level: Level; meta: { a: number }; data: unknown }
+"#;
+        let resolver = resolver(vec![]);
+        assert_eq!(
+            props(&resolver, source, "Partial<Pick<Item, 'name' | 'size'>>"),
+            ["name: String", "size: String"]
+        );
+        assert_eq!(
+            props(&resolver, source, "Omit<Item, 'name' | 'meta'>"),
+            [
+                "data: null required",
+                "level: Number required",
+                "size: String required"
+            ]
+        );
+        assert_eq!(
+            props(
+                &resolver,
+                source,
+                "{ a: Item['meta']['a']; b: Item['level' | 'name'] }"
+            ),
+            ["a: Number required", "b: [Number, String] required"]
+        );
+        assert_eq!(
+            props(&resolver, source, "Required<Record<'x' | 'y', Date>>"),
+            ["x: Date required", "y: Date required"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_generic_alias() {
+        let source = "type WithValue<T> = { value: T; list: T[] }";
+        let resolver = resolver(vec![]);
+        assert_eq!(
+            props(&resolver, source, "WithValue<number>"),
+            ["list: Array required", "value: Number required"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_component_generics() {
+        let resolver = TypeResolver::new(TypeResolveOptions {
+            generic: Some("T extends Item, K extends keyof T = 'id'".to_string()),
+            ..Default::default()
+        });
+        let source = "interface Item { id: string }\ntype T = { shadowed: true }";
+        let props = resolver
+            .resolve_props(
+                source,
+                "{ value: T; key: K; list: T[]; item: Item; on: (v: T) => void }",
+            )
+            .unwrap();
+        assert_eq!(props["value"].js_type, "null");
+        assert_eq!(props["key"].js_type, "null");
+        assert_eq!(props["list"].js_type, "Array");
+        assert_eq!(props["on"].js_type, "Function");
+        assert_eq!(props["item"].js_type, "Object");
+        assert_eq!(props["list"].ts_type, None);
+        assert_eq!(props["on"].ts_type, None);
+        assert_eq!(props["item"].ts_type.as_deref(), Some("Item"));
+    }
+
+    #[test]
+    fn test_resolve_imported_types() {
+        let resolver = resolver(vec![
+            (
+                "/project/src/components/types.ts",
+                "export * from './base'\nexport { Color as Colour } from '../colors'\n\
+                 import type { Shared } from '@/shared'\n\
+                 export interface Props extends Shared { title: string }",
+            ),
+            (
+                "/project/src/components/base.ts",
+                "export type Base = { disabled?: boolean }",
+            ),
+            (
+                "/project/src/colors/index.ts",
+                "export enum Color { Red = 'red' }",
+            ),
+            ("/project/src/shared.d.ts", "export interface Shared { size: number }"),
+            (
+                "/project/tsconfig.json",
+                "{\n  // comment\n  \"compilerOptions\": { \"paths\": { \"@/*\": [\"./src/*\"] }, },\n}",
+            ),
+        ]);
+        let source = "import type { Props, Base } from './types'\nimport * as T from './types.js'";
+        assert_eq!(
+            props(&resolver, source, "Props & Base & { color: T.Colour }"),
+            [
+                "color: String required",
+                "disabled: Boolean",
+                "size: Number required",
+                "title: String required",
+            ]
+        );
+        let mut dependencies = resolver.dependencies();
+        dependencies.sort();
+        assert_eq!(
+            dependencies,
+            [
+                "/project/src/colors/index.ts",
+                "/project/src/components/base.ts",
+                "/project/src/components/types.ts",
+                "/project/src/shared.d.ts",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_tsconfig_extends_base_url() {
+        let resolver = resolver(vec![
+            (
+                "/project/tsconfig.json",
+                "{ \"extends\": \"./tsconfig.base\" }",
+            ),
+            (
+                "/project/tsconfig.base.json",
+                "{ \"compilerOptions\": { \"baseUrl\": \"./src\", /* aliases */ \
+                 \"paths\": { \"~types\": [\"types/index.ts\"] } } }",
+            ),
+            (
+                "/project/src/types/index.ts",
+                "export type A = { a: string }",
+            ),
+            ("/project/src/lib/b.ts", "export type B = { b: number }"),
+        ]);
+        let source = "import { A } from '~types'\nimport { B } from 'lib/b'";
+        assert_eq!(
+            props(&resolver, source, "A & B"),
+            ["a: String required", "b: Number required"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_ts_type_only_for_local_types() {
+        let resolver = resolver(vec![(
+            "/project/src/components/types.ts",
+            "export interface Props { remote: string }",
+        )]);
+        let source = "import type { Props } from './types'";
+        let props = resolver
+            .resolve_props(source, "Props & { local: string[] }")
+            .unwrap();
+        assert_eq!(props["local"].ts_type.as_deref(), Some("string[]"));
+        assert_eq!(props["remote"].ts_type, None);
+    }
+
+    #[test]
+    fn test_resolve_unresolvable() {
+        let resolver = resolver(vec![]);
+        assert!(resolver
+            .resolve_props("import type { Props } from './missing'", "Props")
+            .is_none());
+        assert!(resolver.resolve_props("", "string").is_none());
+    }
+
+    #[test]
+    fn test_resolve_emits() {
+        let resolver = resolver(vec![(
+            "/project/src/components/events.ts",
+            "export type ChangeEvent = 'change' | 'update'\n\
+             export interface Emits { (e: ChangeEvent, value: string): void; (e: 'close'): void }",
+        )]);
+        let source = "import type { Emits } from './events'";
+        assert_eq!(
+            resolver.resolve_emits(source, "Emits").unwrap(),
+            ["change", "update", "close"]
+        );
+        assert_eq!(
+            resolver
+                .resolve_emits(source, "{ submit: [id: number]; 'before-leave': [] }")
+                .unwrap(),
+            ["submit", "before-leave"]
+        );
+        assert_eq!(
+            resolver
+                .resolve_emits("", "((e: 'a') => void) | ((e: 'b' | 'a') => void)")
+                .unwrap(),
+            ["a", "b"]
+        );
+    }
+}
@@ -0,0 +1,307 @@
+//! Source maps for compiled SFC modules.
+//!
+//! The compiled module is stitched together from several independently
+//! transformed pieces (rewritten script, inlined setup, OXC-stripped
+//! TypeScript, template render function), so exact offsets are not carried
+//! through every step. Instead, two strategies are combined:
+//!
+//! - Template render functions reuse the template codegen map, shifted to the
+//!   template block's position in the SFC. When the render function is inlined
+//!   into `setup()`, the map is carried through the inlining and composed with
+//!   the map of the OXC reprint.
+//! - All other lines are anchored to the source line they came from by
+//!   matching normalized line text inside the relevant blocks. This is a
+//!   heuristic: a line the compiler rewrote (e.g. a macro call) gets no
+//!   mapping, and when the same text appears on several source lines the
+//!   anchor may pick the wrong one.
+
+use oxc_sourcemap::{SourceMap, SourceMapBuilder};
+
+/// A mapping token before encoding
+pub(crate) struct Token {
+    pub dst_line: u32,
+    pub dst_col: u32,
+    pub src_line: u32,
+    pub src_col: u32,
+}
+
+/// Converts byte offsets in a text to 0-based lines and UTF-16 columns
+struct LineIndex<'a> {
+    text: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(text: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { text, line_starts }
+    }
+
+    fn position(&self, offset: usize) -> (u32, u32) {
+        let offset = offset.min(self.text.len());
+        let line = self
+            .line_starts
+            .partition_point(|&start| start <= offset)
+            .saturating_sub(1);
+        let column = self
+            .text
+            .get(self.line_starts[line]..offset)
+            .map_or(0, |s| s.encode_utf16().count());
+        (line as u32, column as u32)
+    }
+}
+
+/// Builds a single source map pointing back into the `.vue` file
+pub(crate) struct SfcSourceMapGenerator<'a> {
+    source: LineIndex<'a>,
+    filename: &'a str,
+    tokens: Vec<Token>,
+    /// Generated lines already covered by a precise (template) map
+    covered_lines: Vec<(u32, u32)>,
+}
+
+impl<'a> SfcSourceMapGenerator<'a> {
+    pub fn new(filename: &'a str, source: &'a str) -> Self {
+        Self {
+            source: LineIndex::new(source),
+            filename,
+            tokens: Vec::new(),
+            covered_lines: Vec::new(),
+        }
+    }
+
+    /// Resolve a byte offset in the SFC source into a 0-based line and UTF-16 column
+    fn position(&self, offset: usize) -> (u32, u32) {
+        self.source.position(offset)
+    }
+
+    /// Reuse the map of a compiled block, such as a template render function
+    /// or a style block printed by LightningCSS.
+    ///
+    /// `code_start_line` is the generated line where the block's code begins and
+    /// `block_start` is the byte offset of the block content in the SFC.
+    pub fn add_block_map(&mut self, map_json: &str, code_start_line: u32, block_start: usize) {
+        let Ok(map) = SourceMap::from_json_string(map_json) else {
+            return;
+        };
+        let (base_line, base_col) = self.position(block_start);
+
+        let mut first_line = u32::MAX;
+        let mut last_line = code_start_line;
+        for token in map.get_tokens() {
+            let src_line = token.get_src_line();
+            let src_col = if src_line == 0 {
+                base_col + token.get_src_col()
+            } else {
+                token.get_src_col()
+            };
+            let dst_line = code_start_line + token.get_dst_line();
+            first_line = first_line.min(dst_line);
+            last_line = last_line.max(dst_line);
+            self.tokens.push(Token {
+                dst_line,
+                dst_col: token.get_dst_col(),
+                src_line: base_line + src_line,
+                src_col,
+            });
+        }
+        self.covered_lines
+            .push((first_line.min(last_line), last_line));
+    }
+
+    /// Anchor generated lines to matching lines inside the given source ranges
+    pub fn add_line_anchors(&mut self, code: &str, ranges: &[(usize, usize)]) {
+        // Candidate source lines, in source order
+        let mut candidates: Vec<(String, usize)> = Vec::new();
+        for &(start, end) in ranges {
+            let Some(block) = self.source.text.get(start..end) else {
+                continue;
+            };
+            let mut offset = start;
+            for line in block.split_inclusive('\n') {
+                let indent = line.len() - line.trim_start().len();
+                if let Some(key) = normalize_line(line) {
+                    candidates.push((key, offset + indent));
+                }
+                offset += line.len();
+            }
+        }
+        if candidates.is_empty() {
+            return;
+        }
+
+        let mut used = vec![false; candidates.len()];
+        let mut cursor = 0;
+        for (dst_line, line) in code.lines().enumerate() {
+            let dst_line = dst_line as u32;
+            if self
+                .covered_lines
+                .iter()
+                .any(|&(start, end)| (start..=end).contains(&dst_line))
+            {
+                continue;
+            }
+            let Some(key) = normalize_line(line) else {
+                continue;
+            };
+
+            // Prefer the next match after the previous one so repeated lines map in order
+            let found = (cursor..candidates.len())
+                .chain(0..cursor)
+                .find(|&i| !used[i] && candidates[i].0 == key);
+            let Some(index) = found else {
+                continue;
+            };
+            used[index] = true;
+            cursor = index + 1;
+
+            let indent = line.len() - line.trim_start().len();
+            let (src_line, src_col) = self.position(candidates[index].1);
+            self.tokens.push(Token {
+                dst_line,
+                dst_col: indent as u32,
+                src_line,
+                src_col,
+            });
+        }
+    }
+
+    /// Encode the collected mappings as a v3 source map
+    pub fn into_json(self) -> serde_json::Value {
+        let map = build_map(self.filename, self.source.text, self.tokens);
+        serde_json::from_str(&map.to_json_string()).unwrap_or_default()
+    }
+}
+
+/// Encode tokens as a map of a single source
+pub(crate) fn build_map(source: &str, content: &str, mut tokens: Vec<Token>) -> SourceMap {
+    let mut builder = SourceMapBuilder::default();
+    let source_id = builder.set_source_and_content(source, content);
+
+    tokens.sort_by_key(|token| (token.dst_line, token.dst_col));
+    tokens.dedup_by_key(|token| (token.dst_line, token.dst_col));
+    for token in tokens.iter() {
+        builder.add_token(
+            token.dst_line,
+            token.dst_col,
+            token.src_line,
+            token.src_col,
+            Some(source_id),
+            None,
+        );
+    }
+    builder.into_sourcemap()
+}
+
+/// Build a map with the source of `map` from the given tokens
+fn rebuild_map(map: &SourceMap, tokens: Vec<Token>) -> SourceMap {
+    let (source, content) = map.get_source_and_content(0).unwrap_or(("", ""));
+    build_map(source, content, tokens)
+}
+
+/// Move generated line `n` of `map` to the line and column offset returned by
+/// `place(n)`, dropping the tokens of lines it returns `None` for
+pub(crate) fn relocate_lines(
+    map: &SourceMap,
+    place: impl Fn(u32) -> Option<(u32, u32)>,
+) -> SourceMap {
+    let tokens = map
+        .get_tokens()
+        .filter_map(|token| {
+            let (dst_line, shift) = place(token.get_dst_line())?;
+            Some(Token {
+                dst_line,
+                dst_col: token.get_dst_col() + shift,
+                src_line: token.get_src_line(),
+                src_col: token.get_src_col(),
+            })
+        })
+        .collect();
+    rebuild_map(map, tokens)
+}
+
+/// Compose `outer`, which maps code to an intermediate code, with `inner`,
+/// which maps that intermediate code to the source
+pub(crate) fn compose(outer: &SourceMap, inner: &SourceMap) -> SourceMap {
+    let table = inner.generate_lookup_table();
+    let tokens = outer
+        .get_tokens()
+        .filter_map(|token| {
+            let original = inner.lookup_token(&table, token.get_src_line(), token.get_src_col())?;
+            Some(Token {
+                dst_line: token.get_dst_line(),
+                dst_col: token.get_dst_col(),
+                src_line: original.get_src_line(),
+                src_col: original.get_src_col(),
+            })
+        })
+        .collect();
+    rebuild_map(inner, tokens)
+}
+
+/// Normalize a line so that reformatting by the compiler doesn't prevent a match.
+/// Lines without any identifier characters (e.g. lone braces) are not anchored.
+fn normalize_line(line: &str) -> Option<String> {
+    let trimmed = line.trim();
+    let trimmed = trimmed.strip_suffix(';').unwrap_or(trimmed).trim_end();
+    if !trimmed.chars().any(|c| c.is_alphanumeric()) {
+        return None;
+    }
+
+    let mut key = String::with_capacity(trimmed.len());
+    let mut last_space = false;
+    for c in trimmed.chars() {
+        if c.is_whitespace() {
+            if !last_space {
+                key.push(' ');
+            }
+            last_space = true;
+            continue;
+        }
+        last_space = false;
+        key.push(if c == '\'' { '"' } else { c });
+    }
+    Some(key)
+}
+
+/// Find the 0-based line of `code` that starts with `needle`
+pub(crate) fn find_line(code: &str, needle: &str) -> Option<u32> {
+    code.lines()
+        .position(|line| line.starts_with(needle))
+        .map(|line| line as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_line() {
+        assert_eq!(
+            normalize_line("  const msg = ref('hi');"),
+            Some("const msg = ref(\"hi\")".to_string())
+        );
+        assert_eq!(normalize_line("  })"), None);
+        assert_eq!(normalize_line(""), None);
+    }
+
+    #[test]
+    fn test_line_anchors() {
+        let source = "<script setup lang=\"ts\">\nconst a: number = 1\nconst b = ref(a)\n</script>";
+        let start = source.find("const").unwrap();
+        let end = source.find("</script>").unwrap();
+        let mut generator = SfcSourceMapGenerator::new("App.vue", source);
+        // The first line was rewritten by the TypeScript transform
+        generator.add_line_anchors("const a = 1;\n  const b = ref(a);", &[(start, end)]);
+        let map = generator.into_json();
+
+        let map = SourceMap::from_json_string(&map.to_string()).unwrap();
+        let tokens: Vec<_> = map
+            .get_tokens()
+            .map(|t| (t.get_dst_line(), t.get_dst_col(), t.get_src_line()))
+            .collect();
+        assert_eq!(tokens, vec![(1, 2, 2)]);
+    }
+}
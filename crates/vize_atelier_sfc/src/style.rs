@@ -1,17 +1,91 @@
 //! Style block processing and scoped CSS.
 
+#[cfg(feature = "native")]
+mod native;
+#[cfg(feature = "sass")]
+mod sass;
+
+use std::collections::BTreeMap;
+
 use crate::types::*;
 
-/// Compile a style block
+/// Compiled style block
+#[derive(Debug, Clone, Default)]
+pub struct StyleCompileResult {
+    /// Compiled CSS
+    pub code: String,
+
+    /// Source map of the compiled CSS, relative to the block content
+    pub map: Option<String>,
+
+    /// Generated names of a `<style module>` block, keyed by local name
+    pub modules: BTreeMap<String, String>,
+}
+
+/// Compile a style block.
+///
+/// Sass and SCSS are compiled to CSS first when the `sass` feature is on.
+/// Plain CSS is parsed and printed by LightningCSS on native builds, which
+/// reports syntax errors. Other languages and wasm builds are scoped as text.
+/// Classes and keyframes of `<style module>` blocks are renamed after
+/// [`StyleCompileOptions::modules_pattern`].
 pub fn compile_style(
     style: &SfcStyleBlock,
     options: &StyleCompileOptions,
-) -> Result<String, SfcError> {
-    let mut output: String = style.content.to_string();
+) -> Result<StyleCompileResult, SfcError> {
+    let scope_attr = (style.scoped || options.scoped).then_some(options.id.as_str());
+    let modules_pattern = modules_pattern(style, options);
+    let preprocessed = preprocess(style, options)?;
+    let source = preprocessed.as_deref().unwrap_or(&style.content);
+
+    // Custom property names use the bare scope ID, like `@vue/compiler-sfc`
+    let id = options.id.strip_prefix("data-v-").unwrap_or(&options.id);
+    let css = rewrite_css_vars(source, id, options.is_prod);
+
+    #[cfg(feature = "native")]
+    if preprocessed.is_some()
+        || matches!(block_lang(style, options), None | Some("css" | "postcss"))
+    {
+        let compiled = native::compile(
+            &css,
+            "style.css",
+            scope_attr,
+            modules_pattern.as_deref(),
+            options,
+        )
+        .map_err(|error| {
+            style_error(
+                style,
+                format!("CSS syntax error: {}", error.message),
+                "CSS_SYNTAX_ERROR",
+                // Positions in preprocessor output don't match the block
+                preprocessed.is_none().then_some((error.line, error.column)),
+            )
+        })?;
+        let code = if options.trim {
+            compiled.code.trim().to_string()
+        } else {
+            compiled.code
+        };
+        return Ok(StyleCompileResult {
+            code,
+            map: compiled.map.filter(|_| preprocessed.is_none()),
+            modules: compiled.modules,
+        });
+    }
+
+    let mut output = css;
 
     // Apply scoped transformation if needed
-    if style.scoped || options.scoped {
-        output = apply_scoped_css(&output, &options.id);
+    if let Some(attr) = scope_attr {
+        output = apply_scoped_css(&output, attr);
+    }
+
+    let mut modules = BTreeMap::new();
+    if let Some(pattern) = &modules_pattern {
+        let (module_css, mapping) = apply_css_modules(&output, pattern, "", "");
+        output = module_css;
+        modules.extend(mapping);
     }
 
     // Trim if requested
@@ -19,7 +93,116 @@ pub fn compile_style(
         output = output.trim().to_string();
     }
 
-    Ok(output)
+    Ok(StyleCompileResult {
+        code: output,
+        map: None,
+        modules,
+    })
+}
+
+/// Name pattern of a `<style module>` block with the component name (the
+/// file stem) and hash (the scope ID) filled in
+fn modules_pattern(style: &SfcStyleBlock, options: &StyleCompileOptions) -> Option<String> {
+    style.module.as_ref()?;
+    let name = std::path::Path::new(&options.filename)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("anonymous");
+    let hash = options.id.strip_prefix("data-v-").unwrap_or(&options.id);
+    let pattern = options
+        .modules_pattern
+        .as_deref()
+        .unwrap_or(DEFAULT_MODULES_PATTERN);
+    Some(pattern.replace("[name]", name).replace("[hash]", hash))
+}
+
+/// The language of a style block, overridden by `preprocessor_lang`
+#[cfg(any(feature = "native", feature = "sass"))]
+fn block_lang<'a>(style: &'a SfcStyleBlock, options: &'a StyleCompileOptions) -> Option<&'a str> {
+    options
+        .preprocessor_lang
+        .as_deref()
+        .or(style.lang.as_deref())
+}
+
+/// Compile Sass and SCSS blocks to CSS, returning `None` for other languages
+#[cfg(feature = "sass")]
+fn preprocess(
+    style: &SfcStyleBlock,
+    options: &StyleCompileOptions,
+) -> Result<Option<String>, SfcError> {
+    let Some(syntax @ ("scss" | "sass")) = block_lang(style, options) else {
+        return Ok(None);
+    };
+    let filename = if options.filename.is_empty() {
+        "anonymous.vue"
+    } else {
+        &options.filename
+    };
+
+    sass::compile(
+        &style.content,
+        filename,
+        syntax == "sass",
+        &options.load_paths,
+    )
+    .map(Some)
+    .map_err(|error| {
+        style_error(
+            style,
+            format!("Sass error: {}", error.message),
+            "SASS_ERROR",
+            error.position,
+        )
+    })
+}
+
+#[cfg(not(feature = "sass"))]
+fn preprocess(
+    _style: &SfcStyleBlock,
+    _options: &StyleCompileOptions,
+) -> Result<Option<String>, SfcError> {
+    Ok(None)
+}
+
+/// An error at `position` in a style block: a 0-based line and 1-based
+/// column in the block content, or the start of the block if unknown
+#[cfg(any(feature = "native", feature = "sass"))]
+fn style_error(
+    style: &SfcStyleBlock,
+    message: String,
+    code: &str,
+    position: Option<(u32, u32)>,
+) -> SfcError {
+    let (line, column) = position.map_or((0, 1), |(line, column)| {
+        (line as usize, column.max(1) as usize)
+    });
+    let line_start: usize = style
+        .content
+        .split_inclusive('\n')
+        .take(line)
+        .map(str::len)
+        .sum();
+    let offset = (style.loc.start + line_start + column - 1).min(style.loc.end);
+    let start_line = style.loc.start_line + line;
+    let start_column = if line == 0 {
+        style.loc.start_column + column - 1
+    } else {
+        column
+    };
+
+    SfcError {
+        message,
+        code: Some(code.to_string()),
+        loc: Some(BlockLocation {
+            start: offset,
+            end: offset,
+            start_line,
+            start_column,
+            end_line: start_line,
+            end_column: start_column,
+        }),
+    }
 }
 
 /// Apply scoped CSS transformation
@@ -89,10 +272,7 @@ pub fn apply_scoped_css(css: &str, scope_id: &str) -> String {
                     last_selector_end = current.len();
                 }
             }
-            '@' => {
-                // Handle at-rules
-                output.push(c);
-            }
+            '@' if !in_selector => output.push(c),
             _ if in_selector => {
                 // Still building selector
             }
@@ -107,11 +287,19 @@ pub fn apply_scoped_css(css: &str, scope_id: &str) -> String {
         output.push_str(&current[last_selector_end..]);
     }
 
-    output
+    scope_keyframes(
+        &output,
+        scope_id.strip_prefix("data-v-").unwrap_or(scope_id),
+    )
 }
 
 /// Add scope attribute to a selector
 fn scope_selector(selector: &str, attr_selector: &str) -> String {
+    // At-rule preludes have no selectors
+    if selector.starts_with('@') {
+        return selector.to_string();
+    }
+
     // Handle multiple selectors separated by comma
     selector
         .split(',')
@@ -242,25 +430,409 @@ fn transform_global(selector: &str) -> String {
     selector.to_string()
 }
 
+/// Default name pattern for CSS module classes and keyframes
+pub const DEFAULT_MODULES_PATTERN: &str = "_[local]_[hash]";
+
+/// Rewrite a CSS module as text: classes and keyframes declared in `css` are
+/// renamed using `pattern`, where `[local]` is the original name, `[name]` the
+/// component name and `[hash]` the component hash.
+///
+/// This is the fallback for wasm builds and languages LightningCSS can't parse.
+/// Returns the rewritten CSS and the `local -> generated` mapping in source order.
+pub fn apply_css_modules(
+    css: &str,
+    pattern: &str,
+    name: &str,
+    hash: &str,
+) -> (String, Vec<(String, String)>) {
+    let mut modules = NameRewriter {
+        pattern,
+        name,
+        hash,
+        classes: true,
+        keyframes: collect_keyframes(css),
+        mapping: Vec::new(),
+    };
+    let output = modules.rewrite(css);
+    (output, modules.mapping)
+}
+
+/// Rename the keyframes declared in a scoped style block to `{name}-{id}`,
+/// along with the `animation` and `animation-name` declarations using them,
+/// so animations of different components don't collide.
+pub fn scope_keyframes(css: &str, id: &str) -> String {
+    let keyframes: Vec<String> = collect_keyframes(css)
+        .into_iter()
+        .filter(|name| !name.ends_with(&format!("-{}", id)))
+        .collect();
+    if keyframes.is_empty() {
+        return css.to_string();
+    }
+    NameRewriter {
+        pattern: "[local]-[hash]",
+        name: "",
+        hash: id,
+        classes: false,
+        keyframes,
+        mapping: Vec::new(),
+    }
+    .rewrite(css)
+}
+
+/// Renames classes and keyframes of a stylesheet
+struct NameRewriter<'a> {
+    pattern: &'a str,
+    name: &'a str,
+    hash: &'a str,
+    /// Whether classes are renamed as well as keyframes
+    classes: bool,
+    /// Keyframes declared in the stylesheet
+    keyframes: Vec<String>,
+    mapping: Vec<(String, String)>,
+}
+
+impl NameRewriter<'_> {
+    fn rewrite(&mut self, css: &str) -> String {
+        let mut output = String::with_capacity(css.len() + css.len() / 4);
+        let mut segment = String::new();
+        let mut chars = css.chars().peekable();
+        let mut paren_depth = 0usize;
+
+        while let Some(c) = chars.next() {
+            match c {
+                '/' if chars.peek() == Some(&'*') => {
+                    // Comments are copied through as they are
+                    output.push_str(&self.rewrite_declaration(&segment));
+                    segment.clear();
+                    output.push(c);
+                    let mut last = c;
+                    for c in chars.by_ref() {
+                        output.push(c);
+                        if last == '*' && c == '/' {
+                            break;
+                        }
+                        last = c;
+                    }
+                }
+                '"' | '\'' => {
+                    segment.push(c);
+                    let mut escaped = false;
+                    for s in chars.by_ref() {
+                        segment.push(s);
+                        if escaped {
+                            escaped = false;
+                        } else if s == '\\' {
+                            escaped = true;
+                        } else if s == c {
+                            break;
+                        }
+                    }
+                }
+                '(' => {
+                    paren_depth += 1;
+                    segment.push(c);
+                }
+                ')' => {
+                    paren_depth = paren_depth.saturating_sub(1);
+                    segment.push(c);
+                }
+                '{' if paren_depth == 0 => {
+                    output.push_str(&self.rewrite_prelude(&segment));
+                    output.push(c);
+                    segment.clear();
+                }
+                ';' | '}' if paren_depth == 0 => {
+                    output.push_str(&self.rewrite_declaration(&segment));
+                    output.push(c);
+                    segment.clear();
+                }
+                _ => segment.push(c),
+            }
+        }
+        output.push_str(&segment);
+        output
+    }
+
+    /// Generated name for a local class or keyframes name
+    fn generate(&mut self, local: &str) -> String {
+        if let Some((_, generated)) = self.mapping.iter().find(|(l, _)| l == local) {
+            return generated.clone();
+        }
+        let generated = self
+            .pattern
+            .replace("[local]", local)
+            .replace("[name]", self.name)
+            .replace("[hash]", self.hash);
+        self.mapping.push((local.to_string(), generated.clone()));
+        generated
+    }
+
+    /// Rewrite a rule prelude: a selector or an at-rule
+    fn rewrite_prelude(&mut self, prelude: &str) -> String {
+        let trimmed = prelude.trim_start();
+        if let Some(at_rule) = trimmed.strip_prefix('@') {
+            let keyword_len = at_rule
+                .find(|c: char| c.is_whitespace())
+                .unwrap_or(at_rule.len());
+            if at_rule[..keyword_len].ends_with("keyframes") {
+                let rest = &at_rule[keyword_len..];
+                let local = rest.trim();
+                if self.keyframes.iter().any(|k| k == local) {
+                    let leading = &prelude[..prelude.len() - trimmed.len()];
+                    let generated = self.generate(local);
+                    let trailing = &rest[rest.trim_end().len()..];
+                    return format!(
+                        "{}@{} {}{}",
+                        leading,
+                        &at_rule[..keyword_len],
+                        generated,
+                        trailing
+                    );
+                }
+            }
+            return prelude.to_string();
+        }
+        self.rewrite_selector(prelude)
+    }
+
+    /// Rename classes in a selector, honoring `:global()` and `:local()`
+    fn rewrite_selector(&mut self, selector: &str) -> String {
+        if !self.classes {
+            return selector.to_string();
+        }
+        let mut output = String::with_capacity(selector.len());
+        let mut rest = selector;
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix(":global(") {
+                let end = find_closing_paren(after);
+                output.push_str(&after[..end]);
+                rest = after.get(end + 1..).unwrap_or("");
+                continue;
+            }
+            if let Some(after) = rest.strip_prefix(":local(") {
+                let end = find_closing_paren(after);
+                output.push_str(&self.rewrite_selector(&after[..end]));
+                rest = after.get(end + 1..).unwrap_or("");
+                continue;
+            }
+
+            let c = rest.chars().next().unwrap();
+            if c == '"' || c == '\'' || c == '[' {
+                // Attribute selectors and strings may contain dots
+                let close = if c == '[' { ']' } else { c };
+                let end = rest[1..].find(close).map_or(rest.len(), |i| i + 2);
+                output.push_str(&rest[..end]);
+                rest = &rest[end..];
+                continue;
+            }
+            if c == '.' {
+                let ident_len = css_ident_len(&rest[1..]);
+                if ident_len > 0 {
+                    let local = &rest[1..1 + ident_len];
+                    output.push('.');
+                    output.push_str(&self.generate(local));
+                    rest = &rest[1 + ident_len..];
+                    continue;
+                }
+            }
+            output.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+        output
+    }
+
+    /// Rename keyframes referenced from `animation` / `animation-name`
+    fn rewrite_declaration(&mut self, declaration: &str) -> String {
+        let Some(colon) = declaration.find(':') else {
+            return declaration.to_string();
+        };
+        let property = declaration[..colon].trim();
+        let property = property
+            .strip_prefix("-webkit-")
+            .or_else(|| property.strip_prefix("-moz-"))
+            .unwrap_or(property);
+        if property != "animation" && property != "animation-name" {
+            return declaration.to_string();
+        }
+
+        let mut output = String::from(&declaration[..=colon]);
+        let value = &declaration[colon + 1..];
+        let mut rest = value;
+        while !rest.is_empty() {
+            let ident_len = css_ident_len(rest);
+            if ident_len > 0 {
+                let word = &rest[..ident_len];
+                if self.keyframes.iter().any(|k| k == word) {
+                    let generated = self.generate(word);
+                    output.push_str(&generated);
+                } else {
+                    output.push_str(word);
+                }
+                rest = &rest[ident_len..];
+                continue;
+            }
+            // Copy anything else, including numbers like `1.5s`, a whole token at a time
+            let token_len = rest
+                .find(|c: char| c.is_whitespace() || c == ',')
+                .map_or(rest.len(), |i| i.max(1));
+            output.push_str(&rest[..token_len]);
+            rest = &rest[token_len..];
+        }
+        output
+    }
+}
+
+/// Names declared with `@keyframes` in a stylesheet
+fn collect_keyframes(css: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut search_from = 0;
+    while let Some(pos) = css[search_from..].find("keyframes") {
+        let start = search_from + pos;
+        search_from = start + "keyframes".len();
+        let is_at_rule = css[..start]
+            .strip_suffix("-webkit-")
+            .unwrap_or(&css[..start])
+            .ends_with('@');
+        if !is_at_rule {
+            continue;
+        }
+        let rest = css[search_from..].trim_start();
+        let len = css_ident_len(rest);
+        if len > 0 && !names.iter().any(|n| n == &rest[..len]) {
+            names.push(rest[..len].to_string());
+        }
+    }
+    names
+}
+
+/// Length of the CSS identifier at the start of `s` (0 if there is none)
+fn css_ident_len(s: &str) -> usize {
+    let mut len = 0;
+    for (i, c) in s.char_indices() {
+        let valid = c.is_ascii_alphanumeric() || c == '-' || c == '_' || !c.is_ascii();
+        // Identifiers can't start with a digit (`.5em`, `50.5%`)
+        if !valid || (i == 0 && c.is_ascii_digit()) {
+            break;
+        }
+        len = i + c.len_utf8();
+    }
+    if s[..len].starts_with("--")
+        || s[..len].starts_with('-') && s[1..len].starts_with(|c: char| c.is_ascii_digit())
+    {
+        return 0;
+    }
+    len
+}
+
+/// Index of the parenthesis closing an already opened group
+fn find_closing_paren(s: &str) -> usize {
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return i,
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
 /// Extract CSS v-bind() expressions
 pub fn extract_css_vars(css: &str) -> Vec<String> {
-    let mut vars = Vec::new();
+    find_v_binds(css)
+        .into_iter()
+        .map(|(_, expr)| expr.to_string())
+        .collect()
+}
+
+/// Replace `v-bind(expr)` with `var(--name)`, using [`gen_css_var_name`]
+pub fn rewrite_css_vars(css: &str, id: &str, is_prod: bool) -> String {
+    let mut output = String::with_capacity(css.len());
+    let mut last = 0;
+    for (range, expr) in find_v_binds(css) {
+        output.push_str(&css[last..range.start]);
+        output.push_str("var(--");
+        if is_prod {
+            output.push_str(&gen_css_var_name(id, expr, true));
+        } else {
+            output.push_str(id);
+            output.push('-');
+            output.push_str(&escape_css_var_name(expr));
+        }
+        output.push(')');
+        last = range.end;
+    }
+    output.push_str(&css[last..]);
+    output
+}
+
+/// Name of the custom property set for a `v-bind()` expression, without the
+/// leading `--`: `{id}-{expression}` in development, a hash of both in production
+pub fn gen_css_var_name(id: &str, raw: &str, is_prod: bool) -> String {
+    if is_prod {
+        use sha2::{Digest, Sha256};
+        let digest = Sha256::digest(format!("{}{}", id, raw).as_bytes());
+        digest[..4].iter().map(|b| format!("{:02x}", b)).collect()
+    } else {
+        format!("{}-{}", id, raw)
+    }
+}
+
+/// Escape characters that aren't valid in a CSS identifier
+fn escape_css_var_name(raw: &str) -> String {
+    let mut escaped = String::with_capacity(raw.len());
+    for c in raw.chars() {
+        if " !\"#$%&'()*+,./:;<=>?@[\\]^`{|}~".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Find `v-bind(...)` calls, returning each call's range and trimmed, unquoted expression
+fn find_v_binds(css: &str) -> Vec<(std::ops::Range<usize>, &str)> {
+    let mut found = Vec::new();
     let mut search_from = 0;
 
     while let Some(pos) = css[search_from..].find("v-bind(") {
-        let start = search_from + pos + 7;
-        if let Some(end) = css[start..].find(')') {
-            let expr = css[start..start + end].trim();
-            // Remove quotes if present
-            let expr = expr.trim_matches(|c| c == '"' || c == '\'');
-            vars.push(expr.to_string());
-            search_from = start + end + 1;
-        } else {
-            break;
+        let start = search_from + pos;
+        let args_start = start + "v-bind(".len();
+
+        // Find the matching paren, skipping nested calls and strings
+        let mut depth = 0;
+        let mut quote = None;
+        let mut end = None;
+        for (i, c) in css[args_start..].char_indices() {
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '"' | '\'') => quote = Some(c),
+                (None, '(') => depth += 1,
+                (None, ')') if depth == 0 => {
+                    end = Some(args_start + i);
+                    break;
+                }
+                (None, ')') => depth -= 1,
+                _ => {}
+            }
         }
+        let Some(end) = end else {
+            break;
+        };
+
+        let expr = css[args_start..end].trim();
+        // Remove quotes if present
+        let expr = expr
+            .strip_prefix(['"', '\''])
+            .and_then(|e| e.strip_suffix(['"', '\'']))
+            .unwrap_or(expr);
+        found.push((start..end + 1, expr));
+        search_from = end + 1;
     }
 
-    vars
+    found
 }
 
 #[cfg(test)]
@@ -297,6 +869,409 @@ mod tests {
         assert_eq!(result, ".foo");
     }
 
+    #[cfg(feature = "native")]
+    fn compile_scoped(css: &str, options: StyleCompileOptions) -> Result<String, SfcError> {
+        let style = SfcStyleBlock {
+            content: css.into(),
+            loc: BlockLocation {
+                start: 0,
+                end: css.len(),
+                start_line: 1,
+                start_column: 1,
+                end_line: 1,
+                end_column: 1,
+            },
+            lang: None,
+            src: None,
+            scoped: true,
+            module: None,
+            attrs: Default::default(),
+        };
+        let options = StyleCompileOptions {
+            id: "data-v-123".into(),
+            ..options
+        };
+        compile_style(&style, &options).map(|result| result.code)
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn test_scope_inside_at_rules() {
+        let css = "@media (max-width: 600px) { .a .b:hover, .c::before { color: red } }\n\
+                   @supports (display: grid) { .d { display: grid } }\n\
+                   @container (min-width: 400px) { .e { color: red } }\n\
+                   @layer base { .f { color: red } }\n\
+                   @keyframes spin { from { opacity: 0 } to { opacity: 1 } }";
+        let output = compile_scoped(css, Default::default()).unwrap();
+        assert!(output.contains(".a .b[data-v-123]:hover, .c[data-v-123]:before {"));
+        assert!(output.contains(".d[data-v-123] {"));
+        assert!(output.contains(".e[data-v-123] {"));
+        assert!(output.contains(".f[data-v-123] {"));
+        assert!(output.contains("  from {"), "{}", output);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn test_scope_nested_rules() {
+        let css = ".a { color: red; .b { color: blue } &:hover { color: green } }";
+        // Nesting is lowered for the default targets
+        let output = compile_scoped(css, Default::default()).unwrap();
+        assert!(output.starts_with(".a[data-v-123] {"), "{}", output);
+        assert!(
+            output.contains(".a[data-v-123] .b[data-v-123] {"),
+            "{}",
+            output
+        );
+        assert!(output.contains(".a[data-v-123]:hover {"), "{}", output);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn test_scope_vue_pseudos() {
+        let cases = [
+            (".a :deep(.b) { color: red }", ".a[data-v-123] .b {"),
+            ("::v-deep(.b .c) { color: red }", "[data-v-123] .b .c {"),
+            (".a >>> .b { color: red }", ".a[data-v-123] .b {"),
+            (".a /deep/ .b { color: red }", ".a[data-v-123] .b {"),
+            (
+                ":slotted(div.b):hover { color: red }",
+                "div.b[data-v-123-s]:hover {",
+            ),
+            (".a :global(.b) { color: red }", ".b {"),
+            (":hover { color: red }", "[data-v-123]:hover {"),
+            (".a:not(.b) { color: red }", ".a[data-v-123]:not(.b) {"),
+        ];
+        for (css, expected) in cases {
+            let output = compile_scoped(css, Default::default()).unwrap();
+            assert!(output.starts_with(expected), "{} => {}", css, output);
+        }
+
+        // Rules nested under `:deep()` reach into children as well
+        let output = compile_scoped(".a :deep(.b) { .c { color: red } }", Default::default());
+        let output = output.unwrap();
+        assert!(output.contains(".a[data-v-123] .b .c {"), "{}", output);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn test_compile_style_minify_with_targets() {
+        let output = compile_scoped(
+            ".a {\n  user-select: none;\n  color: #ff0000;\n}\n",
+            StyleCompileOptions {
+                minify: true,
+                targets: Some(crate::css::CssTargets {
+                    safari: Some(13),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            output,
+            ".a[data-v-123]{-webkit-user-select:none;user-select:none;color:red}"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn test_compile_style_default_targets() {
+        // Without configured targets, media queries aren't lowered to range syntax
+        let output = compile_scoped(
+            "@media (min-width: 1px) and (max-width: 2px) { .a { color: red } }",
+            Default::default(),
+        )
+        .unwrap();
+        assert!(
+            output.contains("@media (min-width: 1px) and (max-width: 2px)"),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn test_compile_style_syntax_error() {
+        let error = compile_scoped(
+            ".a { color: red }\n.b!! { color: blue }",
+            Default::default(),
+        )
+        .unwrap_err();
+        assert_eq!(error.code.as_deref(), Some("CSS_SYNTAX_ERROR"));
+        let loc = error.loc.unwrap();
+        assert_eq!(loc.start_line, 2);
+        assert_eq!(loc.start_column, 3);
+        assert_eq!(loc.start, 20);
+    }
+
+    #[test]
+    #[cfg(all(feature = "sass", feature = "native"))]
+    fn test_compile_scss() {
+        let scss = "$color: red;\n.a { color: $color; &:hover { color: blue } .b { margin: 0 } }";
+        let output = compile_scoped(
+            scss,
+            StyleCompileOptions {
+                preprocessor_lang: Some("scss".into()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(
+            output.contains(".a[data-v-123] {\n  color: red;"),
+            "{}",
+            output
+        );
+        assert!(output.contains(".a[data-v-123]:hover"), "{}", output);
+        assert!(output.contains(".a .b[data-v-123]"), "{}", output);
+    }
+
+    #[test]
+    #[cfg(all(feature = "sass", feature = "native"))]
+    fn test_compile_indented_sass() {
+        let output = compile_scoped(
+            "$size: 2px\n.a\n  margin: $size * 2\n",
+            StyleCompileOptions {
+                preprocessor_lang: Some("sass".into()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(output.contains(".a[data-v-123]"), "{}", output);
+        assert!(output.contains("margin: 4px"), "{}", output);
+    }
+
+    #[test]
+    #[cfg(all(feature = "sass", feature = "native"))]
+    fn test_compile_scss_imports() {
+        let dir = std::env::temp_dir().join(format!("vize-sass-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("styles")).unwrap();
+        std::fs::write(dir.join("_local.scss"), "$local: 1px;").unwrap();
+        std::fs::write(dir.join("styles/_theme.scss"), "$primary: green;").unwrap();
+
+        let output = compile_scoped(
+            "@use 'local';\n@use 'theme';\n.a { color: theme.$primary; width: local.$local }",
+            StyleCompileOptions {
+                preprocessor_lang: Some("scss".into()),
+                filename: dir.join("App.vue").to_string_lossy().into_owned(),
+                load_paths: vec![dir.join("styles").to_string_lossy().into_owned()],
+                ..Default::default()
+            },
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let output = output.unwrap();
+        assert!(output.contains("color: green"), "{}", output);
+        assert!(output.contains("width: 1px"), "{}", output);
+    }
+
+    #[test]
+    #[cfg(all(feature = "sass", feature = "native"))]
+    fn test_compile_scss_error() {
+        let error = compile_scoped(
+            ".a { color: red }\n.b { color: $missing }",
+            StyleCompileOptions {
+                preprocessor_lang: Some("scss".into()),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(error.code.as_deref(), Some("SASS_ERROR"));
+        assert!(
+            error.message.contains("Undefined variable"),
+            "{}",
+            error.message
+        );
+        let loc = error.loc.unwrap();
+        assert_eq!(loc.start_line, 2);
+        assert_eq!(loc.start_column, 13);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn test_scope_keyframes_native() {
+        let css = "@keyframes spin { from { opacity: 0 } to { opacity: 1 } }\n\
+                   @media (min-width: 1px) { @keyframes fade { to { opacity: 0 } } }\n\
+                   .a { animation: spin 1s linear infinite, fade 2s, other 3s }\n\
+                   .b { animation-name: fade, other; }";
+        let output = compile_scoped(css, Default::default()).unwrap();
+        assert!(output.contains("@keyframes spin-123 {"), "{}", output);
+        assert!(output.contains("@keyframes fade-123 {"), "{}", output);
+        assert!(
+            output.contains("animation: 1s linear infinite spin-123, 2s fade-123, 3s other;"),
+            "{}",
+            output
+        );
+        assert!(
+            output.contains("animation-name: fade-123, other;"),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_scope_keyframes_text() {
+        let css = "@keyframes spin { from { opacity: 0 } }\n\
+                   @-webkit-keyframes spin { from { opacity: 0 } }\n\
+                   .a { animation: spin 1.5s ease-in-out, spinner 2s; }\n\
+                   .b { -webkit-animation-name: spin; }";
+        let output = apply_scoped_css(css, "data-v-123");
+        assert!(output.contains("@keyframes spin-123"), "{}", output);
+        assert!(output.contains("@-webkit-keyframes spin-123"), "{}", output);
+        assert!(
+            output.contains("animation: spin-123 1.5s ease-in-out, spinner 2s;"),
+            "{}",
+            output
+        );
+        assert!(
+            output.contains("-webkit-animation-name: spin-123;"),
+            "{}",
+            output
+        );
+        assert!(output.contains("from { opacity: 0 }"), "{}", output);
+
+        // Unscoped keyframes and already scoped names are left alone
+        assert_eq!(
+            scope_keyframes(".a { animation: x 1s }", "123"),
+            ".a { animation: x 1s }"
+        );
+        let scoped = "@keyframes spin-123 { to { opacity: 0 } }";
+        assert_eq!(scope_keyframes(scoped, "123"), scoped);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn test_css_modules_native() {
+        let css = ".a:not(.b) { content: \".c\" }\n\
+                   @media (min-width: 10.5em) { :global(.x) .d { animation: fade 1s } }\n\
+                   @keyframes fade { to { opacity: 0 } }";
+        let style = SfcStyleBlock {
+            content: css.into(),
+            loc: Default::default(),
+            lang: None,
+            src: None,
+            scoped: false,
+            module: Some("$style".into()),
+            attrs: Default::default(),
+        };
+        let options = StyleCompileOptions {
+            id: "data-v-123".into(),
+            filename: "src/App.vue".into(),
+            modules_pattern: Some("[name]_[local]_[hash]".into()),
+            ..Default::default()
+        };
+        let result = compile_style(&style, &options).unwrap();
+        let output = &result.code;
+        assert!(output.contains(".App_a_123:not(.App_b_123)"), "{}", output);
+        assert!(output.contains("content: \".c\""), "{}", output);
+        assert!(output.contains("@media (min-width: 10.5em)"), "{}", output);
+        assert!(output.contains(".x .App_d_123"), "{}", output);
+        assert!(output.contains("animation: 1s App_fade_123"), "{}", output);
+        assert!(output.contains("@keyframes App_fade_123"), "{}", output);
+        let locals: Vec<&str> = result.modules.keys().map(String::as_str).collect();
+        assert_eq!(locals, ["a", "b", "d", "fade"]);
+        assert_eq!(result.modules["a"], "App_a_123");
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn test_css_modules_composes() {
+        let css = ".red { color: red }\n\
+                   .comp { composes: red; composes: app from global; composes: btn from \"./b.css\" }";
+        let style = SfcStyleBlock {
+            content: css.into(),
+            loc: Default::default(),
+            lang: None,
+            src: None,
+            scoped: false,
+            module: Some("$style".into()),
+            attrs: Default::default(),
+        };
+        let options = StyleCompileOptions {
+            id: "data-v-123".into(),
+            ..Default::default()
+        };
+        let result = compile_style(&style, &options).unwrap();
+        assert_eq!(result.modules["red"], "_red_123");
+        assert_eq!(result.modules["comp"], "_comp_123 _red_123 app btn");
+        assert!(!result.code.contains("composes"), "{}", result.code);
+    }
+
+    #[test]
+    fn test_css_modules_classes() {
+        let css = ".red { color: red }\n.red.big, div > .small:hover { font-size: 1.5em }";
+        let (output, mapping) = apply_css_modules(css, "[local]_[hash]", "App", "abc");
+        assert_eq!(
+            output,
+            ".red_abc { color: red }\n.red_abc.big_abc, div > .small_abc:hover { font-size: 1.5em }"
+        );
+        assert_eq!(
+            mapping,
+            vec![
+                ("red".to_string(), "red_abc".to_string()),
+                ("big".to_string(), "big_abc".to_string()),
+                ("small".to_string(), "small_abc".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_css_modules_global_and_local() {
+        let css = ":global(.app) .title, :local(.sub) :global(.x.y) { margin: 0 }";
+        let (output, mapping) = apply_css_modules(css, "[name]__[local]", "App", "abc");
+        assert_eq!(output, ".app .App__title, .App__sub .x.y { margin: 0 }");
+        assert_eq!(mapping.len(), 2);
+    }
+
+    #[test]
+    fn test_css_modules_keyframes() {
+        let css = ".a { animation: fade 1.5s ease-in, spin 2s; }\n\
+                   @keyframes fade { 0% { opacity: 0 } 50.5% { opacity: .5 } }\n\
+                   .b { animation-name: fade; }";
+        let (output, mapping) = apply_css_modules(css, "[local]_[hash]", "App", "h");
+        assert!(output.contains(".a_h { animation: fade_h 1.5s ease-in, spin 2s; }"));
+        assert!(output.contains("@keyframes fade_h { 0% { opacity: 0 } 50.5% { opacity: .5 } }"));
+        assert!(output.contains(".b_h { animation-name: fade_h; }"));
+        assert!(mapping.contains(&("fade".to_string(), "fade_h".to_string())));
+    }
+
+    #[test]
+    fn test_css_modules_skips_strings_and_at_rules() {
+        let css = "@media (min-width: 10.5em) { a[href$=\".pdf\"] { content: \".x\" } }";
+        let (output, mapping) = apply_css_modules(css, "[local]_[hash]", "App", "h");
+        assert_eq!(output, css);
+        assert!(mapping.is_empty());
+    }
+
+    #[test]
+    fn test_rewrite_css_vars() {
+        let css = ".a { color: v-bind(color); width: v-bind('size.w + \"px\"'); }";
+        let output = rewrite_css_vars(css, "abc", false);
+        assert_eq!(
+            output,
+            ".a { color: var(--abc-color); width: var(--abc-size\\.w\\ \\+\\ \\\"px\\\"); }"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_css_vars_prod() {
+        let output = rewrite_css_vars(".a { color: v-bind(color) }", "abc", true);
+        assert_eq!(
+            output,
+            format!(
+                ".a {{ color: var(--{}) }}",
+                gen_css_var_name("abc", "color", true)
+            )
+        );
+        assert_eq!(gen_css_var_name("abc", "color", true).len(), 8);
+        assert_eq!(gen_css_var_name("abc", "color", false), "abc-color");
+    }
+
+    #[test]
+    fn test_extract_css_vars_nested_parens() {
+        let vars = extract_css_vars(".a { width: v-bind(fn(a, b)); color: v-bind(\"c\") }");
+        assert_eq!(vars, vec!["fn(a, b)", "c"]);
+    }
+
     #[test]
     fn test_extract_css_vars() {
         let css = ".foo { color: v-bind(color); background: v-bind('bgColor'); }";
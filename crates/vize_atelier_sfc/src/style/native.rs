@@ -0,0 +1,458 @@
+//! Style compilation with LightningCSS.
+//!
+//! Scoped selectors are rewritten on the parsed stylesheet instead of the
+//! source text, so rules inside `@media`, `@supports`, `@container`, `@layer`
+//! and nested rules are scoped the same way as top-level ones. CSS module
+//! names are generated by LightningCSS's own `css_modules` support.
+
+use std::collections::BTreeMap;
+
+use lightningcss::css_modules::{Config as CssModulesConfig, CssModuleReference, Pattern};
+use lightningcss::printer::PrinterOptions;
+use lightningcss::properties::animation::AnimationName;
+use lightningcss::properties::Property;
+use lightningcss::rules::keyframes::KeyframesName;
+use lightningcss::rules::{style::StyleRule, CssRule, CssRuleList};
+use lightningcss::selector::{
+    Combinator, Component, PseudoClass, PseudoElement, Selector, SelectorList,
+};
+use lightningcss::stylesheet::{MinifyOptions, ParserFlags, ParserOptions, StyleSheet};
+use lightningcss::traits::{ParseWithOptions, ToCss};
+use lightningcss::values::ident::CustomIdent;
+use parcel_sourcemap::SourceMap;
+use vize_carton::Bump;
+
+use crate::css::lightningcss_targets;
+use crate::types::StyleCompileOptions;
+
+const PARSER_FLAGS: ParserFlags = ParserFlags::NESTING.union(ParserFlags::DEEP_SELECTOR_COMBINATOR);
+
+/// Printed CSS and its source map
+pub(super) struct NativeOutput {
+    pub code: String,
+    pub map: Option<String>,
+    /// CSS module names, local to generated
+    pub modules: BTreeMap<String, String>,
+}
+
+/// A CSS syntax error inside the style block
+pub(super) struct NativeError {
+    pub message: String,
+    /// Line in the block content (0-based)
+    pub line: u32,
+    /// Column in the line (1-based)
+    pub column: u32,
+}
+
+/// Parse, scope, optionally minify and print `css`.
+///
+/// `scope_attr` is the scope attribute name (`data-v-xxxxxxxx`) when the
+/// block is scoped. `modules_pattern` turns the block into a CSS module whose
+/// classes, ids, keyframes and other custom idents are renamed after the
+/// pattern, where `[local]` is the only placeholder left.
+pub(super) fn compile(
+    css: &str,
+    filename: &str,
+    scope_attr: Option<&str>,
+    modules_pattern: Option<&str>,
+    options: &StyleCompileOptions,
+) -> Result<NativeOutput, NativeError> {
+    // Selectors re-parsed from `:deep()` and friends borrow from here,
+    // so it must outlive the stylesheet
+    let arena = Bump::new();
+
+    let css_modules = modules_pattern
+        .map(|pattern| {
+            let pattern = Pattern::parse(pattern).map_err(|e| NativeError {
+                message: e.to_string(),
+                line: 0,
+                column: 1,
+            })?;
+            Ok(CssModulesConfig {
+                pattern,
+                // Grid lines and container names stay global, like `postcss-modules`
+                grid: false,
+                container: false,
+                ..Default::default()
+            })
+        })
+        .transpose()?;
+    let parser_options = ParserOptions {
+        filename: filename.to_string(),
+        css_modules,
+        flags: PARSER_FLAGS,
+        ..Default::default()
+    };
+    let mut stylesheet = StyleSheet::parse(css, parser_options).map_err(|e| {
+        let (line, column) = e.loc.map_or((0, 1), |loc| (loc.line, loc.column));
+        NativeError {
+            message: e.kind.to_string(),
+            line,
+            column,
+        }
+    })?;
+
+    if let Some(attr) = scope_attr {
+        let scoper = Scoper {
+            attr: arena.alloc_str(attr),
+            slotted_attr: arena.alloc_str(&format!("{}-s", attr)),
+            arena: &arena,
+        };
+        scoper.scope_rules(&mut stylesheet.rules, false);
+        scoper.scope_keyframes(&mut stylesheet.rules);
+    }
+
+    let targets = lightningcss_targets(options.targets.as_ref());
+    if options.minify {
+        // Minification only fails on invalid custom media, which isn't enabled
+        let _ = stylesheet.minify(MinifyOptions {
+            targets,
+            ..Default::default()
+        });
+    }
+
+    let mut source_map = options.source_map.then(|| {
+        let mut map = SourceMap::new("/");
+        let source = map.add_source(filename);
+        let _ = map.set_source_content(source as usize, css);
+        map
+    });
+    let printed = stylesheet.to_css(PrinterOptions {
+        minify: options.minify,
+        source_map: source_map.as_mut(),
+        targets,
+        ..Default::default()
+    });
+    let (code, exports) = match printed {
+        Ok(printed) => (printed.code, printed.exports),
+        Err(e) => {
+            return Err(NativeError {
+                message: e.kind.to_string(),
+                line: e.loc.as_ref().map_or(0, |loc| loc.line),
+                column: e.loc.as_ref().map_or(1, |loc| loc.column),
+            })
+        }
+    };
+    let map = source_map.and_then(|mut map| map.to_json(None).ok());
+    // A class with `composes` applies the composed classes along with its own
+    let modules = exports
+        .into_iter()
+        .flatten()
+        .map(|(local, export)| {
+            let mut classes = export.name;
+            for reference in export.composes {
+                let (CssModuleReference::Local { name }
+                | CssModuleReference::Global { name }
+                | CssModuleReference::Dependency { name, .. }) = reference;
+                classes.push(' ');
+                classes.push_str(&name);
+            }
+            (local, classes)
+        })
+        .collect();
+
+    Ok(NativeOutput { code, map, modules })
+}
+
+/// Vue's scoping pseudo selectors
+enum VuePseudo<'i> {
+    /// `:deep(inner)`, `::v-deep(inner)`, `::v-deep`, `>>>` and `/deep/`
+    Deep(Option<Selector<'i>>),
+    /// `:slotted(inner)` and `::v-slotted(inner)`
+    Slotted(Selector<'i>),
+    /// `:global(inner)` and `::v-global(inner)`
+    Global(Selector<'i>),
+}
+
+/// Adds the scope attribute to the selectors of a stylesheet
+struct Scoper<'i> {
+    attr: &'i str,
+    slotted_attr: &'i str,
+    arena: &'i Bump,
+}
+
+impl<'i> Scoper<'i> {
+    /// Scope every style rule in `rules`, descending into grouping at-rules.
+    /// Keyframe selectors are left alone.
+    fn scope_rules(&self, rules: &mut CssRuleList<'i>, deep: bool) {
+        for rule in rules.0.iter_mut() {
+            match rule {
+                CssRule::Style(style) => self.scope_style_rule(style, deep),
+                CssRule::Nesting(nesting) => self.scope_style_rule(&mut nesting.style, deep),
+                CssRule::Media(media) => self.scope_rules(&mut media.rules, deep),
+                CssRule::Supports(supports) => self.scope_rules(&mut supports.rules, deep),
+                CssRule::Container(container) => self.scope_rules(&mut container.rules, deep),
+                CssRule::LayerBlock(layer) => self.scope_rules(&mut layer.rules, deep),
+                CssRule::Scope(scope) => self.scope_rules(&mut scope.rules, deep),
+                CssRule::StartingStyle(starting) => self.scope_rules(&mut starting.rules, deep),
+                CssRule::MozDocument(document) => self.scope_rules(&mut document.rules, deep),
+                _ => {}
+            }
+        }
+    }
+
+    /// Rules nested in a rule that reaches into child components with
+    /// `:deep()` are not scoped again
+    fn scope_style_rule(&self, rule: &mut StyleRule<'i>, deep: bool) {
+        let mut nested_deep = deep;
+        if !deep {
+            let selectors = rule
+                .selectors
+                .0
+                .iter()
+                .map(|selector| {
+                    let (selector, is_deep) = self.scope_selector(selector, self.attr);
+                    nested_deep |= is_deep;
+                    selector
+                })
+                .collect();
+            rule.selectors = SelectorList::from_vec(selectors);
+        }
+        self.scope_rules(&mut rule.rules, nested_deep);
+    }
+
+    /// Rename the keyframes declared in the stylesheet to `{name}-{id}` and
+    /// point the `animation` and `animation-name` declarations at them
+    fn scope_keyframes(&self, rules: &mut CssRuleList<'i>) {
+        let id = self.attr.strip_prefix("data-v-").unwrap_or(self.attr);
+        let suffix = format!("-{}", id);
+        let mut renamed: Vec<(String, &'i str)> = Vec::new();
+        visit_rules(rules, &mut |rule| {
+            let CssRule::Keyframes(keyframes) = rule else {
+                return;
+            };
+            let name = match &keyframes.name {
+                KeyframesName::Ident(ident) => ident.0.to_string(),
+                KeyframesName::Custom(name) => name.to_string(),
+            };
+            if name.ends_with(&suffix) {
+                return;
+            }
+            let scoped: &'i str = self.arena.alloc_str(&format!("{}{}", name, suffix));
+            keyframes.name = match keyframes.name {
+                KeyframesName::Ident(_) => KeyframesName::Ident(CustomIdent(scoped.into())),
+                KeyframesName::Custom(_) => KeyframesName::Custom(scoped.into()),
+            };
+            renamed.push((name, scoped));
+        });
+        if renamed.is_empty() {
+            return;
+        }
+
+        let rename = |name: &mut AnimationName<'i>| {
+            let current = match name {
+                AnimationName::Ident(ident) => ident.0.as_ref(),
+                AnimationName::String(string) => string.0.as_ref(),
+                AnimationName::None => return,
+            };
+            if let Some(&(_, scoped)) = renamed.iter().find(|(name, _)| name == current) {
+                *name = AnimationName::Ident(CustomIdent(scoped.into()));
+            }
+        };
+        visit_rules(rules, &mut |rule| {
+            let style = match rule {
+                CssRule::Style(style) => style,
+                CssRule::Nesting(nesting) => &mut nesting.style,
+                _ => return,
+            };
+            let declarations = &mut style.declarations;
+            for property in declarations
+                .declarations
+                .iter_mut()
+                .chain(declarations.important_declarations.iter_mut())
+            {
+                match property {
+                    Property::AnimationName(names, _) => names.iter_mut().for_each(rename),
+                    Property::Animation(animations, _) => animations
+                        .iter_mut()
+                        .for_each(|animation| rename(&mut animation.name)),
+                    _ => {}
+                }
+            }
+        });
+    }
+
+    /// Scope a single selector the way `@vue/compiler-sfc` does: the attribute
+    /// goes after the last compound part that isn't a pseudo-class or
+    /// pseudo-element. Returns whether the selector used `:deep()`.
+    fn scope_selector(&self, selector: &Selector<'i>, attr: &'i str) -> (Selector<'i>, bool) {
+        let mut components = parse_order(selector).into_iter();
+        let mut out: Vec<Component<'i>> = Vec::with_capacity(selector.len() + 2);
+        // Index right after the node that receives the attribute
+        let mut inject_at = None;
+
+        while let Some(component) = components.next() {
+            let Some(pseudo) = self.vue_pseudo(&component) else {
+                let is_node = match &component {
+                    Component::Combinator(_) => false,
+                    Component::Is(_) | Component::Where(_) => inject_at.is_none(),
+                    component => !is_pseudo(component),
+                };
+                out.push(component);
+                if is_node {
+                    inject_at = Some(out.len());
+                }
+                continue;
+            };
+
+            if matches!(
+                out.last(),
+                Some(Component::Combinator(Combinator::PseudoElement))
+            ) {
+                out.pop();
+            }
+            match pseudo {
+                // `.a :global(.b)` -> `.b`
+                VuePseudo::Global(inner) => return (inner, false),
+                // `.a :slotted(.b)` -> `.a .b[data-v-xxx-s]`
+                VuePseudo::Slotted(inner) => {
+                    let (inner, _) = self.scope_selector(&inner, self.slotted_attr);
+                    out.extend(parse_order(&inner));
+                    out.extend(components);
+                    return (Selector::from(out), false);
+                }
+                // `.a :deep(.b)` -> `.a[data-v-xxx] .b`
+                VuePseudo::Deep(inner) => {
+                    out.insert(inject_at.unwrap_or(0), attribute(attr));
+                    let is_combinator = matches!(component, Component::Combinator(_));
+                    if let Some(inner) = inner {
+                        if !matches!(out.last(), Some(Component::Combinator(_))) {
+                            out.push(Component::Combinator(Combinator::Descendant));
+                        }
+                        out.extend(parse_order(&inner));
+                    } else if is_combinator {
+                        out.push(Component::Combinator(Combinator::Descendant));
+                    }
+                    out.extend(components);
+                    return (Selector::from(out), true);
+                }
+            }
+        }
+
+        let nested_only = inject_at
+            .and_then(|at| out.get(at - 1))
+            .is_some_and(|node| matches!(node, Component::Nesting));
+        // `&:hover` is already scoped through its parent rule
+        if !nested_only {
+            out.insert(inject_at.unwrap_or(0), attribute(attr));
+        }
+        (Selector::from(out), false)
+    }
+
+    /// Recognize Vue's scoping pseudo selectors
+    fn vue_pseudo(&self, component: &Component<'i>) -> Option<VuePseudo<'i>> {
+        let (name, has_args) = match component {
+            Component::Combinator(Combinator::DeepDescendant | Combinator::Deep) => {
+                return Some(VuePseudo::Deep(None));
+            }
+            // Parsed by LightningCSS itself in CSS modules
+            Component::NonTSPseudoClass(PseudoClass::Global { selector }) => {
+                return Some(VuePseudo::Global((**selector).clone()));
+            }
+            Component::NonTSPseudoClass(PseudoClass::Custom { name })
+            | Component::PseudoElement(PseudoElement::Custom { name }) => (name.as_ref(), false),
+            Component::NonTSPseudoClass(PseudoClass::CustomFunction { name, .. })
+            | Component::PseudoElement(PseudoElement::CustomFunction { name, .. }) => {
+                (name.as_ref(), true)
+            }
+            _ => return None,
+        };
+
+        match (name, has_args) {
+            ("deep" | "v-deep", false) => Some(VuePseudo::Deep(None)),
+            ("deep" | "v-deep", true) => Some(VuePseudo::Deep(Some(self.argument(component)?))),
+            ("slotted" | "v-slotted", true) => Some(VuePseudo::Slotted(self.argument(component)?)),
+            ("global" | "v-global", true) => Some(VuePseudo::Global(self.argument(component)?)),
+            _ => None,
+        }
+    }
+
+    /// Parse the selector argument of a functional pseudo selector.
+    ///
+    /// LightningCSS keeps unknown pseudo arguments as raw tokens, so the
+    /// argument is printed and parsed again as a selector.
+    fn argument(&self, component: &Component<'i>) -> Option<Selector<'i>> {
+        let printed = Selector::from(vec![component.clone()])
+            .to_css_string(PrinterOptions::default())
+            .ok()?;
+        let inner = &printed[printed.find('(')? + 1..printed.rfind(')')?];
+        let inner: &'i str = self.arena.alloc_str(inner);
+        Selector::parse_string_with_options(
+            inner,
+            ParserOptions {
+                flags: PARSER_FLAGS,
+                ..Default::default()
+            },
+        )
+        .ok()
+    }
+}
+
+/// Call `f` on every rule in `rules`, including nested ones
+fn visit_rules<'i>(rules: &mut CssRuleList<'i>, f: &mut impl FnMut(&mut CssRule<'i>)) {
+    for rule in rules.0.iter_mut() {
+        f(rule);
+        let children = match rule {
+            CssRule::Style(style) => &mut style.rules,
+            CssRule::Nesting(nesting) => &mut nesting.style.rules,
+            CssRule::Media(media) => &mut media.rules,
+            CssRule::Supports(supports) => &mut supports.rules,
+            CssRule::Container(container) => &mut container.rules,
+            CssRule::LayerBlock(layer) => &mut layer.rules,
+            CssRule::Scope(scope) => &mut scope.rules,
+            CssRule::StartingStyle(starting) => &mut starting.rules,
+            CssRule::MozDocument(document) => &mut document.rules,
+            _ => continue,
+        };
+        visit_rules(children, f);
+    }
+}
+
+/// Components of `selector` in source order.
+///
+/// Selectors are stored right to left by compound, with each compound kept in
+/// source order, so only the compounds are reversed.
+fn parse_order<'i>(selector: &Selector<'i>) -> Vec<Component<'i>> {
+    let mut components = Vec::with_capacity(selector.len());
+    let mut compound = Vec::new();
+    for component in selector.iter_raw_parse_order_from(0) {
+        if component.is_combinator() {
+            components.extend(compound.drain(..).rev());
+            components.push(component.clone());
+        } else {
+            compound.push(component.clone());
+        }
+    }
+    components.extend(compound.into_iter().rev());
+    components
+}
+
+/// `[data-v-xxxxxxxx]`
+fn attribute<'i>(attr: &'i str) -> Component<'i> {
+    Component::AttributeInNoNamespaceExists {
+        local_name: attr.into(),
+        local_name_lower: attr.into(),
+    }
+}
+
+/// Pseudo-classes and pseudo-elements, which are never given the attribute
+fn is_pseudo(component: &Component<'_>) -> bool {
+    matches!(
+        component,
+        Component::Negation(_)
+            | Component::Root
+            | Component::Empty
+            | Component::Scope
+            | Component::Nth(_)
+            | Component::NthOf(_)
+            | Component::NonTSPseudoClass(_)
+            | Component::Slotted(_)
+            | Component::Part(_)
+            | Component::Host(_)
+            | Component::Where(_)
+            | Component::Is(_)
+            | Component::Any(..)
+            | Component::Has(_)
+            | Component::PseudoElement(_)
+    )
+}
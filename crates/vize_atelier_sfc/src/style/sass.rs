@@ -0,0 +1,101 @@
+//! Sass and SCSS preprocessing with grass.
+//!
+//! The block content is compiled as if it were the `.vue` file itself, so
+//! relative `@use` and `@import` resolve next to the component, then against
+//! the configured load paths.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use grass_compiler::{ErrorKind, Fs, InputSyntax, Options, OutputStyle};
+
+/// A Sass error, with its position when it is inside the style block
+pub(super) struct SassError {
+    pub message: String,
+    /// Line in the block content (0-based) and column (1-based)
+    pub position: Option<(u32, u32)>,
+}
+
+/// Compile Sass (`indented`) or SCSS to CSS
+pub(super) fn compile(
+    source: &str,
+    filename: &str,
+    indented: bool,
+    load_paths: &[String],
+) -> Result<String, SassError> {
+    let fs = BlockFs {
+        path: Path::new(filename),
+        source,
+    };
+    let options = Options::default()
+        .fs(&fs)
+        .quiet(true)
+        .style(OutputStyle::Expanded)
+        .load_paths(load_paths)
+        .input_syntax(if indented {
+            InputSyntax::Sass
+        } else {
+            InputSyntax::Scss
+        });
+
+    grass_compiler::from_path(filename, &options).map_err(|error| match error.kind() {
+        ErrorKind::ParseError { message, loc, .. } => {
+            if loc.file.name() == filename {
+                SassError {
+                    message,
+                    position: Some((loc.begin.line as u32, loc.begin.column as u32 + 1)),
+                }
+            } else {
+                SassError {
+                    message: format!("{} ({})", message, loc),
+                    position: None,
+                }
+            }
+        }
+        ErrorKind::IoError(error) => SassError {
+            message: error.to_string(),
+            position: None,
+        },
+        ErrorKind::FromUtf8Error(message) => SassError {
+            message,
+            position: None,
+        },
+        _ => SassError {
+            message: "Unknown Sass error".to_string(),
+            position: None,
+        },
+    })
+}
+
+/// The real file system, with the style block standing in for the `.vue` file
+#[derive(Debug)]
+struct BlockFs<'a> {
+    path: &'a Path,
+    source: &'a str,
+}
+
+impl Fs for BlockFs<'_> {
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path == self.path || path.is_file()
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        if path == self.path {
+            Ok(self.source.as_bytes().to_vec())
+        } else {
+            std::fs::read(path)
+        }
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        if path == self.path {
+            Ok(path.to_path_buf())
+        } else {
+            std::fs::canonicalize(path)
+        }
+    }
+}
@@ -5,6 +5,9 @@
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
 
 /// SFC Descriptor - parsed result of a .vue file
 /// Uses Cow<str> for zero-copy parsing with optional ownership
@@ -42,7 +45,8 @@ pub struct SfcDescriptor<'a> {
     #[serde(default)]
     pub slotted: bool,
 
-    /// Whether the component should inherit attrs
+    /// Whether template changes must reload the component instead of only
+    /// rerendering it (see [`diff_descriptors`](crate::diff_descriptors))
     #[serde(default)]
     pub should_force_reload: bool,
 }
@@ -340,6 +344,35 @@ pub enum BindingType {
     Options,
 }
 
+impl From<BindingType> for vize_atelier_core::options::BindingType {
+    fn from(binding_type: BindingType) -> Self {
+        match binding_type {
+            BindingType::Data => Self::Data,
+            BindingType::Props => Self::Props,
+            BindingType::PropsAliased => Self::PropsAliased,
+            BindingType::SetupLet => Self::SetupLet,
+            BindingType::SetupConst => Self::SetupConst,
+            BindingType::SetupMaybeRef => Self::SetupMaybeRef,
+            BindingType::SetupRef => Self::SetupRef,
+            BindingType::SetupReactiveConst => Self::SetupReactiveConst,
+            BindingType::LiteralConst => Self::LiteralConst,
+            BindingType::Options => Self::Options,
+        }
+    }
+}
+
+impl From<&BindingMetadata> for vize_atelier_core::options::BindingMetadata {
+    fn from(metadata: &BindingMetadata) -> Self {
+        Self {
+            bindings: metadata
+                .bindings
+                .iter()
+                .map(|(name, &binding_type)| (name.as_str().into(), binding_type.into()))
+                .collect(),
+        }
+    }
+}
+
 /// Parse options for SFC
 #[derive(Debug, Clone, Default)]
 pub struct SfcParseOptions {
@@ -385,6 +418,40 @@ pub struct SfcCompileOptions {
 
     /// Style compile options
     pub style: StyleCompileOptions,
+
+    /// Scope ID for `data-v-*` attributes.
+    /// Derived from the filename (see [`generate_scope_id`](crate::generate_scope_id)) when unset.
+    pub scope_id: Option<String>,
+
+    /// Project root, so generated scope IDs don't depend on where the project lives
+    pub root: Option<String>,
+
+    /// Production mode: the source is hashed into generated scope IDs as well
+    pub is_prod: bool,
+
+    /// Register the component with the Vue HMR runtime and attach `__hmrId`
+    /// and `__file`. Ignored in production.
+    pub hmr: bool,
+
+    /// Loader for the content of blocks with a `src` attribute.
+    /// Reads from the file system when unset.
+    pub file_loader: Option<Arc<dyn FileLoader>>,
+}
+
+/// Loads external block content referenced by `src` attributes
+pub trait FileLoader: std::fmt::Debug + Send + Sync {
+    /// Read the file at `path`, already resolved against the SFC's directory
+    fn read_file(&self, path: &Path) -> std::io::Result<String>;
+}
+
+/// [`FileLoader`] reading from the file system
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FsFileLoader;
+
+impl FileLoader for FsFileLoader {
+    fn read_file(&self, path: &Path) -> std::io::Result<String> {
+        std::fs::read_to_string(path)
+    }
 }
 
 /// Script compile options
@@ -407,6 +474,9 @@ pub struct ScriptCompileOptions {
 
     /// Define model options
     pub define_model: bool,
+
+    /// Fail compilation on TypeScript errors instead of reporting them in `errors`
+    pub strict: bool,
 }
 
 /// Props destructure mode
@@ -442,7 +512,7 @@ pub struct TemplateCompileOptions {
     /// Whether TypeScript mode
     pub is_ts: bool,
 
-    /// Compiler options
+    /// Compiler options, including custom node and directive transforms
     pub compiler_options: Option<vize_atelier_dom::DomCompilerOptions>,
 }
 
@@ -458,16 +528,40 @@ pub struct StyleCompileOptions {
     /// Whether trim
     pub trim: bool,
 
-    /// Source map
+    /// Whether to generate a source map of the compiled CSS
     pub source_map: bool,
 
-    /// Preprocessor language
+    /// Whether to minify the compiled CSS
+    pub minify: bool,
+
+    /// Browser targets for syntax lowering and vendor prefixes, Vite's
+    /// default build target when not set
+    pub targets: Option<crate::css::CssTargets>,
+
+    /// Whether in production mode, which hashes `v-bind()` variable names
+    pub is_prod: bool,
+
+    /// Preprocessor language, overriding the `lang` attribute of the block
     pub preprocessor_lang: Option<String>,
 
+    /// Path of the SFC, which relative preprocessor imports resolve against
+    pub filename: String,
+
+    /// Extra directories searched by Sass `@use` and `@import`
+    pub load_paths: Vec<String>,
+
     /// Custom data attributes to add
     pub data_attrs: Vec<String>,
+
+    /// Name pattern for CSS module classes and keyframes, with `[local]`,
+    /// `[name]` (component name) and `[hash]` (scope ID) placeholders.
+    /// Defaults to [`DEFAULT_MODULES_PATTERN`](crate::style::DEFAULT_MODULES_PATTERN).
+    pub modules_pattern: Option<String>,
 }
 
+/// CSS module class names by module name (`$style` or the `module` attribute value)
+pub type CssModules = BTreeMap<String, BTreeMap<String, String>>;
+
 /// SFC compilation result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -478,9 +572,12 @@ pub struct SfcCompileResult {
     /// Compiled CSS (from all style blocks)
     pub css: Option<String>,
 
-    /// Source map
+    /// Source map of the compiled JavaScript module
     pub map: Option<serde_json::Value>,
 
+    /// Source map of the compiled CSS
+    pub css_map: Option<serde_json::Value>,
+
     /// Errors
     pub errors: Vec<SfcError>,
 
@@ -489,6 +586,15 @@ pub struct SfcCompileResult {
 
     /// Binding metadata
     pub bindings: Option<BindingMetadata>,
+
+    /// Generated class and keyframes names of `<style module>` blocks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub css_modules: Option<CssModules>,
+
+    /// Files loaded through `src` attributes, which should trigger a
+    /// recompile when they change
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<String>,
 }
 
 /// SFC error/warning
@@ -5,8 +5,13 @@
 use std::fmt::Write;
 
 use crate::ir::*;
+use crate::transforms::generate_memo_check;
+use crate::VaporCompilerOptions;
 use rustc_hash::FxHashMap;
-use vize_atelier_core::ExpressionNode;
+use vize_atelier_core::options::{BindingMetadata, BindingType};
+use vize_atelier_core::transforms::transform_expression::prefix_identifiers_in_expression;
+use vize_atelier_core::{ExpressionNode, SimpleExpressionNode};
+use vize_carton::general::is_simple_identifier;
 
 /// Vapor code generation result
 pub struct VaporGenerateResult {
@@ -18,7 +23,16 @@ pub struct VaporGenerateResult {
 
 /// Generate Vapor code from IR
 pub fn generate_vapor(ir: &RootIRNode<'_>) -> VaporGenerateResult {
-    let mut ctx = GenerateContext::new(&ir.element_template_map);
+    generate_vapor_with_options(ir, &VaporCompilerOptions::default())
+}
+
+/// Generate Vapor code from IR. In inline mode, setup bindings are referenced
+/// directly instead of through `_ctx`.
+pub fn generate_vapor_with_options(
+    ir: &RootIRNode<'_>,
+    options: &VaporCompilerOptions,
+) -> VaporGenerateResult {
+    let mut ctx = GenerateContext::new(&ir.element_template_map, options);
 
     // Template helper is always used if we have templates
     if !ir.templates.is_empty() {
@@ -27,12 +41,20 @@ pub fn generate_vapor(ir: &RootIRNode<'_>) -> VaporGenerateResult {
 
     // Generate template declarations (to separate string, we'll prepend imports later)
     let mut template_code = String::new();
+    // Templates of root nodes are flagged so that hydration adopts them
+    // from the component's server-rendered DOM
     for (i, template) in ir.templates.iter().enumerate() {
+        let root = if ir.root_template_indexes.contains(&i) {
+            ", true"
+        } else {
+            ""
+        };
         writeln!(
             template_code,
-            "const t{} = _template(\"{}\", true)",
+            "const t{} = _template(\"{}\"{})",
             i,
-            escape_template(template)
+            escape_template(template),
+            root
         )
         .ok();
     }
@@ -44,8 +66,48 @@ pub fn generate_vapor(ir: &RootIRNode<'_>) -> VaporGenerateResult {
     ctx.push_line("export function render(_ctx) {");
     ctx.indent();
 
-    // Generate block content (includes template instantiation, text nodes, operations, effects, return)
-    generate_block(&mut ctx, &ir.block, &ir.element_template_map);
+    if ir.has_template_ref {
+        ctx.use_helper("createTemplateRefSetter");
+        ctx.push_line("const _setTemplateRef = _createTemplateRefSetter()");
+    }
+
+    // Assets are resolved once, before anything is rendered
+    for component in ir.component.iter() {
+        ctx.use_helper("resolveComponent");
+        ctx.push_line(
+            &[
+                "const ",
+                &component_var(component),
+                " = _resolveComponent(\"",
+                component.as_str(),
+                "\")",
+            ]
+            .concat(),
+        );
+    }
+    for directive in ir.directive.iter() {
+        ctx.use_helper("resolveDirective");
+        ctx.push_line(
+            &[
+                "const ",
+                &asset_var("_directive_", directive),
+                " = _resolveDirective(\"",
+                directive.as_str(),
+                "\")",
+            ]
+            .concat(),
+        );
+    }
+    if ir.has_deferred_v_show {
+        ctx.push_line("const deferredApplyVShows = []");
+    }
+
+    // Generate block content (includes template instantiation, text nodes, operations, effects)
+    generate_block_content(&mut ctx, &ir.block, &ir.element_template_map);
+    if ir.has_deferred_v_show {
+        ctx.push_line("deferredApplyVShows.forEach(fn => fn())");
+    }
+    generate_block_return(&mut ctx, &ir.block);
 
     ctx.deindent();
     ctx.push_line("}");
@@ -108,10 +170,19 @@ struct GenerateContext<'a> {
     delegate_events: std::collections::HashSet<std::string::String>,
     /// Text node references (element_id -> text_node_var)
     text_nodes: FxHashMap<usize, std::string::String>,
+    /// Number of v-memo dependency caches declared
+    memo_count: usize,
+    /// Whether the render function is inlined in setup
+    inline: bool,
+    /// Bindings of `<script setup>`
+    bindings: Option<&'a BindingMetadata>,
 }
 
 impl<'a> GenerateContext<'a> {
-    fn new(element_template_map: &'a FxHashMap<usize, usize>) -> Self {
+    fn new(
+        element_template_map: &'a FxHashMap<usize, usize>,
+        options: &'a VaporCompilerOptions,
+    ) -> Self {
         Self {
             code: String::with_capacity(4096),
             indent_level: 0,
@@ -120,9 +191,16 @@ impl<'a> GenerateContext<'a> {
             used_helpers: std::collections::HashSet::new(),
             delegate_events: std::collections::HashSet::new(),
             text_nodes: FxHashMap::default(),
+            memo_count: 0,
+            inline: options.inline,
+            bindings: options.binding_metadata.as_ref(),
         }
     }
 
+    fn binding_type(&self, name: &str) -> Option<BindingType> {
+        self.bindings?.bindings.get(name).copied()
+    }
+
     fn add_delegate_event(&mut self, event_name: &str) {
         self.delegate_events.insert(event_name.to_string());
     }
@@ -166,6 +244,12 @@ impl<'a> GenerateContext<'a> {
         }
     }
 
+    fn next_memo(&mut self) -> String {
+        let name = format!("_memo{}", self.memo_count);
+        self.memo_count += 1;
+        name
+    }
+
     fn next_temp(&mut self) -> String {
         let name = format!("_t{}", self.temp_count);
         self.temp_count += 1;
@@ -183,21 +267,33 @@ fn generate_imports(ctx: &GenerateContext) -> String {
     fn helper_priority(name: &str) -> u32 {
         match name {
             "resolveComponent" => 1,
-            "createComponentWithFallback" => 2,
+            "VaporTransition" | "VaporTransitionGroup" | "VaporTeleport" | "VaporKeepAlive" => 2,
+            "createComponentWithFallback" => 3,
+            "resolveDirective" => 4,
+            "createTemplateRefSetter" => 5,
+            "unref" => 6,
             "child" => 10,
             "next" => 11,
-            "txt" => 20,
+            "nthChild" => 12,
+            "txt" => 19,
+            "applyVShow" => 20,
+            "applyTextModel" | "applyCheckboxModel" | "applyRadioModel" | "applySelectModel"
+            | "applyDynamicModel" => 20,
             "toDisplayString" => 21,
             "setText" => 22,
+            "setHtml" => 23,
             "setClass" => 30,
             "setProp" => 31,
             "setStyle" => 32,
             "createInvoker" => 40,
             "delegateEvents" => 41,
+            "withVaporDirectives" => 60,
             "setInsertionState" => 78,
             "renderEffect" => 79,
             "createIf" => 80,
             "createFor" => 81,
+            "createComponent" => 85,
+            "createDynamicComponent" => 86,
             "template" => 100,
             _ => 50,
         }
@@ -220,6 +316,16 @@ fn generate_block(
     ctx: &mut GenerateContext,
     block: &BlockIRNode<'_>,
     element_template_map: &FxHashMap<usize, usize>,
+) {
+    generate_block_content(ctx, block, element_template_map);
+    generate_block_return(ctx, block);
+}
+
+/// Generate the statements of a block, up to its return
+fn generate_block_content(
+    ctx: &mut GenerateContext,
+    block: &BlockIRNode<'_>,
+    element_template_map: &FxHashMap<usize, usize>,
 ) {
     // Instantiate templates for elements in this block's returns
     for element_id in block.returns.iter() {
@@ -234,34 +340,102 @@ fn generate_block(
         }
     }
 
-    // Generate text node references for effects in this block
-    for effect in block.effect.iter() {
-        for op in effect.operations.iter() {
-            if let OperationNode::SetText(set_text) = op {
-                ctx.use_helper("txt");
-                let var_name = ctx.next_text_node(set_text.element);
-                let mut line = std::string::String::with_capacity(32);
-                line.push_str("const ");
-                line.push_str(&var_name);
-                line.push_str(" = _txt(n");
-                line.push_str(&set_text.element.to_string());
-                line.push(')');
-                ctx.push_line(&line);
+    // Locate dynamic descendants inside the instantiated templates
+    for child_ref in block.child_refs.iter() {
+        let id = child_ref.id.to_string();
+        let parent = child_ref.parent.to_string();
+        let index = child_ref.index.to_string();
+        let line = match child_ref.prev {
+            Some(prev) => {
+                ctx.use_helper("next");
+                [
+                    "const n",
+                    &id,
+                    " = _next(n",
+                    &prev.to_string(),
+                    ", ",
+                    &index,
+                    ")",
+                ]
+                .concat()
+            }
+            None if child_ref.index == 0 => {
+                ctx.use_helper("child");
+                ["const n", &id, " = _child(n", &parent, ")"].concat()
             }
+            None => {
+                ctx.use_helper("nthChild");
+                ["const n", &id, " = _nthChild(n", &parent, ", ", &index, ")"].concat()
+            }
+        };
+        ctx.push_line(&line);
+    }
+
+    // Generate text node references for text set in this block
+    let operations = block.operation.iter().chain(
+        block
+            .effect
+            .iter()
+            .flat_map(|effect| effect.operations.iter()),
+    );
+    for op in operations {
+        if let OperationNode::SetText(set_text) = op {
+            ctx.use_helper("txt");
+            let var_name = ctx.next_text_node(set_text.element);
+            let mut line = std::string::String::with_capacity(32);
+            line.push_str("const ");
+            line.push_str(&var_name);
+            line.push_str(" = _txt(n");
+            line.push_str(&set_text.element.to_string());
+            line.push(')');
+            ctx.push_line(&line);
         }
     }
 
-    // Generate operations
-    for op in block.operation.iter() {
+    // Generate operations. Custom directives of an element are applied
+    // together, where the first of them is.
+    let mut directive_elements = std::vec::Vec::new();
+    for (i, op) in block.operation.iter().enumerate() {
+        if let OperationNode::Directive(directive) = op {
+            if !directive.builtin {
+                if !directive_elements.contains(&directive.element) {
+                    directive_elements.push(directive.element);
+                    let directives: Vec<_> = block.operation[i..]
+                        .iter()
+                        .filter_map(|op| match op {
+                            OperationNode::Directive(d)
+                                if !d.builtin && d.element == directive.element =>
+                            {
+                                Some(d)
+                            }
+                            _ => None,
+                        })
+                        .collect();
+                    generate_custom_directives(ctx, directive.element, &directives);
+                }
+                continue;
+            }
+        }
         generate_operation(ctx, op, element_template_map);
     }
 
-    // Generate effects
-    for effect in block.effect.iter() {
-        generate_effect(ctx, effect, element_template_map);
+    // Generate effects. Adjacent effects of a v-memo subtree share a check.
+    let mut effects = block.effect.iter().peekable();
+    while let Some(effect) = effects.next() {
+        let Some(ref deps) = effect.memo else {
+            generate_effect(ctx, effect, element_template_map);
+            continue;
+        };
+        let mut memoized = vec![effect];
+        while let Some(next) = effects.next_if(|e| e.memo.as_ref() == Some(deps)) {
+            memoized.push(next);
+        }
+        generate_memo_effect(ctx, deps, &memoized, element_template_map);
     }
+}
 
-    // Generate return
+/// Generate the return of a block
+fn generate_block_return(ctx: &mut GenerateContext, block: &BlockIRNode<'_>) {
     if !block.returns.is_empty() {
         let returns = block
             .returns
@@ -303,6 +477,9 @@ fn generate_operation(
         OperationNode::SetTemplateRef(set_ref) => {
             generate_set_template_ref(ctx, set_ref);
         }
+        OperationNode::DeclareOldRef(declare) => {
+            ctx.push_line(&["let r", &declare.id.to_string()].concat());
+        }
         OperationNode::InsertNode(insert) => {
             generate_insert_node(ctx, insert);
         }
@@ -319,7 +496,7 @@ fn generate_operation(
             generate_for(ctx, for_node, element_template_map);
         }
         OperationNode::CreateComponent(component) => {
-            generate_create_component(ctx, component);
+            generate_create_component(ctx, component, element_template_map);
         }
         OperationNode::SlotOutlet(slot) => {
             generate_slot_outlet(ctx, slot);
@@ -356,6 +533,31 @@ fn generate_effect(
     }
 }
 
+/// Generate a single effect for the effects of a v-memo subtree, which
+/// returns early while the memo dependencies are unchanged
+fn generate_memo_effect(
+    ctx: &mut GenerateContext,
+    deps: &str,
+    effects: &[&IREffect<'_>],
+    element_template_map: &FxHashMap<usize, usize>,
+) {
+    ctx.use_helper("renderEffect");
+
+    let cache_var = ctx.next_memo();
+    ctx.push_line(&["let ", &cache_var].concat());
+    ctx.push_line("_renderEffect(() => {");
+    ctx.indent();
+    let deps = prefix_identifiers_in_expression(deps);
+    for line in generate_memo_check(&cache_var, &deps).lines() {
+        ctx.push_line(line);
+    }
+    for op in effects.iter().flat_map(|effect| effect.operations.iter()) {
+        generate_operation(ctx, op, element_template_map);
+    }
+    ctx.deindent();
+    ctx.push_line("})");
+}
+
 /// Generate operation inline (returns code string)
 fn generate_operation_inline(ctx: &mut GenerateContext, op: &OperationNode<'_>) -> String {
     match op {
@@ -407,6 +609,19 @@ fn generate_operation_inline(ctx: &mut GenerateContext, op: &OperationNode<'_>)
                 format!("_setText({}, {})", text_ref, values.join(" + "))
             }
         }
+        OperationNode::SetHtml(set_html) => {
+            ctx.use_helper("setHtml");
+            [
+                "_setHtml(n",
+                &set_html.element.to_string(),
+                ", ",
+                &html_value(set_html),
+                ")",
+            ]
+            .concat()
+        }
+        OperationNode::SetDynamicProps(set_props) => dynamic_props_call(ctx, set_props),
+        OperationNode::SetTemplateRef(set_ref) => template_ref_call(ctx, set_ref),
         _ => String::from("/* unsupported */"),
     }
 }
@@ -438,16 +653,34 @@ fn generate_set_prop(ctx: &mut GenerateContext, set_prop: &SetPropIRNode<'_>) {
 
 /// Generate SetDynamicProps
 fn generate_set_dynamic_props(ctx: &mut GenerateContext, set_props: &SetDynamicPropsIRNode<'_>) {
-    let element = format!("n{}", set_props.element);
+    let call = dynamic_props_call(ctx, set_props);
+    ctx.push_line(&call);
+}
 
-    for prop in set_props.props.iter() {
-        let expr = if prop.is_static {
-            format!("\"{}\"", prop.content)
-        } else {
-            prop.content.to_string()
-        };
-        ctx.push_line(&format!("Object.assign({}, {})", element, expr));
-    }
+/// Call setting the props of `v-bind="obj"`
+fn dynamic_props_call(ctx: &mut GenerateContext, set_props: &SetDynamicPropsIRNode<'_>) -> String {
+    ctx.use_helper("setDynamicProps");
+
+    let props = set_props
+        .props
+        .iter()
+        .map(|prop| {
+            if prop.is_static {
+                ["\"", prop.content.as_str(), "\""].concat()
+            } else {
+                prefix_identifiers_in_expression(&prop.content)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    [
+        "_setDynamicProps(n",
+        &set_props.element.to_string(),
+        ", [",
+        &props,
+        "])",
+    ]
+    .concat()
 }
 
 /// Generate SetText
@@ -527,28 +760,64 @@ fn is_inline_statement(handler: &str) -> bool {
 
 /// Generate SetHtml
 fn generate_set_html(ctx: &mut GenerateContext, set_html: &SetHtmlIRNode<'_>) {
-    let element = format!("n{}", set_html.element);
+    ctx.use_helper("setHtml");
+    ctx.push_line(&format!(
+        "_setHtml(n{}, {})",
+        set_html.element,
+        html_value(set_html)
+    ));
+}
 
-    let value = if set_html.value.is_static {
+/// Value of a SetHtml operation
+fn html_value(set_html: &SetHtmlIRNode<'_>) -> String {
+    if set_html.value.is_static {
         format!("\"{}\"", set_html.value.content)
     } else {
-        set_html.value.content.to_string()
-    };
-
-    ctx.push_line(&format!("{}.innerHTML = {}", element, value));
+        format!("_ctx.{}", set_html.value.content)
+    }
 }
 
 /// Generate SetTemplateRef
 fn generate_set_template_ref(ctx: &mut GenerateContext, set_ref: &SetTemplateRefIRNode<'_>) {
-    let element = format!("n{}", set_ref.element);
+    let call = template_ref_call(ctx, set_ref);
+    ctx.push_line(&call);
+}
 
-    let value = if set_ref.value.is_static {
-        format!("\"{}\"", set_ref.value.content)
+/// Call setting a template ref. Dynamic refs pass and update the previous
+/// ref, refs in v-for collect their elements into an array.
+fn template_ref_call(ctx: &mut GenerateContext, set_ref: &SetTemplateRefIRNode<'_>) -> String {
+    let element = set_ref.element.to_string();
+    let value = &set_ref.value.content;
+    let value = if !set_ref.value.is_static {
+        prefix_identifiers_in_expression(value)
+    } else if ctx.inline
+        && matches!(
+            ctx.binding_type(value),
+            Some(BindingType::SetupLet | BindingType::SetupRef | BindingType::SetupMaybeRef)
+        )
+    {
+        // Inline render functions have no setup state to look the ref up
+        // in, so the ref itself is passed
+        value.to_string()
     } else {
-        set_ref.value.content.to_string()
+        ["\"", value.as_str(), "\""].concat()
     };
 
-    ctx.push_line(&format!("_setRef({}, {})", element, value));
+    let mut call = String::new();
+    if set_ref.effect {
+        call.push_str(&["r", &element, " = "].concat());
+    }
+    call.push_str(&["_setTemplateRef(n", &element, ", ", &value].concat());
+    if set_ref.effect {
+        call.push_str(&[", r", &element].concat());
+    } else if set_ref.ref_for {
+        call.push_str(", void 0");
+    }
+    if set_ref.ref_for {
+        call.push_str(", true");
+    }
+    call.push(')');
+    call
 }
 
 /// Generate InsertNode
@@ -583,6 +852,19 @@ fn generate_prepend_node(ctx: &mut GenerateContext, prepend: &PrependNodeIRNode)
 
 /// Generate Directive
 fn generate_directive(ctx: &mut GenerateContext, directive: &DirectiveIRNode<'_>) {
+    if directive.builtin && directive.name == "show" {
+        generate_v_show(ctx, directive);
+        return;
+    }
+    if let Some(model_type) = directive.model_type {
+        generate_v_model(ctx, directive, model_type);
+        return;
+    }
+    if !directive.builtin {
+        generate_custom_directives(ctx, directive.element, &[directive]);
+        return;
+    }
+
     let element = format!("n{}", directive.element);
     let name = &directive.name;
 
@@ -622,6 +904,186 @@ fn generate_directive(ctx: &mut GenerateContext, directive: &DirectiveIRNode<'_>
     ));
 }
 
+/// The `{ name: true }` object of directive modifiers, with the names that
+/// aren't identifiers (`.a-b`) quoted
+fn modifiers_object(modifiers: &[SimpleExpressionNode<'_>]) -> String {
+    let modifiers = modifiers
+        .iter()
+        .map(|m| {
+            if is_simple_identifier(&m.content) {
+                [m.content.as_str(), ": true"].concat()
+            } else {
+                ["\"", m.content.as_str(), "\": true"].concat()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    ["{ ", &modifiers, " }"].concat()
+}
+
+/// Apply the custom directives of an element with `withVaporDirectives`
+fn generate_custom_directives(
+    ctx: &mut GenerateContext,
+    element: usize,
+    directives: &[&DirectiveIRNode<'_>],
+) {
+    ctx.use_helper("withVaporDirectives");
+
+    let directives = directives
+        .iter()
+        .map(|directive| {
+            let dir = &directive.dir;
+            let mut args = vec![Some(directive_reference(ctx, directive))];
+            args.push(
+                dir.exp
+                    .as_ref()
+                    .map(|exp| ["() => ", &expression(exp)].concat()),
+            );
+            args.push(dir.arg.as_ref().map(expression));
+            args.push((!dir.modifiers.is_empty()).then(|| modifiers_object(&dir.modifiers)));
+            // Trailing missing arguments are left out, the others are `void 0`
+            while args.last().is_some_and(Option::is_none) {
+                args.pop();
+            }
+            let args = args
+                .into_iter()
+                .map(|arg| arg.unwrap_or_else(|| String::from("void 0")))
+                .collect::<Vec<_>>()
+                .join(", ");
+            ["[", &args, "]"].concat()
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    ctx.push_line(
+        &[
+            "_withVaporDirectives(n",
+            &element.to_string(),
+            ", [",
+            &directives,
+            "])",
+        ]
+        .concat(),
+    );
+}
+
+/// Reference to a custom directive, either resolved by name or a setup
+/// binding
+fn directive_reference(ctx: &mut GenerateContext, directive: &DirectiveIRNode<'_>) -> String {
+    let name = directive.name.as_str();
+    if directive.asset {
+        return asset_var("_directive_", name);
+    }
+    if !ctx.inline {
+        return ["_ctx.", name].concat();
+    }
+    match ctx.binding_type(name) {
+        Some(BindingType::SetupLet | BindingType::SetupRef | BindingType::SetupMaybeRef) => {
+            ctx.use_helper("unref");
+            ["_unref(", name, ")"].concat()
+        }
+        _ => name.to_string(),
+    }
+}
+
+/// Expression reading from the render context
+fn expression(exp: &ExpressionNode<'_>) -> String {
+    match exp {
+        ExpressionNode::Simple(exp) if exp.is_static => ["\"", exp.content.as_str(), "\""].concat(),
+        ExpressionNode::Simple(exp) => prefix_identifiers_in_expression(&exp.content),
+        ExpressionNode::Compound(exp) => prefix_identifiers_in_expression(&exp.loc.source),
+    }
+}
+
+/// Generate a builtin v-show. Deferred ones are applied after the
+/// enclosing `<Transition appear>` has been created.
+fn generate_v_show(ctx: &mut GenerateContext, directive: &DirectiveIRNode<'_>) {
+    ctx.use_helper("applyVShow");
+
+    let value = match directive.dir.exp {
+        Some(ExpressionNode::Simple(ref exp)) if exp.is_static => {
+            ["\"", exp.content.as_str(), "\""].concat()
+        }
+        Some(ExpressionNode::Simple(ref exp)) => prefix_identifiers_in_expression(&exp.content),
+        _ => String::from("undefined"),
+    };
+    let apply = [
+        "_applyVShow(n",
+        &directive.element.to_string(),
+        ", () => (",
+        &value,
+        "))",
+    ]
+    .concat();
+
+    if directive.deferred {
+        ctx.push_line(&["deferredApplyVShows.push(() => ", &apply, ")"].concat());
+    } else {
+        ctx.push_line(&apply);
+    }
+}
+
+/// Generate a v-model on a native element, which reads the value in an
+/// effect and assigns it back from the element's events
+fn generate_v_model(
+    ctx: &mut GenerateContext,
+    directive: &DirectiveIRNode<'_>,
+    model_type: VModelType,
+) {
+    ctx.use_helper(model_type.helper());
+
+    let value = match directive.dir.exp {
+        Some(ExpressionNode::Simple(ref exp)) => exp.content.as_str(),
+        _ => "undefined",
+    };
+    let getter = prefix_identifiers_in_expression(value);
+    let setter = prefix_identifiers_in_expression(&["_value => (", value, " = _value)"].concat());
+    let mut line = [
+        "_",
+        model_type.helper(),
+        "(n",
+        &directive.element.to_string(),
+        ", () => (",
+        &getter,
+        "), ",
+        &setter,
+    ]
+    .concat();
+    if !directive.dir.modifiers.is_empty() {
+        line.push_str(", ");
+        line.push_str(&modifiers_object(&directive.dir.modifiers));
+    }
+    line.push(')');
+    ctx.push_line(&line);
+}
+
+/// Generate the insertion state of a block nested in an element, so that it
+/// is created in place (or adopted from the parent's DOM when hydrating)
+fn generate_insertion_state(
+    ctx: &mut GenerateContext,
+    parent: Option<usize>,
+    anchor: Option<usize>,
+) {
+    let Some(parent) = parent else {
+        return;
+    };
+    ctx.use_helper("setInsertionState");
+    let anchor = match anchor {
+        Some(index) => index.to_string(),
+        None => String::from("null, true"),
+    };
+    ctx.push_line(
+        &[
+            "_setInsertionState(n",
+            &parent.to_string(),
+            ", ",
+            &anchor,
+            ")",
+        ]
+        .concat(),
+    );
+}
+
 /// Generate If
 fn generate_if(
     ctx: &mut GenerateContext,
@@ -645,6 +1107,7 @@ fn generate_if_inner(
         ["(_ctx.", if_node.condition.content.as_str(), ")"].concat()
     };
 
+    generate_insertion_state(ctx, if_node.parent, if_node.anchor);
     ctx.push_line(
         &[
             "const n",
@@ -660,6 +1123,8 @@ fn generate_if_inner(
     generate_block(ctx, &if_node.positive, element_template_map);
     ctx.deindent();
 
+    // Blocks in v-once subtrees don't track the condition
+    let once = if if_node.once { ", true" } else { "" };
     if let Some(ref negative) = if_node.negative {
         match negative {
             NegativeBranch::Block(block) => {
@@ -667,16 +1132,18 @@ fn generate_if_inner(
                 ctx.indent();
                 generate_block(ctx, block, element_template_map);
                 ctx.deindent();
-                ctx.push_line("})");
+                ctx.push_line(&["}", once, ")"].concat());
             }
             NegativeBranch::If(nested_if) => {
                 // v-else-if: inline format without block wrapper
                 ctx.push_indent();
                 ctx.push("}, () => ");
                 generate_nested_if(ctx, nested_if, element_template_map);
-                ctx.push(")\n");
+                ctx.push(&[once, ")\n"].concat());
             }
         }
+    } else if if_node.once {
+        ctx.push_line("}, undefined, true)");
     } else {
         ctx.push_line("})");
     }
@@ -754,32 +1221,60 @@ fn generate_for(
         _ => value_name.to_string(),
     };
 
-    ctx.push_line(&["_createFor(() => ", &source, ", (", &params, ") => {"].concat());
+    generate_insertion_state(ctx, for_node.parent, for_node.anchor);
+    ctx.push_line(
+        &[
+            "const n",
+            &for_node.id.to_string(),
+            " = _createFor(() => ",
+            &source,
+            ", (",
+            &params,
+            ") => {",
+        ]
+        .concat(),
+    );
     ctx.indent();
     generate_block(ctx, &for_node.render, element_template_map);
     ctx.deindent();
-    ctx.push_line("})");
+    if for_node.once {
+        // No key getter, and the `VaporVForFlags.ONCE` flag
+        ctx.push_line("}, undefined, 4)");
+    } else {
+        ctx.push_line("})");
+    }
 }
 
 /// Generate CreateComponent
-fn generate_create_component(ctx: &mut GenerateContext, component: &CreateComponentIRNode<'_>) {
-    ctx.use_helper("resolveComponent");
-    ctx.use_helper("createComponentWithFallback");
-
-    let tag = &component.tag;
-    let component_var = ["_component_", tag.as_str()].concat();
-
-    // Resolve component
-    ctx.push_line(
-        &[
-            "const ",
-            &component_var,
-            " = _resolveComponent(\"",
-            tag.as_str(),
-            "\")",
-        ]
-        .concat(),
-    );
+fn generate_create_component(
+    ctx: &mut GenerateContext,
+    component: &CreateComponentIRNode<'_>,
+    element_template_map: &FxHashMap<usize, usize>,
+) {
+    // Resolved components are declared at the top of the render function
+    let (create, target) = match &component.kind {
+        ComponentKind::Resolved => {
+            ctx.use_helper("createComponentWithFallback");
+            (
+                "_createComponentWithFallback(",
+                component_var(&component.tag),
+            )
+        }
+        ComponentKind::Builtin(builtin) => {
+            ctx.use_helper(builtin.helper());
+            ctx.use_helper("createComponent");
+            ("_createComponent(", ["_", builtin.helper()].concat())
+        }
+        ComponentKind::Dynamic(is) => {
+            ctx.use_helper("createDynamicComponent");
+            let is = if is.is_static {
+                ["\"", is.content.as_str(), "\""].concat()
+            } else {
+                prefix_identifiers_in_expression(&is.content)
+            };
+            ("_createDynamicComponent(", ["() => (", &is, ")"].concat())
+        }
+    };
 
     // Props object
     let props = if component.props.is_empty() {
@@ -793,51 +1288,102 @@ fn generate_create_component(ctx: &mut GenerateContext, component: &CreateCompon
                 let is_event = key.as_str().starts_with("on") && key.len() > 2;
 
                 let value = if let Some(first) = p.values.first() {
+                    let content = first.content.as_str();
                     if first.is_static {
-                        ["() => (\"", first.content.as_str(), "\")"].concat()
+                        ["() => (\"", content, "\")"].concat()
                     } else if is_event {
                         // Event handlers: () => _ctx.handler
-                        ["() => _ctx.", first.content.as_str()].concat()
+                        ["() => ", &prefix_identifiers_in_expression(content)].concat()
                     } else {
                         // Regular props: () => (_ctx.value)
-                        ["() => (_ctx.", first.content.as_str(), ")"].concat()
+                        ["() => (", &prefix_identifiers_in_expression(content), ")"].concat()
                     }
                 } else {
                     "undefined".to_string()
                 };
-                [key.as_str(), ": ", &value].concat()
+                // `onUpdate:modelValue` and other keys that aren't identifiers
+                if is_simple_identifier(key) {
+                    [key.as_str(), ": ", &value].concat()
+                } else {
+                    ["\"", key.as_str(), "\": ", &value].concat()
+                }
             })
             .collect();
         ["{ ", &prop_strs.join(", "), " }"].concat()
     };
 
+    let end = if component.once {
+        ", true, true)"
+    } else {
+        ", true)"
+    };
+
     // Generate component creation
-    ctx.push_line(
-        &[
-            "const n",
-            &component.id.to_string(),
-            " = _createComponentWithFallback(",
-            &component_var,
-            ", ",
-            &props,
-            ", null, true)",
-        ]
-        .concat(),
-    );
+    generate_insertion_state(ctx, component.parent, component.anchor);
+    let start = [
+        "const n",
+        &component.id.to_string(),
+        " = ",
+        create,
+        &target,
+        ", ",
+        &props,
+        ", ",
+    ]
+    .concat();
+    if component.slots.is_empty() {
+        ctx.push_line(&[&start, "null", end].concat());
+        return;
+    }
+
+    // Each slot renders its block
+    ctx.push_line(&[&start, "{"].concat());
+    ctx.indent();
+    for (i, slot) in component.slots.iter().enumerate() {
+        ctx.push_line(&["\"", slot.name.content.as_str(), "\": () => {"].concat());
+        ctx.indent();
+        generate_block(ctx, &slot.block, element_template_map);
+        ctx.deindent();
+        ctx.push_line(if i + 1 < component.slots.len() {
+            "},"
+        } else {
+            "}"
+        });
+    }
+    ctx.deindent();
+    ctx.push_line(&["}", end].concat());
+}
+
+/// Variable holding a resolved component
+fn component_var(tag: &str) -> String {
+    asset_var("_component_", tag)
+}
+
+/// Variable holding a resolved asset, as in `toValidAssetId`
+fn asset_var(prefix: &str, name: &str) -> String {
+    let mut var = String::from(prefix);
+    for c in name.chars() {
+        match c {
+            '-' => var.push('_'),
+            c if c.is_ascii_alphanumeric() || c == '_' => var.push(c),
+            c => var.push_str(&(c as u32).to_string()),
+        }
+    }
+    var
 }
 
 /// Generate SlotOutlet
 fn generate_slot_outlet(ctx: &mut GenerateContext, slot: &SlotOutletIRNode<'_>) {
-    let name = ctx.next_temp();
     let slot_name = if slot.name.is_static {
         format!("\"{}\"", slot.name.content)
     } else {
         slot.name.content.to_string()
     };
 
+    generate_insertion_state(ctx, slot.parent, slot.anchor);
     ctx.push_line(&format!(
-        "const {} = _renderSlot($slots, {})",
-        name, slot_name
+        "const n{} = _renderSlot($slots, {})",
+        slot.id, slot_name
     ));
 }
 
@@ -82,20 +82,18 @@ pub fn generate_resolve_component(name: &str) -> String {
     format!("_resolveComponent(\"{}\")", name)
 }
 
-/// Generate dynamic component
+/// Generate dynamic component, re-created when `component_expr` changes
 pub fn generate_dynamic_component(
     component_expr: &str,
     props: &str,
     slots: Option<&str>,
 ) -> String {
-    if let Some(slots_code) = slots {
-        format!(
-            "_createComponent({}, {}, {})",
-            component_expr, props, slots_code
-        )
-    } else {
-        format!("_createComponent({}, {})", component_expr, props)
-    }
+    format!(
+        "_createDynamicComponent(() => ({}), {}, {})",
+        component_expr,
+        props,
+        slots.unwrap_or("null")
+    )
 }
 
 /// Generate async component wrapper
@@ -2,7 +2,9 @@
 
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
-use vize_atelier_core::{Namespace, RootNode, SimpleExpressionNode, TemplateChildNode};
+use vize_atelier_core::{
+    CompilerError, Namespace, RootNode, SimpleExpressionNode, TemplateChildNode,
+};
 use vize_carton::{Box, Bump, String, Vec};
 
 /// IR node type discriminant
@@ -57,6 +59,8 @@ pub struct RootIRNode<'a> {
     pub templates: Vec<'a, String>,
     /// Mapping from element ID to template index
     pub element_template_map: FxHashMap<usize, usize>,
+    /// Errors reported while building the IR
+    pub errors: std::vec::Vec<CompilerError>,
 }
 
 /// Block IR node - unit of reactive computation
@@ -68,6 +72,9 @@ pub struct BlockIRNode<'a> {
     pub effect: Vec<'a, IREffect<'a>>,
     pub operation: Vec<'a, OperationNode<'a>>,
     pub returns: Vec<'a, usize>,
+    /// Dynamic descendants of the block's template roots, located by walking
+    /// the instantiated (or, when hydrating, adopted) DOM
+    pub child_refs: Vec<'a, ChildRefIRNode>,
 }
 
 impl<'a> BlockIRNode<'a> {
@@ -79,10 +86,22 @@ impl<'a> BlockIRNode<'a> {
             effect: Vec::new_in(allocator),
             operation: Vec::new_in(allocator),
             returns: Vec::new_in(allocator),
+            child_refs: Vec::new_in(allocator),
         }
     }
 }
 
+/// Reference to a dynamic child element inside a template
+#[derive(Debug)]
+pub struct ChildRefIRNode {
+    pub id: usize,
+    pub parent: usize,
+    /// Previous sibling when it is referenced too and directly precedes the child
+    pub prev: Option<usize>,
+    /// Index of the child among the parent's template nodes
+    pub index: usize,
+}
+
 /// Dynamic info for IR nodes
 #[derive(Debug, Default)]
 pub struct IRDynamicInfo {
@@ -95,6 +114,9 @@ pub struct IRDynamicInfo {
 #[derive(Debug)]
 pub struct IREffect<'a> {
     pub operations: Vec<'a, OperationNode<'a>>,
+    /// Dependency array of an enclosing v-memo, the effect only re-runs
+    /// when one of them changed
+    pub memo: Option<String>,
 }
 
 /// All operation node variants
@@ -106,6 +128,7 @@ pub enum OperationNode<'a> {
     SetEvent(SetEventIRNode<'a>),
     SetHtml(SetHtmlIRNode<'a>),
     SetTemplateRef(SetTemplateRefIRNode<'a>),
+    DeclareOldRef(DeclareOldRefIRNode),
     InsertNode(InsertNodeIRNode),
     PrependNode(PrependNodeIRNode),
     Directive(DirectiveIRNode<'a>),
@@ -185,7 +208,17 @@ pub struct SetHtmlIRNode<'a> {
 pub struct SetTemplateRefIRNode<'a> {
     pub element: usize,
     pub value: Box<'a, SimpleExpressionNode<'a>>,
+    /// Whether the ref is inside v-for, collecting elements into an array
     pub ref_for: bool,
+    /// Whether the ref is dynamic and set in an effect, which passes the
+    /// previous ref so that it can be unset
+    pub effect: bool,
+}
+
+/// Declare the variable holding the previous value of a dynamic ref
+#[derive(Debug)]
+pub struct DeclareOldRefIRNode {
+    pub id: usize,
 }
 
 /// Insert node operation
@@ -210,6 +243,39 @@ pub struct DirectiveIRNode<'a> {
     pub dir: Box<'a, vize_atelier_core::DirectiveNode<'a>>,
     pub name: String,
     pub builtin: bool,
+    /// Whether a custom directive is resolved with `resolveDirective`.
+    /// Otherwise `name` is the setup binding the directive is read from.
+    pub asset: bool,
+    /// Whether a builtin v-show waits for the enclosing `<Transition appear>`
+    /// to be set up before it is applied
+    pub deferred: bool,
+    /// Kind of element a builtin v-model is applied to
+    pub model_type: Option<VModelType>,
+}
+
+/// Kind of element bound with v-model, which selects its runtime helper
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VModelType {
+    /// Text inputs and textareas
+    Text,
+    Checkbox,
+    Radio,
+    Select,
+    /// Inputs whose type is only known at runtime
+    Dynamic,
+}
+
+impl VModelType {
+    /// Runtime helper applying the v-model
+    pub fn helper(self) -> &'static str {
+        match self {
+            Self::Text => "applyTextModel",
+            Self::Checkbox => "applyCheckboxModel",
+            Self::Radio => "applyRadioModel",
+            Self::Select => "applySelectModel",
+            Self::Dynamic => "applyDynamicModel",
+        }
+    }
 }
 
 /// If operation
@@ -244,6 +310,8 @@ pub struct ForIRNode<'a> {
     pub once: bool,
     pub component: bool,
     pub only_child: bool,
+    pub parent: Option<usize>,
+    pub anchor: Option<usize>,
 }
 
 /// Create component operation
@@ -253,9 +321,54 @@ pub struct CreateComponentIRNode<'a> {
     pub tag: String,
     pub props: Vec<'a, IRProp<'a>>,
     pub slots: Vec<'a, IRSlot<'a>>,
-    pub asset: bool,
+    pub kind: ComponentKind<'a>,
     pub once: bool,
     pub dynamic_slots: bool,
+    pub parent: Option<usize>,
+    pub anchor: Option<usize>,
+}
+
+/// How the component of a CreateComponent operation is referenced
+#[derive(Debug)]
+pub enum ComponentKind<'a> {
+    /// Resolved by name with `resolveComponent`
+    Resolved,
+    /// Built-in component imported from the runtime
+    Builtin(BuiltinComponent),
+    /// `<component :is>`, resolved each time the expression changes
+    Dynamic(Box<'a, SimpleExpressionNode<'a>>),
+}
+
+/// Built-in components with a Vapor implementation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BuiltinComponent {
+    Transition,
+    TransitionGroup,
+    Teleport,
+    KeepAlive,
+}
+
+impl BuiltinComponent {
+    /// Look up the built-in component used by a tag
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "Transition" | "transition" => Some(Self::Transition),
+            "TransitionGroup" | "transition-group" => Some(Self::TransitionGroup),
+            "Teleport" | "teleport" => Some(Self::Teleport),
+            "KeepAlive" | "keep-alive" => Some(Self::KeepAlive),
+            _ => None,
+        }
+    }
+
+    /// Name of the runtime export
+    pub fn helper(self) -> &'static str {
+        match self {
+            Self::Transition => "VaporTransition",
+            Self::TransitionGroup => "VaporTransitionGroup",
+            Self::Teleport => "VaporTeleport",
+            Self::KeepAlive => "VaporKeepAlive",
+        }
+    }
 }
 
 /// IR slot
@@ -273,6 +386,8 @@ pub struct SlotOutletIRNode<'a> {
     pub name: Box<'a, SimpleExpressionNode<'a>>,
     pub props: Vec<'a, IRProp<'a>>,
     pub fallback: Option<BlockIRNode<'a>>,
+    pub parent: Option<usize>,
+    pub anchor: Option<usize>,
 }
 
 /// Get text child operation
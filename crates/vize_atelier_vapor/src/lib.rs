@@ -2,6 +2,12 @@
 //!
 //! Vapor mode is a new compilation strategy that generates more efficient code
 //! by eliminating the virtual DOM overhead for static parts of the template.
+//!
+//! Server rendering goes through the same string-based `ssrRender` output as
+//! the virtual DOM compiler, so Vapor components render with the regular SSR
+//! renderer. On the client, only root nodes of each block are instantiated from
+//! templates and dynamic nodes are located inside them, which lets the runtime
+//! adopt server-rendered DOM while hydrating.
 
 #![allow(clippy::collapsible_match)]
 
@@ -18,10 +24,13 @@ pub use transform::*;
 pub use transforms::*;
 
 use vize_atelier_core::{
-    options::{ParserOptions, TransformOptions},
+    codegen::generate,
+    options::{BaseTransformOptions, CodegenMode, CodegenOptions, ParserOptions, TransformOptions},
     parser::parse_with_options,
     transform::transform,
+    CompilerError,
 };
+use vize_atelier_dom::DomCompilerOptions;
 use vize_carton::Bump;
 
 /// Vapor compiler options
@@ -29,7 +38,7 @@ use vize_carton::Bump;
 pub struct VaporCompilerOptions {
     /// Whether to prefix identifiers
     pub prefix_identifiers: bool,
-    /// Whether in SSR mode
+    /// Whether in SSR mode, which generates an `ssrRender` function instead
     pub ssr: bool,
     /// Binding metadata
     pub binding_metadata: Option<vize_atelier_core::options::BindingMetadata>,
@@ -44,8 +53,9 @@ pub struct VaporCompileResult {
     pub code: std::string::String,
     /// Template strings for static parts
     pub templates: Vec<vize_carton::String>,
-    /// Error messages during compilation
-    pub error_messages: Vec<std::string::String>,
+    /// Errors reported while parsing, transforming and building the IR.
+    /// Code is still generated unless parsing failed.
+    pub errors: Vec<CompilerError>,
 }
 
 /// Compile a Vue template to Vapor mode
@@ -54,44 +64,103 @@ pub fn compile_vapor<'a>(
     source: &'a str,
     options: VaporCompilerOptions,
 ) -> VaporCompileResult {
-    // Parse
-    let parser_opts = ParserOptions::default();
+    // Parse. Server rendering parses like the virtual DOM compiler, so that
+    // its output is the same.
+    let parser_opts = if options.ssr {
+        vize_atelier_dom::parser_options(&DomCompilerOptions::default())
+    } else {
+        ParserOptions::default()
+    };
     let (mut root, errors) = parse_with_options(allocator, source, parser_opts);
 
     if !errors.is_empty() {
         return VaporCompileResult {
             code: String::new(),
             templates: Vec::new(),
-            error_messages: errors.iter().map(|e| e.message.clone()).collect(),
+            errors: errors.into_iter().collect(),
         };
     }
 
+    if options.ssr {
+        return compile_ssr(allocator, root, options);
+    }
+
     // Transform to Vapor IR
     let transform_opts = TransformOptions {
-        prefix_identifiers: options.prefix_identifiers,
-        ssr: options.ssr,
-        binding_metadata: options.binding_metadata,
-        inline: options.inline,
+        base: BaseTransformOptions {
+            prefix_identifiers: options.prefix_identifiers,
+            ssr: options.ssr,
+            binding_metadata: options.binding_metadata.clone(),
+            inline: options.inline,
+            ..Default::default()
+        },
         ..Default::default()
     };
-    transform(allocator, &mut root, transform_opts);
+    let mut errors = transform(allocator, &mut root, transform_opts);
 
     // Transform to Vapor IR
-    let ir = transform_to_ir(allocator, &root);
+    let mut ir = transform_to_ir_with_options(allocator, &root, &options);
+    errors.append(&mut ir.errors);
 
     // Generate Vapor code
-    let result = generate_vapor(&ir);
+    let result = generate_vapor_with_options(&ir, &options);
 
     VaporCompileResult {
         code: result.code,
         templates: result.templates,
-        error_messages: Vec::new(),
+        errors,
+    }
+}
+
+/// Compile to an `ssrRender` function, whose string output matches the
+/// virtual DOM compiler's so that the client can hydrate it
+fn compile_ssr<'a>(
+    allocator: &'a Bump,
+    mut root: vize_atelier_core::RootNode<'a>,
+    options: VaporCompilerOptions,
+) -> VaporCompileResult {
+    // Module output has no `with (_ctx)` scope, like the client render
+    // function which always reads from `_ctx`
+    let transform_opts = TransformOptions {
+        base: BaseTransformOptions {
+            prefix_identifiers: true,
+            ssr: true,
+            binding_metadata: options.binding_metadata.clone(),
+            inline: options.inline,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let errors = transform(allocator, &mut root, transform_opts);
+
+    let codegen_opts = CodegenOptions {
+        mode: CodegenMode::Module,
+        prefix_identifiers: true,
+        ssr: true,
+        inline: options.inline,
+        binding_metadata: options.binding_metadata,
+        ..Default::default()
+    };
+    let result = generate(&root, codegen_opts);
+
+    let mut code = result.preamble;
+    if !code.is_empty() && !code.ends_with('\n') {
+        code.push('\n');
+    }
+    code.push_str(&result.code);
+
+    VaporCompileResult {
+        code,
+        templates: Vec::new(),
+        errors,
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use vize_atelier_core::options::{BindingMetadata, BindingType};
+    use vize_atelier_core::ErrorCode;
 
     fn normalize_code(code: &str) -> String {
         code.lines()
@@ -106,7 +175,7 @@ mod tests {
         let allocator = Bump::new();
         let result = compile_vapor(&allocator, "<div>hello</div>", Default::default());
 
-        assert!(result.error_messages.is_empty(), "Expected no errors");
+        assert!(result.errors.is_empty(), "Expected no errors");
 
         let code = normalize_code(&result.code);
 
@@ -142,7 +211,7 @@ mod tests {
         let allocator = Bump::new();
         let result = compile_vapor(&allocator, "<div>{{ msg }}</div>", Default::default());
 
-        assert!(result.error_messages.is_empty(), "Expected no errors");
+        assert!(result.errors.is_empty(), "Expected no errors");
 
         let code = normalize_code(&result.code);
 
@@ -176,7 +245,7 @@ mod tests {
             Default::default(),
         );
 
-        assert!(result.error_messages.is_empty(), "Expected no errors");
+        assert!(result.errors.is_empty(), "Expected no errors");
 
         let code = normalize_code(&result.code);
 
@@ -212,9 +281,9 @@ mod tests {
         );
 
         assert!(
-            result.error_messages.is_empty(),
+            result.errors.is_empty(),
             "Expected no errors: {:?}",
-            result.error_messages
+            result.errors
         );
 
         let code = normalize_code(&result.code);
@@ -238,9 +307,9 @@ mod tests {
         );
 
         assert!(
-            result.error_messages.is_empty(),
+            result.errors.is_empty(),
             "Expected no errors: {:?}",
-            result.error_messages
+            result.errors
         );
 
         let code = normalize_code(&result.code);
@@ -253,4 +322,706 @@ mod tests {
         );
         assert!(code.contains("items"), "Should reference items source");
     }
+
+    #[test]
+    fn test_compile_ssr() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<div :id="id">{{ msg }}<span v-if="ok">yes</span></div>"#,
+            VaporCompilerOptions {
+                ssr: true,
+                ..Default::default()
+            },
+        );
+
+        assert!(result.errors.is_empty(), "Expected no errors");
+        assert!(result.templates.is_empty(), "SSR should not use templates");
+
+        let code = normalize_code(&result.code);
+        assert!(
+            code.contains("export function ssrRender(_ctx, _push, _parent, _attrs)"),
+            "Should export ssrRender: {}",
+            code
+        );
+        assert!(
+            code.contains("_ssrInterpolate(_ctx.msg)"),
+            "Should render interpolation to string: {}",
+            code
+        );
+        assert!(
+            code.contains("if (_ctx.ok) {"),
+            "Should render v-if branches: {}",
+            code
+        );
+        assert!(!code.contains("_template("), "Should not clone templates");
+    }
+
+    #[test]
+    fn test_compile_ssr_dynamic_component() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<div><component :is="current" /></div>"#,
+            VaporCompilerOptions {
+                ssr: true,
+                ..Default::default()
+            },
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let code = normalize_code(&result.code);
+        assert!(
+            code.contains(
+                "_ssrRenderVNode(_push, _createVNode(_resolveDynamicComponent(_ctx.current)"
+            ),
+            "Should render the resolved component: {}",
+            code
+        );
+        assert!(!code.contains("<component"), "{}", code);
+    }
+
+    #[test]
+    fn test_compile_ssr_v_model() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<div><input type="checkbox" v-model="checked"><select v-model="selected"><option value="a">a</option></select></div>"#,
+            VaporCompilerOptions {
+                ssr: true,
+                ..Default::default()
+            },
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let code = normalize_code(&result.code);
+        assert!(
+            code.contains(
+                r#"(_ssrIncludeBooleanAttr(Array.isArray(_ctx.checked) ? _ssrLooseContain(_ctx.checked, null) : _ctx.checked)) ? " checked" : """#
+            ),
+            "Should render the checkbox state: {}",
+            code
+        );
+        assert!(
+            code.contains(r#"_ssrLooseEqual(_ctx.selected, "a"))) ? " selected" : """#),
+            "Should render the selected option: {}",
+            code
+        );
+        assert!(!code.contains(r#"_ssrRenderAttr("value""#), "{}", code);
+    }
+
+    #[test]
+    fn test_compile_nested_dynamic_children() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<div><span>{{ a }}</span><p :id="b"></p><i :id="c"></i></div>"#,
+            Default::default(),
+        );
+
+        let code = normalize_code(&result.code);
+
+        // Children are located inside the root template, so that hydration
+        // can adopt them instead of cloning templates of their own
+        assert_eq!(result.templates.len(), 1, "Should use one template");
+        assert!(
+            code.contains(
+                "const t0 = _template(\"<div><span> </span><p></p><i></i></div>\", true)"
+            ),
+            "Should include children in root template: {}",
+            code
+        );
+        assert!(code.contains("const n3 = t0()"), "{}", code);
+        assert!(code.contains("const n0 = _child(n3)"), "{}", code);
+        assert!(code.contains("const n1 = _next(n0, 1)"), "{}", code);
+        assert!(code.contains("const n2 = _next(n1, 2)"), "{}", code);
+        assert!(code.contains("return n3"), "{}", code);
+    }
+
+    #[test]
+    fn test_compile_nested_block_insertion() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<div><span v-if="ok">yes</span><p>end</p><b v-for="item in items">x</b></div>"#,
+            Default::default(),
+        );
+
+        let code = normalize_code(&result.code);
+
+        assert!(
+            code.contains("const t0 = _template(\"<span>yes</span>\")"),
+            "Nested block templates are not root templates: {}",
+            code
+        );
+        assert!(
+            code.contains("_template(\"<div><p>end</p></div>\", true)"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("_setInsertionState(n5, 0)\nconst n0 = _createIf("),
+            "v-if should be inserted before <p>: {}",
+            code
+        );
+        assert!(
+            code.contains("_setInsertionState(n5, null, true)\nconst n4 = _createFor("),
+            "v-for should be appended: {}",
+            code
+        );
+        assert!(code.contains("return n5"), "{}", code);
+    }
+
+    #[test]
+    fn test_compile_reports_located_errors() {
+        let allocator = Bump::new();
+        let source = "<div>\n  <p v-if>a</p>\n  <input v-on=\"handlers\">\n  <i v-for=\"1 in items\"></i>\n</div>";
+        let result = compile_vapor(&allocator, source, Default::default());
+
+        let codes: Vec<_> = result.errors.iter().map(|e| e.code).collect();
+        assert_eq!(
+            codes,
+            vec![
+                ErrorCode::VIfNoExpression,
+                ErrorCode::VaporUnsupportedDirective,
+                ErrorCode::VForMalformedExpression,
+            ],
+            "{:?}",
+            result.errors
+        );
+
+        let v_if = result.errors[0].loc.as_ref().unwrap();
+        assert_eq!((v_if.start.line, v_if.start.column), (2, 6), "{:?}", v_if);
+        assert_eq!(v_if.source.as_str(), "v-if");
+
+        let v_on = &result.errors[1];
+        assert_eq!(
+            v_on.message,
+            "v-on without an argument is not supported in Vapor mode."
+        );
+        assert_eq!(v_on.loc.as_ref().unwrap().start.line, 3);
+
+        let v_for = &result.errors[2];
+        assert_eq!(v_for.message, "v-for has invalid alias \"1\".");
+        assert_eq!(v_for.loc.as_ref().unwrap().start.line, 4);
+
+        // Code is still generated for the rest of the template
+        assert!(result.code.contains("export function render(_ctx)"));
+    }
+
+    #[test]
+    fn test_compile_v_model() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<div><input v-model.trim="msg" /><input type="checkbox" v-model="checked" /><select v-model="selected"></select></div>"#,
+            Default::default(),
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let code = normalize_code(&result.code);
+        assert!(
+            code.contains(
+                "_applyTextModel(n0, () => (_ctx.msg), _value => (_ctx.msg = _value), { trim: true })"
+            ),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains(
+                "_applyCheckboxModel(n1, () => (_ctx.checked), _value => (_ctx.checked = _value))"
+            ),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains(
+                "_applySelectModel(n2, () => (_ctx.selected), _value => (_ctx.selected = _value))"
+            ),
+            "{}",
+            code
+        );
+        // The update listener added by the core transform isn't bound
+        assert!(!code.contains("update:modelValue"), "{}", code);
+    }
+
+    #[test]
+    fn test_compile_v_model_errors() {
+        let allocator = Bump::new();
+        let source = r#"<div><input v-model="a + b" /><p v-model="m"></p></div>"#;
+        let result = compile_vapor(&allocator, source, Default::default());
+
+        let errors: Vec<_> = result
+            .errors
+            .iter()
+            .map(|e| (e.code, e.loc.as_ref().map(|loc| loc.start.offset as usize)))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                (ErrorCode::VModelMalformedExpression, source.find("a + b")),
+                (
+                    ErrorCode::VModelOnInvalidElement,
+                    source.find(r#"v-model="m""#)
+                ),
+            ]
+        );
+        // Neither model is applied
+        let code = normalize_code(&result.code);
+        assert!(!code.contains("Model("), "{}", code);
+    }
+
+    #[test]
+    fn test_compile_component_v_model() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<Comp v-model.trim="msg" />"#,
+            Default::default(),
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let code = normalize_code(&result.code);
+        assert!(
+            code.contains(
+                r#"{ modelValue: () => (_ctx.msg), "onUpdate:modelValue": () => $event => ((_ctx.msg) = $event), modelModifiers: () => ({ trim: true }) }"#
+            ),
+            "{}",
+            code
+        );
+    }
+
+    #[test]
+    fn test_compile_object_v_bind() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<div v-bind="attrs"></div>"#,
+            Default::default(),
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let code = normalize_code(&result.code);
+        assert!(
+            code.contains("_renderEffect(() => _setDynamicProps(n0, [_ctx.attrs]))"),
+            "{}",
+            code
+        );
+    }
+
+    #[test]
+    fn test_compile_v_for_destructured_alias() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<div v-for="({ id, name }, i) in items">{{ name }}</div>"#,
+            Default::default(),
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        assert!(
+            result.code.contains("({ id, name }, i) =>"),
+            "{}",
+            result.code
+        );
+    }
+
+    #[test]
+    fn test_compile_malformed_v_for() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<div v-for="items"></div>"#,
+            Default::default(),
+        );
+
+        assert_eq!(result.errors.len(), 1, "{:?}", result.errors);
+        assert_eq!(result.errors[0].code, ErrorCode::VForMalformedExpression);
+        assert_eq!(
+            result.errors[0].loc.as_ref().unwrap().source.as_str(),
+            "items"
+        );
+    }
+
+    #[test]
+    fn test_compile_v_html_and_v_text() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<div v-html="raw"></div><p v-text="msg"></p>"#,
+            Default::default(),
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let code = normalize_code(&result.code);
+        assert!(
+            code.contains("_template(\"<div></div>\", true)"),
+            "{}",
+            code
+        );
+        assert!(code.contains("_template(\"<p> </p>\", true)"), "{}", code);
+        assert!(code.contains("const x1 = _txt(n1)"), "{}", code);
+        assert!(
+            code.contains("_renderEffect(() => _setHtml(n0, _ctx.raw))"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("_renderEffect(() => _setText(x1, _toDisplayString(_ctx.msg)))"),
+            "{}",
+            code
+        );
+    }
+
+    #[test]
+    fn test_compile_v_html_with_children() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<div v-html="raw"><span>{{ a }}</span></div>"#,
+            Default::default(),
+        );
+
+        assert_eq!(result.errors.len(), 1, "{:?}", result.errors);
+        assert_eq!(result.errors[0].code, ErrorCode::VHtmlWithChildren);
+        assert!(result.code.contains("_template(\"<div></div>\", true)"));
+        assert!(!result.code.contains("_ctx.a"), "{}", result.code);
+    }
+
+    #[test]
+    fn test_compile_v_once() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<div v-once><span :id="a">{{ msg }}</span><p v-if="ok">x</p></div>"#,
+            Default::default(),
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let code = normalize_code(&result.code);
+        assert!(!code.contains("_renderEffect"), "{}", code);
+        assert!(code.contains("_setProp(n0, \"id\", _ctx.a)"), "{}", code);
+        assert!(
+            code.contains("_setText(x0, _toDisplayString(_ctx.msg))"),
+            "{}",
+            code
+        );
+        assert!(code.contains("}, undefined, true)"), "{}", code);
+    }
+
+    #[test]
+    fn test_compile_v_memo() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<div v-memo="[a, b]"><span :title="t">{{ msg }}</span></div>"#,
+            Default::default(),
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let code = normalize_code(&result.code);
+        assert!(
+            code.contains(
+                "let _memo0\n_renderEffect(() => {\nconst _deps = [_ctx.a, _ctx.b]\nif (_memo0 && _deps.every((dep, i) => dep === _memo0[i])) return\n_memo0 = _deps\n_setProp(n0, \"title\", _ctx.t)\n_setText(x0, _toDisplayString(_ctx.msg))\n})"
+            ),
+            "{}",
+            code
+        );
+        assert_eq!(code.matches("_renderEffect(").count(), 1, "{}", code);
+    }
+
+    #[test]
+    fn test_compile_component_slots() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<MyComponent><template #header>H</template><template #footer>F</template></MyComponent>"#,
+            Default::default(),
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let code = normalize_code(&result.code);
+        assert!(
+            code.contains("const _component_MyComponent = _resolveComponent(\"MyComponent\")"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains(
+                "const n4 = _createComponentWithFallback(_component_MyComponent, null, {"
+            ),
+            "{}",
+            code
+        );
+        assert!(code.contains("\"header\": () => {"), "{}", code);
+        assert!(code.contains("\"footer\": () => {"), "{}", code);
+        assert!(code.contains("const n2 = t1()"), "{}", code);
+    }
+
+    #[test]
+    fn test_compile_teleport() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<Teleport to="body"><div>content</div></Teleport>"#,
+            Default::default(),
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let code = normalize_code(&result.code);
+        assert!(code.contains("VaporTeleport as _VaporTeleport"), "{}", code);
+        assert!(!code.contains("_resolveComponent"), "{}", code);
+        assert!(
+            code.contains(
+                "const n1 = _createComponent(_VaporTeleport, { to: () => (\"body\") }, {"
+            ),
+            "{}",
+            code
+        );
+        assert!(code.contains("const t0 = _template(\"<div>content</div>\")"));
+    }
+
+    #[test]
+    fn test_compile_transition_v_show() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<Transition><div v-show="visible">modal</div></Transition>"#,
+            Default::default(),
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let code = normalize_code(&result.code);
+        assert!(
+            code.contains("_createComponent(_VaporTransition, { persisted: () => (\"\") }, {"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("_applyVShow(n0, () => (_ctx.visible))"),
+            "{}",
+            code
+        );
+        assert!(!code.contains("deferredApplyVShows"), "{}", code);
+    }
+
+    #[test]
+    fn test_compile_transition_appear_defers_v_show() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<Transition appear><div v-show="visible">modal</div></Transition>"#,
+            Default::default(),
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let code = normalize_code(&result.code);
+        assert!(code.contains("const deferredApplyVShows = []"), "{}", code);
+        assert!(
+            code.contains("deferredApplyVShows.push(() => _applyVShow(n0, () => (_ctx.visible)))"),
+            "{}",
+            code
+        );
+        let apply = code.find("deferredApplyVShows.forEach(fn => fn())");
+        let ret = code.find("return n1");
+        assert!(apply.is_some() && apply < ret, "{}", code);
+    }
+
+    #[test]
+    fn test_compile_transition_invalid_children() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            "<Transition><div /><p /></Transition>",
+            Default::default(),
+        );
+
+        assert_eq!(result.errors.len(), 1, "{:?}", result.errors);
+        assert_eq!(result.errors[0].code, ErrorCode::TransitionInvalidChildren);
+    }
+
+    #[test]
+    fn test_compile_dynamic_component() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<component :is="current" :msg="message" />"#,
+            Default::default(),
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let code = normalize_code(&result.code);
+        assert!(
+            code.contains(
+                "const n0 = _createDynamicComponent(() => (_ctx.current), { msg: () => (_ctx.message) }, null, true)"
+            ),
+            "{}",
+            code
+        );
+        assert!(!code.contains("_template"), "{}", code);
+    }
+
+    fn setup_bindings() -> BindingMetadata {
+        let mut metadata = BindingMetadata::default();
+        metadata
+            .bindings
+            .insert("vFocus".into(), BindingType::SetupConst);
+        metadata
+            .bindings
+            .insert("vTip".into(), BindingType::SetupMaybeRef);
+        metadata.bindings.insert("el".into(), BindingType::SetupRef);
+        metadata
+    }
+
+    #[test]
+    fn test_compile_custom_directives() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<input v-focus v-tip:top.once="msg" v-my-dir.a-b="x" />"#,
+            VaporCompilerOptions {
+                binding_metadata: Some(setup_bindings()),
+                ..Default::default()
+            },
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let code = normalize_code(&result.code);
+        assert!(
+            code.contains(r#"const _directive_my_dir = _resolveDirective("my-dir")"#),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains(
+                r#"_withVaporDirectives(n0, [[_ctx.vFocus], [_ctx.vTip, () => _ctx.msg, "top", { once: true }], [_directive_my_dir, () => _ctx.x, void 0, { "a-b": true }]])"#
+            ),
+            "{}",
+            code
+        );
+    }
+
+    #[test]
+    fn test_compile_custom_directives_inline() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<input v-focus v-tip="msg" />"#,
+            VaporCompilerOptions {
+                binding_metadata: Some(setup_bindings()),
+                inline: true,
+                ..Default::default()
+            },
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let code = normalize_code(&result.code);
+        assert!(
+            code.contains("_withVaporDirectives(n0, [[vFocus], [_unref(vTip), () => _ctx.msg]])"),
+            "{}",
+            code
+        );
+        assert!(!code.contains("_resolveDirective"), "{}", code);
+    }
+
+    #[test]
+    fn test_compile_template_refs() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<div ref="el"><span ref="inner">a</span></div>"#,
+            Default::default(),
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let code = normalize_code(&result.code);
+        assert!(
+            code.contains("const _setTemplateRef = _createTemplateRefSetter()"),
+            "{}",
+            code
+        );
+        assert!(code.contains(r#"_setTemplateRef(n0, "inner")"#), "{}", code);
+        assert!(code.contains(r#"_setTemplateRef(n1, "el")"#), "{}", code);
+        assert!(
+            code.contains(r#"_template("<div><span>a</span></div>", true)"#),
+            "{}",
+            code
+        );
+
+        // Setup refs are passed directly in inline mode
+        let result = compile_vapor(
+            &allocator,
+            r#"<div ref="el"></div>"#,
+            VaporCompilerOptions {
+                binding_metadata: Some(setup_bindings()),
+                inline: true,
+                ..Default::default()
+            },
+        );
+        let code = normalize_code(&result.code);
+        assert!(code.contains("_setTemplateRef(n0, el)"), "{}", code);
+    }
+
+    #[test]
+    fn test_compile_template_ref_in_v_for() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<ul><li v-for="i in items" ref="items">{{ i }}</li></ul>"#,
+            Default::default(),
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let code = normalize_code(&result.code);
+        assert!(
+            code.contains(r#"_setTemplateRef(n0, "items", void 0, true)"#),
+            "{}",
+            code
+        );
+    }
+
+    #[test]
+    fn test_compile_dynamic_template_ref() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<div :ref="(el) => last = el"></div>"#,
+            Default::default(),
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let code = normalize_code(&result.code);
+        assert!(code.contains("let r0"), "{}", code);
+        assert!(
+            code.contains(
+                "_renderEffect(() => r0 = _setTemplateRef(n0, (el) => _ctx.last = el, r0))"
+            ),
+            "{}",
+            code
+        );
+    }
+
+    #[test]
+    fn test_compile_component_ref_and_directive() {
+        let allocator = Bump::new();
+        let result = compile_vapor(
+            &allocator,
+            r#"<MyComp ref="comp" v-focus />"#,
+            Default::default(),
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let code = normalize_code(&result.code);
+        assert!(code.contains(r#"_setTemplateRef(n0, "comp")"#), "{}", code);
+        assert!(
+            code.contains(r#"const _directive_focus = _resolveDirective("focus")"#),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("_withVaporDirectives(n0, [[_directive_focus]])"),
+            "{}",
+            code
+        );
+    }
 }
@@ -3,53 +3,195 @@
 //! Transforms the template AST into Vapor IR for code generation.
 
 use rustc_hash::FxHashMap;
-use vize_carton::{Box, Bump, String, Vec};
+use vize_carton::{
+    general::{camelize, capitalize, is_builtin_directive, is_simple_identifier},
+    Box, Bump, String, Vec,
+};
 
 use crate::ir::*;
+use crate::transforms::{
+    get_memo_deps, has_v_once, transform_v_html, transform_v_model, transform_v_show,
+    transform_v_text,
+};
+use crate::VaporCompilerOptions;
+use vize_atelier_core::options::{BindingMetadata, BindingType};
+use vize_atelier_core::transforms::transform_expression::is_member_expression;
 use vize_atelier_core::{
-    DirectiveNode, ElementNode, ElementType, ExpressionNode, ForNode, IfNode, InterpolationNode,
-    PropNode, RootNode, SimpleExpressionNode, SourceLocation, TemplateChildNode, TextNode,
+    CompilerError, DirectiveNode, ElementNode, ElementType, ErrorCode, ExpressionNode, ForNode,
+    IfNode, InterpolationNode, PropNode, RootNode, SimpleExpressionNode, SourceLocation,
+    TemplateChildNode, TextNode,
 };
 
 /// Transform AST to Vapor IR
 pub fn transform_to_ir<'a>(allocator: &'a Bump, root: &RootNode<'a>) -> RootIRNode<'a> {
-    let mut ctx = TransformContext::new(allocator);
+    transform_to_ir_with_options(allocator, root, &VaporCompilerOptions::default())
+}
+
+/// Transform AST to Vapor IR, resolving custom directives against the
+/// setup bindings of `options`
+pub fn transform_to_ir_with_options<'a>(
+    allocator: &'a Bump,
+    root: &RootNode<'a>,
+    options: &VaporCompilerOptions,
+) -> RootIRNode<'a> {
+    let mut ctx = TransformContext::new(allocator, options.binding_metadata.as_ref());
 
     // Create block for root
-    let block = transform_children(&mut ctx, &root.children);
+    let block = transform_children(&mut ctx, root.children.iter());
 
     RootIRNode {
         node: RootNode::new(allocator, ""),
         source: String::from(""),
         template: Default::default(),
         template_index_map: Default::default(),
-        root_template_indexes: Vec::new_in(allocator),
-        component: Vec::new_in(allocator),
-        directive: Vec::new_in(allocator),
+        root_template_indexes: ctx.root_template_indexes,
+        component: ctx.components,
+        directive: ctx.directives,
         block,
-        has_template_ref: false,
-        has_deferred_v_show: false,
+        has_template_ref: ctx.has_template_ref,
+        has_deferred_v_show: ctx.has_deferred_v_show,
         templates: ctx.templates,
         element_template_map: ctx.element_template_map,
+        errors: ctx.errors,
     }
 }
 
 /// Transform context
-struct TransformContext<'a> {
+struct TransformContext<'a, 'o> {
     allocator: &'a Bump,
+    /// Bindings of `<script setup>`
+    bindings: Option<&'o BindingMetadata>,
     temp_id: usize,
     templates: Vec<'a, String>,
     element_template_map: FxHashMap<usize, usize>,
+    /// Templates of the component's root nodes
+    root_template_indexes: Vec<'a, usize>,
+    /// Whether nodes being transformed are root nodes of the component
+    root: bool,
+    /// Whether nodes being transformed are in a v-once subtree
+    in_v_once: bool,
+    /// Whether nodes being transformed are rendered by a v-for
+    in_v_for: bool,
+    /// Dependencies of the enclosing v-memo
+    memo: Option<String>,
+    /// Components resolved by name, in order of first use
+    components: Vec<'a, String>,
+    /// Custom directives resolved by name, in order of first use
+    directives: Vec<'a, String>,
+    /// Whether a template ref is set
+    has_template_ref: bool,
+    /// Whether nodes being transformed are the content of `<Transition appear>`
+    transition_appear: bool,
+    /// Whether a v-show waits for a transition to be set up
+    has_deferred_v_show: bool,
+    errors: std::vec::Vec<CompilerError>,
 }
 
-impl<'a> TransformContext<'a> {
-    fn new(allocator: &'a Bump) -> Self {
+impl<'a, 'o> TransformContext<'a, 'o> {
+    fn new(allocator: &'a Bump, bindings: Option<&'o BindingMetadata>) -> Self {
         Self {
             allocator,
+            bindings,
             temp_id: 0,
             templates: Vec::new_in(allocator),
             element_template_map: FxHashMap::default(),
+            root_template_indexes: Vec::new_in(allocator),
+            root: true,
+            in_v_once: false,
+            in_v_for: false,
+            memo: None,
+            components: Vec::new_in(allocator),
+            directives: Vec::new_in(allocator),
+            has_template_ref: false,
+            transition_appear: false,
+            has_deferred_v_show: false,
+            errors: std::vec::Vec::new(),
+        }
+    }
+
+    /// Register an operation that re-runs when its dependencies change.
+    /// In a v-once subtree it only runs when the block is created.
+    fn register_effect(&mut self, block: &mut BlockIRNode<'a>, op: OperationNode<'a>) {
+        if self.in_v_once {
+            block.operation.push(op);
+            return;
+        }
+        let mut operations = Vec::new_in(self.allocator);
+        operations.push(op);
+        block.effect.push(IREffect {
+            operations,
+            memo: self.memo.clone(),
+        });
+    }
+
+    /// Register a component that is resolved by name
+    fn register_component(&mut self, tag: &str) {
+        if !self.components.iter().any(|c| c.as_str() == tag) {
+            self.components.push(String::from(tag));
+        }
+    }
+
+    /// Register a custom directive that is resolved by name
+    fn register_directive(&mut self, name: &str) {
+        if !self.directives.iter().any(|d| d.as_str() == name) {
+            self.directives.push(String::from(name));
+        }
+    }
+
+    /// Find the setup binding a custom directive is read from. A `vFocus`
+    /// (or `VFocus`) binding of `<script setup>` is used as `v-focus`.
+    fn resolve_setup_directive(&self, name: &str) -> Option<String> {
+        let bindings = &self.bindings?.bindings;
+        let camelized = camelize(&["v-", name].concat());
+        let pascal = capitalize(&camelized);
+        [camelized, pascal].into_iter().find(|candidate| {
+            matches!(
+                bindings.get(candidate.as_str()),
+                Some(
+                    BindingType::SetupConst
+                        | BindingType::SetupReactiveConst
+                        | BindingType::LiteralConst
+                        | BindingType::SetupLet
+                        | BindingType::SetupRef
+                        | BindingType::SetupMaybeRef
+                )
+            )
+        })
+    }
+
+    /// Transform a v-show, which is deferred in `<Transition appear>` so
+    /// that the transition can run when the element is first shown
+    fn show_directive(
+        &mut self,
+        dir: &DirectiveNode<'a>,
+        element_id: usize,
+    ) -> Option<OperationNode<'a>> {
+        if dir.exp.is_none() {
+            self.on_error(ErrorCode::VShowNoExpression, &dir.loc);
+            return None;
         }
+        self.has_deferred_v_show |= self.transition_appear;
+        Some(transform_v_show(
+            self.allocator,
+            dir,
+            element_id,
+            self.transition_appear,
+        ))
+    }
+
+    /// Report an error
+    fn on_error(&mut self, code: ErrorCode, loc: &SourceLocation) {
+        self.errors
+            .push(CompilerError::new(code, Some(loc.clone())));
+    }
+
+    /// Report a directive that has no Vapor equivalent yet
+    fn unsupported_directive(&mut self, dir: &DirectiveNode<'a>, what: &str) {
+        self.errors.push(CompilerError::with_message(
+            ErrorCode::VaporUnsupportedDirective,
+            format!("{} is not supported in Vapor mode.", what),
+            Some(dir.loc.clone()),
+        ));
     }
 
     fn next_id(&mut self) -> usize {
@@ -62,15 +204,21 @@ impl<'a> TransformContext<'a> {
         let template_index = self.templates.len();
         self.templates.push(template);
         self.element_template_map.insert(element_id, template_index);
+        if self.root {
+            self.root_template_indexes.push(template_index);
+        }
         template_index
     }
 }
 
 /// Transform children nodes
-fn transform_children<'a>(
-    ctx: &mut TransformContext<'a>,
-    children: &[TemplateChildNode<'a>],
-) -> BlockIRNode<'a> {
+fn transform_children<'a, 'n>(
+    ctx: &mut TransformContext<'a, '_>,
+    children: impl IntoIterator<Item = &'n TemplateChildNode<'a>>,
+) -> BlockIRNode<'a>
+where
+    'a: 'n,
+{
     let mut block = BlockIRNode::new(ctx.allocator);
     // Note: Don't consume an ID for the block itself - element IDs should start from 0
 
@@ -86,10 +234,13 @@ fn transform_children<'a>(
                 transform_interpolation(ctx, interp, &mut block);
             }
             TemplateChildNode::If(if_node) => {
-                transform_if_node(ctx, if_node, &mut block);
+                if let Some(if_id) = transform_if_node(ctx, if_node, &mut block) {
+                    block.returns.push(if_id);
+                }
             }
             TemplateChildNode::For(for_node) => {
-                transform_for_node(ctx, for_node, &mut block);
+                let for_id = transform_for_node(ctx, for_node, &mut block);
+                block.returns.push(for_id);
             }
             TemplateChildNode::Comment(_) => {
                 // Comments are ignored in Vapor mode
@@ -103,240 +254,623 @@ fn transform_children<'a>(
 
 /// Transform element node
 fn transform_element<'a>(
-    ctx: &mut TransformContext<'a>,
+    ctx: &mut TransformContext<'a, '_>,
     el: &ElementNode<'a>,
     block: &mut BlockIRNode<'a>,
 ) {
-    let element_id = ctx.next_id();
-
     match el.tag_type {
-        ElementType::Element => {
-            // Generate template string and register it
+        ElementType::Element if !is_component(el) => {
+            let mut child_refs = std::vec::Vec::new();
+            let element_id = transform_element_contents(ctx, el, block, &mut child_refs);
             let template = generate_element_template(el);
             ctx.add_template(element_id, template);
-
-            // Process props and events
-            for prop in el.props.iter() {
-                match prop {
-                    PropNode::Directive(dir) => {
-                        transform_directive(ctx, dir, element_id, el, block);
-                    }
-                    PropNode::Attribute(_attr) => {
-                        // Static attributes are included in the template
-                    }
-                }
-            }
-
-            // Check if we have mixed text and interpolation children
-            let has_text_or_interpolation = el.children.iter().any(|c| {
-                matches!(
-                    c,
-                    TemplateChildNode::Text(_) | TemplateChildNode::Interpolation(_)
-                )
-            });
-            let has_interpolation = el
-                .children
-                .iter()
-                .any(|c| matches!(c, TemplateChildNode::Interpolation(_)));
-
-            if has_interpolation && has_text_or_interpolation {
-                // Collect all text parts and interpolations together
-                transform_text_children(ctx, &el.children, element_id, block);
-            }
-
-            // Process other dynamic children
+            block.child_refs.extend(child_refs);
+            block.returns.push(element_id);
+        }
+        ElementType::Element | ElementType::Component => {
+            let component_id = transform_component(ctx, el, block);
+            block.returns.push(component_id);
+        }
+        ElementType::Slot => {
+            let slot_id = transform_slot_outlet(ctx, block);
+            block.returns.push(slot_id);
+        }
+        ElementType::Template => {
+            // Template element - process children directly
             for child in el.children.iter() {
                 match child {
-                    TemplateChildNode::Interpolation(_) | TemplateChildNode::Text(_) => {
-                        // Already handled above
-                    }
                     TemplateChildNode::Element(child_el) => {
-                        // Only process dynamic child elements
-                        if !is_static_element(child_el) {
-                            transform_element(ctx, child_el, block);
-                        }
+                        transform_element(ctx, child_el, block);
                     }
-                    TemplateChildNode::If(if_node) => {
-                        transform_if_node(ctx, if_node, block);
+                    TemplateChildNode::Text(text) => {
+                        transform_text(ctx, text, block);
                     }
-                    TemplateChildNode::For(for_node) => {
-                        transform_for_node(ctx, for_node, block);
+                    TemplateChildNode::Interpolation(interp) => {
+                        transform_interpolation(ctx, interp, block);
                     }
                     _ => {}
                 }
             }
         }
-        ElementType::Component => {
-            // Component handling - process props and events
-            let mut props = Vec::new_in(ctx.allocator);
-            let slots = Vec::new_in(ctx.allocator);
-
-            // Process props (v-bind and v-on directives, and static attributes)
-            for prop in el.props.iter() {
-                match prop {
-                    PropNode::Directive(dir) => {
-                        if dir.name.as_str() == "bind" {
-                            // v-bind -> prop
-                            if let Some(ref arg) = dir.arg {
-                                if let ExpressionNode::Simple(key_exp) = arg {
-                                    let key_node = SimpleExpressionNode::new(
-                                        key_exp.content.clone(),
-                                        key_exp.is_static,
-                                        key_exp.loc.clone(),
+    }
+}
+
+/// Transform the props and children of an element that is part of a
+/// template, returning its ID.
+///
+/// Children get their IDs first so that numbering matches the reference
+/// compiler. Dynamic child elements are not given templates of their own:
+/// they are located inside the parent's template through `child_refs`, which
+/// also lets hydration adopt them from server-rendered DOM. Blocks nested in
+/// the element (v-if, v-for, components and slots) are inserted into it.
+fn transform_element_contents<'a>(
+    ctx: &mut TransformContext<'a, '_>,
+    el: &ElementNode<'a>,
+    block: &mut BlockIRNode<'a>,
+    child_refs: &mut std::vec::Vec<ChildRefIRNode>,
+) -> usize {
+    let was_root = std::mem::replace(&mut ctx.root, false);
+    let transition_appear = std::mem::replace(&mut ctx.transition_appear, false);
+    let was_in_v_once = ctx.in_v_once;
+    ctx.in_v_once |= has_v_once(el);
+    let outer_memo = ctx.memo.clone();
+    if let Some(deps) = get_memo_deps(el) {
+        ctx.memo = Some(deps);
+    }
+
+    // v-html and v-text replace the element's children
+    let children: &[TemplateChildNode<'a>] = if has_html_or_text(el) {
+        &[]
+    } else {
+        &el.children
+    };
+
+    // Index of the next child node in the element's template. Adjacent text
+    // and interpolations are a single text node.
+    let mut index = 0;
+    let mut in_text = false;
+    // Referenced child elements with their index and their own references
+    let mut referenced = std::vec::Vec::new();
+    // Operations creating nested blocks, with the index they are inserted at
+    let mut inserted = std::vec::Vec::new();
+
+    for child in children.iter() {
+        match child {
+            TemplateChildNode::Text(_) | TemplateChildNode::Interpolation(_) => {
+                if !in_text {
+                    in_text = true;
+                    index += 1;
+                }
+            }
+            TemplateChildNode::Element(child_el)
+                if matches!(
+                    child_el.tag_type,
+                    ElementType::Element | ElementType::Template
+                ) && !is_component(child_el) =>
+            {
+                if !is_static_element(child_el) {
+                    let mut refs = std::vec::Vec::new();
+                    let child_id = transform_element_contents(ctx, child_el, block, &mut refs);
+                    referenced.push((child_id, index, refs));
+                }
+                in_text = false;
+                index += 1;
+            }
+            TemplateChildNode::Element(child_el) => {
+                inserted.push((block.operation.len(), index));
+                if is_component(child_el) {
+                    transform_component(ctx, child_el, block);
+                } else {
+                    transform_slot_outlet(ctx, block);
+                }
+            }
+            TemplateChildNode::If(if_node) => {
+                inserted.push((block.operation.len(), index));
+                transform_if_node(ctx, if_node, block);
+            }
+            TemplateChildNode::For(for_node) => {
+                inserted.push((block.operation.len(), index));
+                transform_for_node(ctx, for_node, block);
+            }
+            _ => {}
+        }
+    }
+
+    ctx.root = was_root;
+    ctx.transition_appear = transition_appear;
+    let element_id = ctx.next_id();
+
+    // Process props and events
+    for prop in el.props.iter() {
+        match prop {
+            PropNode::Directive(dir) => {
+                transform_directive(ctx, dir, element_id, el, block);
+            }
+            PropNode::Attribute(attr) if attr.name == "ref" => {
+                let value = static_ref_value(attr);
+                transform_template_ref(ctx, value, element_id, block);
+            }
+            PropNode::Attribute(_attr) => {
+                // Static attributes are included in the template
+            }
+        }
+    }
+
+    if children
+        .iter()
+        .any(|c| matches!(c, TemplateChildNode::Interpolation(_)))
+    {
+        // Collect all text parts and interpolations together
+        transform_text_children(ctx, children, element_id, block);
+    }
+    ctx.in_v_once = was_in_v_once;
+    ctx.memo = outer_memo;
+
+    let mut prev: Option<(usize, usize)> = None;
+    for (child_id, child_index, refs) in referenced {
+        let prev_id = prev
+            .filter(|&(_, prev_index)| prev_index + 1 == child_index)
+            .map(|(prev_id, _)| prev_id);
+        child_refs.push(ChildRefIRNode {
+            id: child_id,
+            parent: element_id,
+            prev: prev_id,
+            index: child_index,
+        });
+        child_refs.extend(refs);
+        prev = Some((child_id, child_index));
+    }
+
+    for (operation_index, child_index) in inserted {
+        // Blocks after the last template node are appended
+        let anchor = (child_index < index).then_some(child_index);
+        if let Some(op) = block.operation.get_mut(operation_index) {
+            set_insertion_state(op, element_id, anchor);
+        }
+    }
+
+    element_id
+}
+
+/// Set where the block created by `op` is inserted into its parent element
+fn set_insertion_state(op: &mut OperationNode<'_>, parent: usize, anchor: Option<usize>) {
+    match op {
+        OperationNode::If(if_node) => {
+            if_node.parent = Some(parent);
+            if_node.anchor = anchor;
+        }
+        OperationNode::For(for_node) => {
+            for_node.parent = Some(parent);
+            for_node.anchor = anchor;
+        }
+        OperationNode::CreateComponent(component) => {
+            component.parent = Some(parent);
+            component.anchor = anchor;
+        }
+        OperationNode::SlotOutlet(slot) => {
+            slot.parent = Some(parent);
+            slot.anchor = anchor;
+        }
+        _ => {}
+    }
+}
+
+/// Transform component node, returning its ID
+fn transform_component<'a>(
+    ctx: &mut TransformContext<'a, '_>,
+    el: &ElementNode<'a>,
+    block: &mut BlockIRNode<'a>,
+) -> usize {
+    let kind = component_kind(ctx.allocator, el);
+    let is_transition = matches!(kind, ComponentKind::Builtin(BuiltinComponent::Transition));
+
+    // Slot contents are numbered before the component
+    let transition_appear = std::mem::replace(&mut ctx.transition_appear, false);
+    if is_transition {
+        check_transition_children(ctx, el);
+        ctx.transition_appear = has_prop(el, "appear");
+    }
+    let slots = transform_component_slots(ctx, el);
+    ctx.transition_appear = transition_appear;
+
+    let element_id = ctx.next_id();
+
+    // Component handling - process props and events
+    let mut props = Vec::new_in(ctx.allocator);
+    // Directives applied to the component once it is created
+    let mut directives = std::vec::Vec::new();
+    let mut template_ref = None;
+
+    // Process props (v-bind and v-on directives, and static attributes)
+    for prop in el.props.iter() {
+        if is_dynamic_component_is(el, prop) {
+            // Consumed by `component_kind`
+            continue;
+        }
+        match prop {
+            PropNode::Directive(dir) => {
+                if is_ref_binding(dir) {
+                    template_ref = ref_binding_value(dir);
+                } else if dir.name.as_str() == "bind" {
+                    // v-bind -> prop
+                    if let Some(ref arg) = dir.arg {
+                        if let ExpressionNode::Simple(key_exp) = arg {
+                            let key_node = SimpleExpressionNode::new(
+                                key_exp.content.clone(),
+                                key_exp.is_static,
+                                key_exp.loc.clone(),
+                            );
+                            let key = Box::new_in(key_node, ctx.allocator);
+
+                            let mut values = Vec::new_in(ctx.allocator);
+                            if let Some(ref exp) = dir.exp {
+                                if let ExpressionNode::Simple(val_exp) = exp {
+                                    let val_node = SimpleExpressionNode::new(
+                                        val_exp.content.clone(),
+                                        val_exp.is_static,
+                                        val_exp.loc.clone(),
                                     );
-                                    let key = Box::new_in(key_node, ctx.allocator);
-
-                                    let mut values = Vec::new_in(ctx.allocator);
-                                    if let Some(ref exp) = dir.exp {
-                                        if let ExpressionNode::Simple(val_exp) = exp {
-                                            let val_node = SimpleExpressionNode::new(
-                                                val_exp.content.clone(),
-                                                val_exp.is_static,
-                                                val_exp.loc.clone(),
-                                            );
-                                            values.push(Box::new_in(val_node, ctx.allocator));
-                                        }
-                                    }
-
-                                    props.push(IRProp {
-                                        key,
-                                        values,
-                                        is_component: true,
-                                    });
+                                    values.push(Box::new_in(val_node, ctx.allocator));
                                 }
                             }
-                        } else if dir.name.as_str() == "on" {
-                            // v-on -> onXxx prop
-                            if let Some(ref arg) = dir.arg {
-                                if let ExpressionNode::Simple(event_exp) = arg {
-                                    // Convert event name to onXxx format
-                                    let event_name = event_exp.content.as_str();
-                                    let on_name = if event_name.is_empty() {
-                                        String::from("on")
-                                    } else {
-                                        let mut s = String::from("on");
-                                        let mut chars = event_name.chars();
-                                        if let Some(c) = chars.next() {
-                                            s.push(c.to_ascii_uppercase());
-                                        }
-                                        for c in chars {
-                                            s.push(c);
-                                        }
-                                        s
-                                    };
-
-                                    let key_node = SimpleExpressionNode::new(
-                                        on_name,
-                                        true,
-                                        event_exp.loc.clone(),
+
+                            props.push(IRProp {
+                                key,
+                                values,
+                                is_component: true,
+                            });
+                        }
+                    } else {
+                        ctx.unsupported_directive(dir, "v-bind without an argument");
+                    }
+                } else if dir.name.as_str() == "on" {
+                    // v-on -> onXxx prop
+                    if let Some(ref arg) = dir.arg {
+                        if let ExpressionNode::Simple(event_exp) = arg {
+                            // Convert event name to onXxx format
+                            let event_name = event_exp.content.as_str();
+                            let on_name = if event_name.is_empty() {
+                                String::from("on")
+                            } else {
+                                let mut s = String::from("on");
+                                let mut chars = event_name.chars();
+                                if let Some(c) = chars.next() {
+                                    s.push(c.to_ascii_uppercase());
+                                }
+                                for c in chars {
+                                    s.push(c);
+                                }
+                                s
+                            };
+
+                            let key_node =
+                                SimpleExpressionNode::new(on_name, true, event_exp.loc.clone());
+                            let key = Box::new_in(key_node, ctx.allocator);
+
+                            let mut values = Vec::new_in(ctx.allocator);
+                            if let Some(ref exp) = dir.exp {
+                                if let ExpressionNode::Simple(val_exp) = exp {
+                                    let val_node = SimpleExpressionNode::new(
+                                        val_exp.content.clone(),
+                                        val_exp.is_static,
+                                        val_exp.loc.clone(),
                                     );
-                                    let key = Box::new_in(key_node, ctx.allocator);
-
-                                    let mut values = Vec::new_in(ctx.allocator);
-                                    if let Some(ref exp) = dir.exp {
-                                        if let ExpressionNode::Simple(val_exp) = exp {
-                                            let val_node = SimpleExpressionNode::new(
-                                                val_exp.content.clone(),
-                                                val_exp.is_static,
-                                                val_exp.loc.clone(),
-                                            );
-                                            values.push(Box::new_in(val_node, ctx.allocator));
-                                        }
-                                    }
-
-                                    props.push(IRProp {
-                                        key,
-                                        values,
-                                        is_component: true,
-                                    });
+                                    values.push(Box::new_in(val_node, ctx.allocator));
                                 }
                             }
-                        }
-                    }
-                    PropNode::Attribute(attr) => {
-                        // Static attribute -> prop
-                        let key_node = SimpleExpressionNode::new(
-                            attr.name.clone(),
-                            true,
-                            SourceLocation::STUB,
-                        );
-                        let key = Box::new_in(key_node, ctx.allocator);
-
-                        let mut values = Vec::new_in(ctx.allocator);
-                        if let Some(ref value) = attr.value {
-                            let val_node = SimpleExpressionNode::new(
-                                value.content.clone(),
-                                true,
-                                SourceLocation::STUB,
-                            );
-                            values.push(Box::new_in(val_node, ctx.allocator));
-                        }
 
-                        props.push(IRProp {
-                            key,
-                            values,
-                            is_component: true,
-                        });
+                            props.push(IRProp {
+                                key,
+                                values,
+                                is_component: true,
+                            });
+                        }
+                    } else {
+                        ctx.unsupported_directive(dir, "v-on without an argument");
                     }
+                } else if dir.name.as_str() == "show" {
+                    directives.extend(ctx.show_directive(dir, element_id));
+                } else if !is_builtin_directive(&dir.name) {
+                    directives.push(transform_custom_directive(ctx, dir, element_id));
+                } else if !matches!(dir.name.as_str(), "slot" | "once" | "cloak") {
+                    // v-slot is handled with the component's slots
+                    ctx.unsupported_directive(dir, &format!("v-{} on components", dir.name));
                 }
             }
+            PropNode::Attribute(attr) if attr.name == "ref" => {
+                template_ref = Some(static_ref_value(attr));
+            }
+            PropNode::Attribute(attr) => {
+                // Static attribute -> prop
+                let key_node =
+                    SimpleExpressionNode::new(attr.name.clone(), true, SourceLocation::STUB);
+                let key = Box::new_in(key_node, ctx.allocator);
+
+                // Attributes without a value are passed as empty strings
+                let value = attr
+                    .value
+                    .as_ref()
+                    .map_or_else(String::default, |value| value.content.clone());
+                let mut values = Vec::new_in(ctx.allocator);
+                let val_node = SimpleExpressionNode::new(value, true, SourceLocation::STUB);
+                values.push(Box::new_in(val_node, ctx.allocator));
+
+                props.push(IRProp {
+                    key,
+                    values,
+                    is_component: true,
+                });
+            }
+        }
+    }
 
-            let create_component = CreateComponentIRNode {
-                id: element_id,
-                tag: el.tag.clone(),
-                props,
-                slots,
-                asset: true,
-                once: false,
-                dynamic_slots: false,
-            };
-
-            block
-                .operation
-                .push(OperationNode::CreateComponent(create_component));
+    // A transition toggled with v-show keeps its content in the DOM
+    if is_transition && has_v_show_child(el) {
+        let key_node = SimpleExpressionNode::new("persisted", true, SourceLocation::STUB);
+        let mut values = Vec::new_in(ctx.allocator);
+        let val_node = SimpleExpressionNode::new("", true, SourceLocation::STUB);
+        values.push(Box::new_in(val_node, ctx.allocator));
+        props.push(IRProp {
+            key: Box::new_in(key_node, ctx.allocator),
+            values,
+            is_component: true,
+        });
+    }
+
+    if let ComponentKind::Resolved = kind {
+        ctx.register_component(&el.tag);
+    }
+
+    let create_component = CreateComponentIRNode {
+        id: element_id,
+        tag: el.tag.clone(),
+        props,
+        slots,
+        kind,
+        once: ctx.in_v_once || has_v_once(el),
+        dynamic_slots: false,
+        parent: None,
+        anchor: None,
+    };
+
+    block
+        .operation
+        .push(OperationNode::CreateComponent(create_component));
+    if let Some(value) = template_ref {
+        transform_template_ref(ctx, value, element_id, block);
+    }
+    block.operation.extend(directives);
+    element_id
+}
+
+/// Determine how a component is created. `<component>` with an `is` prop is
+/// dynamic, a static `is` still goes through `createDynamicComponent` so that
+/// it can name an element.
+fn component_kind<'a>(allocator: &'a Bump, el: &ElementNode<'a>) -> ComponentKind<'a> {
+    for prop in el.props.iter() {
+        if !is_dynamic_component_is(el, prop) {
+            continue;
         }
-        ElementType::Slot => {
-            // Slot outlet handling
-            let name_exp = SimpleExpressionNode::new("default", true, SourceLocation::STUB);
-            let slot_outlet = SlotOutletIRNode {
-                id: element_id,
-                name: Box::new_in(name_exp, ctx.allocator),
-                props: Vec::new_in(ctx.allocator),
-                fallback: None,
-            };
+        let is = match prop {
+            PropNode::Attribute(attr) => attr.value.as_ref().map(|value| {
+                SimpleExpressionNode::new(value.content.clone(), true, value.loc.clone())
+            }),
+            PropNode::Directive(dir) => dir.exp.as_ref().map(|exp| match exp {
+                ExpressionNode::Simple(simple) => SimpleExpressionNode::new(
+                    simple.content.clone(),
+                    simple.is_static,
+                    simple.loc.clone(),
+                ),
+                ExpressionNode::Compound(compound) => SimpleExpressionNode::new(
+                    compound.loc.source.clone(),
+                    false,
+                    compound.loc.clone(),
+                ),
+            }),
+        };
+        if let Some(is) = is {
+            return ComponentKind::Dynamic(Box::new_in(is, allocator));
+        }
+    }
+
+    match BuiltinComponent::from_tag(&el.tag) {
+        Some(builtin) => ComponentKind::Builtin(builtin),
+        None => ComponentKind::Resolved,
+    }
+}
 
-            block.operation.push(OperationNode::SlotOutlet(slot_outlet));
+/// Check if a prop is the `is` prop of `<component>`
+fn is_dynamic_component_is(el: &ElementNode<'_>, prop: &PropNode<'_>) -> bool {
+    if el.tag != "component" {
+        return false;
+    }
+    match prop {
+        PropNode::Attribute(attr) => attr.name == "is",
+        PropNode::Directive(dir) => {
+            dir.name == "bind"
+                && matches!(&dir.arg, Some(ExpressionNode::Simple(arg)) if arg.is_static && arg.content == "is")
         }
-        ElementType::Template => {
-            // Template element - process children directly
-            for child in el.children.iter() {
-                match child {
-                    TemplateChildNode::Element(child_el) => {
-                        transform_element(ctx, child_el, block);
-                    }
-                    TemplateChildNode::Text(text) => {
-                        transform_text(ctx, text, block);
-                    }
-                    TemplateChildNode::Interpolation(interp) => {
-                        transform_interpolation(ctx, interp, block);
-                    }
-                    _ => {}
-                }
-            }
+    }
+}
+
+/// Transform the children of a component into slots. `<template v-slot>`
+/// children are named slots and the remaining content forms the default
+/// slot, unless v-slot is used on the component itself.
+fn transform_component_slots<'a>(
+    ctx: &mut TransformContext<'a, '_>,
+    el: &ElementNode<'a>,
+) -> Vec<'a, IRSlot<'a>> {
+    let mut slots = Vec::new_in(ctx.allocator);
+    // Slots render their own blocks, which are never component roots
+    let was_root = std::mem::replace(&mut ctx.root, false);
+
+    if let Some(dir) = find_slot_directive(el) {
+        if let Some(template) = el.children.iter().find_map(slot_template) {
+            ctx.on_error(ErrorCode::VSlotMixedSlotUsage, &template.loc);
+        }
+        if let Some(name) = slot_name(ctx, dir) {
+            let block = transform_children(ctx, el.children.iter());
+            slots.push(IRSlot {
+                name,
+                fn_exp: None,
+                block,
+            });
         }
+        ctx.root = was_root;
+        return slots;
     }
 
-    block.returns.push(element_id);
+    let is_default_content = |child: &&TemplateChildNode<'a>| slot_template(child).is_none();
+    if el
+        .children
+        .iter()
+        .filter(is_default_content)
+        .any(|child| !is_blank(child))
+    {
+        let block = transform_children(ctx, el.children.iter().filter(is_default_content));
+        let name = SimpleExpressionNode::new("default", true, SourceLocation::STUB);
+        slots.push(IRSlot {
+            name: Box::new_in(name, ctx.allocator),
+            fn_exp: None,
+            block,
+        });
+    }
+
+    for template in el.children.iter().filter_map(slot_template) {
+        let Some(dir) = find_slot_directive(template) else {
+            continue;
+        };
+        let Some(name) = slot_name(ctx, dir) else {
+            continue;
+        };
+        if slots.iter().any(|slot| slot.name.content == name.content) {
+            ctx.on_error(ErrorCode::VSlotDuplicateSlotNames, &dir.loc);
+        }
+        let block = transform_children(ctx, template.children.iter());
+        // The `<template>` is numbered after its content, as in the
+        // reference compiler
+        ctx.next_id();
+        slots.push(IRSlot {
+            name,
+            fn_exp: None,
+            block,
+        });
+    }
+
+    ctx.root = was_root;
+    slots
+}
+
+/// Name of the slot declared by a v-slot directive. Scoped slots and
+/// dynamic slot names are not supported yet.
+fn slot_name<'a>(
+    ctx: &mut TransformContext<'a, '_>,
+    dir: &DirectiveNode<'a>,
+) -> Option<Box<'a, SimpleExpressionNode<'a>>> {
+    if dir.exp.is_some() {
+        ctx.unsupported_directive(dir, "Scoped slots");
+    }
+    let name = match &dir.arg {
+        None => "default",
+        Some(ExpressionNode::Simple(arg)) if arg.is_static => arg.content.as_str(),
+        Some(_) => {
+            ctx.unsupported_directive(dir, "Dynamic slot names");
+            return None;
+        }
+    };
+    let name = SimpleExpressionNode::new(name, true, dir.loc.clone());
+    Some(Box::new_in(name, ctx.allocator))
+}
+
+/// Find the v-slot directive of an element
+fn find_slot_directive<'n, 'a>(el: &'n ElementNode<'a>) -> Option<&'n DirectiveNode<'a>> {
+    el.props.iter().find_map(|prop| match prop {
+        PropNode::Directive(dir) if dir.name == "slot" => Some(&**dir),
+        _ => None,
+    })
+}
+
+/// Get a `<template v-slot>` child of a component
+fn slot_template<'n, 'a>(child: &'n TemplateChildNode<'a>) -> Option<&'n ElementNode<'a>> {
+    match child {
+        TemplateChildNode::Element(el)
+            if el.tag_type == ElementType::Template && find_slot_directive(el).is_some() =>
+        {
+            Some(el)
+        }
+        _ => None,
+    }
+}
+
+/// Report `<Transition>` content that is not a single element or component
+fn check_transition_children<'a>(ctx: &mut TransformContext<'a, '_>, el: &ElementNode<'a>) {
+    let mut children = el.children.iter().filter(|child| !is_blank(child));
+    let invalid = matches!(
+        (children.next(), children.next()),
+        (_, Some(_)) | (Some(TemplateChildNode::For(_)), None)
+    );
+    if invalid {
+        ctx.on_error(ErrorCode::TransitionInvalidChildren, &el.loc);
+    }
+}
+
+/// Check if the content of a transition is toggled with v-show
+fn has_v_show_child(el: &ElementNode<'_>) -> bool {
+    el.children.iter().any(|child| {
+        matches!(child, TemplateChildNode::Element(child_el) if child_el.props.iter().any(
+            |prop| matches!(prop, PropNode::Directive(dir) if dir.name == "show")
+        ))
+    })
+}
+
+/// Check if an element has a prop, static or bound
+fn has_prop(el: &ElementNode<'_>, name: &str) -> bool {
+    el.props.iter().any(|prop| match prop {
+        PropNode::Attribute(attr) => attr.name == name,
+        PropNode::Directive(dir) => {
+            dir.name == "bind"
+                && matches!(&dir.arg, Some(ExpressionNode::Simple(arg)) if arg.is_static && arg.content == name)
+        }
+    })
+}
+
+/// Check if a child renders nothing
+fn is_blank(child: &TemplateChildNode<'_>) -> bool {
+    match child {
+        TemplateChildNode::Comment(_) => true,
+        TemplateChildNode::Text(text) => text.content.trim().is_empty(),
+        _ => false,
+    }
+}
+
+/// Transform slot outlet node, returning its ID
+fn transform_slot_outlet<'a>(
+    ctx: &mut TransformContext<'a, '_>,
+    block: &mut BlockIRNode<'a>,
+) -> usize {
+    let element_id = ctx.next_id();
+    let name_exp = SimpleExpressionNode::new("default", true, SourceLocation::STUB);
+    let slot_outlet = SlotOutletIRNode {
+        id: element_id,
+        name: Box::new_in(name_exp, ctx.allocator),
+        props: Vec::new_in(ctx.allocator),
+        fallback: None,
+        parent: None,
+        anchor: None,
+    };
+
+    block.operation.push(OperationNode::SlotOutlet(slot_outlet));
+    element_id
 }
 
-/// Transform IfNode (from compiler-core v-if transform)
+/// Transform IfNode (from compiler-core v-if transform), returning its ID
 fn transform_if_node<'a>(
-    ctx: &mut TransformContext<'a>,
+    ctx: &mut TransformContext<'a, '_>,
     if_node: &IfNode<'a>,
     block: &mut BlockIRNode<'a>,
-) {
+) -> Option<usize> {
     if if_node.branches.is_empty() {
-        return;
+        return None;
     }
 
     // Allocate ID for the if node itself
@@ -375,7 +909,7 @@ fn transform_if_node<'a>(
     let _positive_branch_id = ctx.next_id();
 
     // Transform first branch children
-    let positive = transform_children(ctx, &first_branch.children);
+    let positive = transform_children(ctx, first_branch.children.iter());
 
     // Handle remaining branches (v-else-if, v-else)
     let negative = if if_node.branches.len() > 1 {
@@ -389,7 +923,7 @@ fn transform_if_node<'a>(
         condition,
         positive,
         negative,
-        once: false,
+        once: ctx.in_v_once,
         parent: None,
         anchor: None,
     };
@@ -397,12 +931,12 @@ fn transform_if_node<'a>(
     block
         .operation
         .push(OperationNode::If(Box::new_in(ir_if, ctx.allocator)));
-    block.returns.push(if_id);
+    Some(if_id)
 }
 
 /// Transform remaining if branches (v-else-if, v-else)
 fn transform_remaining_branches<'a>(
-    ctx: &mut TransformContext<'a>,
+    ctx: &mut TransformContext<'a, '_>,
     branches: &[vize_atelier_core::IfBranchNode<'a>],
 ) -> NegativeBranch<'a> {
     if branches.is_empty() {
@@ -438,7 +972,7 @@ fn transform_remaining_branches<'a>(
         // Consume ID for positive branch block
         let _positive_branch_id = ctx.next_id();
 
-        let positive = transform_children(ctx, &branch.children);
+        let positive = transform_children(ctx, branch.children.iter());
 
         let negative = if branches.len() > 1 {
             // Consume ID for negative branch callback block
@@ -453,7 +987,7 @@ fn transform_remaining_branches<'a>(
             condition,
             positive,
             negative,
-            once: false,
+            once: ctx.in_v_once,
             parent: None,
             anchor: None,
         };
@@ -462,16 +996,16 @@ fn transform_remaining_branches<'a>(
     } else {
         // v-else: consume ID for the else branch block
         let _else_branch_id = ctx.next_id();
-        NegativeBranch::Block(transform_children(ctx, &branch.children))
+        NegativeBranch::Block(transform_children(ctx, branch.children.iter()))
     }
 }
 
-/// Transform ForNode (from compiler-core v-for transform)
+/// Transform ForNode (from compiler-core v-for transform), returning its ID
 fn transform_for_node<'a>(
-    ctx: &mut TransformContext<'a>,
+    ctx: &mut TransformContext<'a, '_>,
     for_node: &ForNode<'a>,
     block: &mut BlockIRNode<'a>,
-) {
+) -> usize {
     // Get source expression
     let source = match &for_node.source {
         ExpressionNode::Simple(simple) => {
@@ -540,42 +1074,63 @@ fn transform_for_node<'a>(
         }
     });
 
-    // Transform children as render block
-    let render = transform_children(ctx, &for_node.children);
+    // Aliases are bound as arrow function parameters
+    for alias in [&value, &key, &index].into_iter().flatten() {
+        let alias = alias.content.trim();
+        let is_pattern = (alias.starts_with('{') && alias.ends_with('}'))
+            || (alias.starts_with('[') && alias.ends_with(']'));
+        if !is_simple_identifier(alias) && !is_pattern {
+            ctx.errors.push(CompilerError::with_message(
+                ErrorCode::VForMalformedExpression,
+                format!("v-for has invalid alias \"{}\".", alias),
+                Some(for_node.loc.clone()),
+            ));
+        }
+    }
+
+    // Transform children as render block. Items are never component roots.
+    let was_root = std::mem::replace(&mut ctx.root, false);
+    let was_in_v_for = std::mem::replace(&mut ctx.in_v_for, true);
+    let render = transform_children(ctx, for_node.children.iter());
+    ctx.root = was_root;
+    ctx.in_v_for = was_in_v_for;
 
+    let for_id = ctx.next_id();
     let ir_for = ForIRNode {
-        id: ctx.next_id(),
+        id: for_id,
         source,
         value,
         key,
         index,
         key_prop: None, // TODO: Handle key prop from element
         render,
-        once: false,
+        once: ctx.in_v_once,
         component: false,
         only_child: for_node.children.len() == 1,
+        parent: None,
+        anchor: None,
     };
 
     block
         .operation
         .push(OperationNode::For(Box::new_in(ir_for, ctx.allocator)));
+    for_id
 }
 
 /// Transform text node
 fn transform_text<'a>(
-    ctx: &mut TransformContext<'a>,
+    ctx: &mut TransformContext<'a, '_>,
     text: &TextNode,
     block: &mut BlockIRNode<'a>,
 ) {
     let element_id = ctx.next_id();
-    let template: String = text.content.clone();
-    ctx.templates.push(template);
+    ctx.add_template(element_id, text.content.clone());
     block.returns.push(element_id);
 }
 
 /// Transform interpolation node (standalone, not inside element)
 fn transform_interpolation<'a>(
-    ctx: &mut TransformContext<'a>,
+    ctx: &mut TransformContext<'a, '_>,
     interp: &InterpolationNode<'a>,
     block: &mut BlockIRNode<'a>,
 ) {
@@ -602,19 +1157,14 @@ fn transform_interpolation<'a>(
     };
 
     // Add to effects (reactive)
-    let mut effect_ops = Vec::new_in(ctx.allocator);
-    effect_ops.push(OperationNode::SetText(set_text));
-
-    block.effect.push(IREffect {
-        operations: effect_ops,
-    });
+    ctx.register_effect(block, OperationNode::SetText(set_text));
 
     block.returns.push(element_id);
 }
 
 /// Transform text children (combined text and interpolations)
 fn transform_text_children<'a>(
-    ctx: &mut TransformContext<'a>,
+    ctx: &mut TransformContext<'a, '_>,
     children: &[TemplateChildNode<'a>],
     parent_element_id: usize,
     block: &mut BlockIRNode<'a>,
@@ -654,24 +1204,24 @@ fn transform_text_children<'a>(
             values,
         };
 
-        let mut effect_ops = Vec::new_in(ctx.allocator);
-        effect_ops.push(OperationNode::SetText(set_text));
-
-        block.effect.push(IREffect {
-            operations: effect_ops,
-        });
+        ctx.register_effect(block, OperationNode::SetText(set_text));
     }
 }
 
 /// Transform directive
 fn transform_directive<'a>(
-    ctx: &mut TransformContext<'a>,
+    ctx: &mut TransformContext<'a, '_>,
     dir: &DirectiveNode<'a>,
     element_id: usize,
     el: &ElementNode<'a>,
     block: &mut BlockIRNode<'a>,
 ) {
     match dir.name.as_str() {
+        "bind" if is_ref_binding(dir) => {
+            if let Some(value) = ref_binding_value(dir) {
+                transform_template_ref(ctx, value, element_id, block);
+            }
+        }
         "bind" => {
             // v-bind - SetProp
             if let Some(ref arg) = dir.arg {
@@ -711,16 +1261,32 @@ fn transform_directive<'a>(
                     };
 
                     // Reactive prop - add to effects
-                    let mut effect_ops = Vec::new_in(ctx.allocator);
-                    effect_ops.push(OperationNode::SetProp(set_prop));
-                    block.effect.push(IREffect {
-                        operations: effect_ops,
-                    });
+                    ctx.register_effect(block, OperationNode::SetProp(set_prop));
                 }
+            } else if let Some(ExpressionNode::Simple(exp)) = &dir.exp {
+                // v-bind="obj" - SetDynamicProps
+                let mut props = Vec::new_in(ctx.allocator);
+                props.push(Box::new_in(
+                    SimpleExpressionNode::new(exp.content.clone(), exp.is_static, exp.loc.clone()),
+                    ctx.allocator,
+                ));
+                let set_props = SetDynamicPropsIRNode {
+                    element: element_id,
+                    props,
+                };
+                ctx.register_effect(block, OperationNode::SetDynamicProps(set_props));
+            } else {
+                ctx.unsupported_directive(dir, "v-bind without an argument");
             }
         }
+        "on" if is_model_update_handler(el, dir) => {
+            // Replaced by the v-model helper
+        }
         "on" => {
             // v-on - SetEvent
+            if dir.exp.is_none() && dir.modifiers.is_empty() {
+                ctx.on_error(ErrorCode::VOnNoExpression, &dir.loc);
+            }
             if let Some(ref arg) = dir.arg {
                 if let ExpressionNode::Simple(key_exp) = arg {
                     let key_node = SimpleExpressionNode::new(
@@ -756,6 +1322,8 @@ fn transform_directive<'a>(
 
                     block.operation.push(OperationNode::SetEvent(set_event));
                 }
+            } else {
+                ctx.unsupported_directive(dir, "v-on without an argument");
             }
         }
         "if" => {
@@ -768,7 +1336,7 @@ fn transform_directive<'a>(
                         cond_exp.loc.clone(),
                     );
                     let condition = Box::new_in(cond_node, ctx.allocator);
-                    let positive = transform_children(ctx, &el.children);
+                    let positive = transform_children(ctx, el.children.iter());
 
                     let if_node = IfIRNode {
                         id: ctx.next_id(),
@@ -796,7 +1364,7 @@ fn transform_directive<'a>(
                         source_exp.loc.clone(),
                     );
                     let source = Box::new_in(source_node, ctx.allocator);
-                    let render = transform_children(ctx, &el.children);
+                    let render = transform_children(ctx, el.children.iter());
 
                     let for_node = ForIRNode {
                         id: ctx.next_id(),
@@ -809,6 +1377,8 @@ fn transform_directive<'a>(
                         once: false,
                         component: el.tag_type == ElementType::Component,
                         only_child: false,
+                        parent: None,
+                        anchor: None,
                     };
 
                     block
@@ -818,69 +1388,192 @@ fn transform_directive<'a>(
             }
         }
         "html" => {
-            // v-html
-            if let Some(ref exp) = dir.exp {
-                if let ExpressionNode::Simple(val_exp) = exp {
-                    let val_node = SimpleExpressionNode::new(
-                        val_exp.content.clone(),
-                        val_exp.is_static,
-                        val_exp.loc.clone(),
-                    );
-                    let value = Box::new_in(val_node, ctx.allocator);
-                    let set_html = SetHtmlIRNode {
-                        element: element_id,
-                        value,
-                    };
-
-                    let mut effect_ops = Vec::new_in(ctx.allocator);
-                    effect_ops.push(OperationNode::SetHtml(set_html));
-                    block.effect.push(IREffect {
-                        operations: effect_ops,
-                    });
-                }
+            if dir.exp.is_none() {
+                ctx.on_error(ErrorCode::VHtmlNoExpression, &dir.loc);
+            }
+            if !el.children.is_empty() {
+                ctx.on_error(ErrorCode::VHtmlWithChildren, &dir.loc);
+            }
+            if let Some(op) = transform_v_html(ctx.allocator, dir, element_id) {
+                ctx.register_effect(block, op);
             }
         }
         "text" => {
-            // v-text
-            if let Some(ref exp) = dir.exp {
-                if let ExpressionNode::Simple(val_exp) = exp {
-                    let mut values = Vec::new_in(ctx.allocator);
-                    let val_node = SimpleExpressionNode::new(
-                        val_exp.content.clone(),
-                        val_exp.is_static,
-                        val_exp.loc.clone(),
-                    );
-                    values.push(Box::new_in(val_node, ctx.allocator));
-
-                    let set_text = SetTextIRNode {
-                        element: element_id,
-                        values,
-                    };
-
-                    let mut effect_ops = Vec::new_in(ctx.allocator);
-                    effect_ops.push(OperationNode::SetText(set_text));
-                    block.effect.push(IREffect {
-                        operations: effect_ops,
-                    });
-                }
+            if dir.exp.is_none() {
+                ctx.on_error(ErrorCode::VTextNoExpression, &dir.loc);
+            }
+            if !el.children.is_empty() {
+                ctx.on_error(ErrorCode::VTextWithChildren, &dir.loc);
+            }
+            if let Some(op) = transform_v_text(ctx.allocator, dir, element_id) {
+                ctx.register_effect(block, op);
+            }
+        }
+        "once" | "memo" => {
+            // Apply to the whole subtree, see `transform_element_contents`
+        }
+        "show" => {
+            if let Some(op) = ctx.show_directive(dir, element_id) {
+                block.operation.push(op);
+            }
+        }
+        "model" => match &dir.exp {
+            None => ctx.on_error(ErrorCode::VModelNoExpression, &dir.loc),
+            Some(_) if dir.arg.is_some() => ctx.on_error(ErrorCode::VModelArgOnElement, &dir.loc),
+            Some(_) if !matches!(el.tag.as_str(), "input" | "textarea" | "select") => {
+                ctx.on_error(ErrorCode::VModelOnInvalidElement, &dir.loc)
+            }
+            // The value is assigned to, so it must be a reference
+            Some(exp) if !is_member_expression(&exp.loc().source) => {
+                ctx.on_error(ErrorCode::VModelMalformedExpression, exp.loc())
+            }
+            Some(_) => {
+                let op = transform_v_model(ctx.allocator, dir, el, element_id);
+                block.operation.push(op);
             }
+        },
+        "slot" => {
+            ctx.on_error(ErrorCode::VSlotMisplaced, &dir.loc);
+        }
+        "cloak" => {
+            // No-op, as in the virtual DOM compiler
         }
         _ => {
-            // Custom directive - create a copy of the directive
-            let new_dir = DirectiveNode::new(ctx.allocator, dir.name.clone(), dir.loc.clone());
+            let op = transform_custom_directive(ctx, dir, element_id);
+            block.operation.push(op);
+        }
+    }
+}
 
-            let dir_node = DirectiveIRNode {
-                element: element_id,
-                dir: Box::new_in(new_dir, ctx.allocator),
-                name: dir.name.clone(),
-                builtin: false,
-            };
+/// Transform a custom directive, which reads a setup binding when there is
+/// one and is resolved by name otherwise
+fn transform_custom_directive<'a>(
+    ctx: &mut TransformContext<'a, '_>,
+    dir: &DirectiveNode<'a>,
+    element_id: usize,
+) -> OperationNode<'a> {
+    let (name, asset) = match ctx.resolve_setup_directive(&dir.name) {
+        Some(binding) => (binding, false),
+        None => {
+            ctx.register_directive(&dir.name);
+            (dir.name.clone(), true)
+        }
+    };
+
+    // Copy the parts of the directive used by the runtime
+    let mut new_dir = DirectiveNode::new(ctx.allocator, dir.name.clone(), dir.loc.clone());
+    new_dir.exp = dir
+        .exp
+        .as_ref()
+        .map(|exp| copy_expression(ctx.allocator, exp));
+    new_dir.arg = dir
+        .arg
+        .as_ref()
+        .map(|arg| copy_expression(ctx.allocator, arg));
+    for modifier in dir.modifiers.iter() {
+        new_dir.modifiers.push(SimpleExpressionNode::new(
+            modifier.content.clone(),
+            modifier.is_static,
+            modifier.loc.clone(),
+        ));
+    }
 
-            block.operation.push(OperationNode::Directive(dir_node));
+    OperationNode::Directive(DirectiveIRNode {
+        element: element_id,
+        dir: Box::new_in(new_dir, ctx.allocator),
+        name,
+        builtin: false,
+        asset,
+        deferred: false,
+        model_type: None,
+    })
+}
+
+/// Copy an expression as a simple expression
+fn copy_expression<'a>(allocator: &'a Bump, exp: &ExpressionNode<'a>) -> ExpressionNode<'a> {
+    let copy = match exp {
+        ExpressionNode::Simple(simple) => {
+            SimpleExpressionNode::new(simple.content.clone(), simple.is_static, simple.loc.clone())
+        }
+        ExpressionNode::Compound(compound) => {
+            SimpleExpressionNode::new(compound.loc.source.clone(), false, compound.loc.clone())
         }
+    };
+    ExpressionNode::Simple(Box::new_in(copy, allocator))
+}
+
+/// Set a template ref. Refs that can change are set in an effect, which
+/// keeps the previous ref in a variable so that the runtime can unset it.
+fn transform_template_ref<'a>(
+    ctx: &mut TransformContext<'a, '_>,
+    value: SimpleExpressionNode<'a>,
+    element_id: usize,
+    block: &mut BlockIRNode<'a>,
+) {
+    ctx.has_template_ref = true;
+    let effect = !value.is_static && !ctx.in_v_once;
+    let set_ref = OperationNode::SetTemplateRef(SetTemplateRefIRNode {
+        element: element_id,
+        value: Box::new_in(value, ctx.allocator),
+        ref_for: ctx.in_v_for,
+        effect,
+    });
+    if effect {
+        block
+            .operation
+            .push(OperationNode::DeclareOldRef(DeclareOldRefIRNode {
+                id: element_id,
+            }));
+        ctx.register_effect(block, set_ref);
+    } else {
+        block.operation.push(set_ref);
     }
 }
 
+/// Value of a static `ref` attribute
+fn static_ref_value<'a>(attr: &vize_atelier_core::AttributeNode) -> SimpleExpressionNode<'a> {
+    let value = attr
+        .value
+        .as_ref()
+        .map_or_else(String::default, |value| value.content.clone());
+    SimpleExpressionNode::new(value, true, attr.loc.clone())
+}
+
+/// Check if a v-on is the `update:modelValue` listener that the core
+/// transform adds after a v-model on a native element
+fn is_model_update_handler(el: &ElementNode<'_>, dir: &DirectiveNode<'_>) -> bool {
+    let is_update = matches!(
+        &dir.arg,
+        Some(ExpressionNode::Simple(arg)) if arg.content == "update:modelValue"
+    );
+    is_update
+        && el.props.iter().any(|prop| {
+            matches!(
+                prop,
+                PropNode::Directive(model)
+                    if model.name == "model" && model.loc.start.offset == dir.loc.start.offset
+            )
+        })
+}
+
+/// Check if a directive is a `:ref` binding
+fn is_ref_binding(dir: &DirectiveNode<'_>) -> bool {
+    dir.name == "bind"
+        && matches!(&dir.arg, Some(ExpressionNode::Simple(arg)) if arg.is_static && arg.content == "ref")
+}
+
+/// Value of a `:ref` binding, which may be a function
+fn ref_binding_value<'a>(dir: &DirectiveNode<'a>) -> Option<SimpleExpressionNode<'a>> {
+    dir.exp.as_ref().map(|exp| match exp {
+        ExpressionNode::Simple(simple) => {
+            SimpleExpressionNode::new(simple.content.clone(), simple.is_static, simple.loc.clone())
+        }
+        ExpressionNode::Compound(compound) => {
+            SimpleExpressionNode::new(compound.loc.source.clone(), false, compound.loc.clone())
+        }
+    })
+}
+
 /// Generate element template string (recursively includes static children)
 fn generate_element_template(el: &ElementNode<'_>) -> String {
     let mut template = format!("<{}", el.tag);
@@ -888,6 +1581,10 @@ fn generate_element_template(el: &ElementNode<'_>) -> String {
     // Add static attributes
     for prop in el.props.iter() {
         if let PropNode::Attribute(attr) = prop {
+            if attr.name == "ref" {
+                // Set at runtime
+                continue;
+            }
             if let Some(ref value) = attr.value {
                 template.push_str(&format!(" {}=\"{}\"", attr.name, value.content));
             } else {
@@ -901,30 +1598,55 @@ fn generate_element_template(el: &ElementNode<'_>) -> String {
     } else {
         template.push('>');
 
-        // Check if there are any interpolations - if so, use a space placeholder
-        let has_interpolation = el
-            .children
+        // v-text sets a placeholder text node, v-html the element's content
+        let text = el
+            .props
             .iter()
-            .any(|c| matches!(c, TemplateChildNode::Interpolation(_)));
-
-        if has_interpolation {
-            // Use single space as placeholder for interpolation text content
+            .any(|prop| matches!(prop, PropNode::Directive(dir) if dir.name == "text"));
+        if text {
             template.push(' ');
+        }
+        let children = if has_html_or_text(el) {
+            &[]
         } else {
-            // Recursively add static children (text and static elements)
-            for child in el.children.iter() {
-                match child {
-                    TemplateChildNode::Text(text) => {
-                        template.push_str(&text.content);
-                    }
-                    TemplateChildNode::Element(child_el) => {
-                        // Include child elements in template
-                        template.push_str(&generate_element_template(child_el));
-                    }
-                    _ => {
-                        // Other dynamic content is handled elsewhere
+            el.children.as_slice()
+        };
+
+        // Children in the order of their DOM nodes. Adjacent text and
+        // interpolations form one text node, which uses a single space as
+        // placeholder when its content is dynamic. Nested blocks are inserted
+        // at runtime and have no nodes in the template.
+        let mut children = children.iter().peekable();
+        while let Some(child) = children.next() {
+            match child {
+                TemplateChildNode::Text(_) | TemplateChildNode::Interpolation(_) => {
+                    let mut text = std::string::String::new();
+                    let mut dynamic = false;
+                    let mut next = Some(child);
+                    while let Some(node) = next {
+                        match node {
+                            TemplateChildNode::Text(t) => text.push_str(&t.content),
+                            TemplateChildNode::Interpolation(_) => dynamic = true,
+                            _ => {}
+                        }
+                        next = children.next_if(|c| {
+                            matches!(
+                                c,
+                                TemplateChildNode::Text(_) | TemplateChildNode::Interpolation(_)
+                            )
+                        });
                     }
+                    template.push_str(if dynamic { " " } else { &text });
+                }
+                TemplateChildNode::Element(child_el)
+                    if matches!(
+                        child_el.tag_type,
+                        ElementType::Element | ElementType::Template
+                    ) && !is_component(child_el) =>
+                {
+                    template.push_str(&generate_element_template(child_el));
                 }
+                _ => {}
             }
         }
 
@@ -934,12 +1656,29 @@ fn generate_element_template(el: &ElementNode<'_>) -> String {
     template.into()
 }
 
+/// Check if an element's children are replaced by v-html or v-text
+fn has_html_or_text(el: &ElementNode<'_>) -> bool {
+    el.props.iter().any(|prop| {
+        matches!(prop, PropNode::Directive(dir) if matches!(dir.name.as_str(), "html" | "text"))
+    })
+}
+
+/// Check if an element is created as a component. The parser only knows
+/// `<component>` and lowercase built-ins as plain elements.
+fn is_component(el: &ElementNode<'_>) -> bool {
+    el.tag_type == ElementType::Component
+        || el.tag == "component"
+        || BuiltinComponent::from_tag(&el.tag).is_some()
+}
+
 /// Check if an element is static (no dynamic directives)
 fn is_static_element(el: &ElementNode<'_>) -> bool {
-    // Check if any prop is a directive (dynamic)
+    // Check if any prop is a directive (dynamic) or a template ref
     for prop in el.props.iter() {
-        if matches!(prop, PropNode::Directive(_)) {
-            return false;
+        match prop {
+            PropNode::Directive(_) => return false,
+            PropNode::Attribute(attr) if attr.name == "ref" => return false,
+            PropNode::Attribute(_) => {}
         }
     }
 
@@ -948,7 +1687,10 @@ fn is_static_element(el: &ElementNode<'_>) -> bool {
         match child {
             TemplateChildNode::Interpolation(_) => return false,
             TemplateChildNode::Element(child_el) => {
-                if !is_static_element(child_el) {
+                if is_component(child_el)
+                    || child_el.tag_type == ElementType::Slot
+                    || !is_static_element(child_el)
+                {
                     return false;
                 }
             }
@@ -7,17 +7,25 @@ pub mod transform_slot;
 pub mod transform_text;
 pub mod v_bind;
 pub mod v_for;
+pub mod v_html;
 pub mod v_if;
+pub mod v_memo;
 pub mod v_model;
 pub mod v_on;
+pub mod v_once;
 pub mod v_show;
+pub mod v_text;
 
 pub use element::*;
 pub use transform_slot::*;
 pub use transform_text::*;
 pub use v_bind::*;
 pub use v_for::*;
+pub use v_html::*;
 pub use v_if::*;
+pub use v_memo::*;
 pub use v_model::*;
 pub use v_on::*;
+pub use v_once::*;
 pub use v_show::*;
+pub use v_text::*;
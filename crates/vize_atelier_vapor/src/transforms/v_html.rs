@@ -0,0 +1,29 @@
+//! v-html transform for Vapor mode.
+//!
+//! Transforms v-html directive into SetHtmlIRNode.
+
+use vize_carton::{Box, Bump};
+
+use crate::ir::{OperationNode, SetHtmlIRNode};
+use vize_atelier_core::{DirectiveNode, ExpressionNode, SimpleExpressionNode};
+
+/// Transform v-html directive to IR
+pub fn transform_v_html<'a>(
+    allocator: &'a Bump,
+    dir: &DirectiveNode<'a>,
+    element_id: usize,
+) -> Option<OperationNode<'a>> {
+    let value = match dir.exp.as_ref()? {
+        ExpressionNode::Simple(simple) => {
+            SimpleExpressionNode::new(simple.content.clone(), simple.is_static, simple.loc.clone())
+        }
+        ExpressionNode::Compound(compound) => {
+            SimpleExpressionNode::new(compound.loc.source.clone(), false, compound.loc.clone())
+        }
+    };
+
+    Some(OperationNode::SetHtml(SetHtmlIRNode {
+        element: element_id,
+        value: Box::new_in(value, allocator),
+    }))
+}
@@ -0,0 +1,41 @@
+//! v-memo transform for Vapor mode.
+//!
+//! Effects in the subtree of an element with v-memo are wrapped with a check
+//! of the memo dependencies, and are skipped while none of them changed.
+
+use vize_carton::String;
+
+use vize_atelier_core::{ElementNode, ExpressionNode, PropNode};
+
+/// Get the dependency array expression of an element's v-memo
+pub fn get_memo_deps(el: &ElementNode<'_>) -> Option<String> {
+    el.props.iter().find_map(|prop| match prop {
+        PropNode::Directive(dir) if dir.name == "memo" => dir.exp.as_ref().map(|exp| match exp {
+            ExpressionNode::Simple(s) => s.content.clone(),
+            ExpressionNode::Compound(c) => c.loc.source.clone(),
+        }),
+        _ => None,
+    })
+}
+
+/// Generate the statements that skip a memoized effect, given the variable
+/// caching its last dependencies
+pub fn generate_memo_check(cache_var: &str, deps: &str) -> std::string::String {
+    format!(
+        "const _deps = {deps}\nif ({cache_var} && _deps.every((dep, i) => dep === {cache_var}[i])) return\n{cache_var} = _deps"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_memo_check() {
+        let result = generate_memo_check("_memo0", "[_ctx.a]");
+        assert_eq!(
+            result,
+            "const _deps = [_ctx.a]\nif (_memo0 && _deps.every((dep, i) => dep === _memo0[i])) return\n_memo0 = _deps"
+        );
+    }
+}
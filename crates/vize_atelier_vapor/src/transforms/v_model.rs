@@ -4,34 +4,86 @@
 
 use vize_carton::{Box, Bump, String};
 
-use crate::ir::{DirectiveIRNode, OperationNode};
-use vize_atelier_core::{DirectiveNode, ElementNode, ExpressionNode};
+use crate::ir::{DirectiveIRNode, OperationNode, VModelType};
+use vize_atelier_core::{
+    DirectiveNode, ElementNode, ExpressionNode, PropNode, SimpleExpressionNode,
+};
 
-/// Transform v-model directive to IR
+/// Transform a v-model on a native element to IR. Components receive their
+/// v-model as props from the core transform.
 pub fn transform_v_model<'a>(
     allocator: &'a Bump,
     dir: &DirectiveNode<'a>,
-    _el: &ElementNode<'a>,
+    el: &ElementNode<'a>,
     element_id: usize,
-) -> Vec<OperationNode<'a>> {
-    let mut operations = Vec::new();
-
-    // v-model is syntactic sugar for :value + @input
-    // For Vapor mode, we use the v-model directive directly
-
-    // Create a copy of the directive for IR
-    let new_dir = DirectiveNode::new(allocator, "model", dir.loc.clone());
+) -> OperationNode<'a> {
+    let mut new_dir = DirectiveNode::new(allocator, "model", dir.loc.clone());
+    new_dir.exp = dir.exp.as_ref().map(|exp| {
+        let value = match exp {
+            ExpressionNode::Simple(simple) => SimpleExpressionNode::new(
+                simple.content.clone(),
+                simple.is_static,
+                simple.loc.clone(),
+            ),
+            ExpressionNode::Compound(compound) => {
+                SimpleExpressionNode::new(compound.loc.source.clone(), false, compound.loc.clone())
+            }
+        };
+        ExpressionNode::Simple(Box::new_in(value, allocator))
+    });
+    for modifier in dir.modifiers.iter() {
+        new_dir.modifiers.push(SimpleExpressionNode::new(
+            modifier.content.clone(),
+            modifier.is_static,
+            modifier.loc.clone(),
+        ));
+    }
 
     let dir_ir = DirectiveIRNode {
         element: element_id,
         dir: Box::new_in(new_dir, allocator),
         name: String::new("model"),
         builtin: true,
+        asset: false,
+        deferred: false,
+        model_type: Some(get_model_type(el)),
     };
 
-    operations.push(OperationNode::Directive(dir_ir));
+    OperationNode::Directive(dir_ir)
+}
 
-    operations
+/// Get the kind of element a v-model is applied to
+pub fn get_model_type(el: &ElementNode<'_>) -> VModelType {
+    match el.tag.as_str() {
+        "select" => VModelType::Select,
+        "input" => {
+            for prop in el.props.iter() {
+                match prop {
+                    PropNode::Attribute(attr) if attr.name == "type" => {
+                        return match attr.value.as_ref().map(|v| v.content.as_str()) {
+                            Some("checkbox") => VModelType::Checkbox,
+                            Some("radio") => VModelType::Radio,
+                            _ => VModelType::Text,
+                        };
+                    }
+                    // `:type` or `v-bind="obj"` may set the type at runtime
+                    PropNode::Directive(dir) if dir.name == "bind" => {
+                        let is_type = match &dir.arg {
+                            Some(ExpressionNode::Simple(arg)) => arg.content == "type",
+                            Some(ExpressionNode::Compound(_)) => false,
+                            None => true,
+                        };
+                        if is_type {
+                            return VModelType::Dynamic;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            VModelType::Text
+        }
+        _ => VModelType::Text,
+    }
 }
 
 /// Get v-model binding expression
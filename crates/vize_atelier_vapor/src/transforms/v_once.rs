@@ -0,0 +1,36 @@
+//! v-once transform for Vapor mode.
+//!
+//! Elements with v-once render their subtree a single time: operations that
+//! would otherwise be registered as effects run once when the block is
+//! created, and nested blocks are created with their `once` flag set.
+
+use vize_atelier_core::{ElementNode, PropNode};
+
+/// Check if an element has v-once
+pub fn has_v_once(el: &ElementNode<'_>) -> bool {
+    el.props
+        .iter()
+        .any(|prop| matches!(prop, PropNode::Directive(dir) if dir.name == "once"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vize_atelier_core::{parser::parse, TemplateChildNode};
+    use vize_carton::Bump;
+
+    #[test]
+    fn test_has_v_once() {
+        let allocator = Bump::new();
+        let (root, _) = parse(&allocator, r#"<div v-once>{{ a }}</div><p>{{ b }}</p>"#);
+        let once: std::vec::Vec<bool> = root
+            .children
+            .iter()
+            .map(|child| match child {
+                TemplateChildNode::Element(el) => has_v_once(el),
+                _ => false,
+            })
+            .collect();
+        assert_eq!(once, vec![true, false]);
+    }
+}
@@ -5,22 +5,40 @@
 use vize_carton::{Box, Bump, String};
 
 use crate::ir::{DirectiveIRNode, OperationNode};
-use vize_atelier_core::{DirectiveNode, ExpressionNode};
+use vize_atelier_core::{DirectiveNode, ExpressionNode, SimpleExpressionNode};
 
-/// Transform v-show directive to IR
+/// Transform v-show directive to IR. A `deferred` v-show is applied once the
+/// enclosing `<Transition appear>` has been set up.
 pub fn transform_v_show<'a>(
     allocator: &'a Bump,
     dir: &DirectiveNode<'a>,
     element_id: usize,
+    deferred: bool,
 ) -> OperationNode<'a> {
     // v-show is implemented as a directive that toggles display style
-    let new_dir = DirectiveNode::new(allocator, "show", dir.loc.clone());
+    let mut new_dir = DirectiveNode::new(allocator, "show", dir.loc.clone());
+    new_dir.exp = dir.exp.as_ref().map(|exp| {
+        let value = match exp {
+            ExpressionNode::Simple(simple) => SimpleExpressionNode::new(
+                simple.content.clone(),
+                simple.is_static,
+                simple.loc.clone(),
+            ),
+            ExpressionNode::Compound(compound) => {
+                SimpleExpressionNode::new(compound.loc.source.clone(), false, compound.loc.clone())
+            }
+        };
+        ExpressionNode::Simple(Box::new_in(value, allocator))
+    });
 
     let dir_ir = DirectiveIRNode {
         element: element_id,
         dir: Box::new_in(new_dir, allocator),
         name: String::new("show"),
         builtin: true,
+        asset: false,
+        deferred,
+        model_type: None,
     };
 
     OperationNode::Directive(dir_ir)
@@ -52,6 +70,22 @@ pub fn generate_v_show_effect(element_var: &str, condition: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use vize_atelier_core::SourceLocation;
+
+    #[test]
+    fn test_transform_v_show_keeps_expression() {
+        let allocator = Bump::new();
+        let mut dir = DirectiveNode::new(&allocator, "show", SourceLocation::STUB);
+        let exp = SimpleExpressionNode::new("visible", false, SourceLocation::STUB);
+        dir.exp = Some(ExpressionNode::Simple(Box::new_in(exp, &allocator)));
+
+        let OperationNode::Directive(op) = transform_v_show(&allocator, &dir, 3, false) else {
+            panic!("expected a directive operation");
+        };
+        assert_eq!(op.element, 3);
+        assert!(op.builtin);
+        assert_eq!(get_show_condition(&op.dir).as_deref(), Some("visible"));
+    }
 
     #[test]
     fn test_generate_v_show_effect() {
@@ -0,0 +1,57 @@
+//! v-text transform for Vapor mode.
+//!
+//! Transforms v-text directive into SetTextIRNode. The element's template
+//! holds a single placeholder text node, which the text is set on.
+
+use vize_carton::{Box, Bump, Vec};
+
+use crate::ir::{OperationNode, SetTextIRNode};
+use vize_atelier_core::{DirectiveNode, ExpressionNode, SimpleExpressionNode};
+
+/// Transform v-text directive to IR
+pub fn transform_v_text<'a>(
+    allocator: &'a Bump,
+    dir: &DirectiveNode<'a>,
+    element_id: usize,
+) -> Option<OperationNode<'a>> {
+    let value = match dir.exp.as_ref()? {
+        ExpressionNode::Simple(simple) => {
+            SimpleExpressionNode::new(simple.content.clone(), simple.is_static, simple.loc.clone())
+        }
+        ExpressionNode::Compound(compound) => {
+            SimpleExpressionNode::new(compound.loc.source.clone(), false, compound.loc.clone())
+        }
+    };
+
+    let mut values = Vec::new_in(allocator);
+    values.push(Box::new_in(value, allocator));
+
+    Some(OperationNode::SetText(SetTextIRNode {
+        element: element_id,
+        values,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vize_atelier_core::{parser::parse, PropNode, TemplateChildNode};
+
+    #[test]
+    fn test_transform_v_text() {
+        let allocator = Bump::new();
+        let (root, _) = parse(&allocator, r#"<div v-text="msg"></div>"#);
+        let TemplateChildNode::Element(el) = &root.children[0] else {
+            panic!("Expected element");
+        };
+        let PropNode::Directive(dir) = &el.props[0] else {
+            panic!("Expected directive");
+        };
+
+        let Some(OperationNode::SetText(set_text)) = transform_v_text(&allocator, dir, 3) else {
+            panic!("Expected SetText");
+        };
+        assert_eq!(set_text.element, 3);
+        assert_eq!(set_text.values[0].content.as_str(), "msg");
+    }
+}
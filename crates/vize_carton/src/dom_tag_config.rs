@@ -111,6 +111,22 @@ pub fn is_rcdata_tag(tag: &str) -> bool {
     RCDATA_TAGS.contains(tag)
 }
 
+/// Boolean attributes whose presence alone means `true`
+/// https://html.spec.whatwg.org/multipage/indices.html#attributes-3
+pub static BOOLEAN_ATTRS: phf::Set<&'static str> = phf_set! {
+    "itemscope", "allowfullscreen", "formnovalidate", "ismap", "nomodule",
+    "novalidate", "readonly", "async", "autofocus", "autoplay", "controls",
+    "default", "defer", "disabled", "hidden", "inert", "loop", "open",
+    "required", "reversed", "scoped", "seamless", "checked", "muted",
+    "multiple", "selected"
+};
+
+/// Check if attribute is a boolean attribute
+#[inline]
+pub fn is_boolean_attr(name: &str) -> bool {
+    BOOLEAN_ATTRS.contains(name)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -145,4 +161,11 @@ mod tests {
         assert!(is_raw_text_tag("style"));
         assert!(!is_raw_text_tag("div"));
     }
+
+    #[test]
+    fn test_boolean_attrs() {
+        assert!(is_boolean_attr("disabled"));
+        assert!(is_boolean_attr("checked"));
+        assert!(!is_boolean_attr("value"));
+    }
 }
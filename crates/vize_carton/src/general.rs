@@ -247,6 +247,23 @@ pub fn can_set_value_directly(tag_name: &str) -> bool {
     tag_name != "PROGRESS" && !tag_name.contains('-')
 }
 
+/// Escape text for use in HTML content and double-quoted attribute values
+/// Example: "<a & b>" -> "&lt;a &amp; b&gt;"
+pub fn escape_html(s: &str) -> std::string::String {
+    let mut result = std::string::String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&#39;"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -297,4 +314,11 @@ mod tests {
         assert!(!is_simple_identifier("foo-bar"));
         assert!(!is_simple_identifier(""));
     }
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(escape_html("<a & b>"), "&lt;a &amp; b&gt;");
+        assert_eq!(escape_html("say \"hi\""), "say &quot;hi&quot;");
+        assert_eq!(escape_html("plain"), "plain");
+    }
 }
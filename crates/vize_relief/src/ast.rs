@@ -167,6 +167,8 @@ pub struct RootNode<'a> {
     pub loc: SourceLocation,
     pub codegen_node: Option<CodegenNode<'a>>,
     pub transformed: bool,
+    /// Processed `ssrCssVars` object, bound to the root elements as `_cssVars`
+    pub ssr_css_vars: Option<String>,
 }
 
 impl<'a> RootNode<'a> {
@@ -184,6 +186,7 @@ impl<'a> RootNode<'a> {
             loc: SourceLocation::STUB,
             codegen_node: None,
             transformed: false,
+            ssr_css_vars: None,
         }
     }
 
@@ -462,6 +465,8 @@ pub struct DirectiveNode<'a> {
     pub modifiers: Vec<'a, SimpleExpressionNode<'a>>,
     /// Parsed result for v-for
     pub for_parse_result: Option<ForParseResult<'a>>,
+    /// Cache slot for a cached v-on handler (`_cache[n]`)
+    pub cache_index: Option<u32>,
     pub loc: SourceLocation,
 }
 
@@ -474,6 +479,7 @@ impl<'a> DirectiveNode<'a> {
             arg: None,
             modifiers: Vec::new_in(allocator),
             for_parse_result: None,
+            cache_index: None,
             loc,
         }
     }
@@ -99,6 +99,17 @@ pub enum ErrorCode {
     CacheHandlerNotSupported = 51,
     ScopeIdNotSupported = 52,
 
+    // DOM transform errors
+    VHtmlNoExpression = 53,
+    VHtmlWithChildren = 54,
+    VTextNoExpression = 55,
+    VTextWithChildren = 56,
+    TransitionInvalidChildren = 57,
+    VModelOnInvalidElement = 58,
+
+    // Vapor errors
+    VaporUnsupportedDirective = 60,
+
     // Extended errors
     UnhandledCodePath = 100,
     ExtendPoint = 1000,
@@ -178,6 +189,19 @@ impl ErrorCode {
             Self::CacheHandlerNotSupported => "cacheHandlers option is not supported in this mode.",
             Self::ScopeIdNotSupported => "scopeId option is not supported in this mode.",
 
+            Self::VHtmlNoExpression => "v-html is missing expression.",
+            Self::VHtmlWithChildren => "v-html will override element children.",
+            Self::VTextNoExpression => "v-text is missing expression.",
+            Self::VTextWithChildren => "v-text will override element children.",
+            Self::TransitionInvalidChildren => {
+                "<Transition> expects exactly one child element or component."
+            }
+            Self::VModelOnInvalidElement => {
+                "v-model can only be used on <input>, <textarea> and <select> elements."
+            }
+
+            Self::VaporUnsupportedDirective => "Directive is not supported in Vapor mode.",
+
             Self::UnhandledCodePath => "Unhandled code path.",
             Self::ExtendPoint => "Extension point.",
         }
@@ -189,7 +213,25 @@ impl ErrorCode {
 
     pub fn is_transform_error(&self) -> bool {
         let code = *self as u16;
-        code >= (Self::VIfNoExpression as u16) && code < (Self::PrefixIdNotSupported as u16)
+        (code >= (Self::VIfNoExpression as u16) && code < (Self::PrefixIdNotSupported as u16))
+            || self.is_dom_error()
+            || self.is_vapor_error()
+    }
+
+    pub fn is_dom_error(&self) -> bool {
+        matches!(
+            self,
+            Self::VHtmlNoExpression
+                | Self::VHtmlWithChildren
+                | Self::VTextNoExpression
+                | Self::VTextWithChildren
+                | Self::TransitionInvalidChildren
+                | Self::VModelOnInvalidElement
+        )
+    }
+
+    pub fn is_vapor_error(&self) -> bool {
+        matches!(self, Self::VaporUnsupportedDirective)
     }
 }
 
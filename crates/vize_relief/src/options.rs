@@ -85,7 +85,10 @@ pub enum WhitespaceStrategy {
     Preserve,
 }
 
-/// Transform options
+/// Transform options.
+///
+/// The compiler's `vize_atelier_core::options::TransformOptions` embeds these
+/// next to user node and directive transforms.
 #[derive(Debug, Clone)]
 pub struct TransformOptions {
     /// Filename for error messages
@@ -100,7 +103,7 @@ pub struct TransformOptions {
     pub scope_id: Option<String>,
     /// Whether in SSR mode
     pub ssr: bool,
-    /// Whether SSR optimize is enabled
+    /// CSS variables object from `v-bind()` in `<style>`, rendered on SSR roots
     pub ssr_css_vars: Option<String>,
     /// Binding metadata from script setup
     pub binding_metadata: Option<BindingMetadata>,
@@ -8,10 +8,10 @@ use std::fs;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use vize_carton::Bump;
 
-use crate::{CompileResult, CompilerOptions};
+use crate::{CompileError, CompileResult, CompilerOptions};
 use vize_atelier_core::{
     codegen::generate,
-    options::{CodegenMode, CodegenOptions, TransformOptions},
+    options::{BaseTransformOptions, CodegenMode, CodegenOptions, TransformOptions},
     parser::parse,
     transform::transform,
 };
@@ -39,11 +39,14 @@ pub fn compile(template: String, options: Option<CompilerOptions>) -> Result<Com
     // Transform
     // In module mode, prefix_identifiers defaults to true (like Vue)
     let transform_opts = TransformOptions {
-        prefix_identifiers: opts.prefix_identifiers.unwrap_or(is_module_mode),
-        hoist_static: opts.hoist_static.unwrap_or(false),
-        cache_handlers: opts.cache_handlers.unwrap_or(false),
-        scope_id: opts.scope_id.clone().map(|s| s.into()),
-        ssr: opts.ssr.unwrap_or(false),
+        base: BaseTransformOptions {
+            prefix_identifiers: opts.prefix_identifiers.unwrap_or(is_module_mode),
+            hoist_static: opts.hoist_static.unwrap_or(false),
+            cache_handlers: opts.cache_handlers.unwrap_or(false),
+            scope_id: opts.scope_id.clone().map(|s| s.into()),
+            ssr: opts.ssr.unwrap_or(false),
+            ..Default::default()
+        },
         ..Default::default()
     };
     transform(&allocator, &mut root, transform_opts);
@@ -56,6 +59,11 @@ pub fn compile(template: String, options: Option<CompilerOptions>) -> Result<Com
             CodegenMode::Function
         },
         source_map: opts.source_map.unwrap_or(false),
+        filename: opts
+            .filename
+            .as_deref()
+            .map(Into::into)
+            .unwrap_or_else(|| CodegenOptions::default().filename),
         ssr: opts.ssr.unwrap_or(false),
         ..Default::default()
     };
@@ -71,7 +79,10 @@ pub fn compile(template: String, options: Option<CompilerOptions>) -> Result<Com
         code: result.code.to_string(),
         preamble: result.preamble.to_string(),
         ast,
-        map: None,
+        map: result
+            .map
+            .as_deref()
+            .and_then(|map| serde_json::from_str(map).ok()),
         helpers,
         templates: None,
     })
@@ -91,11 +102,13 @@ pub fn compile_vapor(template: String, options: Option<CompilerOptions>) -> Resu
     };
     let result = vapor_compile(&allocator, &template, vapor_opts);
 
-    if !result.error_messages.is_empty() {
-        return Err(Error::new(
-            Status::GenericFailure,
-            result.error_messages.join("\n"),
-        ));
+    if !result.errors.is_empty() {
+        let messages: Vec<String> = result
+            .errors
+            .into_iter()
+            .map(|err| CompileError::from(err).to_string())
+            .collect();
+        return Err(Error::new(Status::GenericFailure, messages.join("\n")));
     }
 
     Ok(CompileResult {
@@ -142,6 +155,18 @@ pub struct SfcCompileOptionsNapi {
     pub filename: Option<String>,
     pub source_map: Option<bool>,
     pub ssr: Option<bool>,
+    /// Scope ID for scoped CSS (derived from the filename when omitted)
+    pub scope_id: Option<String>,
+    /// Project root the filename is made relative to for scope IDs
+    pub root: Option<String>,
+    /// Production mode: the source is hashed into the scope ID as well
+    pub is_prod: Option<bool>,
+    /// Name pattern for CSS module classes (`[local]`, `[name]`, `[hash]`)
+    pub css_modules_pattern: Option<String>,
+    /// Minify the compiled CSS
+    pub minify_css: Option<bool>,
+    /// Register the component with the Vue HMR runtime (ignored in production)
+    pub hmr: Option<bool>,
 }
 
 /// SFC compile result for NAPI
@@ -151,6 +176,14 @@ pub struct SfcCompileResultNapi {
     pub code: String,
     /// Generated CSS (if any)
     pub css: Option<String>,
+    /// Source map of the generated JavaScript (JSON)
+    pub map: Option<String>,
+    /// Source map of the generated CSS (JSON)
+    pub css_map: Option<String>,
+    /// Class names of `<style module>` blocks by module name (JSON)
+    pub css_modules: Option<String>,
+    /// Files loaded through `src` attributes
+    pub dependencies: Vec<String>,
     /// Compilation errors
     pub errors: Vec<String>,
     /// Compilation warnings
@@ -262,6 +295,10 @@ pub fn compile_sfc(
             return Ok(SfcCompileResultNapi {
                 code: String::new(),
                 css: None,
+                map: None,
+                css_map: None,
+                css_modules: None,
+                dependencies: vec![],
                 errors: vec![e.message],
                 warnings: vec![],
             });
@@ -270,9 +307,11 @@ pub fn compile_sfc(
 
     // Compile
     let has_scoped = descriptor.styles.iter().any(|s| s.scoped);
+    let source_map = opts.source_map.unwrap_or(false);
     let compile_opts = SfcCompileOptions {
         parse: SfcParseOptions {
             filename: filename.clone(),
+            source_map,
             ..Default::default()
         },
         script: ScriptCompileOptions {
@@ -288,26 +327,82 @@ pub fn compile_sfc(
         style: StyleCompileOptions {
             id: filename,
             scoped: has_scoped,
+            source_map,
+            minify: opts.minify_css.unwrap_or(false),
+            modules_pattern: opts.css_modules_pattern,
             ..Default::default()
         },
+        scope_id: opts.scope_id,
+        root: opts.root,
+        is_prod: opts.is_prod.unwrap_or(false),
+        hmr: opts.hmr.unwrap_or(false),
+        file_loader: None,
     };
 
     match sfc_compile(&descriptor, compile_opts) {
         Ok(result) => Ok(SfcCompileResultNapi {
             code: result.code,
             css: result.css,
+            map: result.map.map(|map| map.to_string()),
+            css_map: result.css_map.map(|map| map.to_string()),
+            css_modules: result
+                .css_modules
+                .and_then(|modules| serde_json::to_string(&modules).ok()),
+            dependencies: result.dependencies,
             errors: result.errors.into_iter().map(|e| e.message).collect(),
             warnings: result.warnings.into_iter().map(|e| e.message).collect(),
         }),
         Err(e) => Ok(SfcCompileResultNapi {
             code: String::new(),
             css: None,
+            map: None,
+            css_map: None,
+            css_modules: None,
+            dependencies: vec![],
             errors: vec![e.message],
             warnings: vec![],
         }),
     }
 }
 
+/// Hot update needed between two versions of an SFC
+#[napi(object)]
+pub struct SfcDiffNapi {
+    /// "none", "styleOnly", "rerender" or "reload"
+    pub kind: String,
+    pub template_changed: bool,
+    pub script_changed: bool,
+    /// Indices of the changed or added style blocks
+    pub styles_changed: Vec<u32>,
+    pub styles_removed: bool,
+}
+
+/// Compare two versions of an SFC for a hot update
+#[napi(js_name = "diffSfc")]
+pub fn diff_sfc(prev: String, next: String, filename: Option<String>) -> Result<SfcDiffNapi> {
+    use vize_atelier_sfc::{diff_descriptors, parse_sfc as sfc_parse, SfcParseOptions};
+
+    let opts = SfcParseOptions {
+        filename: filename.unwrap_or_else(|| "anonymous.vue".to_string()),
+        ..Default::default()
+    };
+    let prev = sfc_parse(&prev, opts.clone())
+        .map_err(|e| Error::new(Status::GenericFailure, e.message))?;
+    let next = sfc_parse(&next, opts).map_err(|e| Error::new(Status::GenericFailure, e.message))?;
+    let diff = diff_descriptors(&prev, &next);
+
+    Ok(SfcDiffNapi {
+        kind: serde_json::to_value(diff.kind)
+            .ok()
+            .and_then(|kind| kind.as_str().map(String::from))
+            .unwrap_or_default(),
+        template_changed: diff.template_changed,
+        script_changed: diff.script_changed,
+        styles_changed: diff.styles_changed.into_iter().map(|i| i as u32).collect(),
+        styles_removed: diff.styles_removed,
+    })
+}
+
 /// Batch compile options for NAPI
 #[napi(object)]
 #[derive(Default)]
@@ -434,6 +529,7 @@ pub fn compile_sfc_batch(
                 scoped: has_scoped,
                 ..Default::default()
             },
+            ..Default::default()
         };
 
         match sfc_compile(&descriptor, compile_opts) {
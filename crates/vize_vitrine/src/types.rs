@@ -22,9 +22,12 @@ pub struct CompilerOptions {
     /// Whether to cache event handlers
     #[serde(default)]
     pub cache_handlers: Option<bool>,
-    /// Scope ID for scoped CSS
+    /// Scope ID for scoped CSS (derived from the filename when omitted)
     #[serde(default)]
     pub scope_id: Option<String>,
+    /// Project root the filename is made relative to for scope IDs
+    #[serde(default)]
+    pub root: Option<String>,
     /// Whether in SSR mode
     #[serde(default)]
     pub ssr: Option<bool>,
@@ -44,6 +47,12 @@ pub struct CompilerOptions {
     /// Defaults to "downcompile"
     #[serde(default)]
     pub script_ext: Option<String>,
+    /// Production mode: disables HMR and hashes the source into scope IDs
+    #[serde(default)]
+    pub is_prod: Option<bool>,
+    /// Register SFCs with the Vue HMR runtime
+    #[serde(default)]
+    pub hmr: Option<bool>,
 }
 
 /// Compile result
@@ -95,3 +104,35 @@ pub struct Position {
     pub line: u32,
     pub column: u32,
 }
+
+impl From<vize_atelier_core::CompilerError> for CompileError {
+    fn from(err: vize_atelier_core::CompilerError) -> Self {
+        Self {
+            code: format!("{:?}", err.code),
+            message: err.message,
+            loc: err.loc.map(|loc| SourceLocation {
+                start: Position {
+                    offset: loc.start.offset,
+                    line: loc.start.line,
+                    column: loc.start.column,
+                },
+                end: Position {
+                    offset: loc.end.offset,
+                    line: loc.end.line,
+                    column: loc.end.column,
+                },
+                source: loc.source.to_string(),
+            }),
+        }
+    }
+}
+
+impl std::fmt::Display for CompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)?;
+        if let Some(loc) = &self.loc {
+            write!(f, " ({}:{})", loc.start.line, loc.start.column)?;
+        }
+        Ok(())
+    }
+}
@@ -4,14 +4,14 @@ use serde::Serialize;
 use vize_carton::Bump;
 use wasm_bindgen::prelude::*;
 
-use crate::{CompileResult, CompilerOptions};
+use crate::{CompileError, CompileResult, CompilerOptions};
 use vize_atelier_core::options::CodegenMode;
 use vize_atelier_core::parser::parse;
 use vize_atelier_dom::{compile_template_with_options, DomCompilerOptions};
 use vize_atelier_sfc::{
-    compile_css, compile_sfc as sfc_compile, parse_sfc, CssCompileOptions, CssTargets,
-    ScriptCompileOptions, SfcCompileOptions, SfcDescriptor, SfcParseOptions, StyleCompileOptions,
-    TemplateCompileOptions,
+    compile_css, compile_sfc as sfc_compile, diff_descriptors, parse_sfc, CssCompileOptions,
+    CssTargets, ScriptCompileOptions, SfcCompileOptions, SfcDescriptor, SfcParseOptions,
+    StyleCompileOptions, TemplateCompileOptions,
 };
 use vize_atelier_vapor::{compile_vapor as vapor_compile, VaporCompilerOptions};
 
@@ -24,6 +24,8 @@ pub struct SfcWasmResult {
     pub script: SfcScriptResult,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub css: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "cssModules")]
+    pub css_modules: Option<vize_atelier_sfc::CssModules>,
     pub errors: Vec<String>,
     pub warnings: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none", rename = "bindingMetadata")]
@@ -113,6 +115,17 @@ impl Compiler {
         }
     }
 
+    /// Compare two versions of an SFC for a hot update
+    #[wasm_bindgen(js_name = "diffSfc")]
+    pub fn diff_sfc(&self, prev: &str, next: &str) -> Result<JsValue, JsValue> {
+        let prev = parse_sfc(prev, SfcParseOptions::default())
+            .map_err(|e| JsValue::from_str(&e.message))?;
+        let next = parse_sfc(next, SfcParseOptions::default())
+            .map_err(|e| JsValue::from_str(&e.message))?;
+        let diff = diff_descriptors(&prev, &next);
+        serde_wasm_bindgen::to_value(&diff).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
     /// Compile CSS with LightningCSS
     #[wasm_bindgen(js_name = "compileCss")]
     pub fn compile_css_method(&self, css: &str, options: JsValue) -> Result<JsValue, JsValue> {
@@ -218,6 +231,11 @@ impl Compiler {
                 scoped: descriptor.styles.iter().any(|s| s.scoped),
                 ..Default::default()
             },
+            scope_id: opts.scope_id.clone(),
+            root: opts.root.clone(),
+            is_prod: opts.is_prod.unwrap_or(false),
+            hmr: opts.hmr.unwrap_or(false),
+            ..Default::default()
         };
 
         // Compile the full SFC
@@ -243,6 +261,7 @@ impl Compiler {
                     .map(|b| serde_json::to_value(&b).unwrap_or_default()),
             },
             css: sfc_result.css,
+            css_modules: sfc_result.css_modules,
             errors: sfc_result.errors.into_iter().map(|e| e.message).collect(),
             warnings: sfc_result.warnings.into_iter().map(|e| e.message).collect(),
             binding_metadata,
@@ -275,8 +294,13 @@ fn compile_internal(
         };
         let result = vapor_compile(&allocator, template, vapor_opts);
 
-        if !result.error_messages.is_empty() {
-            return Err(result.error_messages.join("\n"));
+        if !result.errors.is_empty() {
+            let messages: Vec<String> = result
+                .errors
+                .into_iter()
+                .map(|err| CompileError::from(err).to_string())
+                .collect();
+            return Err(messages.join("\n"));
         }
 
         return Ok(CompileResult {
@@ -307,6 +331,7 @@ fn compile_internal(
         scope_id: opts.scope_id.clone().map(|s| s.into()),
         ssr: opts.ssr.unwrap_or(false),
         source_map: opts.source_map.unwrap_or(false),
+        filename: opts.filename.as_deref().map(Into::into),
         is_ts: opts.is_ts.unwrap_or(false),
         ..Default::default()
     };
@@ -327,7 +352,10 @@ fn compile_internal(
         code: result.code.to_string(),
         preamble: result.preamble.to_string(),
         ast,
-        map: None,
+        map: result
+            .map
+            .as_deref()
+            .and_then(|map| serde_json::from_str(map).ok()),
         helpers,
         templates: None,
     })
@@ -443,6 +471,12 @@ pub fn compile_sfc_fn(source: &str, options: JsValue) -> Result<JsValue, JsValue
     Compiler::new().compile_sfc(source, options)
 }
 
+/// Diff SFC versions (free function)
+#[wasm_bindgen(js_name = "diffSfc")]
+pub fn diff_sfc_fn(prev: &str, next: &str) -> Result<JsValue, JsValue> {
+    Compiler::new().diff_sfc(prev, next)
+}
+
 /// Compile CSS (free function)
 #[wasm_bindgen(js_name = "compileCss")]
 pub fn compile_css_fn(css: &str, options: JsValue) -> Result<JsValue, JsValue> {
@@ -1,37 +1,12 @@
 import type { Plugin, ResolvedConfig } from 'vite';
-import { createHash } from 'node:crypto';
 import path from 'node:path';
 import fs from 'node:fs';
 import { fileURLToPath } from 'node:url';
 import { createRequire } from 'node:module';
-import MagicString from 'magic-string';
 
 const __dirname = path.dirname(fileURLToPath(import.meta.url));
 const require = createRequire(import.meta.url);
 
-/**
- * Rewrite `export default { ... }` to `const __sfc__ = { ... }; export default __sfc__;`
- */
-function rewriteDefault(code: string, as: string): string {
-  const s = new MagicString(code);
-  const exportDefaultMatch = code.match(/\bexport\s+default\s+/);
-
-  if (!exportDefaultMatch || exportDefaultMatch.index === undefined) {
-    return code;
-  }
-
-  const start = exportDefaultMatch.index;
-  const end = start + exportDefaultMatch[0].length;
-
-  // Replace "export default " with "const __sfc__ = "
-  s.overwrite(start, end, `const ${as} = `);
-
-  // Append "export default __sfc__;" at the end
-  s.append(`\nexport default ${as};`);
-
-  return s.toString();
-}
-
 export interface VizeOptions {
   include?: string | RegExp | (string | RegExp)[];
   exclude?: string | RegExp | (string | RegExp)[];
@@ -59,8 +34,17 @@ interface SfcCompileResult {
   warnings: string[];
 }
 
+interface SfcDiff {
+  kind: 'none' | 'styleOnly' | 'rerender' | 'reload';
+  templateChanged: boolean;
+  scriptChanged: boolean;
+  stylesChanged: number[];
+  stylesRemoved: boolean;
+}
+
 interface WasmModule {
   compileSfc: (source: string, options: Record<string, unknown>) => SfcCompileResult;
+  diffSfc: (prev: string, next: string) => SfcDiff;
 }
 
 let wasmModule: WasmModule | null = null;
@@ -85,6 +69,7 @@ function loadWasm(): WasmModule {
       // Extract the exported functions
       wasmModule = {
         compileSfc: wasmModule_.compileSfc,
+        diffSfc: wasmModule_.diffSfc,
       } as WasmModule;
       return wasmModule;
     }
@@ -94,11 +79,6 @@ function loadWasm(): WasmModule {
   }
 }
 
-function generateScopeId(filename: string): string {
-  const hash = createHash('sha256').update(filename).digest('hex');
-  return hash.slice(0, 8);
-}
-
 function createFilter(
   include?: string | RegExp | (string | RegExp)[],
   exclude?: string | RegExp | (string | RegExp)[]
@@ -125,6 +105,8 @@ export function vize(options: VizeOptions = {}): Plugin {
   const filter = createFilter(options.include, options.exclude);
   let config: ResolvedConfig;
   let isProduction = options.isProduction ?? false;
+  // Last compiled source of each SFC, to tell template-only edits apart
+  const sources = new Map<string, string>();
 
   return {
     name: 'vite-plugin-vize',
@@ -152,6 +134,7 @@ export function vize(options: VizeOptions = {}): Plugin {
 
         const result = wasm.compileSfc(source, {
           filename,
+          root: config?.root,
           mode: 'module',
           sourceMap: options.sourceMap ?? !isProduction,
           outputMode: options.vapor ? 'vapor' : 'vdom',
@@ -162,22 +145,36 @@ export function vize(options: VizeOptions = {}): Plugin {
       return null;
     },
 
+    handleHotUpdate(ctx) {
+      if (!filter(ctx.file) || !ctx.file.endsWith('.vue')) return;
+      const prev = sources.get(ctx.file);
+      if (prev === undefined) return;
+
+      // Skip saves that don't change the compiled output
+      return ctx.read().then(next => {
+        if (loadWasm().diffSfc(prev, next).kind === 'none') {
+          return [];
+        }
+      });
+    },
+
     transform(code: string, id: string) {
       if (!filter(id)) return null;
       if (!id.endsWith('.vue')) return null;
 
       const wasm = loadWasm();
-      const scopeId = generateScopeId(id);
-      const hasScoped = /<style[^>]*\bscoped\b/.test(code);
+      const hmr = !isProduction && config?.command === 'serve' && !(options.ssr ?? false);
 
       try {
         const result = wasm.compileSfc(code, {
           filename: id,
+          root: config?.root,
           mode: 'module',
-          scopeId: hasScoped ? `data-v-${scopeId}` : undefined,
           sourceMap: options.sourceMap ?? !isProduction,
           ssr: options.ssr ?? false,
           outputMode: options.vapor ? 'vapor' : 'vdom',
+          isProd: isProduction,
+          hmr,
         });
 
         if (result.errors.length > 0) {
@@ -186,38 +183,46 @@ export function vize(options: VizeOptions = {}): Plugin {
 
         let output = result.script.code;
 
-        // Inject CSS
+        // Inject CSS, replacing the previous version on hot updates
         if (result.css) {
           const cssCode = JSON.stringify(result.css);
           output = `
 const __css__ = ${cssCode};
 (function() {
   if (typeof document !== 'undefined') {
-    const style = document.createElement('style');
+    const id = ${JSON.stringify(id)};
+    let style = document.querySelector('style[data-vize-id="' + CSS.escape(id) + '"]');
+    if (!style) {
+      style = document.createElement('style');
+      style.setAttribute('data-vize-id', id);
+      document.head.appendChild(style);
+    }
     style.textContent = __css__;
-    document.head.appendChild(style);
   }
 })();
 ${output}`;
         }
 
-        // Add HMR support
-        if (!isProduction && config?.command === 'serve') {
-          // Rewrite "export default { ... }" to "const __sfc__ = { ... }; export default __sfc__;"
-          output = rewriteDefault(output, '__sfc__');
+        // The compiler registers the component with __VUE_HMR_RUNTIME__,
+        // accept updates by rerendering when only the template or styles changed
+        if (hmr) {
+          const prev = sources.get(id);
+          sources.set(id, code);
+          const kind = prev === undefined ? 'reload' : wasm.diffSfc(prev, code).kind;
+          if (kind === 'rerender' || kind === 'styleOnly') {
+            output += `\nexport const _rerender_only = true`;
+          }
           output += `
 if (import.meta.hot) {
-  __sfc__.__hmrId = ${JSON.stringify(scopeId)};
   import.meta.hot.accept(mod => {
     if (!mod) return;
-    const { default: updated } = mod;
-    if (typeof __VUE_HMR_RUNTIME__ !== 'undefined') {
-      __VUE_HMR_RUNTIME__.reload(__sfc__.__hmrId, updated);
+    const { default: updated, _rerender_only } = mod;
+    if (_rerender_only) {
+      __VUE_HMR_RUNTIME__.rerender(updated.__hmrId, updated.render);
+    } else {
+      __VUE_HMR_RUNTIME__.reload(updated.__hmrId, updated);
     }
   });
-  if (typeof __VUE_HMR_RUNTIME__ !== 'undefined') {
-    __VUE_HMR_RUNTIME__.createRecord(__sfc__.__hmrId, __sfc__);
-  }
 }`;
         }
 
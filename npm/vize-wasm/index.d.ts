@@ -14,8 +14,10 @@ export interface CompilerOptions {
   hoistStatic?: boolean;
   /** Whether to cache event handlers */
   cacheHandlers?: boolean;
-  /** Scope ID for scoped CSS */
+  /** Scope ID for scoped CSS (derived from the filename when omitted) */
   scopeId?: string;
+  /** Project root the filename is made relative to for scope IDs */
+  root?: string;
   /** Whether in SSR mode */
   ssr?: boolean;
   /** Whether to generate source map */
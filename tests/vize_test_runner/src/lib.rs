@@ -7,7 +7,7 @@ use serde::Deserialize;
 use std::path::Path;
 use vize_atelier_core::{
     codegen::generate,
-    options::{CodegenMode, CodegenOptions, ParserOptions, TransformOptions},
+    options::{BaseTransformOptions, CodegenMode, CodegenOptions, ParserOptions, TransformOptions},
     parser::parse_with_options,
     transform::transform,
 };
@@ -165,10 +165,13 @@ pub fn compile_vdom(input: &str, options: &TestOptions) -> String {
     let (mut root, _errors) = parse_with_options(&allocator, input, parser_opts);
 
     let transform_opts = TransformOptions {
-        prefix_identifiers: options.prefix_identifiers.unwrap_or(true),
-        hoist_static: options.hoist_static.unwrap_or(false),
-        cache_handlers: options.cache_handlers.unwrap_or(false),
-        ssr: options.ssr.unwrap_or(false),
+        base: BaseTransformOptions {
+            prefix_identifiers: options.prefix_identifiers.unwrap_or(true),
+            hoist_static: options.hoist_static.unwrap_or(false),
+            cache_handlers: options.cache_handlers.unwrap_or(false),
+            ssr: options.ssr.unwrap_or(false),
+            ..Default::default()
+        },
         ..Default::default()
     };
     transform(&allocator, &mut root, transform_opts);